@@ -30,14 +30,45 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
+use crate::util::FloorRem;
 use std::convert::TryFrom;
 use std::num::TryFromIntError;
 
 #[macro_use]
 mod def_macro;
-
-define_two_property_arithmetic_struct!(Position, UPosition, FPosition, x, y, ORIGIN, "({}, {})");
-define_two_property_arithmetic_struct!(Size, USize, FSize, width, height, ZERO, "{}x{}");
+mod direction;
+mod interop;
+mod neighbors;
+mod polygon;
+mod segment;
+mod transform;
+
+pub use direction::Direction;
+pub use neighbors::Neighbors;
+pub use polygon::Polygon;
+pub use segment::{FSegment, Segment};
+pub use transform::{Rotation, Transform2};
+
+define_arithmetic_struct!(Position, UPosition, FPosition, [x: 0, y: 1], [y, x], ORIGIN, "({}, {})");
+define_arithmetic_struct!(Size, USize, FSize, [width: 0, height: 1], [height, width], ZERO, "{}x{}");
+define_arithmetic_struct!(
+    Position3,
+    UPosition3,
+    FPosition3,
+    [x: 0, y: 1, z: 2],
+    [z, y, x],
+    ORIGIN,
+    "({}, {}, {})"
+);
+define_arithmetic_struct!(
+    Size3,
+    USize3,
+    FSize3,
+    [width: 0, height: 1, depth: 2],
+    [depth, height, width],
+    ZERO,
+    "{}x{}x{}"
+);
 
 impl Size {
     /// Returns the area represented by this size
@@ -60,8 +91,49 @@ impl FSize {
     }
 }
 
+impl Size3 {
+    /// Returns the volume represented by this size
+    ///
+    /// # Panics
+    ///
+    /// If the volume doesn't fit in an `i32`.
+    pub fn volume(self) -> i32 {
+        self.checked_volume()
+            .unwrap_or_else(|| panic!("the volume of {} overflows an i32", self))
+    }
+
+    /// Returns the volume represented by this size, or `None` if it doesn't fit in an `i32`.
+    pub fn checked_volume(self) -> Option<i32> {
+        self.width.checked_mul(self.height)?.checked_mul(self.depth)
+    }
+}
+
+impl USize3 {
+    /// Returns the volume represented by this size
+    ///
+    /// # Panics
+    ///
+    /// If the volume doesn't fit in a `u32`.
+    pub fn volume(self) -> u32 {
+        self.checked_volume()
+            .unwrap_or_else(|| panic!("the volume of {} overflows a u32", self))
+    }
+
+    /// Returns the volume represented by this size, or `None` if it doesn't fit in a `u32`.
+    pub fn checked_volume(self) -> Option<u32> {
+        self.width.checked_mul(self.height)?.checked_mul(self.depth)
+    }
+}
+
+impl FSize3 {
+    /// Returns the volume represented by this size
+    pub fn volume(self) -> f32 {
+        self.width * self.height * self.depth
+    }
+}
+
 /// Represents a rectangle, using a position and size.
-#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
 #[cfg_attr(
     feature = "serialization",
     derive(serde_derive::Serialize, serde_derive::Deserialize)
@@ -87,6 +159,56 @@ impl Rectangle {
         }
     }
 
+    /// Returns the smallest rectangle that has the two given positions as opposite corners. The
+    /// positions can be given in any order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle};
+    /// let r = Rectangle::from_corners(Position::new(5, 1), Position::new(2, 4));
+    /// assert_eq!(r, Rectangle::new_from_raw(2, 1, 3, 3));
+    /// ```
+    pub fn from_corners(a: Position, b: Position) -> Self {
+        let x = a.x.min(b.x);
+        let y = a.y.min(b.y);
+
+        Self {
+            position: Position::new(x, y),
+            size: USize::new(
+                (i64::from(a.x) - i64::from(b.x)).unsigned_abs() as u32,
+                (i64::from(a.y) - i64::from(b.y)).unsigned_abs() as u32,
+            ),
+        }
+    }
+
+    /// Returns the center of the rectangle, rounded towards the upper-left corner. A center
+    /// beyond `i32::MAX` saturates at `i32::MAX`.
+    pub fn center(&self) -> Position {
+        Position::new(
+            saturating_offset(self.position.x, i64::from(self.size.width / 2)),
+            saturating_offset(self.position.y, i64::from(self.size.height / 2)),
+        )
+    }
+
+    /// Returns the four corners of the rectangle, in clockwise order starting with the
+    /// upper-left corner.
+    ///
+    /// The corners are the same ones that `contains_position` treats as the rectangle's
+    /// boundary, so passing two opposite corners to `from_corners` gives back this rectangle.
+    /// Corners beyond `i32::MAX` saturate at `i32::MAX`.
+    pub fn corners(&self) -> [Position; 4] {
+        let Position { x, y } = self.position;
+        let right = saturating_offset(x, i64::from(self.size.width));
+        let bottom = saturating_offset(y, i64::from(self.size.height));
+
+        [
+            Position::new(x, y),
+            Position::new(right, y),
+            Position::new(right, bottom),
+            Position::new(x, bottom),
+        ]
+    }
+
     /// Returns whether a given position is within the rectangle or not
     pub fn contains_position(&self, position: Position) -> bool {
         position.x >= self.position.x
@@ -95,6 +217,60 @@ impl Rectangle {
             && position.y <= self.position.y + self.size.height as i32
     }
 
+    /// Returns the position inside the rectangle that is closest to `position`.
+    ///
+    /// The rectangle is treated as a block of `width` by `height` cells, so the result is never
+    /// on the rectangle's right or bottom edge (unlike `contains_position`, which includes them);
+    /// along an axis where the rectangle has a size of 0, the rectangle's own coordinate is used.
+    /// Cells beyond `i32::MAX` can't be represented, so a rectangle reaching past it is cut off
+    /// there.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle};
+    /// let map = Rectangle::new_from_raw(0, 0, 80, 50);
+    /// assert_eq!(map.clamp(Position::new(-5, 20)), Position::new(0, 20));
+    /// assert_eq!(map.clamp(Position::new(100, 60)), Position::new(79, 49));
+    /// ```
+    pub fn clamp(&self, position: Position) -> Position {
+        let Position { x, y } = self.position;
+        let right = saturating_offset(x, i64::from(self.size.width.saturating_sub(1)));
+        let bottom = saturating_offset(y, i64::from(self.size.height.saturating_sub(1)));
+
+        Position::new(position.x.clamp(x, right), position.y.clamp(y, bottom))
+    }
+
+    /// Returns `position` wrapped around the edges of the rectangle, as on a torus; positions
+    /// that fall off one side come back in on the opposite side.
+    ///
+    /// Like `clamp`, the rectangle is treated as a block of `width` by `height` cells, so a
+    /// position on the right edge wraps around to the left edge.
+    ///
+    /// # Panics
+    /// If the rectangle's width or height is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle};
+    /// let world = Rectangle::new_from_raw(0, 0, 80, 50);
+    /// assert_eq!(world.wrap(Position::new(-1, 50)), Position::new(79, 0));
+    /// assert_eq!(world.wrap(Position::new(165, -101)), Position::new(5, 49));
+    /// ```
+    pub fn wrap(&self, position: Position) -> Position {
+        assert!(
+            self.size.width > 0 && self.size.height > 0,
+            "cannot wrap a position into an empty rectangle"
+        );
+        let Position { x, y } = self.position;
+        let width = i64::from(self.size.width);
+        let height = i64::from(self.size.height);
+
+        Position::new(
+            x + (i64::from(position.x) - i64::from(x)).floor_modulo(width) as i32,
+            y + (i64::from(position.y) - i64::from(y)).floor_modulo(height) as i32,
+        )
+    }
+
     /// Returns whether a given position is within the rectangle or not
     pub fn contains_fposition(&self, position: FPosition) -> bool {
         position.x >= self.position.x as f32
@@ -102,6 +278,65 @@ impl Rectangle {
             && position.y >= self.position.y as f32
             && position.y <= self.position.y as f32 + self.size.height as f32
     }
+
+    /// Returns a rectangle that has been grown by `amount` on every side, i.e. its position
+    /// moves up and to the left by `amount`, and its width and height both grow by `2 * amount`.
+    ///
+    /// The position saturates at `i32::MIN` and the size at `u32::MAX` rather than overflowing;
+    /// until the size saturates, the right and bottom edges still move out by the full `amount`.
+    pub fn expand(&self, amount: u32) -> Self {
+        let grow = |start: i32, length: u32| {
+            let amount = i64::from(amount);
+            let end = i64::from(start) + i64::from(length) + amount;
+            let new_start = saturating_offset(start, -amount);
+            let new_length = (end - i64::from(new_start)).min(i64::from(u32::MAX)) as u32;
+            (new_start, new_length)
+        };
+        let (x, width) = grow(self.position.x, self.size.width);
+        let (y, height) = grow(self.position.y, self.size.height);
+
+        Self::new_from_raw(x, y, width, height)
+    }
+
+    /// Returns a rectangle that has been shrunk by `amount` on every side, i.e. its position
+    /// moves down and to the right by `amount`, and its width and height both shrink by
+    /// `2 * amount`.
+    ///
+    /// If the rectangle is too small to be shrunk by the full `amount`, it collapses towards its
+    /// center instead, ending up with a width or height of 0 or 1; it never underflows. The
+    /// position saturates at `i32::MAX` rather than overflowing.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Rectangle;
+    /// let room = Rectangle::new_from_raw(0, 0, 10, 5);
+    /// assert_eq!(room.shrink(1), Rectangle::new_from_raw(1, 1, 8, 3));
+    /// assert_eq!(room.shrink(3), Rectangle::new_from_raw(3, 2, 4, 1));
+    /// ```
+    pub fn shrink(&self, amount: u32) -> Self {
+        let offset_x = amount.min(self.size.width / 2);
+        let offset_y = amount.min(self.size.height / 2);
+
+        Self {
+            position: Position::new(
+                saturating_offset(self.position.x, i64::from(offset_x)),
+                saturating_offset(self.position.y, i64::from(offset_y)),
+            ),
+            size: USize::new(
+                self.size.width - offset_x * 2,
+                self.size.height - offset_y * 2,
+            ),
+        }
+    }
+
+    /// Returns a rectangle of the same size that has been moved by `offset`. The position
+    /// saturates at the bounds of `i32` rather than overflowing.
+    pub fn translated(&self, offset: Position) -> Self {
+        Self {
+            position: self.position.saturating_add(offset),
+            size: self.size,
+        }
+    }
 }
 
 /// Represents a floating-point rectangle, using a position and size.
@@ -168,6 +403,11 @@ impl std::ops::Add<FSize> for FPosition {
     }
 }
 
+// Moves `coordinate` by `distance`, saturating at the bounds of `i32` rather than overflowing.
+fn saturating_offset(coordinate: i32, distance: i64) -> i32 {
+    (i64::from(coordinate) + distance).clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -501,6 +741,49 @@ mod tests {
         assert_eq!(fp, FPosition::new(-1., -1.));
     }
 
+    #[test]
+    fn checked_arithmetic() {
+        let up = UPosition::new(1, 5);
+        let up2 = UPosition::new(2, 3);
+        assert_eq!(up.checked_sub(up2), None);
+        assert_eq!(up2.checked_sub(up), None);
+        assert_eq!(up.checked_add(up2), Some(UPosition::new(3, 8)));
+        assert_eq!(up.saturating_sub(up2), UPosition::new(0, 2));
+        assert_eq!(up.wrapping_sub(up2), UPosition::new(u32::MAX, 2));
+        assert_eq!(
+            UPosition::new(u32::MAX, 0).saturating_add(up),
+            UPosition::new(u32::MAX, 5)
+        );
+        assert_eq!(
+            UPosition::new(u32::MAX, 0).wrapping_add(up),
+            UPosition::new(0, 5)
+        );
+
+        let us = USize::new(3, 1);
+        assert_eq!(us.saturating_sub(USize::new(1, 1)), USize::new(2, 0));
+        assert_eq!(us.checked_sub(USize::new(1, 2)), None);
+
+        let p = Position::new(i32::MIN, 0);
+        assert_eq!(p.checked_sub(Position::new(1, 0)), None);
+        assert_eq!(
+            p.saturating_sub(Position::new(1, 1)),
+            Position::new(i32::MIN, -1)
+        );
+        assert_eq!(
+            p.wrapping_sub(Position::new(1, 0)),
+            Position::new(i32::MAX, 0)
+        );
+
+        assert_eq!(
+            UPosition3::new(1, 2, 3).checked_sub(UPosition3::new(1, 2, 3)),
+            Some(UPosition3::ORIGIN)
+        );
+        assert_eq!(
+            UPosition3::new(1, 2, 3).checked_sub(UPosition3::new(1, 2, 4)),
+            None
+        );
+    }
+
     #[test]
     fn negate() {
         let p = Position::new(-5, -6);
@@ -535,6 +818,158 @@ mod tests {
         assert_eq!(ufp.trunc_u(), UPosition::new(3, 2));
     }
 
+    #[test]
+    fn try_round_and_trunc() {
+        let fp = FPosition::new(-2.5, 2.5);
+        assert_eq!(fp.try_round(), Ok(Position::new(-3, 3)));
+        assert_eq!(fp.try_trunc(), Ok(Position::new(-2, 2)));
+        assert_eq!(fp.try_round_u(), Err(TryFromPositionError::FloatToInt));
+        assert_eq!(fp.try_trunc_u(), Err(TryFromPositionError::FloatToInt));
+
+        let ufp = FPosition::new(2.5, 0.4);
+        assert_eq!(ufp.try_round_u(), Ok(UPosition::new(3, 0)));
+        assert_eq!(ufp.try_trunc_u(), Ok(UPosition::new(2, 0)));
+
+        let big = FPosition::new(1e10, 0.0);
+        assert_eq!(big.try_round(), Err(TryFromPositionError::FloatToInt));
+        assert_eq!(big.try_trunc_u(), Err(TryFromPositionError::FloatToInt));
+
+        assert_eq!(
+            FPosition3::new(0.5, 1.5, -0.2).try_trunc_u(),
+            Ok(UPosition3::new(0, 1, 0))
+        );
+    }
+
+    #[test]
+    fn clamp_and_wrap() {
+        let r = Rectangle::new_from_raw(-2, 3, 4, 2);
+        assert_eq!(r.clamp(Position::new(0, 4)), Position::new(0, 4));
+        assert_eq!(r.clamp(Position::new(-10, 0)), Position::new(-2, 3));
+        assert_eq!(r.clamp(Position::new(2, 5)), Position::new(1, 4));
+
+        let empty = Rectangle::new_from_raw(1, 1, 0, 0);
+        assert_eq!(empty.clamp(Position::new(9, -9)), Position::new(1, 1));
+
+        let huge = Rectangle::new_from_raw(0, i32::MIN, u32::MAX, u32::MAX);
+        assert_eq!(
+            huge.clamp(Position::new(i32::MAX, i32::MIN)),
+            Position::new(i32::MAX, i32::MIN)
+        );
+        assert_eq!(huge.clamp(Position::new(-1, 5)), Position::new(0, 5));
+
+        assert_eq!(r.wrap(Position::new(0, 4)), Position::new(0, 4));
+        assert_eq!(r.wrap(Position::new(2, 5)), Position::new(-2, 3));
+        assert_eq!(r.wrap(Position::new(-3, 2)), Position::new(1, 4));
+        assert_eq!(
+            r.wrap(Position::new(i32::MIN, i32::MAX)),
+            Position::new(0, 3)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrap_into_empty_rectangle_panics() {
+        Rectangle::new_from_raw(0, 0, 0, 5).wrap(Position::ORIGIN);
+    }
+
+    #[test]
+    fn component_wise_min_max_clamp_abs() {
+        let a = Position::new(-3, 7);
+        let b = Position::new(2, -1);
+        assert_eq!(a.component_min(b), Position::new(-3, -1));
+        assert_eq!(a.component_max(b), Position::new(2, 7));
+        assert_eq!(
+            Position::new(-10, 10).component_clamp(a.component_min(b), a.component_max(b)),
+            Position::new(-3, 7)
+        );
+        assert_eq!(a.abs(), Position::new(3, 7));
+
+        // The Ord methods still compare in row-major order.
+        assert_eq!(a.min(b), b);
+        assert_eq!(a.max(b), a);
+
+        // Inverted bounds are swapped rather than panicking.
+        assert_eq!(
+            Position::new(-10, 10).component_clamp(a, b),
+            Position::new(-3, 7)
+        );
+        assert_eq!(
+            FPosition::new(0.5, 9.0)
+                .component_clamp(FPosition::new(1.0, 0.0), FPosition::new(0.0, 1.0)),
+            FPosition::new(0.5, 1.0)
+        );
+
+        assert_eq!(
+            USize::new(4, 1).component_max(USize::new(2, 3)),
+            USize::new(4, 3)
+        );
+        assert_eq!(
+            FSize::new(-1.5, 2.0)
+                .abs()
+                .component_min(FSize::new(1.0, 5.0)),
+            FSize::new(1.0, 2.0)
+        );
+        assert_eq!(
+            UPosition3::new(0, 5, 9)
+                .component_clamp(UPosition3::new(1, 1, 1), UPosition3::new(4, 4, 4)),
+            UPosition3::new(1, 4, 4)
+        );
+    }
+
+    #[test]
+    fn symmetric_and_element_wise_ops() {
+        let p = Position::new(2, -3);
+        assert_eq!(1 + p, p + 1);
+        assert_eq!(3 * p, Position::new(6, -9));
+        assert_eq!(p * Position::new(-1, 2), Position::new(-2, -6));
+
+        let mut s = USize::new(3, 4);
+        s *= USize::new(2, 5);
+        assert_eq!(s, USize::new(6, 20));
+        assert_eq!(2 * s, s * 2);
+
+        assert_eq!(
+            0.5 * FPosition3::new(1.0, 2.0, 4.0),
+            FPosition3::new(0.5, 1.0, 2.0)
+        );
+        assert_eq!(
+            Size3::new(1, 2, 3) * Size3::new(3, 2, 1),
+            Size3::new(3, 4, 3)
+        );
+    }
+
+    #[test]
+    fn row_major_ordering() {
+        let mut positions = vec![
+            Position::new(2, 1),
+            Position::new(0, 2),
+            Position::new(1, 1),
+            Position::new(5, 0),
+        ];
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![
+                Position::new(5, 0),
+                Position::new(1, 1),
+                Position::new(2, 1),
+                Position::new(0, 2),
+            ]
+        );
+
+        assert!(USize::new(9, 1) < USize::new(0, 2));
+        assert!(UPosition3::new(9, 9, 0) < UPosition3::new(0, 0, 1));
+
+        let set: std::collections::HashSet<_> =
+            positions.iter().chain(positions.iter()).copied().collect();
+        assert_eq!(set.len(), 4);
+        let map: std::collections::BTreeMap<_, _> = positions
+            .iter()
+            .map(|&p| (UPosition::try_from(p).unwrap(), ()))
+            .collect();
+        assert_eq!(map.keys().next(), Some(&UPosition::new(5, 0)));
+    }
+
     #[test]
     #[should_panic]
     fn trunc_u_less_than_zero_panics() {
@@ -586,6 +1021,240 @@ mod tests {
         }
     }
 
+    #[test]
+    fn expand_shrink_translate() {
+        let r = Rectangle::new_from_raw(2, 3, 6, 4);
+
+        assert_eq!(r.expand(1), Rectangle::new_from_raw(1, 2, 8, 6));
+        assert_eq!(r.expand(0), r);
+        assert_eq!(r.shrink(1), Rectangle::new_from_raw(3, 4, 4, 2));
+        assert_eq!(r.shrink(1).expand(1), r);
+
+        // Shrinking more than the rectangle allows collapses it instead of underflowing.
+        assert_eq!(r.shrink(10), Rectangle::new_from_raw(5, 5, 0, 0));
+        let odd = Rectangle::new_from_raw(0, 0, 5, 3);
+        assert_eq!(odd.shrink(10), Rectangle::new_from_raw(2, 1, 1, 1));
+
+        let huge = Rectangle::new_from_raw(i32::MIN, 0, u32::MAX, 1);
+        assert_eq!(
+            huge.expand(5),
+            Rectangle::new_from_raw(i32::MIN, -5, u32::MAX, 11)
+        );
+
+        // Expanding past i32::MIN keeps the right edge where it should be.
+        let left = Rectangle::new_from_raw(i32::MIN + 2, 0, 10, 1);
+        assert_eq!(
+            left.expand(5),
+            Rectangle::new_from_raw(i32::MIN, -5, 17, 11)
+        );
+        let shrunk = Rectangle::new_from_raw(i32::MAX, i32::MAX, u32::MAX, 10).shrink(3);
+        assert_eq!(shrunk.position, Position::new(i32::MAX, i32::MAX));
+        assert_eq!(shrunk.size, USize::new(u32::MAX - 6, 4));
+
+        assert_eq!(
+            r.translated(Position::new(-2, 5)),
+            Rectangle::new_from_raw(0, 8, 6, 4)
+        );
+        assert_eq!(
+            r.translated(Position::new(i32::MIN, i32::MAX)).position,
+            Position::new(i32::MIN + 2, i32::MAX)
+        );
+    }
+
+    #[test]
+    fn center_and_corners() {
+        let r = Rectangle::new_from_raw(-2, 1, 5, 4);
+        assert_eq!(r.center(), Position::new(0, 3));
+        assert_eq!(
+            r.corners(),
+            [
+                Position::new(-2, 1),
+                Position::new(3, 1),
+                Position::new(3, 5),
+                Position::new(-2, 5),
+            ]
+        );
+
+        let [top_left, top_right, bottom_right, bottom_left] = r.corners();
+        assert_eq!(Rectangle::from_corners(top_left, bottom_right), r);
+        assert_eq!(Rectangle::from_corners(bottom_right, top_left), r);
+        assert_eq!(Rectangle::from_corners(top_right, bottom_left), r);
+        let widest =
+            Rectangle::from_corners(Position::new(i32::MIN, 0), Position::new(i32::MAX, 0));
+        assert_eq!(widest, Rectangle::new_from_raw(i32::MIN, 0, u32::MAX, 0));
+        assert_eq!(widest.corners()[2], Position::new(i32::MAX, 0));
+        assert_eq!(widest.center(), Position::new(-1, 0));
+
+        let past_the_edge = Rectangle::new_from_raw(i32::MAX - 1, i32::MAX, 10, u32::MAX);
+        assert_eq!(past_the_edge.center(), Position::new(i32::MAX, i32::MAX));
+        assert_eq!(
+            past_the_edge.corners()[2],
+            Position::new(i32::MAX, i32::MAX)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn three_property_types() {
+        let p = Position3::new(-1, 2, -3);
+        assert_eq!((p.x, p.y, p.z), (-1, 2, -3));
+        assert_eq!(p.to_string(), "(-1, 2, -3)");
+        assert_eq!(p + Position3::new(1, 1, 1), Position3::new(0, 3, -2));
+        assert_eq!(p - (1, 2, 3), Position3::new(-2, 0, -6));
+        assert_eq!(p * 2, Position3::new(-2, 4, -6));
+        assert_eq!(-p, Position3::new(1, -2, 3));
+        assert_eq!(<(i32, i32, i32)>::from(p), (-1, 2, -3));
+        assert_eq!(Position3::from((-1, 2, -3)), p);
+
+        let mut up = UPosition3::new(1, 2, 3);
+        up += 1;
+        assert_eq!(up, UPosition3::new(2, 3, 4));
+        assert_eq!(up.to_string(), "(2, 3, 4)");
+
+        let fp = FPosition3::new(1.5, -2.5, 0.4);
+        assert_eq!(fp.round(), Position3::new(2, -3, 0));
+        assert_eq!(fp.trunc(), Position3::new(1, -2, 0));
+        assert_eq!(fp / 0.5, FPosition3::new(3.0, -5.0, 0.8));
+
+        let s = USize3::new(2, 3, 4);
+        assert_eq!(s.to_string(), "2x3x4");
+        assert_eq!(s.volume(), 24);
+        assert_eq!(Size3::new(-1, 2, 3).volume(), -6);
+        assert_eq!(FSize3::new(0.5, 2.0, 3.0).volume(), 3.0);
+    }
+
+    #[test]
+    fn three_property_volume_overflow() {
+        assert_eq!(USize3::new(2, 3, 4).checked_volume(), Some(24));
+        assert_eq!(USize3::new(u32::MAX, 1, 1).checked_volume(), Some(u32::MAX));
+        assert_eq!(USize3::new(65_536, 65_536, 1).checked_volume(), None);
+        assert_eq!(USize3::new(2, 2, u32::MAX / 2).checked_volume(), None);
+        assert_eq!(Size3::new(-2, 3, 4).checked_volume(), Some(-24));
+        assert_eq!(Size3::new(i32::MIN, -1, 1).checked_volume(), None);
+        assert_eq!(Size3::new(i32::MIN, 1, 1).checked_volume(), Some(i32::MIN));
+    }
+
+    #[test]
+    #[should_panic(expected = "the volume of 2097152x2097152x2 overflows a u32")]
+    fn three_property_volume_panics_on_overflow() {
+        USize3::new(1 << 21, 1 << 21, 2).volume();
+    }
+
+    #[test]
+    fn three_property_checked_arithmetic() {
+        let p = Position3::new(i32::MAX, 0, i32::MIN);
+        assert_eq!(
+            p.checked_add(Position3::new(0, 1, 0)),
+            Some(Position3::new(i32::MAX, 1, i32::MIN))
+        );
+        assert_eq!(p.checked_add(Position3::new(1, 0, 0)), None);
+        assert_eq!(p.checked_sub(Position3::new(0, 0, 1)), None);
+        assert_eq!(
+            p.saturating_add(Position3::new(1, 1, -1)),
+            Position3::new(i32::MAX, 1, i32::MIN)
+        );
+        assert_eq!(
+            p.wrapping_add(Position3::new(1, 0, -1)),
+            Position3::new(i32::MIN, 0, i32::MAX)
+        );
+
+        let u = UPosition3::new(0, 5, u32::MAX);
+        assert_eq!(u.checked_sub(UPosition3::new(1, 0, 0)), None);
+        assert_eq!(u.checked_add(UPosition3::new(0, 0, 1)), None);
+        assert_eq!(
+            u.saturating_sub(UPosition3::new(1, 9, 0)),
+            UPosition3::new(0, 0, u32::MAX)
+        );
+        assert_eq!(
+            u.wrapping_sub(UPosition3::new(1, 0, 0)),
+            UPosition3::new(u32::MAX, 5, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn three_property_ordering_and_component_wise_ops() {
+        // Row-major across layers: depth first, then rows, then columns.
+        let mut positions = vec![
+            Position3::new(0, 0, 1),
+            Position3::new(1, 0, 0),
+            Position3::new(0, 1, 0),
+            Position3::new(-1, 0, 0),
+        ];
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![
+                Position3::new(-1, 0, 0),
+                Position3::new(1, 0, 0),
+                Position3::new(0, 1, 0),
+                Position3::new(0, 0, 1),
+            ]
+        );
+
+        let a = Position3::new(-3, 7, 0);
+        let b = Position3::new(2, -1, 5);
+        assert_eq!(a.component_min(b), Position3::new(-3, -1, 0));
+        assert_eq!(a.component_max(b), Position3::new(2, 7, 5));
+        assert_eq!(a.abs(), Position3::new(3, 7, 0));
+        // Inverted bounds are swapped rather than panicking.
+        assert_eq!(
+            Position3::new(9, 9, 9).component_clamp(b, a),
+            Position3::new(2, 7, 5)
+        );
+        assert_eq!(
+            FSize3::new(-1.5, 2.0, 0.5)
+                .abs()
+                .component_min(FSize3::new(1.0, 5.0, 0.25)),
+            FSize3::new(1.0, 2.0, 0.25)
+        );
+    }
+
+    #[test]
+    fn three_property_fallible_rounding() {
+        let fp = FPosition3::new(1.5, -2.5, 0.4);
+        assert_eq!(fp.try_round(), Ok(Position3::new(2, -3, 0)));
+        assert_eq!(fp.try_trunc(), Ok(Position3::new(1, -2, 0)));
+        assert!(fp.try_round_u().is_err());
+        assert!(fp.try_trunc_u().is_err());
+        assert_eq!(
+            FPosition3::new(1.5, 2.5, 0.4).try_round_u(),
+            Ok(UPosition3::new(2, 3, 0))
+        );
+        assert!(FPosition3::new(0.0, 0.0, f32::NAN).try_round().is_err());
+        assert!(FPosition3::new(0.0, f32::NAN, 0.0).try_trunc_u().is_err());
+        assert!(FPosition3::new(0.0, 1e10, 0.0).try_trunc().is_err());
+        assert!(FPosition3::new(2_147_483_648.0, 0.0, 0.0)
+            .try_trunc()
+            .is_err());
+        assert!(FPosition3::new(0.0, 0.0, 4_294_967_296.0)
+            .try_trunc_u()
+            .is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn three_property_round_u_panics_on_negative() {
+        FPosition3::new(1.0, 1.0, -0.5).round_u();
+    }
+
+    #[test]
+    fn three_property_conversions() {
+        use std::convert::TryFrom;
+
+        let p = Position3::new(1, 2, 3);
+        assert_eq!(UPosition3::try_from(p), Ok(UPosition3::new(1, 2, 3)));
+        assert!(UPosition3::try_from(Position3::new(1, 2, -3)).is_err());
+        assert_eq!(FPosition3::from(p), FPosition3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            FPosition3::from(UPosition3::new(1, 2, 3)),
+            FPosition3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            Position3::try_from(FPosition3::new(1.0, 2.0, u32::MAX as f32)),
+            Err(TryFromPosition3Error::FloatToInt)
+        );
+    }
+
     #[test]
     fn from_position_conversions() {
         use std::convert::TryFrom;
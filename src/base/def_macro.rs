@@ -30,685 +30,457 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-macro_rules! define_two_property_arithmetic_struct {
-    ($name:ident, $uname:ident, $fname: ident, $field1:ident, $field2:ident, $zero_constant:ident, $format_string:expr) => {
-        define_two_property_arithmetic_struct!(@IMPL $name, $uname, $fname, stringify!($name), $field1, $field2, stringify!($field1), stringify!($field2), $zero_constant, $format_string);
-    };
-    (@IMPL $name:ident, $uname:ident, $fname: ident, $name_str:expr, $field1:ident, $field2:ident, $field1_str:expr, $field2_str:expr, $zero_constant:ident, $format_string:expr) => {
-        #[doc = "A struct representing a"]
-        #[doc = $name_str]
-        #[doc = "determined by its `"]
-        #[doc = $field1_str]
-        #[doc = "` and `"]
-        #[doc = $field2_str]
-        #[doc = "` values."]
-        #[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
-        #[cfg_attr(feature = "serialization", derive(::serde_derive::Serialize, ::serde_derive::Deserialize))]
-        pub struct $name {
-            /// The `
-            #[doc = $field1_str]
-            /// ` value the `
-            #[doc = $name_str]
-            /// ` is currently representing.
-            pub $field1: i32,
-
-            /// The `
-            #[doc = $field2_str]
-            /// ` value the `
-            #[doc = $name_str]
-            /// ` is currently representing.
-            pub $field2: i32,
-        }
-
+macro_rules! define_checked_integer_arithmetic {
+    ($name:ident, $($field:ident),+) => {
         impl $name {
-            /// A constant representing a `
-            #[doc = $name_str]
-            /// ` where both `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` are 0.
-            pub const $zero_constant: Self = Self {
-                $field1: 0,
-                $field2: 0,
-            };
-
-            /// Returns a `
-            #[doc = $name_str]
-            /// ` with the given `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` values.
-            pub const fn new($field1: i32, $field2: i32) -> Self {
-                Self { $field1, $field2 }
-            }
-        }
-
-        impl From<$name> for (i32, i32) {
-            fn from(f: $name) -> Self {
-                (f.$field1, f.$field2)
-            }
-        }
-
-        impl From<(i32, i32)> for $name {
-            fn from(t: (i32, i32)) -> Self {
-                Self::new(t.0, t.1)
-            }
-        }
-
-        impl std::fmt::Display for $name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, $format_string, self.$field1, self.$field2)
-            }
-        }
-
-        impl std::ops::Add for $name {
-            type Output = Self;
-
-            fn add(self, rhs: Self) -> Self::Output {
-                Self {
-                    $field1: self.$field1 + rhs.$field1,
-                    $field2: self.$field2 + rhs.$field2,
-                }
-            }
-        }
-
-        impl std::ops::Add<i32> for $name {
-            type Output = Self;
-
-            fn add(self, rhs: i32) -> Self::Output {
-                Self {
-                    $field1: self.$field1 + rhs,
-                    $field2: self.$field2 + rhs,
-                }
-            }
-        }
-
-        impl std::ops::Add<(i32, i32)> for $name {
-            type Output = Self;
-
-            fn add(self, rhs: (i32, i32)) -> Self::Output {
-                Self {
-                    $field1: self.$field1 + rhs.0,
-                    $field2: self.$field2 + rhs.1,
-                }
-            }
-        }
-
-        impl std::ops::AddAssign<i32> for $name {
-            fn add_assign(&mut self, rhs: i32) {
-                self.$field1 += rhs;
-                self.$field2 += rhs;
-            }
-        }
-
-        impl std::ops::AddAssign<(i32, i32)> for $name {
-            fn add_assign(&mut self, rhs: (i32, i32)) {
-                self.$field1 += rhs.0;
-                self.$field2 += rhs.1;
-            }
-        }
-
-        impl std::ops::Sub for $name {
-            type Output = Self;
-
-            fn sub(self, rhs: Self) -> Self::Output {
-                Self {
-                    $field1: self.$field1 - rhs.$field1,
-                    $field2: self.$field2 - rhs.$field2,
-                }
-            }
-        }
-
-        impl std::ops::Sub<i32> for $name {
-            type Output = Self;
-
-            fn sub(self, rhs: i32) -> Self::Output {
-                Self {
-                    $field1: self.$field1 - rhs,
-                    $field2: self.$field2 - rhs,
-                }
-            }
-        }
-
-        impl std::ops::Sub<(i32, i32)> for $name {
-            type Output = Self;
-
-            fn sub(self, rhs: (i32, i32)) -> Self::Output {
-                Self {
-                    $field1: self.$field1 - rhs.0,
-                    $field2: self.$field2 - rhs.1,
-                }
-            }
-        }
-
-        impl std::ops::SubAssign<i32> for $name {
-            fn sub_assign(&mut self, rhs: i32) {
-                self.$field1 -= rhs;
-                self.$field2 -= rhs;
+            /// Checked addition. Returns `None` if any of the components overflow.
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                Some(Self {
+                    $($field: self.$field.checked_add(rhs.$field)?),+
+                })
             }
-        }
 
-        impl std::ops::SubAssign<(i32, i32)> for $name {
-            fn sub_assign(&mut self, rhs: (i32, i32)) {
-                self.$field1 -= rhs.0;
-                self.$field2 -= rhs.1;
+            /// Checked subtraction. Returns `None` if any of the components overflow or
+            /// underflow.
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                Some(Self {
+                    $($field: self.$field.checked_sub(rhs.$field)?),+
+                })
             }
-        }
-
-        impl std::ops::Mul<i32> for $name {
-            type Output = Self;
 
-            fn mul(self, rhs: i32) -> Self::Output {
+            /// Saturating addition. Each component saturates at its numeric bounds instead of
+            /// overflowing.
+            pub fn saturating_add(self, rhs: Self) -> Self {
                 Self {
-                    $field1: self.$field1 * rhs,
-                    $field2: self.$field2 * rhs,
+                    $($field: self.$field.saturating_add(rhs.$field)),+
                 }
             }
-        }
 
-        impl std::ops::MulAssign<i32> for $name {
-            fn mul_assign(&mut self, rhs: i32) {
-                self.$field1 *= rhs;
-                self.$field2 *= rhs;
-            }
-        }
-
-        impl std::ops::Div<i32> for $name {
-            type Output = Self;
-
-            fn div(self, rhs: i32) -> Self::Output {
+            /// Saturating subtraction. Each component saturates at its numeric bounds instead of
+            /// overflowing or underflowing.
+            pub fn saturating_sub(self, rhs: Self) -> Self {
                 Self {
-                    $field1: self.$field1 / rhs,
-                    $field2: self.$field2 / rhs,
+                    $($field: self.$field.saturating_sub(rhs.$field)),+
                 }
             }
-        }
-
-        impl std::ops::DivAssign<i32> for $name {
-            fn div_assign(&mut self, rhs: i32) {
-                self.$field1 /= rhs;
-                self.$field2 /= rhs;
-            }
-        }
-
-        impl std::ops::Rem<i32> for $name {
-            type Output = Self;
 
-            fn rem(self, rhs: i32) -> Self::Output {
+            /// Wrapping (modular) addition. Each component wraps around at its numeric bounds.
+            pub fn wrapping_add(self, rhs: Self) -> Self {
                 Self {
-                    $field1: self.$field1 % rhs,
-                    $field2: self.$field2 % rhs,
+                    $($field: self.$field.wrapping_add(rhs.$field)),+
                 }
             }
-        }
-
-        impl std::ops::RemAssign<i32> for $name {
-            fn rem_assign(&mut self, rhs: i32) {
-                self.$field1 %= rhs;
-                self.$field2 %= rhs;
-            }
-        }
 
-        impl std::ops::Neg for $name {
-            type Output = Self;
-
-            fn neg(self) -> Self::Output {
+            /// Wrapping (modular) subtraction. Each component wraps around at its numeric bounds.
+            pub fn wrapping_sub(self, rhs: Self) -> Self {
                 Self {
-                    $field1: -self.$field1,
-                    $field2: -self.$field2,
+                    $($field: self.$field.wrapping_sub(rhs.$field)),+
                 }
             }
         }
+    };
+}
 
-        // Unsigned version:
-
-        #[doc = "A struct representing an unsigned"]
-        #[doc = $name_str]
-        #[doc = "determined by its `"]
-        #[doc = $field1_str]
-        #[doc = "` and `"]
-        #[doc = $field2_str]
-        #[doc = "` values."]
-        #[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
-        #[cfg_attr(feature = "serialization", derive(::serde_derive::Serialize, ::serde_derive::Deserialize))]
-        pub struct $uname {
-            /// The `
-            #[doc = $field1_str]
-            /// ` value the `
-            #[doc = $name_str]
-            /// ` is currently representing.
-            pub $field1: u32,
-
-            /// The `
-            #[doc = $field2_str]
-            /// ` value the `
-            #[doc = $name_str]
-            /// ` is currently representing.
-            pub $field2: u32,
-        }
-
-        impl $uname {
-            /// A constant representing a `
-            #[doc = $name_str]
-            /// ` where both `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` are 0.
-            pub const $zero_constant: Self = Self {
-                $field1: 0,
-                $field2: 0,
-            };
-
-            /// Returns a `
-            #[doc = $name_str]
-            /// ` with the given `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` values.
-            pub const fn new($field1: u32, $field2: u32) -> Self {
-                Self { $field1, $field2 }
-            }
-        }
-
-        impl From<$uname> for (u32, u32) {
-            fn from(f: $uname) -> Self {
-                (f.$field1, f.$field2)
-            }
-        }
-
-        impl From<(u32, u32)> for $uname {
-            fn from(t: (u32, u32)) -> Self {
-                Self::new(t.0, t.1)
-            }
-        }
-
-        impl std::fmt::Display for $uname {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, $format_string, self.$field1, self.$field2)
+macro_rules! define_row_major_ordering {
+    ($name:ident, $($field:ident),+) => {
+        /// Orders values in row-major order; the fields are compared from the last one to the
+        /// first one, so e.g. positions sort top-to-bottom, then left-to-right.
+        impl ::std::cmp::Ord for $name {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                ::std::cmp::Ordering::Equal
+                    $(.then_with(|| self.$field.cmp(&other.$field)))+
             }
         }
 
-        impl std::ops::Add for $uname {
-            type Output = Self;
-
-            fn add(self, rhs: Self) -> Self::Output {
-                Self {
-                    $field1: self.$field1 + rhs.$field1,
-                    $field2: self.$field2 + rhs.$field2,
-                }
+        impl ::std::cmp::PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(self.cmp(other))
             }
         }
+    };
+}
 
-        impl std::ops::Add<u32> for $uname {
-            type Output = Self;
-
-            fn add(self, rhs: u32) -> Self::Output {
+macro_rules! define_component_wise_min_max {
+    ($name:ident, $($field:ident),+) => {
+        impl $name {
+            /// Returns the component-wise minimum of `self` and `other`.
+            ///
+            /// Unlike `Ord::min`, which compares whole values in row-major order, this picks the
+            /// smaller value of each component separately.
+            pub fn component_min(self, other: Self) -> Self {
                 Self {
-                    $field1: self.$field1 + rhs,
-                    $field2: self.$field2 + rhs,
+                    $($field: self.$field.min(other.$field)),+
                 }
             }
-        }
-
-        impl std::ops::Add<(u32, u32)> for $uname {
-            type Output = Self;
 
-            fn add(self, rhs: (u32, u32)) -> Self::Output {
+            /// Returns the component-wise maximum of `self` and `other`.
+            ///
+            /// Unlike `Ord::max`, which compares whole values in row-major order, this picks the
+            /// larger value of each component separately.
+            pub fn component_max(self, other: Self) -> Self {
                 Self {
-                    $field1: self.$field1 + rhs.0,
-                    $field2: self.$field2 + rhs.1,
+                    $($field: self.$field.max(other.$field)),+
                 }
             }
-        }
 
-        impl std::ops::AddAssign<u32> for $uname {
-            fn add_assign(&mut self, rhs: u32) {
-                self.$field1 += rhs;
-                self.$field2 += rhs;
-            }
-        }
-
-        impl std::ops::AddAssign<(u32, u32)> for $uname {
-            fn add_assign(&mut self, rhs: (u32, u32)) {
-                self.$field1 += rhs.0;
-                self.$field2 += rhs.1;
-            }
-        }
-
-        impl std::ops::Sub for $uname {
-            type Output = Self;
-
-            fn sub(self, rhs: Self) -> Self::Output {
+            /// Restricts each component to the range given by the matching components of `min`
+            /// and `max`.
+            ///
+            /// If a component of `min` is greater than the matching component of `max`, the two
+            /// bounds are swapped for that component, so this never panics.
+            pub fn component_clamp(self, min: Self, max: Self) -> Self {
                 Self {
-                    $field1: self.$field1 - rhs.$field1,
-                    $field2: self.$field2 - rhs.$field2,
+                    $($field: {
+                        let (low, high) = if min.$field > max.$field {
+                            (max.$field, min.$field)
+                        } else {
+                            (min.$field, max.$field)
+                        };
+                        self.$field.max(low).min(high)
+                    }),+
                 }
             }
         }
+    };
+}
 
-        impl std::ops::Sub<u32> for $uname {
-            type Output = Self;
-
-            fn sub(self, rhs: u32) -> Self::Output {
+macro_rules! define_component_wise_abs {
+    ($name:ident, $($field:ident),+) => {
+        impl $name {
+            /// Returns the component-wise absolute value of `self`.
+            pub fn abs(self) -> Self {
                 Self {
-                    $field1: self.$field1 - rhs,
-                    $field2: self.$field2 - rhs,
+                    $($field: self.$field.abs()),+
                 }
             }
         }
+    };
+}
 
-        impl std::ops::Sub<(u32, u32)> for $uname {
-            type Output = Self;
+macro_rules! define_symmetric_and_element_wise_ops {
+    ($name:ident, $scalar:ty, $($field:ident),+) => {
+        impl std::ops::Add<$name> for $scalar {
+            type Output = $name;
 
-            fn sub(self, rhs: (u32, u32)) -> Self::Output {
-                Self {
-                    $field1: self.$field1 - rhs.0,
-                    $field2: self.$field2 - rhs.1,
-                }
+            fn add(self, rhs: $name) -> Self::Output {
+                rhs + self
             }
         }
 
-        impl std::ops::SubAssign<u32> for $uname {
-            fn sub_assign(&mut self, rhs: u32) {
-                self.$field1 -= rhs;
-                self.$field2 -= rhs;
-            }
-        }
+        impl std::ops::Mul<$name> for $scalar {
+            type Output = $name;
 
-        impl std::ops::SubAssign<(u32, u32)> for $uname {
-            fn sub_assign(&mut self, rhs: (u32, u32)) {
-                self.$field1 -= rhs.0;
-                self.$field2 -= rhs.1;
+            fn mul(self, rhs: $name) -> Self::Output {
+                rhs * self
             }
         }
 
-        impl std::ops::Mul<u32> for $uname {
+        /// Element-wise multiplication.
+        impl std::ops::Mul for $name {
             type Output = Self;
 
-            fn mul(self, rhs: u32) -> Self::Output {
+            fn mul(self, rhs: Self) -> Self::Output {
                 Self {
-                    $field1: self.$field1 * rhs,
-                    $field2: self.$field2 * rhs,
+                    $($field: self.$field * rhs.$field),+
                 }
             }
         }
 
-        impl std::ops::MulAssign<u32> for $uname {
-            fn mul_assign(&mut self, rhs: u32) {
-                self.$field1 *= rhs;
-                self.$field2 *= rhs;
+        /// Element-wise multiplication.
+        impl std::ops::MulAssign for $name {
+            fn mul_assign(&mut self, rhs: Self) {
+                $(self.$field *= rhs.$field;)+
             }
         }
+    };
+}
 
-        impl std::ops::Div<u32> for $uname {
-            type Output = Self;
-
-            fn div(self, rhs: u32) -> Self::Output {
-                Self {
-                    $field1: self.$field1 / rhs,
-                    $field2: self.$field2 / rhs,
-                }
+macro_rules! define_fallible_float_rounding {
+    ($fname:ident, $name:ident, $uname:ident, $error:ident, $($field:ident),+) => {
+        impl $fname {
+            /// Like `round`, but returns an error instead of silently saturating if any of the
+            /// rounded components is outside the range of an `i32`.
+            pub fn try_round(self) -> Result<$name, $error> {
+                TryFrom::try_from(Self {
+                    $($field: self.$field.round()),+
+                })
             }
-        }
 
-        impl std::ops::DivAssign<u32> for $uname {
-            fn div_assign(&mut self, rhs: u32) {
-                self.$field1 /= rhs;
-                self.$field2 /= rhs;
+            /// Like `round_u`, but returns an error instead of panicking if any of the rounded
+            /// components is negative or outside the range of a `u32`.
+            pub fn try_round_u(self) -> Result<$uname, $error> {
+                TryFrom::try_from(Self {
+                    $($field: self.$field.round()),+
+                })
             }
-        }
 
-        impl std::ops::Rem<u32> for $uname {
-            type Output = Self;
-
-            fn rem(self, rhs: u32) -> Self::Output {
-                Self {
-                    $field1: self.$field1 % rhs,
-                    $field2: self.$field2 % rhs,
-                }
+            /// Like `trunc`, but returns an error instead of silently saturating if any of the
+            /// truncated components is outside the range of an `i32`.
+            pub fn try_trunc(self) -> Result<$name, $error> {
+                TryFrom::try_from(Self {
+                    $($field: self.$field.trunc()),+
+                })
             }
-        }
 
-        impl std::ops::RemAssign<u32> for $uname {
-            fn rem_assign(&mut self, rhs: u32) {
-                self.$field1 %= rhs;
-                self.$field2 %= rhs;
+            /// Like `trunc_u`, but returns an error instead of panicking if any of the truncated
+            /// components is negative or outside the range of a `u32`.
+            pub fn try_trunc_u(self) -> Result<$uname, $error> {
+                TryFrom::try_from(Self {
+                    $($field: self.$field.trunc()),+
+                })
             }
         }
+    };
+}
 
-        // Floating-point version
-
-        #[doc = "A struct representing a floating-point"]
+// Defines a signed, an unsigned and a floating-point struct sharing the given fields. Each field is
+// paired with its index in the matching tuple type, and the fields are listed a second time in the
+// order they should be compared in for the row-major ordering, e.g.
+// `define_arithmetic_struct!(Position, UPosition, FPosition, [x: 0, y: 1], [y, x], ORIGIN, "({}, {})")`.
+macro_rules! define_arithmetic_struct {
+    ($name:ident, $uname:ident, $fname:ident, [$field1:ident: $index1:tt, $field2:ident: $index2:tt], [$($ordered_field:ident),+], $zero_constant:ident, $format_string:expr) => {
+        define_arithmetic_struct!(
+            @IMPL $name, $uname, $fname, stringify!($name),
+            [$field1: $index1: stringify!($field1), $field2: $index2: stringify!($field2)],
+            [$($ordered_field),+],
+            ["`", stringify!($field1), "` and `", stringify!($field2), "`"],
+            ["` where both `", stringify!($field1), "` and `", stringify!($field2), "` are 0."],
+            $zero_constant, $format_string
+        );
+    };
+    ($name:ident, $uname:ident, $fname:ident, [$field1:ident: $index1:tt, $field2:ident: $index2:tt, $field3:ident: $index3:tt], [$($ordered_field:ident),+], $zero_constant:ident, $format_string:expr) => {
+        define_arithmetic_struct!(
+            @IMPL $name, $uname, $fname, stringify!($name),
+            [$field1: $index1: stringify!($field1), $field2: $index2: stringify!($field2), $field3: $index3: stringify!($field3)],
+            [$($ordered_field),+],
+            ["`", stringify!($field1), "`, `", stringify!($field2), "` and `", stringify!($field3), "`"],
+            ["` where `", stringify!($field1), "`, `", stringify!($field2), "` and `", stringify!($field3), "` are all 0."],
+            $zero_constant, $format_string
+        );
+    };
+    (@SCALAR $field:ident, $scalar:ty) => {
+        $scalar
+    };
+    (@TYPE $type:ident, $scalar:ident, $zero:expr, $summary:expr, $name_str:expr, [$($field:ident: $index:tt: $field_str:expr),+], [$($fields_doc:expr),+], [$($zero_doc:expr),+], $zero_constant:ident, $format_string:expr, [$($derive:ident),+]) => {
+        #[doc = $summary]
         #[doc = $name_str]
-        #[doc = "determined by its `"]
-        #[doc = $field1_str]
-        #[doc = "` and `"]
-        #[doc = $field2_str]
-        #[doc = "` values."]
-        #[derive(Copy, Clone, Default, PartialEq, Debug)]
+        /// determined by its
+        $(#[doc = $fields_doc])+
+        /// values.
+        #[derive($($derive),+)]
         #[cfg_attr(feature = "serialization", derive(::serde_derive::Serialize, ::serde_derive::Deserialize))]
-        pub struct $fname {
-            /// The `
-            #[doc = $field1_str]
-            /// ` value the `
-            #[doc = $name_str]
-            /// ` is currently representing.
-            pub $field1: f32,
-
-            /// The `
-            #[doc = $field2_str]
-            /// ` value the `
-            #[doc = $name_str]
-            /// ` is currently representing.
-            pub $field2: f32,
-        }
-
-        impl $fname {
+        pub struct $type {
+            $(
+                /// The `
+                #[doc = $field_str]
+                /// ` value the `
+                #[doc = $name_str]
+                /// ` is currently representing.
+                pub $field: $scalar,
+            )+
+        }
+
+        impl $type {
             /// A constant representing a `
             #[doc = $name_str]
-            /// ` where both `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` are 0.
+            $(#[doc = $zero_doc])+
             pub const $zero_constant: Self = Self {
-                $field1: 0.0,
-                $field2: 0.0,
+                $($field: $zero),+
             };
 
             /// Returns a `
             #[doc = $name_str]
-            /// ` with the given `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` values.
-            pub const fn new($field1: f32, $field2: f32) -> Self {
-                Self { $field1, $field2 }
+            /// ` with the given
+            $(#[doc = $fields_doc])+
+            /// values.
+            pub const fn new($($field: $scalar),+) -> Self {
+                Self { $($field),+ }
             }
         }
 
-        impl From<$fname> for (f32, f32) {
-            fn from(f: $fname) -> Self {
-                (f.$field1, f.$field2)
+        impl From<$type> for ($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+) {
+            fn from(f: $type) -> Self {
+                ($(f.$field),+)
             }
         }
 
-        impl From<(f32, f32)> for $fname {
-            fn from(t: (f32, f32)) -> Self {
-                Self::new(t.0, t.1)
+        impl From<($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)> for $type {
+            fn from(t: ($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)) -> Self {
+                Self::new($(t.$index),+)
             }
         }
 
-        impl std::fmt::Display for $fname {
+        impl std::fmt::Display for $type {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, $format_string, self.$field1, self.$field2)
+                write!(f, $format_string, $(self.$field),+)
             }
         }
 
-        impl std::ops::Add for $fname {
+        impl std::ops::Add for $type {
             type Output = Self;
 
             fn add(self, rhs: Self) -> Self::Output {
                 Self {
-                    $field1: self.$field1 + rhs.$field1,
-                    $field2: self.$field2 + rhs.$field2,
+                    $($field: self.$field + rhs.$field),+
                 }
             }
         }
 
-        impl std::ops::Add<f32> for $fname {
+        impl std::ops::Add<$scalar> for $type {
             type Output = Self;
 
-            fn add(self, rhs: f32) -> Self::Output {
+            fn add(self, rhs: $scalar) -> Self::Output {
                 Self {
-                    $field1: self.$field1 + rhs,
-                    $field2: self.$field2 + rhs,
+                    $($field: self.$field + rhs),+
                 }
             }
         }
 
-        impl std::ops::Add<(f32, f32)> for $fname {
+        impl std::ops::Add<($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)> for $type {
             type Output = Self;
 
-            fn add(self, rhs: (f32, f32)) -> Self::Output {
+            fn add(self, rhs: ($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)) -> Self::Output {
                 Self {
-                    $field1: self.$field1 + rhs.0,
-                    $field2: self.$field2 + rhs.1,
+                    $($field: self.$field + rhs.$index),+
                 }
             }
         }
 
-        impl std::ops::AddAssign<f32> for $fname {
-            fn add_assign(&mut self, rhs: f32) {
-                self.$field1 += rhs;
-                self.$field2 += rhs;
+        impl std::ops::AddAssign<$scalar> for $type {
+            fn add_assign(&mut self, rhs: $scalar) {
+                $(self.$field += rhs;)+
             }
         }
 
-        impl std::ops::AddAssign<(f32, f32)> for $fname {
-            fn add_assign(&mut self, rhs: (f32, f32)) {
-                self.$field1 += rhs.0;
-                self.$field2 += rhs.1;
+        impl std::ops::AddAssign<($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)> for $type {
+            fn add_assign(&mut self, rhs: ($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)) {
+                $(self.$field += rhs.$index;)+
             }
         }
 
-        impl std::ops::Sub for $fname {
+        impl std::ops::Sub for $type {
             type Output = Self;
 
             fn sub(self, rhs: Self) -> Self::Output {
                 Self {
-                    $field1: self.$field1 - rhs.$field1,
-                    $field2: self.$field2 - rhs.$field2,
+                    $($field: self.$field - rhs.$field),+
                 }
             }
         }
 
-        impl std::ops::Sub<f32> for $fname {
+        impl std::ops::Sub<$scalar> for $type {
             type Output = Self;
 
-            fn sub(self, rhs: f32) -> Self::Output {
+            fn sub(self, rhs: $scalar) -> Self::Output {
                 Self {
-                    $field1: self.$field1 - rhs,
-                    $field2: self.$field2 - rhs,
+                    $($field: self.$field - rhs),+
                 }
             }
         }
 
-        impl std::ops::Sub<(f32, f32)> for $fname {
+        impl std::ops::Sub<($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)> for $type {
             type Output = Self;
 
-            fn sub(self, rhs: (f32, f32)) -> Self::Output {
+            fn sub(self, rhs: ($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)) -> Self::Output {
                 Self {
-                    $field1: self.$field1 - rhs.0,
-                    $field2: self.$field2 - rhs.1,
+                    $($field: self.$field - rhs.$index),+
                 }
             }
         }
 
-        impl std::ops::SubAssign<f32> for $fname {
-            fn sub_assign(&mut self, rhs: f32) {
-                self.$field1 -= rhs;
-                self.$field2 -= rhs;
+        impl std::ops::SubAssign<$scalar> for $type {
+            fn sub_assign(&mut self, rhs: $scalar) {
+                $(self.$field -= rhs;)+
             }
         }
 
-        impl std::ops::SubAssign<(f32, f32)> for $fname {
-            fn sub_assign(&mut self, rhs: (f32, f32)) {
-                self.$field1 -= rhs.0;
-                self.$field2 -= rhs.1;
+        impl std::ops::SubAssign<($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)> for $type {
+            fn sub_assign(&mut self, rhs: ($(define_arithmetic_struct!(@SCALAR $field, $scalar)),+)) {
+                $(self.$field -= rhs.$index;)+
             }
         }
 
-        impl std::ops::Mul<f32> for $fname {
+        impl std::ops::Mul<$scalar> for $type {
             type Output = Self;
 
-            fn mul(self, rhs: f32) -> Self::Output {
+            fn mul(self, rhs: $scalar) -> Self::Output {
                 Self {
-                    $field1: self.$field1 * rhs,
-                    $field2: self.$field2 * rhs,
+                    $($field: self.$field * rhs),+
                 }
             }
         }
 
-        impl std::ops::MulAssign<f32> for $fname {
-            fn mul_assign(&mut self, rhs: f32) {
-                self.$field1 *= rhs;
-                self.$field2 *= rhs;
+        impl std::ops::MulAssign<$scalar> for $type {
+            fn mul_assign(&mut self, rhs: $scalar) {
+                $(self.$field *= rhs;)+
             }
         }
 
-        impl std::ops::Div<f32> for $fname {
+        impl std::ops::Div<$scalar> for $type {
             type Output = Self;
 
-            fn div(self, rhs: f32) -> Self::Output {
+            fn div(self, rhs: $scalar) -> Self::Output {
                 Self {
-                    $field1: self.$field1 / rhs,
-                    $field2: self.$field2 / rhs,
+                    $($field: self.$field / rhs),+
                 }
             }
         }
 
-        impl std::ops::DivAssign<f32> for $fname {
-            fn div_assign(&mut self, rhs: f32) {
-                self.$field1 /= rhs;
-                self.$field2 /= rhs;
+        impl std::ops::DivAssign<$scalar> for $type {
+            fn div_assign(&mut self, rhs: $scalar) {
+                $(self.$field /= rhs;)+
             }
         }
 
-        impl std::ops::Rem<f32> for $fname {
+        impl std::ops::Rem<$scalar> for $type {
             type Output = Self;
 
-            fn rem(self, rhs: f32) -> Self::Output {
+            fn rem(self, rhs: $scalar) -> Self::Output {
                 Self {
-                    $field1: self.$field1 % rhs,
-                    $field2: self.$field2 % rhs,
+                    $($field: self.$field % rhs),+
                 }
             }
         }
 
-        impl std::ops::RemAssign<f32> for $fname {
-            fn rem_assign(&mut self, rhs: f32) {
-                self.$field1 %= rhs;
-                self.$field2 %= rhs;
+        impl std::ops::RemAssign<$scalar> for $type {
+            fn rem_assign(&mut self, rhs: $scalar) {
+                $(self.$field %= rhs;)+
             }
         }
 
-        impl std::ops::Neg for $fname {
+        define_symmetric_and_element_wise_ops!($type, $scalar, $($field),+);
+        define_component_wise_min_max!($type, $($field),+);
+    };
+    (@NEG $type:ident, [$($field:ident),+]) => {
+        impl std::ops::Neg for $type {
             type Output = Self;
 
             fn neg(self) -> Self::Output {
                 Self {
-                    $field1: -self.$field1,
-                    $field2: -self.$field2,
+                    $($field: -self.$field),+
                 }
             }
         }
 
+        define_component_wise_abs!($type, $($field),+);
+    };
+    (@IMPL $name:ident, $uname:ident, $fname:ident, $name_str:expr, [$($field:ident: $index:tt: $field_str:expr),+], [$($ordered_field:ident),+], $fields_doc:tt, $zero_doc:tt, $zero_constant:ident, $format_string:expr) => {
+        define_arithmetic_struct!(@TYPE $name, i32, 0, "A struct representing a", $name_str, [$($field: $index: $field_str),+], $fields_doc, $zero_doc, $zero_constant, $format_string, [Copy, Clone, Default, PartialEq, Eq, Hash, Debug]);
+        define_arithmetic_struct!(@NEG $name, [$($field),+]);
+        define_checked_integer_arithmetic!($name, $($field),+);
+        define_row_major_ordering!($name, $($ordered_field),+);
+
+        // Unsigned version:
+
+        define_arithmetic_struct!(@TYPE $uname, u32, 0, "A struct representing an unsigned", $name_str, [$($field: $index: $field_str),+], $fields_doc, $zero_doc, $zero_constant, $format_string, [Copy, Clone, Default, PartialEq, Eq, Hash, Debug]);
+        define_checked_integer_arithmetic!($uname, $($field),+);
+        define_row_major_ordering!($uname, $($ordered_field),+);
+
+        // Floating-point version
+
+        define_arithmetic_struct!(@TYPE $fname, f32, 0.0, "A struct representing a floating-point", $name_str, [$($field: $index: $field_str),+], $fields_doc, $zero_doc, $zero_constant, $format_string, [Copy, Clone, Default, PartialEq, Debug]);
+        define_arithmetic_struct!(@NEG $fname, [$($field),+]);
+
         // Conversions
 
         paste::item! {
@@ -736,8 +508,7 @@ macro_rules! define_two_property_arithmetic_struct {
 
                 fn try_from(value: $name) -> Result<Self, Self::Error> {
                     Ok(Self::new(
-                        TryFrom::try_from(value.$field1)?,
-                        TryFrom::try_from(value.$field2)?,
+                        $(TryFrom::try_from(value.$field)?),+
                     ))
                 }
             }
@@ -745,7 +516,7 @@ macro_rules! define_two_property_arithmetic_struct {
 
         impl From<$name> for $fname {
             fn from(value: $name) -> Self {
-                Self::new(value.$field1 as f32, value.$field2 as f32)
+                Self::new($(value.$field as f32),+)
             }
         }
 
@@ -755,8 +526,7 @@ macro_rules! define_two_property_arithmetic_struct {
 
                 fn try_from(value: $uname) -> Result<Self, Self::Error> {
                     Ok(Self::new(
-                        TryFrom::try_from(value.$field1)?,
-                        TryFrom::try_from(value.$field2)?,
+                        $(TryFrom::try_from(value.$field)?),+
                     ))
                 }
             }
@@ -764,9 +534,7 @@ macro_rules! define_two_property_arithmetic_struct {
 
         impl From<$uname> for $fname {
             fn from(value: $uname) -> Self {
-                let x = value.$field1 as f32;
-                let y = value.$field2 as f32;
-                Self::new(x, y)
+                Self::new($(value.$field as f32),+)
             }
         }
 
@@ -775,14 +543,12 @@ macro_rules! define_two_property_arithmetic_struct {
                 type Error = [< TryFrom $name Error >];
 
                 fn try_from(value: $fname) -> Result<Self, Self::Error> {
-                    if value.$field1 > i32::MAX as f32
-                        || value.$field1 < i32::MIN as f32
-                        || value.$field2 > i32::MAX as f32
-                        || value.$field2 < i32::MIN as f32
-                    {
+                    // `i32::MAX as f32` rounds up to 2^31, which is out of range, and NaN is
+                    // rejected since it fails every comparison.
+                    if $(!(value.$field >= i32::MIN as f32 && value.$field < i32::MAX as f32))||+ {
                         return Err([< TryFrom $name Error >]::FloatToInt);
                     }
-                    Ok(Self::new(value.$field1 as i32, value.$field2 as i32))
+                    Ok(Self::new($(value.$field as i32),+))
                 }
             }
         }
@@ -792,14 +558,12 @@ macro_rules! define_two_property_arithmetic_struct {
                 type Error = [< TryFrom $name Error >];
 
                 fn try_from(value: $fname) -> Result<Self, Self::Error> {
-                    if value.$field1 > u32::MAX as f32
-                        || value.$field1 < u32::MIN as f32
-                        || value.$field2 > u32::MAX as f32
-                        || value.$field2 < u32::MIN as f32
-                    {
+                    // `u32::MAX as f32` rounds up to 2^32, which is out of range, and NaN is
+                    // rejected since it fails every comparison.
+                    if $(!(value.$field >= u32::MIN as f32 && value.$field < u32::MAX as f32))||+ {
                         return Err([< TryFrom $name Error >]::FloatToInt);
                     }
-                    Ok(Self::new(value.$field1 as u32, value.$field2 as u32))
+                    Ok(Self::new($(value.$field as u32),+))
                 }
             }
         }
@@ -809,68 +573,47 @@ macro_rules! define_two_property_arithmetic_struct {
         impl $fname {
             /// Returns a non-floating point `
             #[doc = $name_str]
-            /// ` where the decimal parts of the `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` have been rounded.
+            /// ` where the decimal parts of the components have been rounded.
             pub fn round(self) -> $name {
-                $name::new(self.$field1.round() as i32, self.$field2.round() as i32)
+                $name::new($(self.$field.round() as i32),+)
             }
+
             /// Returns a non-floating point `
             #[doc = $name_str]
-            /// ` where the decimal parts of the `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` have been rounded.
+            /// ` where the decimal parts of the components have been rounded.
             ///
             /// # Panics
             ///
-            /// If the `
-            #[doc = $field1_str]
-            /// ` or the `
-            #[doc = $field2_str]
-            /// ` is < 0.
+            /// If any of the components is < 0.
             pub fn round_u(self) -> $uname {
-                assert!(self.$field1 >= 0.);
-                assert!(self.$field2 >= 0.);
+                $(assert!(self.$field >= 0.);)+
 
-                $uname::new(self.$field1.round() as u32, self.$field2.round() as u32)
+                $uname::new($(self.$field.round() as u32),+)
             }
 
             /// Returns a non-floating point `
             #[doc = $name_str]
-            /// ` where the decimal parts of the `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` have been truncated.
+            /// ` where the decimal parts of the components have been truncated.
             pub fn trunc(self) -> $name {
-                $name::new(self.$field1.trunc() as i32, self.$field2.trunc() as i32)
+                $name::new($(self.$field.trunc() as i32),+)
             }
 
             /// Returns a non-floating point `
             #[doc = $name_str]
-            /// ` where the decimal parts of the `
-            #[doc = $field1_str]
-            /// ` and `
-            #[doc = $field2_str]
-            /// ` have been truncated.
+            /// ` where the decimal parts of the components have been truncated.
             ///
             /// # Panics
             ///
-            /// If the `
-            #[doc = $field1_str]
-            /// ` or the `
-            #[doc = $field2_str]
-            /// ` is < 0.
+            /// If any of the components is < 0.
             pub fn trunc_u(self) -> $uname {
-                assert!(self.$field1 >= 0.);
-                assert!(self.$field2 >= 0.);
+                $(assert!(self.$field >= 0.);)+
 
-                $uname::new(self.$field1.trunc() as u32, self.$field2.trunc() as u32)
+                $uname::new($(self.$field.trunc() as u32),+)
             }
         }
+
+        paste::item! {
+            define_fallible_float_rounding!($fname, $name, $uname, [< TryFrom $name Error >], $($field),+);
+        }
     };
 }
@@ -0,0 +1,210 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::Position;
+
+/// Represents one of the eight compass directions on a grid.
+///
+/// The y axis points downwards, as it does on a console, so `North` is the direction of
+/// decreasing `y`. The four cardinal directions are available as a subset through
+/// `Direction::CARDINAL`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum Direction {
+    /// Up; `(0, -1)`
+    North,
+    /// Up and to the right; `(1, -1)`
+    NorthEast,
+    /// To the right; `(1, 0)`
+    East,
+    /// Down and to the right; `(1, 1)`
+    SouthEast,
+    /// Down; `(0, 1)`
+    South,
+    /// Down and to the left; `(-1, 1)`
+    SouthWest,
+    /// To the left; `(-1, 0)`
+    West,
+    /// Up and to the left; `(-1, -1)`
+    NorthWest,
+}
+
+impl Direction {
+    /// All eight directions, in clockwise order starting with `North`.
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    /// The four cardinal directions, in clockwise order starting with `North`.
+    pub const CARDINAL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    /// The four diagonal directions, in clockwise order starting with `NorthEast`.
+    pub const DIAGONAL: [Self; 4] = [
+        Self::NorthEast,
+        Self::SouthEast,
+        Self::SouthWest,
+        Self::NorthWest,
+    ];
+
+    /// Returns the position offset you get by taking a single step in this direction.
+    pub fn offset(self) -> Position {
+        match self {
+            Self::North => Position::new(0, -1),
+            Self::NorthEast => Position::new(1, -1),
+            Self::East => Position::new(1, 0),
+            Self::SouthEast => Position::new(1, 1),
+            Self::South => Position::new(0, 1),
+            Self::SouthWest => Position::new(-1, 1),
+            Self::West => Position::new(-1, 0),
+            Self::NorthWest => Position::new(-1, -1),
+        }
+    }
+
+    /// Returns the direction pointing from the origin towards `delta`, or `None` if `delta` is
+    /// the origin. Only the sign of each component is considered, so e.g. `(5, -2)` gives
+    /// `NorthEast`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Direction, Position};
+    /// assert_eq!(Direction::from_delta(Position::new(0, 3)), Some(Direction::South));
+    /// assert_eq!(Direction::from_delta(Position::new(-7, -1)), Some(Direction::NorthWest));
+    /// assert_eq!(Direction::from_delta(Position::ORIGIN), None);
+    /// ```
+    pub fn from_delta(delta: Position) -> Option<Self> {
+        match (delta.x.signum(), delta.y.signum()) {
+            (0, -1) => Some(Self::North),
+            (1, -1) => Some(Self::NorthEast),
+            (1, 0) => Some(Self::East),
+            (1, 1) => Some(Self::SouthEast),
+            (0, 1) => Some(Self::South),
+            (-1, 1) => Some(Self::SouthWest),
+            (-1, 0) => Some(Self::West),
+            (-1, -1) => Some(Self::NorthWest),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this is one of the four cardinal directions.
+    pub fn is_cardinal(self) -> bool {
+        matches!(self, Self::North | Self::East | Self::South | Self::West)
+    }
+
+    /// Returns whether this is one of the four diagonal directions.
+    pub fn is_diagonal(self) -> bool {
+        !self.is_cardinal()
+    }
+
+    /// Returns the direction 45 degrees clockwise from this one.
+    pub fn rotate_cw(self) -> Self {
+        self.rotate(1)
+    }
+
+    /// Returns the direction 45 degrees counter-clockwise from this one.
+    pub fn rotate_ccw(self) -> Self {
+        self.rotate(7)
+    }
+
+    /// Returns the direction 90 degrees clockwise from this one. Cardinal directions stay
+    /// cardinal.
+    pub fn rotate_cw_90(self) -> Self {
+        self.rotate(2)
+    }
+
+    /// Returns the direction 90 degrees counter-clockwise from this one. Cardinal directions
+    /// stay cardinal.
+    pub fn rotate_ccw_90(self) -> Self {
+        self.rotate(6)
+    }
+
+    /// Returns the direction pointing the opposite way of this one.
+    pub fn opposite(self) -> Self {
+        self.rotate(4)
+    }
+
+    fn rotate(self, steps: usize) -> Self {
+        Self::ALL[(self as usize + steps) % Self::ALL.len()]
+    }
+}
+
+impl From<Direction> for Position {
+    fn from(direction: Direction) -> Self {
+        direction.offset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_round_trips_through_from_delta() {
+        for &direction in &Direction::ALL {
+            assert_eq!(Direction::from_delta(direction.offset()), Some(direction));
+            assert_eq!(
+                Direction::from_delta(direction.offset() * 3),
+                Some(direction)
+            );
+        }
+        assert_eq!(Direction::from_delta(Position::ORIGIN), None);
+    }
+
+    #[test]
+    fn rotation() {
+        assert_eq!(Direction::North.rotate_cw(), Direction::NorthEast);
+        assert_eq!(Direction::North.rotate_ccw(), Direction::NorthWest);
+        assert_eq!(Direction::West.rotate_cw_90(), Direction::North);
+        assert_eq!(Direction::North.rotate_ccw_90(), Direction::West);
+        for &direction in &Direction::ALL {
+            assert_eq!(direction.rotate_cw().rotate_ccw(), direction);
+            assert_eq!(direction.opposite().offset(), -direction.offset());
+            assert_eq!(direction.is_cardinal(), !direction.is_diagonal());
+        }
+        for &direction in &Direction::CARDINAL {
+            assert!(direction.is_cardinal());
+            assert!(direction.rotate_cw_90().is_cardinal());
+        }
+        for &direction in &Direction::DIAGONAL {
+            assert!(direction.is_diagonal());
+        }
+    }
+}
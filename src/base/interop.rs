@@ -0,0 +1,157 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Conversions between the coordinate types and those of other math crates.
+
+#[cfg(feature = "mint_support")]
+macro_rules! impl_mint_conversions {
+    ($mint_type:ident, $name:ident, $t:ty, $($field:ident: $mint_field:ident),+) => {
+        impl From<$name> for mint::$mint_type<$t> {
+            fn from(value: $name) -> Self {
+                Self {
+                    $($mint_field: value.$field),+
+                }
+            }
+        }
+
+        impl From<mint::$mint_type<$t>> for $name {
+            fn from(value: mint::$mint_type<$t>) -> Self {
+                Self {
+                    $($field: value.$mint_field),+
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mint_support")]
+mod mint_conversions {
+    use crate::{
+        FPosition, FPosition3, FSize, FSize3, Position, Position3, Size, Size3, UPosition,
+        UPosition3, USize, USize3,
+    };
+
+    impl_mint_conversions!(Point2, Position, i32, x: x, y: y);
+    impl_mint_conversions!(Vector2, Position, i32, x: x, y: y);
+    impl_mint_conversions!(Point2, UPosition, u32, x: x, y: y);
+    impl_mint_conversions!(Vector2, UPosition, u32, x: x, y: y);
+    impl_mint_conversions!(Point2, FPosition, f32, x: x, y: y);
+    impl_mint_conversions!(Vector2, FPosition, f32, x: x, y: y);
+    impl_mint_conversions!(Vector2, Size, i32, width: x, height: y);
+    impl_mint_conversions!(Vector2, USize, u32, width: x, height: y);
+    impl_mint_conversions!(Vector2, FSize, f32, width: x, height: y);
+
+    impl_mint_conversions!(Point3, Position3, i32, x: x, y: y, z: z);
+    impl_mint_conversions!(Vector3, Position3, i32, x: x, y: y, z: z);
+    impl_mint_conversions!(Point3, UPosition3, u32, x: x, y: y, z: z);
+    impl_mint_conversions!(Vector3, UPosition3, u32, x: x, y: y, z: z);
+    impl_mint_conversions!(Point3, FPosition3, f32, x: x, y: y, z: z);
+    impl_mint_conversions!(Vector3, FPosition3, f32, x: x, y: y, z: z);
+    impl_mint_conversions!(Vector3, Size3, i32, width: x, height: y, depth: z);
+    impl_mint_conversions!(Vector3, USize3, u32, width: x, height: y, depth: z);
+    impl_mint_conversions!(Vector3, FSize3, f32, width: x, height: y, depth: z);
+}
+
+#[cfg(feature = "glam_support")]
+macro_rules! impl_glam_conversions {
+    ($glam_type:ident, $name:ident, $($field:ident: $glam_field:ident),+) => {
+        impl From<$name> for glam::$glam_type {
+            fn from(value: $name) -> Self {
+                Self::new($(value.$field),+)
+            }
+        }
+
+        impl From<glam::$glam_type> for $name {
+            fn from(value: glam::$glam_type) -> Self {
+                Self::new($(value.$glam_field),+)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "glam_support")]
+mod glam_conversions {
+    use crate::{
+        FPosition, FPosition3, FSize, FSize3, Position, Position3, Size, Size3, UPosition,
+        UPosition3, USize, USize3,
+    };
+
+    impl_glam_conversions!(IVec2, Position, x: x, y: y);
+    impl_glam_conversions!(UVec2, UPosition, x: x, y: y);
+    impl_glam_conversions!(Vec2, FPosition, x: x, y: y);
+    impl_glam_conversions!(IVec2, Size, width: x, height: y);
+    impl_glam_conversions!(UVec2, USize, width: x, height: y);
+    impl_glam_conversions!(Vec2, FSize, width: x, height: y);
+
+    impl_glam_conversions!(IVec3, Position3, x: x, y: y, z: z);
+    impl_glam_conversions!(UVec3, UPosition3, x: x, y: y, z: z);
+    impl_glam_conversions!(Vec3, FPosition3, x: x, y: y, z: z);
+    impl_glam_conversions!(IVec3, Size3, width: x, height: y, depth: z);
+    impl_glam_conversions!(UVec3, USize3, width: x, height: y, depth: z);
+    impl_glam_conversions!(Vec3, FSize3, width: x, height: y, depth: z);
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "mint_support")]
+    #[test]
+    fn mint_round_trip() {
+        use crate::{FPosition, Position3, USize};
+
+        let p: mint::Point2<f32> = FPosition::new(1.5, -2.0).into();
+        assert_eq!(p, mint::Point2 { x: 1.5, y: -2.0 });
+        assert_eq!(FPosition::from(p), FPosition::new(1.5, -2.0));
+
+        let v: mint::Vector2<u32> = USize::new(3, 4).into();
+        assert_eq!(v, mint::Vector2 { x: 3, y: 4 });
+        assert_eq!(USize::from(v), USize::new(3, 4));
+
+        let p3: mint::Vector3<i32> = Position3::new(1, 2, 3).into();
+        assert_eq!(Position3::from(p3), Position3::new(1, 2, 3));
+    }
+
+    #[cfg(feature = "glam_support")]
+    #[test]
+    fn glam_round_trip() {
+        use crate::{FSize, Position, UPosition3};
+
+        let p: glam::IVec2 = Position::new(-1, 2).into();
+        assert_eq!(p, glam::IVec2::new(-1, 2));
+        assert_eq!(Position::from(p), Position::new(-1, 2));
+
+        let s: glam::Vec2 = FSize::new(1.5, 2.5).into();
+        assert_eq!(FSize::from(s), FSize::new(1.5, 2.5));
+
+        let p3: glam::UVec3 = UPosition3::new(1, 2, 3).into();
+        assert_eq!(UPosition3::from(p3), UPosition3::new(1, 2, 3));
+    }
+}
@@ -0,0 +1,183 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::{Direction, Position, Rectangle, USize};
+
+impl Position {
+    /// Returns an iterator over the four orthogonally adjacent positions, in clockwise order
+    /// starting with the one to the north.
+    pub fn neighbors4(self) -> Neighbors {
+        Neighbors::new(self, &Direction::CARDINAL)
+    }
+
+    /// Returns an iterator over the eight surrounding positions, in clockwise order starting
+    /// with the one to the north.
+    pub fn neighbors8(self) -> Neighbors {
+        Neighbors::new(self, &Direction::ALL)
+    }
+
+    /// Returns an iterator over the eight surrounding positions that lie within `bounds`.
+    ///
+    /// See `Neighbors::within` for how `bounds` is interpreted.
+    pub fn neighbors_in(self, bounds: Rectangle) -> Neighbors {
+        self.neighbors8().within(bounds)
+    }
+
+    /// Returns an iterator over the eight surrounding positions that lie within a grid of the
+    /// given `size`, i.e. those with `0 <= x < width` and `0 <= y < height`.
+    pub fn neighbors_in_size(self, size: USize) -> Neighbors {
+        self.neighbors8().within_size(size)
+    }
+}
+
+/// An iterator over the positions adjacent to a position.
+///
+/// Created by `Position::neighbors4`, `Position::neighbors8` and their bounded variants.
+#[derive(Copy, Clone, Debug)]
+pub struct Neighbors {
+    center: Position,
+    directions: &'static [Direction],
+    bounds: Option<Rectangle>,
+}
+
+impl Neighbors {
+    pub(crate) fn new(center: Position, directions: &'static [Direction]) -> Self {
+        Self {
+            center,
+            directions,
+            bounds: None,
+        }
+    }
+
+    /// Restricts the iterator to positions inside `bounds`.
+    ///
+    /// Since neighbors are grid cells, a position is considered inside the rectangle if it's
+    /// one of the `width * height` cells the rectangle covers; that is, the rectangle's right
+    /// and bottom edges are excluded.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle};
+    /// let room = Rectangle::new_from_raw(0, 0, 3, 3);
+    /// assert_eq!(Position::ORIGIN.neighbors4().within(room).count(), 2);
+    /// assert_eq!(Position::new(1, 1).neighbors8().within(room).count(), 8);
+    /// ```
+    pub fn within(mut self, bounds: Rectangle) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Restricts the iterator to positions inside a grid of the given `size`.
+    pub fn within_size(self, size: USize) -> Self {
+        self.within(Rectangle::new(Position::ORIGIN, size))
+    }
+
+    fn in_bounds(&self, position: Position) -> bool {
+        match self.bounds {
+            None => true,
+            Some(Rectangle { position: p, size }) => {
+                i64::from(position.x) >= i64::from(p.x)
+                    && i64::from(position.x) < i64::from(p.x) + i64::from(size.width)
+                    && i64::from(position.y) >= i64::from(p.y)
+                    && i64::from(position.y) < i64::from(p.y) + i64::from(size.height)
+            }
+        }
+    }
+}
+
+impl Iterator for Neighbors {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((&direction, rest)) = self.directions.split_first() {
+            self.directions = rest;
+            let position = self.center + direction.offset();
+            if self.in_bounds(position) {
+                return Some(position);
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = self.directions.len();
+        if self.bounds.is_some() {
+            (0, Some(upper))
+        } else {
+            (upper, Some(upper))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unbounded_neighbors() {
+        let p = Position::new(3, -2);
+        let n4: Vec<_> = p.neighbors4().collect();
+        assert_eq!(
+            n4,
+            vec![
+                Position::new(3, -3),
+                Position::new(4, -2),
+                Position::new(3, -1),
+                Position::new(2, -2),
+            ]
+        );
+        assert_eq!(p.neighbors8().count(), 8);
+        assert!(p.neighbors8().all(|n| n != p));
+    }
+
+    #[test]
+    fn bounded_neighbors() {
+        let size = USize::new(4, 3);
+        assert_eq!(Position::ORIGIN.neighbors_in_size(size).count(), 3);
+        assert_eq!(Position::new(3, 2).neighbors_in_size(size).count(), 3);
+        assert_eq!(Position::new(1, 1).neighbors_in_size(size).count(), 8);
+        assert_eq!(Position::new(-1, -1).neighbors_in_size(size).count(), 1);
+        assert_eq!(Position::new(10, 10).neighbors_in_size(size).count(), 0);
+
+        let rect = Rectangle::new_from_raw(-5, -5, 2, 2);
+        let n: Vec<_> = Position::new(-5, -5).neighbors_in(rect).collect();
+        assert_eq!(
+            n,
+            vec![
+                Position::new(-4, -5),
+                Position::new(-4, -4),
+                Position::new(-5, -4),
+            ]
+        );
+    }
+}
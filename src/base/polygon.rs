@@ -0,0 +1,222 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::{FPosition, FRectangle, FSize};
+
+/// Represents a simple polygon described by its vertices, in order.
+///
+/// The polygon is implicitly closed; the last vertex connects back to the first one. Vertices may
+/// be given in either clockwise or counter-clockwise order.
+#[derive(Clone, Default, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Polygon(pub Vec<FPosition>);
+
+impl Polygon {
+    /// Returns a new polygon with the given vertices
+    pub fn new(vertices: Vec<FPosition>) -> Self {
+        Self(vertices)
+    }
+
+    /// Returns the vertices of the polygon
+    pub fn vertices(&self) -> &[FPosition] {
+        &self.0
+    }
+
+    /// Returns whether `position` lies inside the polygon, using the even-odd rule.
+    ///
+    /// Positions lying exactly on an edge may be reported as either inside or outside.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, Polygon};
+    /// let triangle = Polygon::new(vec![
+    ///     FPosition::new(0.0, 0.0),
+    ///     FPosition::new(4.0, 0.0),
+    ///     FPosition::new(0.0, 4.0),
+    /// ]);
+    /// assert!(triangle.contains(FPosition::new(1.0, 1.0)));
+    /// assert!(!triangle.contains(FPosition::new(3.0, 3.0)));
+    /// ```
+    pub fn contains(&self, position: FPosition) -> bool {
+        let mut inside = false;
+        let mut j = match self.0.len() {
+            0 => return false,
+            len => len - 1,
+        };
+        for (i, &vi) in self.0.iter().enumerate() {
+            let vj = self.0[j];
+            if (vi.y > position.y) != (vj.y > position.y)
+                && position.x < (vj.x - vi.x) * (position.y - vi.y) / (vj.y - vi.y) + vi.x
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+
+        inside
+    }
+
+    /// Returns the smallest rectangle containing every vertex of the polygon, or `None` if the
+    /// polygon has no vertices.
+    pub fn bounding_rect(&self) -> Option<FRectangle> {
+        let (first, rest) = self.0.split_first()?;
+        let (min, max) = rest.iter().fold((*first, *first), |(min, max), v| {
+            (
+                FPosition::new(min.x.min(v.x), min.y.min(v.y)),
+                FPosition::new(max.x.max(v.x), max.y.max(v.y)),
+            )
+        });
+
+        Some(FRectangle::new(
+            min,
+            FSize::new(max.x - min.x, max.y - min.y),
+        ))
+    }
+
+    /// Returns the signed area of the polygon. The sign is positive when the vertices are given in
+    /// clockwise order (with the y axis pointing down, as on a console) and negative otherwise.
+    pub fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Returns the area of the polygon.
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    /// Returns the centroid (center of mass) of the polygon, or `None` if the polygon has no
+    /// vertices.
+    ///
+    /// Degenerate polygons with no area return the average of their vertices instead.
+    pub fn centroid(&self) -> Option<FPosition> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let signed_area = self.signed_area();
+        if signed_area == 0.0 {
+            let sum = self.0.iter().fold(FPosition::ORIGIN, |sum, &v| sum + v);
+            #[allow(clippy::cast_precision_loss)]
+            return Some(sum / self.0.len() as f32);
+        }
+
+        let (x, y) = self.edges().fold((0.0, 0.0), |(x, y), (a, b)| {
+            let cross = a.x * b.y - b.x * a.y;
+            (x + (a.x + b.x) * cross, y + (a.y + b.y) * cross)
+        });
+        let factor = 6.0 * signed_area;
+
+        Some(FPosition::new(x / factor, y / factor))
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (FPosition, FPosition)> + '_ {
+        self.0
+            .iter()
+            .zip(self.0.iter().cycle().skip(1))
+            .map(|(&a, &b)| (a, b))
+    }
+}
+
+impl From<Vec<FPosition>> for Polygon {
+    fn from(vertices: Vec<FPosition>) -> Self {
+        Self(vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Polygon {
+        Polygon::new(vec![
+            FPosition::new(1.0, 1.0),
+            FPosition::new(5.0, 1.0),
+            FPosition::new(5.0, 5.0),
+            FPosition::new(1.0, 5.0),
+        ])
+    }
+
+    #[test]
+    fn contains() {
+        let square = square();
+        assert!(square.contains(FPosition::new(3.0, 3.0)));
+        assert!(!square.contains(FPosition::new(0.0, 3.0)));
+        assert!(!square.contains(FPosition::new(6.0, 3.0)));
+        assert!(!Polygon::default().contains(FPosition::ORIGIN));
+
+        // A concave "U" shape
+        let u = Polygon::new(vec![
+            FPosition::new(0.0, 0.0),
+            FPosition::new(1.0, 0.0),
+            FPosition::new(1.0, 2.0),
+            FPosition::new(2.0, 2.0),
+            FPosition::new(2.0, 0.0),
+            FPosition::new(3.0, 0.0),
+            FPosition::new(3.0, 3.0),
+            FPosition::new(0.0, 3.0),
+        ]);
+        assert!(u.contains(FPosition::new(0.5, 1.0)));
+        assert!(!u.contains(FPosition::new(1.5, 1.0)));
+        assert!(u.contains(FPosition::new(1.5, 2.5)));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn area_centroid_and_bounds() {
+        let square = square();
+        assert_eq!(square.area(), 16.0);
+        assert_eq!(square.signed_area(), 16.0);
+        assert_eq!(square.centroid(), Some(FPosition::new(3.0, 3.0)));
+        assert_eq!(
+            square.bounding_rect(),
+            Some(FRectangle::new_from_raw(1.0, 1.0, 4.0, 4.0))
+        );
+
+        let reversed = Polygon::new(square.vertices().iter().rev().copied().collect());
+        assert_eq!(reversed.signed_area(), -16.0);
+        assert_eq!(reversed.area(), 16.0);
+        assert_eq!(reversed.centroid(), Some(FPosition::new(3.0, 3.0)));
+
+        let line = Polygon::new(vec![FPosition::new(0.0, 0.0), FPosition::new(2.0, 2.0)]);
+        assert_eq!(line.area(), 0.0);
+        assert_eq!(line.centroid(), Some(FPosition::new(1.0, 1.0)));
+
+        assert_eq!(Polygon::default().bounding_rect(), None);
+        assert_eq!(Polygon::default().centroid(), None);
+    }
+}
@@ -0,0 +1,314 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::{FPosition, FRectangle, Position, Rectangle};
+use std::cmp::Ordering;
+
+/// Represents a line segment between two integer positions.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Segment {
+    /// The first end point of the segment
+    pub a: Position,
+    /// The second end point of the segment
+    pub b: Position,
+}
+
+impl Segment {
+    /// Returns a new segment between the given end points
+    pub const fn new(a: Position, b: Position) -> Self {
+        Self { a, b }
+    }
+
+    /// Returns the length of the segment
+    pub fn length(&self) -> f32 {
+        FSegment::from(*self).length()
+    }
+
+    /// Returns whether this segment and `other` share at least one point, including when they
+    /// merely touch at an end point or overlap along the same line.
+    ///
+    /// The test is exact, since it is done entirely with integer arithmetic.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let o1 = orientation(self.a, self.b, other.a);
+        let o2 = orientation(self.a, self.b, other.b);
+        let o3 = orientation(other.a, other.b, self.a);
+        let o4 = orientation(other.a, other.b, self.b);
+
+        if o1 != o2 && o3 != o4 {
+            return true;
+        }
+
+        (o1 == Ordering::Equal && on_segment(self.a, self.b, other.a))
+            || (o2 == Ordering::Equal && on_segment(self.a, self.b, other.b))
+            || (o3 == Ordering::Equal && on_segment(other.a, other.b, self.a))
+            || (o4 == Ordering::Equal && on_segment(other.a, other.b, self.b))
+    }
+
+    /// Returns whether any part of this segment lies within `rectangle`, using the same notion of
+    /// a rectangle's boundary as `Rectangle::contains_position`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle, Segment};
+    /// let wall = Rectangle::new_from_raw(2, 2, 2, 2);
+    /// assert!(Segment::new(Position::new(0, 0), Position::new(5, 5)).intersects_rectangle(&wall));
+    /// assert!(!Segment::new(Position::new(0, 5), Position::new(1, 0)).intersects_rectangle(&wall));
+    /// ```
+    pub fn intersects_rectangle(&self, rectangle: &Rectangle) -> bool {
+        if rectangle.contains_position(self.a) || rectangle.contains_position(self.b) {
+            return true;
+        }
+
+        let [top_left, top_right, bottom_right, bottom_left] = rectangle.corners();
+        [
+            Self::new(top_left, top_right),
+            Self::new(top_right, bottom_right),
+            Self::new(bottom_right, bottom_left),
+            Self::new(bottom_left, top_left),
+        ]
+        .iter()
+        .any(|edge| self.intersects(edge))
+    }
+
+    /// Returns the point on the segment that is closest to `point`.
+    pub fn closest_point(&self, point: FPosition) -> FPosition {
+        FSegment::from(*self).closest_point(point)
+    }
+}
+
+/// Represents a line segment between two floating-point positions.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct FSegment {
+    /// The first end point of the segment
+    pub a: FPosition,
+    /// The second end point of the segment
+    pub b: FPosition,
+}
+
+impl FSegment {
+    /// Returns a new segment between the given end points
+    pub const fn new(a: FPosition, b: FPosition) -> Self {
+        Self { a, b }
+    }
+
+    /// Returns the length of the segment
+    pub fn length(&self) -> f32 {
+        let d = self.b - self.a;
+        (d.x * d.x + d.y * d.y).sqrt()
+    }
+
+    /// Returns whether this segment and `other` share at least one point, including when they
+    /// merely touch at an end point or overlap along the same line.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let o1 = f_orientation(self.a, self.b, other.a);
+        let o2 = f_orientation(self.a, self.b, other.b);
+        let o3 = f_orientation(other.a, other.b, self.a);
+        let o4 = f_orientation(other.a, other.b, self.b);
+
+        if o1 != o2 && o3 != o4 {
+            return true;
+        }
+
+        (o1 == Ordering::Equal && f_on_segment(self.a, self.b, other.a))
+            || (o2 == Ordering::Equal && f_on_segment(self.a, self.b, other.b))
+            || (o3 == Ordering::Equal && f_on_segment(other.a, other.b, self.a))
+            || (o4 == Ordering::Equal && f_on_segment(other.a, other.b, self.b))
+    }
+
+    /// Returns the point where this segment crosses `other`, or `None` if they don't intersect
+    /// or are parallel.
+    pub fn intersection(&self, other: &Self) -> Option<FPosition> {
+        let r = self.b - self.a;
+        let s = other.b - other.a;
+        let denominator = cross(r, s);
+        if denominator == 0.0 {
+            return None;
+        }
+
+        let qp = other.a - self.a;
+        let t = cross(qp, s) / denominator;
+        let u = cross(qp, r) / denominator;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(self.a + r * t)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether any part of this segment lies within `rectangle`, using the same notion of
+    /// a rectangle's boundary as `FRectangle::contains_position`.
+    pub fn intersects_rectangle(&self, rectangle: &FRectangle) -> bool {
+        if rectangle.contains_position(self.a) || rectangle.contains_position(self.b) {
+            return true;
+        }
+
+        let FRectangle { position: p, size } = *rectangle;
+        let top_right = p + (size.width, 0.0);
+        let bottom_right = p + (size.width, size.height);
+        let bottom_left = p + (0.0, size.height);
+        [
+            Self::new(p, top_right),
+            Self::new(top_right, bottom_right),
+            Self::new(bottom_right, bottom_left),
+            Self::new(bottom_left, p),
+        ]
+        .iter()
+        .any(|edge| self.intersects(edge))
+    }
+
+    /// Returns the point on the segment that is closest to `point`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, FSegment};
+    /// let segment = FSegment::new(FPosition::new(0.0, 0.0), FPosition::new(4.0, 0.0));
+    /// assert_eq!(segment.closest_point(FPosition::new(1.0, 3.0)), FPosition::new(1.0, 0.0));
+    /// assert_eq!(segment.closest_point(FPosition::new(9.0, 1.0)), FPosition::new(4.0, 0.0));
+    /// ```
+    pub fn closest_point(&self, point: FPosition) -> FPosition {
+        let d = self.b - self.a;
+        let length_squared = d.x * d.x + d.y * d.y;
+        if length_squared == 0.0 {
+            return self.a;
+        }
+
+        let ap = point - self.a;
+        let t = ((ap.x * d.x + ap.y * d.y) / length_squared).clamp(0.0, 1.0);
+
+        self.a + d * t
+    }
+}
+
+impl From<Segment> for FSegment {
+    fn from(segment: Segment) -> Self {
+        Self::new(segment.a.into(), segment.b.into())
+    }
+}
+
+fn orientation(a: Position, b: Position, c: Position) -> Ordering {
+    let cross = (i64::from(b.x) - i64::from(a.x)) * (i64::from(c.y) - i64::from(a.y))
+        - (i64::from(b.y) - i64::from(a.y)) * (i64::from(c.x) - i64::from(a.x));
+    cross.cmp(&0)
+}
+
+// Assumes that `p` is collinear with `a` and `b`.
+fn on_segment(a: Position, b: Position, p: Position) -> bool {
+    p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+fn cross(a: FPosition, b: FPosition) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn f_orientation(a: FPosition, b: FPosition, c: FPosition) -> Ordering {
+    cross(b - a, c - a)
+        .partial_cmp(&0.0)
+        .unwrap_or(Ordering::Equal)
+}
+
+// Assumes that `p` is collinear with `a` and `b`.
+fn f_on_segment(a: FPosition, b: FPosition, p: FPosition) -> bool {
+    p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_intersection() {
+        let s = Segment::new(Position::new(0, 0), Position::new(4, 4));
+        assert!(s.intersects(&Segment::new(Position::new(0, 4), Position::new(4, 0))));
+        // Touching at an end point
+        assert!(s.intersects(&Segment::new(Position::new(4, 4), Position::new(8, 0))));
+        // Collinear and overlapping
+        assert!(s.intersects(&Segment::new(Position::new(2, 2), Position::new(6, 6))));
+        // Collinear but disjoint
+        assert!(!s.intersects(&Segment::new(Position::new(5, 5), Position::new(6, 6))));
+        // Parallel
+        assert!(!s.intersects(&Segment::new(Position::new(1, 0), Position::new(5, 4))));
+
+        let fs = FSegment::from(s);
+        let other = FSegment::new(FPosition::new(0.0, 4.0), FPosition::new(4.0, 0.0));
+        assert!(fs.intersects(&other));
+        assert_eq!(fs.intersection(&other), Some(FPosition::new(2.0, 2.0)));
+        let short = FSegment::new(FPosition::new(0.0, 4.0), FPosition::new(1.0, 3.0));
+        assert!(!fs.intersects(&short));
+        assert_eq!(fs.intersection(&short), None);
+    }
+
+    #[test]
+    fn rectangle_intersection() {
+        let r = Rectangle::new_from_raw(0, 0, 4, 4);
+        // Fully inside
+        assert!(Segment::new(Position::new(1, 1), Position::new(2, 2)).intersects_rectangle(&r));
+        // Passing through without an end point inside
+        assert!(Segment::new(Position::new(-1, 2), Position::new(6, 2)).intersects_rectangle(&r));
+        // Missing it entirely
+        assert!(!Segment::new(Position::new(5, 0), Position::new(5, 9)).intersects_rectangle(&r));
+
+        let fr = FRectangle::new_from_raw(0.0, 0.0, 4.0, 4.0);
+        assert!(
+            FSegment::new(FPosition::new(-1.0, 5.0), FPosition::new(5.0, -1.0))
+                .intersects_rectangle(&fr)
+        );
+        assert!(
+            !FSegment::new(FPosition::new(-1.0, 5.0), FPosition::new(0.0, 6.0))
+                .intersects_rectangle(&fr)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn closest_point_and_length() {
+        let s = Segment::new(Position::new(0, 0), Position::new(3, 4));
+        assert_eq!(s.length(), 5.0);
+        assert_eq!(
+            s.closest_point(FPosition::new(-2.0, -2.0)),
+            FPosition::new(0.0, 0.0)
+        );
+
+        let point = Segment::new(Position::new(1, 1), Position::new(1, 1));
+        assert_eq!(
+            point.closest_point(FPosition::new(5.0, 5.0)),
+            FPosition::new(1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::{Direction, Position, Rectangle, USize};
+
+/// A rotation by a multiple of 90°, clockwise as seen on the screen (i.e. with the y axis pointing
+/// down).
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum Rotation {
+    /// No rotation
+    #[default]
+    None,
+    /// A 90° clockwise rotation
+    Cw90,
+    /// A 180° rotation
+    Cw180,
+    /// A 270° clockwise (90° counter-clockwise) rotation
+    Cw270,
+}
+
+impl Rotation {
+    /// All the rotations, in increasing order.
+    pub const ALL: [Self; 4] = [Self::None, Self::Cw90, Self::Cw180, Self::Cw270];
+
+    /// Returns the number of clockwise quarter turns this rotation represents.
+    pub fn quarter_turns(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the rotation made up of the given number of clockwise quarter turns.
+    pub fn from_quarter_turns(turns: i32) -> Self {
+        Self::ALL[turns.rem_euclid(4) as usize]
+    }
+
+    /// Returns the rotation that undoes this one.
+    pub fn inverse(self) -> Self {
+        Self::from_quarter_turns(-i32::from(self.quarter_turns()))
+    }
+
+    /// Returns whether this rotation swaps the horizontal and vertical axes.
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Self::Cw90 | Self::Cw270)
+    }
+
+    fn apply(self, position: Position) -> Position {
+        let Position { x, y } = position;
+        match self {
+            Self::None => Position::new(x, y),
+            Self::Cw90 => Position::new(-y, x),
+            Self::Cw180 => Position::new(-x, -y),
+            Self::Cw270 => Position::new(y, -x),
+        }
+    }
+}
+
+/// A transformation of grid geometry made up of, in order of application, an optional
+/// horizontal mirroring, a rotation by a multiple of 90° around the origin, and a translation.
+///
+/// Transforms map cells to cells, so they can be used to place prefab rooms or stamps in any of
+/// the eight possible orientations without distorting them.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, Rotation, Transform2};
+/// let transform = Transform2::new(Position::new(10, 10), Rotation::Cw90, false);
+/// assert_eq!(transform.apply(Position::new(2, 0)), Position::new(10, 12));
+/// assert_eq!(transform.inverse().apply(Position::new(10, 12)), Position::new(2, 0));
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Transform2 {
+    /// The translation that is applied last
+    pub translation: Position,
+    /// The rotation that is applied after mirroring
+    pub rotation: Rotation,
+    /// Whether the x axis is flipped before the rotation is applied
+    pub mirror: bool,
+}
+
+impl Transform2 {
+    /// The transform that leaves everything where it is.
+    pub const IDENTITY: Self = Self {
+        translation: Position::ORIGIN,
+        rotation: Rotation::None,
+        mirror: false,
+    };
+
+    /// Returns a new transform with the given translation, rotation and mirroring.
+    pub fn new(translation: Position, rotation: Rotation, mirror: bool) -> Self {
+        Self {
+            translation,
+            rotation,
+            mirror,
+        }
+    }
+
+    /// Returns a transform that only translates by `offset`.
+    pub fn translation(offset: Position) -> Self {
+        Self::new(offset, Rotation::None, false)
+    }
+
+    /// Returns a transform that only rotates around the origin.
+    pub fn rotation(rotation: Rotation) -> Self {
+        Self::new(Position::ORIGIN, rotation, false)
+    }
+
+    /// Returns a transform that only mirrors the x axis.
+    pub fn mirror() -> Self {
+        Self::new(Position::ORIGIN, Rotation::None, true)
+    }
+
+    /// Returns this transform without its translation.
+    pub fn orientation(&self) -> Self {
+        Self::new(Position::ORIGIN, self.rotation, self.mirror)
+    }
+
+    /// Returns the transform that applies `self` first and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let turns = if next.mirror {
+            i32::from(next.rotation.quarter_turns()) - i32::from(self.rotation.quarter_turns())
+        } else {
+            i32::from(next.rotation.quarter_turns()) + i32::from(self.rotation.quarter_turns())
+        };
+
+        Self {
+            translation: next.apply(self.translation),
+            rotation: Rotation::from_quarter_turns(turns),
+            mirror: self.mirror != next.mirror,
+        }
+    }
+
+    /// Returns the transform that undoes this one.
+    pub fn inverse(&self) -> Self {
+        // Mirroring followed by a rotation is its own inverse.
+        let orientation = if self.mirror {
+            self.orientation()
+        } else {
+            Self::rotation(self.rotation.inverse())
+        };
+
+        Self {
+            translation: orientation.apply(Position::ORIGIN - self.translation),
+            ..orientation
+        }
+    }
+
+    /// Applies the transform to a position.
+    pub fn apply(&self, position: Position) -> Position {
+        let position = if self.mirror {
+            Position::new(-position.x, position.y)
+        } else {
+            position
+        };
+
+        self.rotation.apply(position) + self.translation
+    }
+
+    /// Applies the orientation of the transform to a direction; the translation does not affect
+    /// directions.
+    pub fn apply_direction(&self, direction: Direction) -> Direction {
+        Direction::from_delta(self.orientation().apply(direction.offset()))
+            .expect("a transformed unit offset is still a unit offset")
+    }
+
+    /// Applies the transform to every cell covered by `rectangle`, returning the rectangle
+    /// covering the transformed cells.
+    ///
+    /// The rectangle is treated as a block of `width` by `height` cells, so e.g. rotating a 3x2
+    /// rectangle always gives a 2x3 rectangle.
+    pub fn apply_rectangle(&self, rectangle: &Rectangle) -> Rectangle {
+        let USize { width, height } = rectangle.size;
+        if width == 0 || height == 0 {
+            let size = if self.rotation.swaps_axes() {
+                USize::new(height, width)
+            } else {
+                rectangle.size
+            };
+            return Rectangle::new(self.apply(rectangle.position), size);
+        }
+
+        let first = self.apply(rectangle.position);
+        let last =
+            self.apply(rectangle.position + Position::new(width as i32 - 1, height as i32 - 1));
+        let corners = Rectangle::from_corners(first, last);
+
+        Rectangle::new(corners.position, corners.size + 1)
+    }
+
+    /// Returns the given `width` by `height` block of row-major `cells` reoriented by this
+    /// transform's orientation, along with its new width and height. The translation is ignored.
+    pub(crate) fn reorient_cells<T: Clone>(
+        &self,
+        width: usize,
+        height: usize,
+        cells: &[T],
+    ) -> (usize, usize, Vec<T>) {
+        let bounds = self.orientation().apply_rectangle(&Rectangle::new_from_raw(
+            0,
+            0,
+            width as u32,
+            height as u32,
+        ));
+        let new_width = bounds.size.width as usize;
+        let new_height = bounds.size.height as usize;
+        let inverse = self.orientation().inverse();
+
+        let mut result = Vec::with_capacity(cells.len());
+        for y in 0..new_height {
+            for x in 0..new_width {
+                let source = inverse.apply(Position::new(x as i32, y as i32) + bounds.position);
+                result.push(cells[source.x as usize + source.y as usize * width].clone());
+            }
+        }
+
+        (new_width, new_height, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_and_inverse() {
+        let p = Position::new(3, -1);
+        for &rotation in &Rotation::ALL {
+            for &mirror in &[false, true] {
+                let t = Transform2::new(Position::new(5, 7), rotation, mirror);
+                assert_eq!(t.inverse().apply(t.apply(p)), p);
+                assert_eq!(t.then(&t.inverse()), Transform2::IDENTITY);
+
+                let u = Transform2::new(Position::new(-2, 1), Rotation::Cw90, true);
+                assert_eq!(t.then(&u).apply(p), u.apply(t.apply(p)));
+            }
+        }
+
+        assert_eq!(
+            Transform2::rotation(Rotation::Cw90).apply_direction(Direction::North),
+            Direction::East
+        );
+        assert_eq!(
+            Transform2::mirror().apply_direction(Direction::NorthEast),
+            Direction::NorthWest
+        );
+    }
+
+    #[test]
+    fn apply_rectangle() {
+        let r = Rectangle::new_from_raw(1, 2, 3, 2);
+        assert_eq!(
+            Transform2::rotation(Rotation::Cw90).apply_rectangle(&r),
+            Rectangle::new_from_raw(-3, 1, 2, 3)
+        );
+        assert_eq!(
+            Transform2::mirror().apply_rectangle(&r),
+            Rectangle::new_from_raw(-3, 2, 3, 2)
+        );
+        assert_eq!(
+            Transform2::translation(Position::new(1, 1)).apply_rectangle(&r),
+            r.translated(Position::new(1, 1))
+        );
+    }
+
+    #[test]
+    fn reorient_cells() {
+        // 1 2 3
+        // 4 5 6
+        let cells = [1, 2, 3, 4, 5, 6];
+        assert_eq!(
+            Transform2::rotation(Rotation::Cw90).reorient_cells(3, 2, &cells),
+            (2, 3, vec![4, 1, 5, 2, 6, 3])
+        );
+        assert_eq!(
+            Transform2::mirror().reorient_cells(3, 2, &cells),
+            (3, 2, vec![3, 2, 1, 6, 5, 4])
+        );
+        assert_eq!(
+            Transform2::rotation(Rotation::Cw180).reorient_cells(3, 2, &cells),
+            (3, 2, vec![6, 5, 4, 3, 2, 1])
+        );
+    }
+}
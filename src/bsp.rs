@@ -0,0 +1,569 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Binary space partitioning.
+//!
+//! This toolkit splits a rectangular area into smaller rectangles, recursively, the way
+//! libtcod's bsp toolkit does. The resulting tree is the building block of many dungeon
+//! generators: the leaves are where the rooms go, and sibling nodes are the ones to connect.
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::{Position, Rectangle, USize};
+//! # use doryen_extra::bsp::Bsp;
+//! # use doryen_extra::random::Random;
+//! let mut bsp = Bsp::new(Rectangle::new_from_raw(0, 0, 80, 50));
+//! let mut random = Random::new_mt_from_seed(42);
+//! bsp.split_recursive(&mut random, 4, USize::new(8, 8), 1.5, 1.5);
+//!
+//! assert!(bsp.iter_leaves().count() > 1);
+//! assert!(bsp.find_node(Position::new(10, 10)).unwrap().is_leaf());
+//! ```
+
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{Position, Rectangle, USize};
+use std::collections::VecDeque;
+
+/// A node of a binary space partition tree; the tree is represented by its root node.
+///
+/// A node is either a leaf, or split in two along a horizontal or vertical line, into a left
+/// child (the top or left part) and a right child (the bottom or right part).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Bsp {
+    rectangle: Rectangle,
+    level: u8,
+    horizontal: bool,
+    position: i32,
+    children: Option<Box<(Self, Self)>>,
+}
+
+impl Bsp {
+    /// Returns a new tree, with a single leaf covering `rectangle`.
+    pub fn new(rectangle: Rectangle) -> Self {
+        Self::new_with_level(rectangle, 0)
+    }
+
+    fn new_with_level(rectangle: Rectangle, level: u8) -> Self {
+        Self {
+            rectangle,
+            level,
+            horizontal: false,
+            position: 0,
+            children: None,
+        }
+    }
+
+    /// Returns the area covered by the node.
+    pub fn rectangle(&self) -> Rectangle {
+        self.rectangle
+    }
+
+    /// Returns the depth of the node in the tree; the root is at level 0.
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Returns whether the node is split along a horizontal line. Only meaningful for nodes
+    /// that aren't leaves.
+    pub fn is_horizontal(&self) -> bool {
+        self.horizontal
+    }
+
+    /// Returns the position of the line the node is split along; a y coordinate if the node is
+    /// split horizontally, and an x coordinate otherwise. Only meaningful for nodes that aren't
+    /// leaves.
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Returns whether the node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_none()
+    }
+
+    /// Returns the left (or top) child of the node, if it has been split.
+    pub fn left(&self) -> Option<&Self> {
+        self.children.as_ref().map(|children| &children.0)
+    }
+
+    /// Returns the right (or bottom) child of the node, if it has been split.
+    pub fn right(&self) -> Option<&Self> {
+        self.children.as_ref().map(|children| &children.1)
+    }
+
+    /// Returns the left (or top) child of the node mutably, if it has been split.
+    pub fn left_mut(&mut self) -> Option<&mut Self> {
+        self.children.as_mut().map(|children| &mut children.0)
+    }
+
+    /// Returns the right (or bottom) child of the node mutably, if it has been split.
+    pub fn right_mut(&mut self) -> Option<&mut Self> {
+        self.children.as_mut().map(|children| &mut children.1)
+    }
+
+    /// Removes the children of the node, turning it back into a leaf.
+    pub fn remove_children(&mut self) {
+        self.children = None;
+    }
+
+    /// Splits the node in two along the line at `position`, which is a y coordinate if
+    /// `horizontal` is `true`, and an x coordinate otherwise. Any previous children are replaced.
+    pub fn split_once(&mut self, horizontal: bool, position: i32) {
+        self.horizontal = horizontal;
+        self.position = position;
+        let (left, right) = self.child_rectangles();
+        self.children = Some(Box::new((
+            Self::new_with_level(left, self.level + 1),
+            Self::new_with_level(right, self.level + 1),
+        )));
+    }
+
+    /// Splits the node recursively, `depth` levels deep, at random positions.
+    ///
+    /// Nodes aren't split when that would make a part narrower than `min_size.width` or lower
+    /// than `min_size.height`. A node wider than `max_horizontal_ratio` times its height is
+    /// always split vertically, and one higher than `max_vertical_ratio` times its width always
+    /// horizontally, which keeps the parts roughly square.
+    pub fn split_recursive<A: Algorithm>(
+        &mut self,
+        random: &mut Random<A>,
+        depth: u32,
+        min_size: USize,
+        max_horizontal_ratio: f32,
+        max_vertical_ratio: f32,
+    ) {
+        let Rectangle {
+            position: Position { x, y },
+            size,
+        } = self.rectangle;
+        let can_split_vertically = size.width >= 2 * min_size.width;
+        let can_split_horizontally = size.height >= 2 * min_size.height;
+        if depth == 0 || (!can_split_vertically && !can_split_horizontally) {
+            return;
+        }
+
+        let (width, height) = (size.width as f32, size.height as f32);
+        let horizontal = if !can_split_horizontally || width > height * max_horizontal_ratio {
+            false
+        } else if !can_split_vertically || height > width * max_vertical_ratio {
+            true
+        } else {
+            random.get_index(2) == 0
+        };
+        // Both parts must be at least `min_size`, so the split is drawn uniformly from
+        // `min..=size - min`, whatever the generator's distribution.
+        let position = if horizontal {
+            let choices = size.height - 2 * min_size.height + 1;
+            y + (min_size.height + random.get_index(choices as usize) as u32) as i32
+        } else {
+            let choices = size.width - 2 * min_size.width + 1;
+            x + (min_size.width + random.get_index(choices as usize) as u32) as i32
+        };
+
+        self.split_once(horizontal, position);
+        if let Some(children) = self.children.as_mut() {
+            children.0.split_recursive(
+                random,
+                depth - 1,
+                min_size,
+                max_horizontal_ratio,
+                max_vertical_ratio,
+            );
+            children.1.split_recursive(
+                random,
+                depth - 1,
+                min_size,
+                max_horizontal_ratio,
+                max_vertical_ratio,
+            );
+        }
+    }
+
+    /// Changes the area covered by the node, and resizes its children to match, keeping the
+    /// positions they are split at.
+    pub fn resize(&mut self, rectangle: Rectangle) {
+        self.rectangle = rectangle;
+        if self.children.is_some() {
+            let (left, right) = self.child_rectangles();
+            if let Some(children) = self.children.as_mut() {
+                children.0.resize(left);
+                children.1.resize(right);
+            }
+        }
+    }
+
+    fn child_rectangles(&self) -> (Rectangle, Rectangle) {
+        let Rectangle {
+            position: Position { x, y },
+            size,
+        } = self.rectangle;
+        let (width, height) = (size.width as i32, size.height as i32);
+        let position = self.position;
+        let (left, right) = if self.horizontal {
+            (
+                (x, y, width, position - y),
+                (x, position, width, y + height - position),
+            )
+        } else {
+            (
+                (x, y, position - x, height),
+                (position, y, x + width - position, height),
+            )
+        };
+
+        (to_rectangle(left), to_rectangle(right))
+    }
+
+    /// Returns whether `position` is within the area covered by the node.
+    pub fn contains(&self, position: Position) -> bool {
+        let Rectangle {
+            position: Position { x, y },
+            size,
+        } = self.rectangle;
+
+        position.x >= x
+            && position.y >= y
+            && position.x < x + size.width as i32
+            && position.y < y + size.height as i32
+    }
+
+    /// Returns the leaf containing `position`, or `None` if it's outside the node.
+    pub fn find_node(&self, position: Position) -> Option<&Self> {
+        if !self.contains(position) {
+            return None;
+        }
+        match self.children.as_ref() {
+            Some(children) => children
+                .0
+                .find_node(position)
+                .or_else(|| children.1.find_node(position)),
+            None => Some(self),
+        }
+    }
+
+    /// Returns an iterator over the node, then the nodes of its left subtree, then the nodes
+    /// of its right subtree.
+    pub fn iter_pre_order(&self) -> impl Iterator<Item = &Self> {
+        PreOrder { stack: vec![self] }
+    }
+
+    /// Returns an iterator over the nodes of the left subtree, then the node, then the nodes of
+    /// the right subtree.
+    pub fn iter_in_order(&self) -> impl Iterator<Item = &Self> {
+        InOrder {
+            stack: Vec::new(),
+            current: Some(self),
+        }
+    }
+
+    /// Returns an iterator over the nodes of the left subtree, then the nodes of the right
+    /// subtree, then the node.
+    pub fn iter_post_order(&self) -> impl Iterator<Item = &Self> {
+        PostOrder {
+            stack: vec![(self, false)],
+        }
+    }
+
+    /// Returns an iterator over the nodes level by level, from the root down, and from left to
+    /// right within a level.
+    pub fn iter_level_order(&self) -> impl Iterator<Item = &Self> {
+        let mut queue = VecDeque::new();
+        queue.push_back(self);
+        LevelOrder { queue }
+    }
+
+    /// Returns an iterator over the nodes level by level, from the bottom up, and from right to
+    /// left within a level.
+    pub fn iter_inverted_level_order(&self) -> impl Iterator<Item = &Self> {
+        let nodes: Vec<_> = self.iter_level_order().collect();
+        nodes.into_iter().rev()
+    }
+
+    /// Returns an iterator over the leaves, from left to right.
+    pub fn iter_leaves(&self) -> impl Iterator<Item = &Self> {
+        self.iter_pre_order().filter(|node| node.is_leaf())
+    }
+
+    /// Calls `callback` on the nodes in the order of `iter_pre_order`. The traversal stops as
+    /// soon as `callback` returns `false`, and the return value tells whether it went all the
+    /// way through.
+    pub fn traverse_pre_order<F: FnMut(&Self) -> bool>(&self, callback: F) -> bool {
+        self.iter_pre_order().all(callback)
+    }
+
+    /// Like `traverse_pre_order`, but in the order of `iter_in_order`.
+    pub fn traverse_in_order<F: FnMut(&Self) -> bool>(&self, callback: F) -> bool {
+        self.iter_in_order().all(callback)
+    }
+
+    /// Like `traverse_pre_order`, but in the order of `iter_post_order`.
+    pub fn traverse_post_order<F: FnMut(&Self) -> bool>(&self, callback: F) -> bool {
+        self.iter_post_order().all(callback)
+    }
+
+    /// Like `traverse_pre_order`, but in the order of `iter_level_order`.
+    pub fn traverse_level_order<F: FnMut(&Self) -> bool>(&self, callback: F) -> bool {
+        self.iter_level_order().all(callback)
+    }
+
+    /// Like `traverse_pre_order`, but in the order of `iter_inverted_level_order`.
+    pub fn traverse_inverted_level_order<F: FnMut(&Self) -> bool>(&self, callback: F) -> bool {
+        self.iter_inverted_level_order().all(callback)
+    }
+}
+
+struct PreOrder<'a> {
+    stack: Vec<&'a Bsp>,
+}
+
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = &'a Bsp;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if let Some(children) = node.children.as_ref() {
+            self.stack.push(&children.1);
+            self.stack.push(&children.0);
+        }
+
+        Some(node)
+    }
+}
+
+struct InOrder<'a> {
+    stack: Vec<&'a Bsp>,
+    current: Option<&'a Bsp>,
+}
+
+impl<'a> Iterator for InOrder<'a> {
+    type Item = &'a Bsp;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current {
+            self.stack.push(node);
+            self.current = node.left();
+        }
+        let node = self.stack.pop()?;
+        self.current = node.right();
+
+        Some(node)
+    }
+}
+
+// The flag tells whether the children of the node have already been pushed.
+struct PostOrder<'a> {
+    stack: Vec<(&'a Bsp, bool)>,
+}
+
+impl<'a> Iterator for PostOrder<'a> {
+    type Item = &'a Bsp;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            match node.children.as_ref() {
+                Some(children) if !expanded => {
+                    self.stack.push((node, true));
+                    self.stack.push((&children.1, false));
+                    self.stack.push((&children.0, false));
+                }
+                _ => return Some(node),
+            }
+        }
+    }
+}
+
+struct LevelOrder<'a> {
+    queue: VecDeque<&'a Bsp>,
+}
+
+impl<'a> Iterator for LevelOrder<'a> {
+    type Item = &'a Bsp;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        if let Some(children) = node.children.as_ref() {
+            self.queue.push_back(&children.0);
+            self.queue.push_back(&children.1);
+        }
+
+        Some(node)
+    }
+}
+
+fn to_rectangle((x, y, width, height): (i32, i32, i32, i32)) -> Rectangle {
+    Rectangle::new_from_raw(x, y, width.max(0) as u32, height.max(0) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+
+    fn tree() -> Bsp {
+        let mut bsp = Bsp::new(Rectangle::new_from_raw(0, 0, 20, 10));
+        bsp.split_once(false, 8);
+        bsp.right_mut().unwrap().split_once(true, 4);
+        bsp
+    }
+
+    fn order(traverse: impl FnOnce(&Bsp, &mut dyn FnMut(&Bsp) -> bool) -> bool) -> Vec<Rectangle> {
+        let bsp = tree();
+        let mut rectangles = Vec::new();
+        assert!(traverse(&bsp, &mut |node| {
+            rectangles.push(node.rectangle());
+            true
+        }));
+        rectangles
+    }
+
+    #[test]
+    fn split_and_find() {
+        let mut bsp = tree();
+        let left = Rectangle::new_from_raw(0, 0, 8, 10);
+        let top_right = Rectangle::new_from_raw(8, 0, 12, 4);
+        let bottom_right = Rectangle::new_from_raw(8, 4, 12, 6);
+        assert_eq!(bsp.left().unwrap().rectangle(), left);
+        assert_eq!(bsp.right().unwrap().left().unwrap().rectangle(), top_right);
+        assert_eq!(bsp.right().unwrap().right().unwrap().level(), 2);
+        assert_eq!(
+            bsp.find_node(Position::new(10, 5)).unwrap().rectangle(),
+            bottom_right
+        );
+        assert_eq!(bsp.find_node(Position::new(20, 5)), None);
+
+        bsp.resize(Rectangle::new_from_raw(0, 0, 30, 20));
+        assert_eq!(
+            bsp.right().unwrap().right().unwrap().rectangle(),
+            Rectangle::new_from_raw(8, 4, 22, 16)
+        );
+    }
+
+    #[test]
+    fn traversals() {
+        let root = Rectangle::new_from_raw(0, 0, 20, 10);
+        let left = Rectangle::new_from_raw(0, 0, 8, 10);
+        let right = Rectangle::new_from_raw(8, 0, 12, 10);
+        let top_right = Rectangle::new_from_raw(8, 0, 12, 4);
+        let bottom_right = Rectangle::new_from_raw(8, 4, 12, 6);
+
+        assert_eq!(
+            order(|bsp, f| bsp.traverse_pre_order(f)),
+            vec![root, left, right, top_right, bottom_right]
+        );
+        assert_eq!(
+            order(|bsp, f| bsp.traverse_in_order(f)),
+            vec![left, root, top_right, right, bottom_right]
+        );
+        assert_eq!(
+            order(|bsp, f| bsp.traverse_post_order(f)),
+            vec![left, top_right, bottom_right, right, root]
+        );
+        assert_eq!(
+            order(|bsp, f| bsp.traverse_level_order(f)),
+            vec![root, left, right, top_right, bottom_right]
+        );
+        assert_eq!(
+            order(|bsp, f| bsp.traverse_inverted_level_order(f)),
+            vec![bottom_right, top_right, right, left, root]
+        );
+
+        let leaves: Vec<_> = tree().iter_leaves().map(Bsp::rectangle).collect();
+        assert_eq!(leaves, vec![left, top_right, bottom_right]);
+
+        let mut visited = 0;
+        assert!(!tree().traverse_pre_order(|_| {
+            visited += 1;
+            visited < 2
+        }));
+        assert_eq!(visited, 2);
+    }
+
+    #[test]
+    fn recursive_split_respects_minimum_size() {
+        let mut bsp = Bsp::new(Rectangle::new_from_raw(0, 0, 60, 40));
+        bsp.split_recursive(
+            &mut Random::new_mt_from_seed(1),
+            8,
+            USize::new(6, 5),
+            1.5,
+            1.5,
+        );
+        for node in bsp.iter_pre_order() {
+            let size = node.rectangle().size;
+            assert!(size.width >= 6 && size.height >= 5);
+        }
+        assert!(bsp.iter_leaves().count() > 8);
+    }
+
+    #[test]
+    fn recursive_split_works_with_any_distribution() {
+        fn assert_within(node: &Bsp, min_size: USize) {
+            let Rectangle { position, size } = node.rectangle();
+            assert!(size.width >= min_size.width && size.height >= min_size.height);
+            for child in node.left().into_iter().chain(node.right()) {
+                let child_rectangle = child.rectangle();
+                assert!(node.contains(child_rectangle.position));
+                assert!(
+                    child_rectangle.position.x + child_rectangle.size.width as i32
+                        <= position.x + size.width as i32
+                );
+                assert!(
+                    child_rectangle.position.y + child_rectangle.size.height as i32
+                        <= position.y + size.height as i32
+                );
+                assert_within(child, min_size);
+            }
+        }
+
+        for &distribution in &[
+            Distribution::Linear,
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut random = Random::new_mt_from_seed(1);
+            random.distribution = distribution;
+            let min_size = USize::new(5, 5);
+            let mut bsp = Bsp::new(Rectangle::new_from_raw(0, 0, 80, 50));
+            bsp.split_recursive(&mut random, 5, min_size, 1.5, 1.5);
+            assert_within(&bsp, min_size);
+            assert!(bsp.iter_leaves().count() > 8);
+        }
+    }
+}
@@ -173,7 +173,7 @@ impl Color {
     /// let translucent_light_blue = Color::new_hsv_with_opacity(240.0, 0.75, 1.0, 0.5);
     /// ```
     pub fn new_hsv_with_opacity(hue: f32, saturation: f32, value: f32, opacity: f32) -> Self {
-        let a = (opacity.max(0.0).min(1.0) * 255.0).round() as u8;
+        let a = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
         let mut color = Self::new_with_alpha(0, 0, 0, a);
         color.set_hsv(hue, saturation, value);
 
@@ -190,8 +190,8 @@ impl Color {
     /// Values outside the given ranges are clipped to fit within the allowed range.
     #[allow(clippy::many_single_char_names)]
     pub fn set_hsv(&mut self, hue: f32, saturation: f32, value: f32) {
-        let saturation = saturation.max(0.0).min(1.0);
-        let value = value.max(0.0).min(1.0);
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
 
         if saturation == 0.0 {
             /* achromatic (gray) */
@@ -238,7 +238,7 @@ impl Color {
     ///
     /// # Parameters
     /// * `hue` - The color's hue in degrees. Values outside the
-    /// given range loop around to fit within the allowed range.
+    ///   given range loop around to fit within the allowed range.
     ///
     /// # Examples
     /// ```rust
@@ -312,7 +312,7 @@ impl Color {
     ///
     /// # Parameters
     /// * `saturation` - The color's saturation, from 0 to 1. Values outside the
-    /// given range are clipped to fit within the allowed range.
+    ///   given range are clipped to fit within the allowed range.
     ///
     /// # Examples
     /// ```rust
@@ -458,7 +458,7 @@ impl Color {
     /// # Parameters
     /// * `key_colors` -  The colors to make gradients between.
     /// * `gradient_spans` -  How many interpolated colors to generate between each
-    /// pair of key colors.
+    ///   pair of key colors.
     ///
     /// # Panics
     /// * If `gradient_spans`' length isn't one less than `key_colors`' length.
@@ -525,7 +525,7 @@ impl Color {
     /// # Parameters
     /// * `key_colors` -  The colors to make gradients between.
     /// * `gradient_spans` -  How many interpolated colors to generate between each
-    /// pair of key colors.
+    ///   pair of key colors.
     ///
     /// # Panics
     /// * If `gradient_spans`' length isn't one less than `key_colors`' length.
@@ -592,7 +592,7 @@ impl Color {
     /// If `coefficient` is outside the range \[0, 1\].
     pub fn lerp_rgb(self, other: Self, coefficient: f32) -> Self {
         assert!(
-            coefficient >= 0.0 && coefficient <= 1.0,
+            (0.0..=1.0).contains(&coefficient),
             "coefficient is outside the acceptable range [0, 1]"
         );
 
@@ -615,7 +615,7 @@ impl Color {
     /// If `coefficient` is outside the range \[0, 1\].
     pub fn lerp_hsv(self, other: Self, coefficient: f32) -> Self {
         assert!(
-            coefficient >= 0.0 && coefficient <= 1.0,
+            (0.0..=1.0).contains(&coefficient),
             "coefficient is outside the acceptable range [0, 1]"
         );
         let (self_hue, self_saturation, self_value) = self.get_hsv();
@@ -1139,10 +1139,10 @@ impl Mul<f32> for Color {
     /// Multiply a color with a scalar value and return the result.
     fn mul(self, rhs: f32) -> Self::Output {
         Self::new_with_alpha(
-            (f32::from(self.r) * rhs).min(255.0).max(0.0) as u8,
-            (f32::from(self.g) * rhs).min(255.0).max(0.0) as u8,
-            (f32::from(self.b) * rhs).min(255.0).max(0.0) as u8,
-            (f32::from(self.a) * rhs).min(255.0).max(0.0) as u8,
+            (f32::from(self.r) * rhs).clamp(0.0, 255.0) as u8,
+            (f32::from(self.g) * rhs).clamp(0.0, 255.0) as u8,
+            (f32::from(self.b) * rhs).clamp(0.0, 255.0) as u8,
+            (f32::from(self.a) * rhs).clamp(0.0, 255.0) as u8,
         )
     }
 }
@@ -1222,9 +1222,89 @@ pub enum Level {
     Darkest,
 }
 
+/// Maps scalar values, such as heights, to colors by interpolating between a set of key colors.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::color::{Color, ColorMap};
+/// let map = ColorMap::new(&[(0.0, Color::BLACK), (1.0, Color::WHITE)]);
+///
+/// assert_eq!(map.color_at(-1.0), Color::BLACK);
+/// assert_eq!(map.color_at(0.5), Color::new(127, 127, 127));
+/// assert_eq!(map.color_at(2.0), Color::WHITE);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct ColorMap {
+    keys: Vec<(f32, Color)>,
+}
+
+impl ColorMap {
+    /// Returns a new color map with the given key values and their colors. The keys do not need
+    /// to be given in order.
+    ///
+    /// # Panics
+    /// * If `keys` is empty.
+    /// * If any of the key values is NaN.
+    pub fn new(keys: &[(f32, Color)]) -> Self {
+        assert!(!keys.is_empty(), "a color map needs at least one key");
+        assert!(
+            keys.iter().all(|(value, _)| !value.is_nan()),
+            "color map key values can't be NaN"
+        );
+
+        let mut keys = keys.to_vec();
+        keys.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        Self { keys }
+    }
+
+    /// Returns the key values and their colors, ordered by value.
+    pub fn keys(&self) -> &[(f32, Color)] {
+        &self.keys
+    }
+
+    /// Returns the color for `value`, interpolated (in RGB) between the two nearest keys. Values
+    /// outside the range of the keys get the color of the nearest key.
+    pub fn color_at(&self, value: f32) -> Color {
+        let index = self.keys.partition_point(|(key, _)| *key <= value);
+        if index == 0 {
+            return self.keys[0].1;
+        }
+        if index == self.keys.len() {
+            return self.keys[index - 1].1;
+        }
+
+        let (low, low_color) = self.keys[index - 1];
+        let (high, high_color) = self.keys[index];
+
+        low_color.lerp_rgb(high_color, (value - low) / (high - low))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::color::Color;
+    use crate::color::{Color, ColorMap};
+
+    #[test]
+    fn color_map() {
+        let map = ColorMap::new(&[
+            (1.0, Color::WHITE),
+            (-1.0, Color::BLACK),
+            (0.0, Color::new(0, 0, 200)),
+        ]);
+        assert_eq!(map.keys()[0], (-1.0, Color::BLACK));
+        assert_eq!(map.color_at(-0.5), Color::new(0, 0, 100));
+        assert_eq!(map.color_at(0.0), Color::new(0, 0, 200));
+        assert_eq!(map.color_at(1.0), Color::WHITE);
+        assert_eq!(
+            ColorMap::new(&[(3.0, Color::RED)]).color_at(-9.0),
+            Color::RED
+        );
+    }
 
     #[test]
     fn hsv() {
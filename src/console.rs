@@ -0,0 +1,832 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Engine-agnostic console drawing
+//!
+//! The `ConsoleTarget` trait abstracts over anything made up of a grid of character cells with
+//! a foreground and a background color, so the drawing helpers it provides work with any
+//! rendering backend. The `doryen-rs` console implements it when the `doryen` feature is enabled,
+//! and a `Grid<Cell>` implements it everywhere, as an in-memory buffer that other backends, such
+//! as terminal or SDL renderers, can draw from.
+
+use crate::bresenham::Bresenham;
+use crate::color::{Color, ColorMap};
+use crate::grid::Grid;
+use crate::heightmap::HeightMap;
+use crate::image::Image;
+use crate::raster;
+use crate::text::ColoredRun;
+use crate::{FPosition, Position, Rectangle, USize};
+use std::convert::TryFrom;
+
+/// The character code drawn in place of the characters that don't fit in a `u16` character
+/// code, i.e. those above U+FFFF, such as most emoji.
+pub const REPLACEMENT_CHARACTER: u16 = b'?' as u16;
+
+/// Returns the character code `character` is drawn with, which is `REPLACEMENT_CHARACTER` if
+/// it's above U+FFFF, rather than the truncated value of its code point.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::console::{character_code, REPLACEMENT_CHARACTER};
+/// assert_eq!(character_code('@'), 64);
+/// assert_eq!(character_code('\u{2588}'), 0x2588);
+/// assert_eq!(character_code('\u{1F409}'), REPLACEMENT_CHARACTER);
+/// ```
+pub fn character_code(character: char) -> u16 {
+    u16::try_from(u32::from(character)).unwrap_or(REPLACEMENT_CHARACTER)
+}
+
+/// A grid of character cells that can be drawn on.
+///
+/// Implementors only need to provide access to the size and the individual cells; everything
+/// else is built on top of that. Setting a cell outside of the target does nothing, and reading
+/// one returns `None`.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, USize};
+/// # use doryen_extra::color::Color;
+/// # use doryen_extra::console::{Cell, ConsoleTarget};
+/// # use doryen_extra::grid::Grid;
+/// let mut buffer: Grid<Cell> = Grid::new_default(10, 5);
+/// buffer.draw_line(Position::new(0, 0), Position::new(9, 4), '*', Some(Color::RED), None);
+/// assert_eq!(buffer.ascii(Position::new(9, 4)), Some(u16::from(b'*')));
+/// assert_eq!(buffer.fore(Position::new(0, 0)), Some(Color::RED));
+/// ```
+pub trait ConsoleTarget {
+    /// Returns the size of the target, in cells.
+    fn size(&self) -> USize;
+
+    /// Gets the character code of a cell.
+    fn ascii(&self, position: Position) -> Option<u16>;
+
+    /// Gets the foreground color of a cell.
+    fn fore(&self, position: Position) -> Option<Color>;
+
+    /// Gets the background color of a cell.
+    fn back(&self, position: Position) -> Option<Color>;
+
+    /// Sets the character code of a cell.
+    fn set_ascii(&mut self, position: Position, ascii: u16);
+
+    /// Sets the foreground color of a cell.
+    fn set_fore(&mut self, position: Position, color: Color);
+
+    /// Sets the background color of a cell.
+    fn set_back(&mut self, position: Position, color: Color);
+
+    /// Changes the properties of a cell at once, leaving the ones that are `None` as they are.
+    fn cell(
+        &mut self,
+        position: Position,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        if let Some(ascii) = ascii {
+            self.set_ascii(position, ascii);
+        }
+        if let Some(fore) = fore {
+            self.set_fore(position, fore);
+        }
+        if let Some(back) = back {
+            self.set_back(position, back);
+        }
+    }
+
+    /// Changes the properties of every cell in `rectangle`, leaving the ones that are `None` as
+    /// they are.
+    fn area(
+        &mut self,
+        rectangle: Rectangle,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill_char: Option<u16>,
+    ) {
+        let Rectangle { position, size } = rectangle;
+        for y in 0..size.height as i32 {
+            for x in 0..size.width as i32 {
+                self.cell(position + Position::new(x, y), fill_char, fore, back);
+            }
+        }
+    }
+
+    /// Prints the provided character to the given position. Characters above U+FFFF are drawn
+    /// as `REPLACEMENT_CHARACTER`; see `character_code`.
+    fn print_char(
+        &mut self,
+        position: Position,
+        character: char,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.cell(position, Some(character_code(character)), fore, back);
+    }
+
+    /// Prints runs of colored text, as returned by [`ColorCodeParser::parse`], starting at the
+    /// given position. Line breaks go back to `position.x` on the next row.
+    ///
+    /// The colors of each run override `fore` and `back`, which are used for the runs without
+    /// colors of their own.
+    ///
+    /// [`ColorCodeParser::parse`]: ../text/struct.ColorCodeParser.html#method.parse
+    fn print_runs(
+        &mut self,
+        position: Position,
+        runs: &[ColoredRun],
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        let mut current = position;
+        for run in runs {
+            for character in run.text.chars() {
+                if character == '\n' {
+                    current = Position::new(position.x, current.y + 1);
+                    continue;
+                }
+                self.print_char(current, character, run.fore.or(fore), run.back.or(back));
+                current.x += 1;
+            }
+        }
+    }
+
+    /// Draws the border of `rectangle` with the characters of `border_style`, possibly filling
+    /// its inside with a character.
+    fn draw_frame(
+        &mut self,
+        rectangle: Rectangle,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+        border_style: BorderStyle,
+    ) {
+        let Rectangle {
+            position: Position { x, y },
+            size: USize {
+                width: w,
+                height: h,
+            },
+        } = rectangle;
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        let [top_left, top, top_right, left, right, bottom_left, bottom, bottom_right] =
+            border_style.characters();
+        let right_x = x + w as i32 - 1;
+        let bottom_y = y + h as i32 - 1;
+        let inner_width = w.saturating_sub(2);
+        let inner_height = h.saturating_sub(2);
+
+        self.area(
+            Rectangle::new_from_raw(x + 1, y, inner_width, 1),
+            fore,
+            back,
+            Some(top),
+        );
+        self.area(
+            Rectangle::new_from_raw(x + 1, bottom_y, inner_width, 1),
+            fore,
+            back,
+            Some(bottom),
+        );
+        self.area(
+            Rectangle::new_from_raw(x, y + 1, 1, inner_height),
+            fore,
+            back,
+            Some(left),
+        );
+        self.area(
+            Rectangle::new_from_raw(right_x, y + 1, 1, inner_height),
+            fore,
+            back,
+            Some(right),
+        );
+        self.cell(Position::new(x, bottom_y), Some(bottom_left), fore, back);
+        self.cell(
+            Position::new(right_x, bottom_y),
+            Some(bottom_right),
+            fore,
+            back,
+        );
+        self.cell(Position::new(right_x, y), Some(top_right), fore, back);
+        self.cell(Position::new(x, y), Some(top_left), fore, back);
+        if fill.is_some() {
+            self.area(
+                Rectangle::new_from_raw(x + 1, y + 1, inner_width, inner_height),
+                fore,
+                back,
+                fill,
+            );
+        }
+    }
+
+    /// Draws a line of `character` from `from` to `to`, both ends included. Parts of the line
+    /// that fall outside the target are skipped.
+    fn draw_line(
+        &mut self,
+        from: Position,
+        to: Position,
+        character: char,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.print_char(from, character, fore, back);
+        for position in Bresenham::init(from, to) {
+            self.print_char(position, character, fore, back);
+        }
+    }
+
+    /// Draws the outline of a circle with `outline`, optionally filling its inside with `fill`.
+    fn draw_circle(
+        &mut self,
+        center: Position,
+        radius: u32,
+        outline: char,
+        fill: Option<char>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw_ellipse(
+            center,
+            USize::new(radius, radius),
+            outline,
+            fill,
+            fore,
+            back,
+        );
+    }
+
+    /// Draws the outline of an axis-aligned ellipse with the given horizontal and vertical radii
+    /// with `outline`, optionally filling its inside with `fill`.
+    fn draw_ellipse(
+        &mut self,
+        center: Position,
+        radii: USize,
+        outline: char,
+        fill: Option<char>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        if let Some(fill) = fill {
+            for position in raster::filled_ellipse(center, radii) {
+                self.print_char(position, fill, fore, back);
+            }
+        }
+        for position in raster::ellipse(center, radii) {
+            self.print_char(position, outline, fore, back);
+        }
+    }
+
+    /// Marks every position of `path`, e.g. to inspect the result of a path finding algorithm.
+    /// Each cell's character and colors are only changed for the values that are given, so
+    /// passing only a `back` color highlights the path without hiding what's on it.
+    fn draw_path<I: IntoIterator<Item = Position>>(
+        &mut self,
+        path: I,
+        character: Option<char>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) where
+        Self: Sized,
+    {
+        let ascii = character.map(character_code);
+        for position in path {
+            self.cell(position, ascii, fore, back);
+        }
+    }
+
+    /// Colors the cells along the edges of each of the `regions`, e.g. to inspect the nodes of a
+    /// BSP tree or the rooms of a generated map. Each region is treated as a block of cells, so
+    /// its right and bottom edges are the last cells inside it; the characters are left as they
+    /// are.
+    fn draw_region_outlines<I: IntoIterator<Item = Rectangle>>(
+        &mut self,
+        regions: I,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) where
+        Self: Sized,
+    {
+        for region in regions {
+            let Rectangle {
+                position: Position { x, y },
+                size: USize { width, height },
+            } = region;
+            if width == 0 || height == 0 {
+                continue;
+            }
+
+            let right = x + width as i32 - 1;
+            let bottom = y + height as i32 - 1;
+            for edge_x in x..=right {
+                self.cell(Position::new(edge_x, y), None, fore, back);
+                self.cell(Position::new(edge_x, bottom), None, fore, back);
+            }
+            for edge_y in y..=bottom {
+                self.cell(Position::new(x, edge_y), None, fore, back);
+                self.cell(Position::new(right, edge_y), None, fore, back);
+            }
+        }
+    }
+
+    /// Fills the background of the cells in `rectangle` with colors from `color_map` based on
+    /// the values of `heightmap`, one cell per height map value, starting at the height map's
+    /// upper-left corner. Cells outside of the height map are left untouched.
+    ///
+    /// If `water_level` is provided, terrain above it is shaded as if lit from the upper-left,
+    /// while everything below it is treated as a flat surface at that level.
+    fn draw_heightmap(
+        &mut self,
+        heightmap: &HeightMap,
+        rectangle: Rectangle,
+        color_map: &ColorMap,
+        water_level: Option<f32>,
+    ) {
+        // Light coming from the upper-left and above; a flat surface keeps its original color.
+        const LIGHT: [f32; 3] = [-0.577_35, -0.577_35, 0.577_35];
+
+        let width = (rectangle.size.width as usize).min(heightmap.width());
+        let height = (rectangle.size.height as usize).min(heightmap.height());
+        for y in 0..height {
+            for x in 0..width {
+                let value = heightmap.values()[x + y * heightmap.width()];
+                let mut color = color_map.color_at(value);
+                if let Some(water_level) = water_level {
+                    let [nx, ny, nz] =
+                        heightmap.normal(FPosition::new(x as f32, y as f32), water_level);
+                    let intensity = nx * LIGHT[0] + ny * LIGHT[1] + nz * LIGHT[2];
+                    let shaded = color * (intensity.max(0.0) / LIGHT[2]);
+                    color = Color {
+                        a: color.a,
+                        ..shaded
+                    };
+                }
+
+                self.set_back(
+                    rectangle.position + Position::new(x as i32, y as i32),
+                    color,
+                );
+            }
+        }
+    }
+
+    /// Lights the cells covered by `light`, placed with its upper-left corner at `position`,
+    /// where each value of `light` is the light intensity of the matching cell, from 0 (unlit) to
+    /// 1 (fully lit); values outside that range are clamped.
+    ///
+    /// Each cell's light color is interpolated between `ambient` and `lit` by its intensity and
+    /// then applied according to `mode`. A field of view can be used directly by mapping it to a
+    /// grid of intensities, e.g. with `fov.map(|&visible| if visible { 1.0 } else { 0.0 })`.
+    fn apply_lighting(
+        &mut self,
+        position: Position,
+        light: &Grid<f32>,
+        ambient: Color,
+        lit: Color,
+        mode: LightingMode,
+    ) {
+        for (offset, &intensity) in light.enumerate() {
+            let target_position = position + offset;
+            let light_color = ambient.lerp_rgb(lit, intensity.clamp(0.0, 1.0));
+            match mode {
+                LightingMode::Multiply => {
+                    if let (Some(fore), Some(back)) =
+                        (self.fore(target_position), self.back(target_position))
+                    {
+                        self.set_fore(target_position, fore * light_color);
+                        self.set_back(target_position, back * light_color);
+                    }
+                }
+                LightingMode::Replace => self.set_back(target_position, light_color),
+            }
+        }
+    }
+
+    /// Returns an image with one pixel per cell, colored with the cell's background color, e.g.
+    /// to capture what a console contained in automated tests or bug reports.
+    fn back_to_image(&self) -> Image {
+        let size = self.size();
+        let pixels = (0..size.height as i32)
+            .flat_map(|y| (0..size.width as i32).map(move |x| Position::new(x, y)))
+            .map(|position| self.back(position).unwrap_or_default())
+            .collect();
+
+        Image::new_with_pixels(size, pixels)
+    }
+
+    /// Returns an image with one pixel per cell, colored with the cell's foreground color.
+    fn fore_to_image(&self) -> Image {
+        let size = self.size();
+        let pixels = (0..size.height as i32)
+            .flat_map(|y| (0..size.width as i32).map(move |x| Position::new(x, y)))
+            .map(|position| self.fore(position).unwrap_or_default())
+            .collect();
+
+        Image::new_with_pixels(size, pixels)
+    }
+}
+
+/// A character cell with a foreground and a background color, as stored in a `Grid<Cell>`
+/// console buffer.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Cell {
+    /// The character code of the cell
+    pub ascii: u16,
+    /// The foreground color of the cell
+    pub fore: Color,
+    /// The background color of the cell
+    pub back: Color,
+}
+
+impl Default for Cell {
+    /// Returns a blank cell, with white text on a black background.
+    fn default() -> Self {
+        Self {
+            ascii: u16::from(b' '),
+            fore: Color::WHITE,
+            back: Color::BLACK,
+        }
+    }
+}
+
+impl ConsoleTarget for Grid<Cell> {
+    fn size(&self) -> USize {
+        Self::size(self)
+    }
+
+    fn ascii(&self, position: Position) -> Option<u16> {
+        self.get(position).map(|cell| cell.ascii)
+    }
+
+    fn fore(&self, position: Position) -> Option<Color> {
+        self.get(position).map(|cell| cell.fore)
+    }
+
+    fn back(&self, position: Position) -> Option<Color> {
+        self.get(position).map(|cell| cell.back)
+    }
+
+    fn set_ascii(&mut self, position: Position, ascii: u16) {
+        if let Some(cell) = self.get_mut(position) {
+            cell.ascii = ascii;
+        }
+    }
+
+    fn set_fore(&mut self, position: Position, color: Color) {
+        if let Some(cell) = self.get_mut(position) {
+            cell.fore = color;
+        }
+    }
+
+    fn set_back(&mut self, position: Position, color: Color) {
+        if let Some(cell) = self.get_mut(position) {
+            cell.back = color;
+        }
+    }
+}
+
+#[cfg(feature = "doryen")]
+impl ConsoleTarget for doryen_rs::Console {
+    fn size(&self) -> USize {
+        USize::new(self.get_width(), self.get_height())
+    }
+
+    fn ascii(&self, position: Position) -> Option<u16> {
+        self.get_ascii(position.x, position.y)
+    }
+
+    fn fore(&self, position: Position) -> Option<Color> {
+        self.get_fore(position.x, position.y).map(Into::into)
+    }
+
+    fn back(&self, position: Position) -> Option<Color> {
+        self.get_back(position.x, position.y).map(Into::into)
+    }
+
+    fn set_ascii(&mut self, position: Position, ascii: u16) {
+        Self::ascii(self, position.x, position.y, ascii);
+    }
+
+    fn set_fore(&mut self, position: Position, color: Color) {
+        Self::fore(self, position.x, position.y, color.into());
+    }
+
+    fn set_back(&mut self, position: Position, color: Color) {
+        Self::back(self, position.x, position.y, color.into());
+    }
+
+    fn cell(
+        &mut self,
+        position: Position,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        Self::cell(
+            self,
+            position.x,
+            position.y,
+            ascii,
+            fore.map(Into::into),
+            back.map(Into::into),
+        );
+    }
+
+    fn area(
+        &mut self,
+        rectangle: Rectangle,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill_char: Option<u16>,
+    ) {
+        Self::area(
+            self,
+            rectangle.position.x,
+            rectangle.position.y,
+            rectangle.size.width,
+            rectangle.size.height,
+            fore.map(Into::into),
+            back.map(Into::into),
+            fill_char,
+        );
+    }
+}
+
+/// The characters used to draw the border of a frame with `ConsoleTarget::draw_frame`.
+///
+/// The built-in styles use the box-drawing characters of the CP437 code page, which is the layout
+/// of the fonts used by `doryen-rs`.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum BorderStyle {
+    /// Single lines, e.g. `┌─┐`
+    #[default]
+    Single,
+    /// Double lines, e.g. `╔═╗`
+    Double,
+    /// Thick lines made from block characters, e.g. `█▀█`
+    Heavy,
+    /// Plain ASCII characters, e.g. `+-+`
+    Ascii,
+    /// A custom set of characters, in the order top-left corner, top edge, top-right corner,
+    /// left edge, right edge, bottom-left corner, bottom edge and bottom-right corner.
+    Custom([u16; 8]),
+}
+
+impl BorderStyle {
+    /// Returns the characters of the border style, in the order top-left corner, top edge,
+    /// top-right corner, left edge, right edge, bottom-left corner, bottom edge and bottom-right
+    /// corner.
+    pub fn characters(self) -> [u16; 8] {
+        match self {
+            Self::Single => [218, 196, 191, 179, 179, 192, 196, 217],
+            Self::Double => [201, 205, 187, 186, 186, 200, 205, 188],
+            Self::Heavy => [219, 223, 219, 219, 219, 219, 220, 219],
+            Self::Ascii => [
+                u16::from(b'+'),
+                u16::from(b'-'),
+                u16::from(b'+'),
+                u16::from(b'|'),
+                u16::from(b'|'),
+                u16::from(b'+'),
+                u16::from(b'-'),
+                u16::from(b'+'),
+            ],
+            Self::Custom(characters) => characters,
+        }
+    }
+}
+
+/// How `ConsoleTarget::apply_lighting` applies light colors to the cells.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LightingMode {
+    /// Multiplies both the foreground and background colors of each cell by its light color,
+    /// keeping whatever was drawn visible but tinted and darkened.
+    Multiply,
+    /// Replaces the background color of each cell with its light color.
+    Replace,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::ColorCodeParser;
+
+    #[test]
+    fn grid_buffer_drawing() {
+        let mut buffer: Grid<Cell> = Grid::new_default(6, 4);
+        buffer.draw_frame(
+            Rectangle::new_from_raw(0, 0, 6, 4),
+            Some(Color::RED),
+            None,
+            Some(u16::from(b'.')),
+            BorderStyle::Ascii,
+        );
+        assert_eq!(buffer.ascii(Position::new(0, 0)), Some(u16::from(b'+')));
+        assert_eq!(buffer.ascii(Position::new(3, 0)), Some(u16::from(b'-')));
+        assert_eq!(buffer.ascii(Position::new(5, 2)), Some(u16::from(b'|')));
+        assert_eq!(buffer.ascii(Position::new(2, 2)), Some(u16::from(b'.')));
+        assert_eq!(buffer.fore(Position::new(2, 2)), Some(Color::RED));
+        assert_eq!(buffer.back(Position::new(2, 2)), Some(Color::BLACK));
+
+        // Drawing outside of the buffer is ignored.
+        buffer.draw_circle(Position::new(5, 3), 2, 'o', None, None, Some(Color::BLUE));
+        assert_eq!(buffer.ascii(Position::new(5, 1)), Some(u16::from(b'o')));
+        assert_eq!(buffer.ascii(Position::new(6, 3)), None);
+
+        let mut light = Grid::new(2, 1, 0.0);
+        light[Position::new(1, 0)] = 1.0;
+        buffer.apply_lighting(
+            Position::new(0, 3),
+            &light,
+            Color::GREEN,
+            Color::WHITE,
+            LightingMode::Replace,
+        );
+        let image = buffer.back_to_image();
+        assert_eq!(image.get_pixel(Position::new(0, 3)), Some(Color::GREEN));
+        assert_eq!(image.get_pixel(Position::new(1, 3)), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn draw_heightmap() {
+        let heightmap = HeightMap::new_with_values(3, 2, &[0.0, 1.0, 2.0, 2.0, 2.0, 2.0]);
+        let gray = Color::new(100, 100, 100);
+        let color_map = ColorMap::new(&[(0.0, gray), (2.0, Color::new(200, 200, 200))]);
+        let mut buffer: Grid<Cell> = Grid::new_default(5, 5);
+        buffer.area(
+            Rectangle::new_from_raw(0, 0, 5, 5),
+            None,
+            Some(Color::BLUE),
+            None,
+        );
+
+        // The rectangle is narrower and the height map shorter, so only 2x2 cells are drawn.
+        let rectangle = Rectangle::new_from_raw(1, 1, 2, 5);
+        buffer.draw_heightmap(&heightmap, rectangle, &color_map, None);
+        assert_eq!(buffer.back(Position::new(1, 1)), Some(gray));
+        assert_eq!(
+            buffer.back(Position::new(2, 1)),
+            Some(Color::new(150, 150, 150))
+        );
+        assert_eq!(
+            buffer.back(Position::new(2, 2)),
+            Some(Color::new(200, 200, 200))
+        );
+        assert_eq!(buffer.back(Position::new(3, 1)), Some(Color::BLUE));
+        assert_eq!(buffer.back(Position::new(1, 3)), Some(Color::BLUE));
+        assert_eq!(buffer.back(Position::new(0, 0)), Some(Color::BLUE));
+
+        // Under water, the terrain is flat, so it keeps its color; above it, the slope rising
+        // away from the upper-left faces the light and is brighter.
+        buffer.draw_heightmap(&heightmap, rectangle, &color_map, Some(5.0));
+        assert_eq!(buffer.back(Position::new(1, 1)), Some(gray));
+        buffer.draw_heightmap(&heightmap, rectangle, &color_map, Some(-1.0));
+        let lit = buffer.back(Position::new(1, 1)).unwrap();
+        assert!(lit.r > gray.r && lit.g > gray.g && lit.b > gray.b);
+        // The last row and column have no neighbors to slope towards, so they stay flat.
+        assert_eq!(
+            buffer.back(Position::new(2, 2)),
+            Some(Color::new(200, 200, 200))
+        );
+    }
+
+    // Renders the characters of a buffer as rows of text.
+    fn rows(buffer: &Grid<Cell>) -> Vec<String> {
+        buffer
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.ascii as u8 as char).collect())
+            .collect()
+    }
+
+    #[test]
+    fn draw_path() {
+        let mut buffer: Grid<Cell> = Grid::new_default(4, 3);
+        buffer.print_char(Position::new(1, 1), '@', None, None);
+        let path = vec![
+            Position::new(0, 0),
+            Position::new(1, 1),
+            Position::new(2, 1),
+            Position::new(9, 9),
+        ];
+
+        // Only the background is given, so what's on the path stays visible.
+        buffer.draw_path(path.iter().copied(), None, None, Some(Color::RED));
+        assert_eq!(rows(&buffer), vec!["    ", " @  ", "    "]);
+        for &position in &path[..3] {
+            assert_eq!(buffer.back(position), Some(Color::RED));
+            assert_eq!(buffer.fore(position), Some(Color::WHITE));
+        }
+        assert_eq!(buffer.back(Position::new(3, 1)), Some(Color::BLACK));
+
+        buffer.draw_path(path, Some('*'), Some(Color::GREEN), None);
+        assert_eq!(rows(&buffer), vec!["*   ", " ** ", "    "]);
+        assert_eq!(buffer.fore(Position::new(2, 1)), Some(Color::GREEN));
+        assert_eq!(buffer.back(Position::new(2, 1)), Some(Color::RED));
+    }
+
+    #[test]
+    fn draw_region_outlines() {
+        let mut buffer: Grid<Cell> = Grid::new_default(7, 5);
+        buffer.print_char(Position::new(0, 0), '#', None, None);
+        let regions = vec![
+            Rectangle::new_from_raw(0, 0, 4, 3),
+            Rectangle::new_from_raw(5, 3, 1, 1),
+            Rectangle::new_from_raw(2, 4, 0, 1),
+            Rectangle::new_from_raw(5, 1, 4, 1),
+        ];
+        buffer.draw_region_outlines(regions, None, Some(Color::RED));
+
+        let outlined: Vec<String> = buffer
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| if cell.back == Color::RED { 'o' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            outlined,
+            vec![
+                "oooo...", //
+                "o..o.oo", //
+                "oooo...", //
+                ".....o.", //
+                ".......", //
+            ]
+        );
+        // The characters are left alone.
+        assert_eq!(buffer.ascii(Position::new(0, 0)), Some(u16::from(b'#')));
+        assert_eq!(buffer.fore(Position::new(0, 0)), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn characters_beyond_u16_are_replaced() {
+        let mut buffer: Grid<Cell> = Grid::new_default(3, 1);
+        // U+1F409 would truncate to U+F409 if cast to a u16.
+        buffer.print_char(Position::new(0, 0), '\u{1F409}', None, None);
+        buffer.print_char(Position::new(1, 0), '\u{FFFD}', None, None);
+        buffer.draw_path(vec![Position::new(2, 0)], Some('\u{10000}'), None, None);
+        assert_eq!(
+            buffer.ascii(Position::new(0, 0)),
+            Some(REPLACEMENT_CHARACTER)
+        );
+        assert_eq!(buffer.ascii(Position::new(1, 0)), Some(0xFFFD));
+        assert_eq!(
+            buffer.ascii(Position::new(2, 0)),
+            Some(REPLACEMENT_CHARACTER)
+        );
+    }
+
+    #[test]
+    fn print_runs() {
+        let mut parser = ColorCodeParser::new();
+        parser.register_color("red", Color::RED);
+        let mut buffer: Grid<Cell> = Grid::new_default(4, 2);
+        buffer.print_runs(
+            Position::new(1, 0),
+            &parser.parse("a#[red]b\nc"),
+            Some(Color::GREEN),
+            None,
+        );
+        assert_eq!(buffer.ascii(Position::new(1, 0)), Some(u16::from(b'a')));
+        assert_eq!(buffer.fore(Position::new(1, 0)), Some(Color::GREEN));
+        assert_eq!(buffer.fore(Position::new(2, 0)), Some(Color::RED));
+        assert_eq!(buffer.ascii(Position::new(1, 1)), Some(u16::from(b'c')));
+        assert_eq!(buffer.fore(Position::new(1, 1)), Some(Color::RED));
+    }
+}
@@ -0,0 +1,168 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::USize;
+use std::fmt::{self, Display, Formatter};
+
+/// The error returned by the fallible `try_*` variants of the functions that otherwise panic on
+/// invalid input, so that untrusted data can be validated without catching panics.
+#[derive(Clone, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A width or height of 0 was given where an empty size isn't allowed.
+    EmptySize,
+    /// A buffer didn't have the length its size calls for.
+    LengthMismatch {
+        /// The length the buffer should have had
+        expected: usize,
+        /// The length the buffer had
+        actual: usize,
+    },
+    /// Two values that must have the same size didn't.
+    SizeMismatch {
+        /// The size of the value operated on
+        expected: USize,
+        /// The size of the other value
+        actual: USize,
+    },
+    /// A noise generator or a set of coordinates had the wrong number of dimensions.
+    DimensionMismatch {
+        /// The number of dimensions required
+        expected: usize,
+        /// The number of dimensions given
+        actual: usize,
+    },
+    /// A parameter was outside of its allowed range. Holds the name of the parameter.
+    OutOfRange(&'static str),
+    /// A dice specification couldn't be parsed. Holds the specification.
+    InvalidDice(String),
+    /// Data was read past the end of a buffer.
+    UnexpectedEnd,
+    /// Data couldn't be decoded. Holds a description of what was wrong with it.
+    InvalidData(&'static str),
+    /// Nothing was found by the given name. Holds the name.
+    NotFound(String),
+    /// A text file couldn't be parsed.
+    Syntax {
+        /// The line the error was found on, starting at 1
+        line: usize,
+        /// The column the error was found at, starting at 1
+        column: usize,
+        /// A description of what was wrong
+        message: String,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptySize => write!(f, "the width and height must be greater than 0"),
+            Self::LengthMismatch { expected, actual } => {
+                write!(f, "expected a length of {}, but got {}", expected, actual)
+            }
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected a size of {}x{}, but got {}x{}",
+                expected.width, expected.height, actual.width, actual.height
+            ),
+            Self::DimensionMismatch { expected, actual } => {
+                write!(f, "expected {} dimensions, but got {}", expected, actual)
+            }
+            Self::OutOfRange(name) => write!(f, "'{}' is out of range", name),
+            Self::InvalidDice(specification) => {
+                write!(f, "'{}' is not a valid dice specification", specification)
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of data"),
+            Self::InvalidData(description) => write!(f, "invalid data: {}", description),
+            Self::NotFound(name) => write!(f, "'{}' was not found", name),
+            Self::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::heightmap::HeightMap;
+    use crate::noise::Noise;
+    use crate::random::{Dice, Random};
+
+    #[test]
+    fn try_variants_report_errors() {
+        assert_eq!(HeightMap::try_new(0, 5).unwrap_err(), Error::EmptySize);
+        assert_eq!(
+            HeightMap::try_new_with_values(2, 2, &[0.0; 3]).unwrap_err(),
+            Error::LengthMismatch {
+                expected: 4,
+                actual: 3
+            }
+        );
+
+        let a = HeightMap::new(2, 2);
+        let b = HeightMap::new(3, 2);
+        assert_eq!(
+            a.try_lerp(&b, 0.5).unwrap_err(),
+            Error::SizeMismatch {
+                expected: USize::new(2, 2),
+                actual: USize::new(3, 2)
+            }
+        );
+        assert_eq!(
+            a.try_lerp(&a, 1.5).unwrap_err(),
+            Error::OutOfRange("coefficient")
+        );
+        assert!(a.try_lerp(&a, 0.5).is_ok());
+
+        let noise = Noise::new_simplex(3, 2.0, Random::new_mt_from_seed(1));
+        assert_eq!(
+            noise.try_flat(&[0.0, 0.0]).unwrap_err(),
+            Error::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            }
+        );
+        assert!(noise.try_fbm(&[0.0, 0.0, 0.0], 4.0).is_ok());
+
+        assert!(Grid::try_new_with_values(2, 2, vec![0; 5]).is_err());
+        assert_eq!(
+            Dice::try_new("2d0").unwrap_err().to_string(),
+            "'2d0' is not a valid dice specification"
+        );
+    }
+}
@@ -32,13 +32,23 @@
 
 //! # Extenders for doryen-rs types.
 
-use crate::color::Color;
+use crate::color::{Color, ColorMap};
+use crate::text::ColorCodeParser;
 use crate::{Position, Rectangle, USize};
 use doryen_rs::{Console, TextAlign};
 use ilyvion_util::ownership::Borrowned;
 use std::borrow::{Borrow, BorrowMut};
 use std::ops::{Deref, DerefMut};
 
+mod subcell;
+mod table;
+mod viewport;
+
+pub use crate::console::{BorderStyle, LightingMode};
+pub use subcell::SubcellConsole;
+pub use table::TableColumn;
+pub use viewport::Viewport;
+
 /// Extends the `Console` from `doryen-rs`.
 ///
 /// Replaces most instances of x/y and w/h with `Position` and `USize` respectively, and makes use
@@ -175,6 +185,34 @@ impl ConsoleExtender<'_> {
         )
     }
 
+    /// Writes a multi-color string containing libtcod color control characters and/or
+    /// `#[color_name]` codes, as understood by `parser`. Text without a color of its own is
+    /// white, like with `print_color`, and each line is aligned on its own.
+    pub fn print_color_codes<S: AsRef<str>>(
+        &mut self,
+        position: Position,
+        text: S,
+        parser: &ColorCodeParser,
+        align: TextAlign,
+        back: Option<Color>,
+    ) {
+        for (i, line) in parser.parse_lines(text.as_ref()).iter().enumerate() {
+            let length: i32 = line.iter().map(|run| run.text.chars().count() as i32).sum();
+            let x = match align {
+                TextAlign::Left => position.x,
+                TextAlign::Right => position.x - length + 1,
+                TextAlign::Center => position.x - length / 2,
+            };
+            crate::console::ConsoleTarget::print_runs(
+                self,
+                Position::new(x, position.y + i as i32),
+                line,
+                Some(Color::WHITE),
+                back,
+            );
+        }
+    }
+
     /// Compute the length of a string containing color codes.
     pub fn text_color_len<S: AsRef<str>>(text: S) -> usize {
         Console::text_color_len(text.as_ref())
@@ -316,25 +354,166 @@ impl ConsoleExtender<'_> {
         fill: Option<u16>,
         title: Option<S>,
     ) {
-        self.rectangle(rectangle, fore, back, fill);
+        self.print_frame_ex(
+            rectangle,
+            fore,
+            back,
+            fill,
+            title,
+            BorderStyle::Single,
+            TextAlign::Center,
+        );
+    }
+
+    /// Draws a rectangle with the given border style, possibly filling it with a character,
+    /// possibly with a title at the top, aligned according to `title_align`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn print_frame_ex<S: AsRef<str>>(
+        &mut self,
+        rectangle: Rectangle,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+        title: Option<S>,
+        border_style: BorderStyle,
+        title_align: TextAlign,
+    ) {
+        crate::console::ConsoleTarget::draw_frame(self, rectangle, fore, back, fill, border_style);
+
+        let Rectangle {
+            position: Position { x, y },
+            size: USize { width: w, .. },
+        } = rectangle;
+        if w == 0 || rectangle.size.height == 0 {
+            return;
+        }
 
         if let Some(title) = title {
             let text = format!(" {} ", title.as_ref());
-            let Rectangle {
-                position: Position { x, y },
-                size: USize { width: w, .. },
-            } = rectangle;
+            let title_x = match title_align {
+                TextAlign::Left => x + 1,
+                TextAlign::Center => x + (w / 2) as i32,
+                TextAlign::Right => x + w as i32 - 2,
+            };
+            self.print(Position::new(title_x, y), &text, title_align, fore, back);
+        }
+    }
+
+    /// Prints `text` with each character colored along a gradient through `colors`, e.g. for
+    /// titles or damage numbers. The colors are spread evenly over the text, with the first one
+    /// used for the first character and the last one for the last character.
+    ///
+    /// Color codes in the text, both `#[color_name]` ones and the control characters of the
+    /// `text` module, are removed rather than printed, since the gradient decides the color of
+    /// every character. If `colors` is empty, the foreground colors of the cells are left as they
+    /// are.
+    pub fn print_gradient<S: AsRef<str>>(
+        &mut self,
+        position: Position,
+        text: S,
+        colors: &[Color],
+        align: TextAlign,
+    ) {
+        let characters: Vec<char> = ColorCodeParser::new()
+            .strip(text.as_ref())
+            .chars()
+            .collect();
+        let length = characters.len() as i32;
+        let start_x = match align {
+            TextAlign::Left => position.x,
+            TextAlign::Right => position.x - length + 1,
+            TextAlign::Center => position.x - length / 2,
+        };
+        let color_map = if colors.is_empty() {
+            None
+        } else {
+            let last = (colors.len() - 1).max(1) as f32;
+            let keys: Vec<_> = colors
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| (i as f32 / last, color))
+                .collect();
+            Some(ColorMap::new(&keys))
+        };
+
+        let last = (length - 1).max(1) as f32;
+        for (i, &character) in characters.iter().enumerate() {
+            let fore = color_map
+                .as_ref()
+                .map(|color_map| color_map.color_at(i as f32 / last));
+            self.cell(
+                Position::new(start_x + i as i32, position.y),
+                Some(crate::console::character_code(character)),
+                fore,
+                None,
+            );
+        }
+    }
+
+    /// Draws a horizontal bar filling `rectangle` from the left in proportion to `value / max`,
+    /// e.g. for health bars or loading indicators, optionally with a label centered on it.
+    ///
+    /// The filled part has `fill` as its background, and the rest has `back`. The fractional
+    /// cell at the end of the filled part is drawn with a half block character when it's between
+    /// one quarter and three quarters filled, and is rounded to empty or full otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_bar<S: AsRef<str>>(
+        &mut self,
+        rectangle: Rectangle,
+        value: f32,
+        max: f32,
+        fill: Color,
+        back: Color,
+        label: Option<S>,
+        label_color: Option<Color>,
+    ) {
+        const LEFT_HALF_BLOCK: u16 = 221;
+
+        let Rectangle {
+            position: Position { x, y },
+            size: USize { width, height },
+        } = rectangle;
+        let ratio = if max > 0.0 {
+            (value / max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let filled = ratio * width as f32;
+        let mut full_cells = filled.trunc() as u32;
+        let remainder = filled.fract();
+        let half_cell = (0.25..0.75).contains(&remainder);
+        if remainder >= 0.75 {
+            full_cells += 1;
+        }
+
+        self.area(rectangle, None, Some(back), Some(u16::from(b' ')));
+        self.area(
+            Rectangle::new_from_raw(x, y, full_cells, height),
+            None,
+            Some(fill),
+            None,
+        );
+        if half_cell {
+            self.area(
+                Rectangle::new_from_raw(x + full_cells as i32, y, 1, height),
+                Some(fill),
+                Some(back),
+                Some(LEFT_HALF_BLOCK),
+            );
+        }
+
+        if let Some(label) = label {
             self.print(
-                Position::new(x + (w / 2) as i32, y),
-                &text,
+                Position::new(x + (width / 2) as i32, y + (height / 2) as i32),
+                label,
                 TextAlign::Center,
-                fore.map(Into::into),
-                back.map(Into::into),
+                label_color,
+                None,
             );
         }
     }
 
-    /// Prints the provided character to the give position.
+    /// Prints the provided character to the given position.
     pub fn print_char(
         &mut self,
         position: Position,
@@ -342,7 +521,57 @@ impl ConsoleExtender<'_> {
         fore: Option<Color>,
         back: Option<Color>,
     ) {
-        self.cell(position, Some(character as u16), fore, back);
+        crate::console::ConsoleTarget::print_char(self, position, character, fore, back);
+    }
+}
+
+impl crate::console::ConsoleTarget for ConsoleExtender<'_> {
+    fn size(&self) -> USize {
+        self.get_size()
+    }
+
+    fn ascii(&self, position: Position) -> Option<u16> {
+        Self::ascii(self, position)
+    }
+
+    fn fore(&self, position: Position) -> Option<Color> {
+        Self::fore(self, position)
+    }
+
+    fn back(&self, position: Position) -> Option<Color> {
+        Self::back(self, position)
+    }
+
+    fn set_ascii(&mut self, position: Position, ascii: u16) {
+        Self::set_ascii(self, position, ascii);
+    }
+
+    fn set_fore(&mut self, position: Position, color: Color) {
+        Self::set_fore(self, position, color);
+    }
+
+    fn set_back(&mut self, position: Position, color: Color) {
+        Self::set_back(self, position, color);
+    }
+
+    fn cell(
+        &mut self,
+        position: Position,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        Self::cell(self, position, ascii, fore, back);
+    }
+
+    fn area(
+        &mut self,
+        rectangle: Rectangle,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill_char: Option<u16>,
+    ) {
+        Self::area(self, rectangle, fore, back, fill_char);
     }
 }
 
@@ -383,3 +612,109 @@ impl AsMut<Console> for ConsoleExtender<'_> {
         self.console.as_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::ConsoleTarget;
+
+    #[test]
+    fn print_gradient() {
+        let mut console = ConsoleExtender::new(USize::new(10, 1));
+        console.print_gradient(
+            Position::new(5, 0),
+            "#[red]abc",
+            &[Color::BLACK, Color::WHITE],
+            TextAlign::Center,
+        );
+        assert_eq!(console.ascii(Position::new(4, 0)), Some(u16::from(b'a')));
+        assert_eq!(console.fore(Position::new(4, 0)), Some(Color::BLACK));
+        assert_eq!(
+            console.fore(Position::new(5, 0)),
+            Some(Color::new(127, 127, 127))
+        );
+        assert_eq!(console.fore(Position::new(6, 0)), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn print_color_codes() {
+        let mut parser = ColorCodeParser::new();
+        parser.set_color_control(1, Color::RED, Color::BLUE);
+        let mut console = ConsoleExtender::new(USize::new(10, 2));
+        let text = format!("ab\n{}cd", crate::text::COLCTRL_1);
+        console.print_color_codes(Position::new(9, 0), text, &parser, TextAlign::Right, None);
+        assert_eq!(console.ascii(Position::new(8, 0)), Some(u16::from(b'a')));
+        assert_eq!(console.fore(Position::new(8, 0)), Some(Color::WHITE));
+        assert_eq!(console.ascii(Position::new(9, 1)), Some(u16::from(b'd')));
+        assert_eq!(console.fore(Position::new(9, 1)), Some(Color::RED));
+        assert_eq!(console.back(Position::new(9, 1)), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn draw_bar() {
+        let mut console = ConsoleExtender::new(USize::new(10, 2));
+        let bar = Rectangle::new_from_raw(0, 0, 10, 1);
+        let draw = |console: &mut ConsoleExtender<'_>, value: f32, max: f32| {
+            console.draw_bar(bar, value, max, Color::RED, Color::BLUE, None::<&str>, None);
+            (0..10)
+                .map(|x| console.back(Position::new(x, 0)).unwrap())
+                .filter(|&color| color == Color::RED)
+                .count()
+        };
+
+        assert_eq!(draw(&mut console, 0.0, 100.0), 0);
+        assert_eq!(console.back(Position::new(0, 0)), Some(Color::BLUE));
+        assert_eq!(console.ascii(Position::new(0, 0)), Some(u16::from(b' ')));
+        assert_eq!(draw(&mut console, 100.0, 100.0), 10);
+        assert_eq!(draw(&mut console, 50.0, 100.0), 5);
+        assert_eq!(console.back(Position::new(4, 0)), Some(Color::RED));
+        assert_eq!(console.back(Position::new(5, 0)), Some(Color::BLUE));
+        // The bar below doesn't change.
+        assert_eq!(console.back(Position::new(0, 1)), Some(Color::BLACK));
+
+        // Between a quarter and three quarters of a cell, the last cell is half filled.
+        assert_eq!(draw(&mut console, 45.0, 100.0), 4);
+        assert_eq!(console.ascii(Position::new(4, 0)), Some(221));
+        assert_eq!(console.fore(Position::new(4, 0)), Some(Color::RED));
+        assert_eq!(console.back(Position::new(4, 0)), Some(Color::BLUE));
+        assert_eq!(draw(&mut console, 48.0, 100.0), 5);
+        assert_eq!(console.ascii(Position::new(4, 0)), Some(u16::from(b' ')));
+        assert_eq!(draw(&mut console, 41.0, 100.0), 4);
+        assert_eq!(console.ascii(Position::new(4, 0)), Some(u16::from(b' ')));
+
+        // Out of range values are clamped, and there's nothing to fill without a positive max.
+        assert_eq!(draw(&mut console, 150.0, 100.0), 10);
+        assert_eq!(draw(&mut console, -20.0, 100.0), 0);
+        assert_eq!(draw(&mut console, 5.0, 0.0), 0);
+
+        console.draw_bar(
+            bar,
+            1.0,
+            2.0,
+            Color::RED,
+            Color::BLUE,
+            Some("hp"),
+            Some(Color::GREEN),
+        );
+        assert_eq!(console.ascii(Position::new(4, 0)), Some(u16::from(b'h')));
+        assert_eq!(console.ascii(Position::new(5, 0)), Some(u16::from(b'p')));
+        assert_eq!(console.fore(Position::new(5, 0)), Some(Color::GREEN));
+        assert_eq!(console.back(Position::new(5, 0)), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn to_image() {
+        let mut console = ConsoleExtender::new(USize::new(3, 2));
+        console.clear(Some(Color::WHITE), Some(Color::BLACK), None);
+        console.set_back(Position::new(2, 1), Color::RED);
+        console.set_fore(Position::new(0, 1), Color::BLUE);
+
+        let back = console.back_to_image();
+        assert_eq!(back.size(), USize::new(3, 2));
+        assert_eq!(back.get_pixel(Position::new(2, 1)), Some(Color::RED));
+        assert_eq!(back.get_pixel(Position::new(0, 1)), Some(Color::BLACK));
+        let fore = console.fore_to_image();
+        assert_eq!(fore.get_pixel(Position::new(0, 1)), Some(Color::BLUE));
+        assert_eq!(fore.get_pixel(Position::new(2, 1)), Some(Color::WHITE));
+    }
+}
@@ -0,0 +1,149 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::color::Color;
+use crate::console::ConsoleTarget;
+use crate::grid::Grid;
+use crate::{Position, USize};
+
+const UPPER_HALF_BLOCK: u16 = 223;
+
+/// A canvas with twice the vertical resolution of a console, drawn by splitting each console cell
+/// into an upper and a lower pixel using half block characters.
+///
+/// Pixels are set with `set_pixel`, and the whole canvas is then drawn onto a console with
+/// `render`, which sets the character, foreground and background colors of every cell it covers.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, USize};
+/// # use doryen_extra::color::Color;
+/// # use doryen_extra::extenders::{ConsoleExtender, SubcellConsole};
+/// let mut minimap = SubcellConsole::new(USize::new(20, 10), Color::BLACK);
+/// assert_eq!(minimap.pixel_size(), USize::new(20, 20));
+/// minimap.set_pixel(Position::new(3, 7), Color::RED);
+///
+/// let mut console = ConsoleExtender::new(USize::new(80, 50));
+/// minimap.render(&mut console, Position::new(60, 0));
+/// assert_eq!(console.back(Position::new(63, 3)), Some(Color::RED));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SubcellConsole {
+    pixels: Grid<Color>,
+}
+
+impl SubcellConsole {
+    /// Creates a new canvas covering `size` console cells, with every pixel set to `color`.
+    pub fn new(size: USize, color: Color) -> Self {
+        Self {
+            pixels: Grid::new(size.width as usize, size.height as usize * 2, color),
+        }
+    }
+
+    /// Returns the number of console cells the canvas covers.
+    pub fn cell_size(&self) -> USize {
+        USize::new(
+            self.pixels.width() as u32,
+            (self.pixels.height() / 2) as u32,
+        )
+    }
+
+    /// Returns the number of pixels in the canvas.
+    pub fn pixel_size(&self) -> USize {
+        self.pixels.size()
+    }
+
+    /// Returns the color of the pixel at `position`, or `None` if it's outside the canvas.
+    pub fn pixel(&self, position: Position) -> Option<Color> {
+        self.pixels.get(position).copied()
+    }
+
+    /// Sets the color of the pixel at `position`. Positions outside the canvas are ignored.
+    pub fn set_pixel(&mut self, position: Position, color: Color) {
+        if let Some(pixel) = self.pixels.get_mut(position) {
+            *pixel = color;
+        }
+    }
+
+    /// Sets every pixel to `color`.
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.fill(color);
+    }
+
+    /// Draws the canvas onto `console` with its upper-left corner at `position`.
+    pub fn render<C: ConsoleTarget>(&self, console: &mut C, position: Position) {
+        for (y, rows) in self
+            .pixels
+            .rows()
+            .collect::<Vec<_>>()
+            .chunks_exact(2)
+            .enumerate()
+        {
+            for (x, (&upper, &lower)) in rows[0].iter().zip(rows[1]).enumerate() {
+                let cell_position = position + Position::new(x as i32, y as i32);
+                if upper == lower {
+                    console.cell(cell_position, Some(u16::from(b' ')), None, Some(upper));
+                } else {
+                    console.cell(
+                        cell_position,
+                        Some(UPPER_HALF_BLOCK),
+                        Some(upper),
+                        Some(lower),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extenders::ConsoleExtender;
+
+    #[test]
+    fn render() {
+        let mut canvas = SubcellConsole::new(USize::new(2, 1), Color::BLACK);
+        canvas.set_pixel(Position::new(1, 0), Color::WHITE);
+        canvas.set_pixel(Position::new(5, 5), Color::WHITE);
+        assert_eq!(canvas.pixel(Position::new(1, 0)), Some(Color::WHITE));
+        assert_eq!(canvas.pixel(Position::new(1, 2)), None);
+
+        let mut console = ConsoleExtender::new(USize::new(4, 4));
+        canvas.render(&mut console, Position::new(1, 1));
+        assert_eq!(console.ascii(Position::new(1, 1)), Some(u16::from(b' ')));
+        assert_eq!(console.back(Position::new(1, 1)), Some(Color::BLACK));
+        assert_eq!(console.ascii(Position::new(2, 1)), Some(UPPER_HALF_BLOCK));
+        assert_eq!(console.fore(Position::new(2, 1)), Some(Color::WHITE));
+        assert_eq!(console.back(Position::new(2, 1)), Some(Color::BLACK));
+    }
+}
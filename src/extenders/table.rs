@@ -0,0 +1,292 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::{BorderStyle, ConsoleExtender};
+use crate::color::Color;
+use crate::{Position, Rectangle, USize};
+use doryen_rs::TextAlign;
+use std::fmt;
+
+/// Describes a column of a table drawn with `ConsoleExtender::draw_table`.
+#[derive(Copy, Clone)]
+pub struct TableColumn {
+    /// The width of the column's contents, in cells. Longer texts are truncated.
+    pub width: u32,
+    /// How the texts of the column are aligned within it
+    pub align: TextAlign,
+}
+
+impl TableColumn {
+    /// Returns a new column with the given width and alignment.
+    pub fn new(width: u32, align: TextAlign) -> Self {
+        Self { width, align }
+    }
+}
+
+impl fmt::Debug for TableColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let align = match self.align {
+            TextAlign::Left => "Left",
+            TextAlign::Right => "Right",
+            TextAlign::Center => "Center",
+        };
+        f.debug_struct("TableColumn")
+            .field("width", &self.width)
+            .field("align", &align)
+            .finish()
+    }
+}
+
+impl BorderStyle {
+    // The characters used where lines meet, in the order left tee, right tee, top tee, bottom
+    // tee and cross. Custom styles use their top-left corner for all of them.
+    fn junctions(self) -> [u16; 5] {
+        match self {
+            Self::Single => [195, 180, 194, 193, 197],
+            Self::Double => [204, 185, 203, 202, 206],
+            Self::Heavy => [219; 5],
+            Self::Ascii => [u16::from(b'+'); 5],
+            Self::Custom(characters) => [characters[0]; 5],
+        }
+    }
+}
+
+impl ConsoleExtender<'_> {
+    /// Draws a table with its upper-left corner at `position` and returns the area it covers.
+    ///
+    /// Each row, including the optional `header`, gets one text per column; missing texts are
+    /// left blank and extra texts are ignored. With a `border_style`, the table is framed, the
+    /// columns are separated by lines and the header is separated from the rows; without one, the
+    /// columns are separated by a single space.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, USize};
+    /// # use doryen_extra::extenders::{BorderStyle, ConsoleExtender, TableColumn};
+    /// # use doryen_rs::TextAlign;
+    /// let mut console = ConsoleExtender::new(USize::new(40, 20));
+    /// let area = console.draw_table(
+    ///     Position::new(1, 1),
+    ///     &[TableColumn::new(10, TextAlign::Left), TableColumn::new(3, TextAlign::Right)],
+    ///     Some(&["Item", "Qty"][..]),
+    ///     &[&["Potion", "3"], &["Scroll", "12"]],
+    ///     Some(BorderStyle::Single),
+    ///     None,
+    ///     None,
+    /// );
+    /// assert_eq!(area.size, USize::new(16, 6));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_table<S: AsRef<str>, R: AsRef<[S]>>(
+        &mut self,
+        position: Position,
+        columns: &[TableColumn],
+        header: Option<&[S]>,
+        rows: &[R],
+        border_style: Option<BorderStyle>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) -> Rectangle {
+        let border = u32::from(border_style.is_some());
+        let separator = 1;
+        let content_width = columns.iter().map(|column| column.width).sum::<u32>()
+            + (columns.len() as u32).saturating_sub(1) * separator;
+        let header_height = match header {
+            Some(_) => 1 + border,
+            None => 0,
+        };
+        let size = USize::new(
+            content_width + 2 * border,
+            header_height + rows.len() as u32 + 2 * border,
+        );
+        let area = Rectangle::new(position, size);
+        let inside = position + Position::new(border as i32, border as i32);
+
+        if let Some(border_style) = border_style {
+            self.print_frame_ex(
+                area,
+                fore,
+                back,
+                Some(u16::from(b' ')),
+                None::<&str>,
+                border_style,
+                TextAlign::Left,
+            );
+
+            let [_, top, _, left, _, _, _, _] = border_style.characters();
+            let [left_tee, right_tee, top_tee, bottom_tee, cross] = border_style.junctions();
+            let bottom_y = position.y + size.height as i32 - 1;
+            let mut x = inside.x;
+            for (i, column) in columns.iter().enumerate() {
+                x += column.width as i32;
+                if i + 1 == columns.len() {
+                    break;
+                }
+                self.cell(Position::new(x, position.y), Some(top_tee), fore, back);
+                self.cell(Position::new(x, bottom_y), Some(bottom_tee), fore, back);
+                self.area(
+                    Rectangle::new_from_raw(x, inside.y, 1, size.height - 2),
+                    fore,
+                    back,
+                    Some(left),
+                );
+                x += separator as i32;
+            }
+
+            if header.is_some() {
+                let line_y = inside.y + 1;
+                self.area(
+                    Rectangle::new_from_raw(inside.x, line_y, content_width, 1),
+                    fore,
+                    back,
+                    Some(top),
+                );
+                self.cell(
+                    Position::new(position.x, line_y),
+                    Some(left_tee),
+                    fore,
+                    back,
+                );
+                self.cell(
+                    Position::new(position.x + size.width as i32 - 1, line_y),
+                    Some(right_tee),
+                    fore,
+                    back,
+                );
+                let mut junction_x = inside.x;
+                for column in columns.iter().take(columns.len().saturating_sub(1)) {
+                    junction_x += column.width as i32;
+                    self.cell(Position::new(junction_x, line_y), Some(cross), fore, back);
+                    junction_x += separator as i32;
+                }
+            }
+        } else {
+            self.area(area, fore, back, Some(u16::from(b' ')));
+        }
+
+        let mut y = inside.y;
+        if let Some(header) = &header {
+            self.print_table_row(
+                Position::new(inside.x, y),
+                columns,
+                header.as_ref(),
+                fore,
+                back,
+            );
+            y += header_height as i32;
+        }
+        for row in rows {
+            self.print_table_row(
+                Position::new(inside.x, y),
+                columns,
+                row.as_ref(),
+                fore,
+                back,
+            );
+            y += 1;
+        }
+
+        area
+    }
+
+    fn print_table_row<S: AsRef<str>>(
+        &mut self,
+        position: Position,
+        columns: &[TableColumn],
+        texts: &[S],
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        let mut x = position.x;
+        for (column, text) in columns.iter().zip(texts) {
+            let text: String = text.as_ref().chars().take(column.width as usize).collect();
+            let text_x = match column.align {
+                TextAlign::Left => x,
+                TextAlign::Center => x + (column.width / 2) as i32,
+                TextAlign::Right => x + column.width as i32 - 1,
+            };
+            self.print(
+                Position::new(text_x, position.y),
+                text,
+                column.align,
+                fore,
+                back,
+            );
+            x += column.width as i32 + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let mut console = ConsoleExtender::new(USize::new(30, 10));
+        let columns = [
+            TableColumn::new(4, TextAlign::Left),
+            TableColumn::new(3, TextAlign::Right),
+        ];
+        let rows: [&[&str]; 2] = [&["Sword", "1"], &["Bow"]];
+        let area = console.draw_table(
+            Position::new(2, 1),
+            &columns,
+            Some(&["Name", "#"][..]),
+            &rows,
+            Some(BorderStyle::Ascii),
+            None,
+            None,
+        );
+        assert_eq!(area, Rectangle::new_from_raw(2, 1, 10, 6));
+
+        let ascii = |x, y| console.ascii(Position::new(x, y)).unwrap() as u8 as char;
+        let line = |y| (2..12).map(|x| ascii(x, y)).collect::<String>();
+        assert_eq!(line(1), "+----+---+");
+        assert_eq!(line(2), "|Name|  #|");
+        assert_eq!(line(3), "+----+---+");
+        assert_eq!(line(4), "|Swor|  1|");
+        assert_eq!(line(5), "|Bow |   |");
+        assert_eq!(line(6), "+----+---+");
+
+        let unframed = console.draw_table::<&str, &[&str]>(
+            Position::ORIGIN,
+            &columns,
+            None,
+            &[&["a", "b"]],
+            None,
+            None,
+            None,
+        );
+        assert_eq!(unframed, Rectangle::new_from_raw(0, 0, 8, 1));
+    }
+}
@@ -0,0 +1,218 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::ConsoleExtender;
+use crate::color::Color;
+use crate::{Position, Rectangle, USize};
+use doryen_rs::Console;
+
+/// A scrollable window into a console that is larger than the area it is displayed in, such as
+/// the map view of a game.
+///
+/// The viewport owns the large offscreen console, which you draw the whole world onto, and keeps
+/// track of which part of it is currently visible. Scrolling is always clamped so the view never
+/// extends past the edges of the offscreen console.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, USize};
+/// # use doryen_extra::extenders::Viewport;
+/// let mut viewport = Viewport::new(USize::new(200, 100), USize::new(80, 40));
+/// viewport.center_on(Position::new(5, 50));
+/// assert_eq!(viewport.view().position, Position::new(0, 30));
+/// assert_eq!(viewport.world_to_view(Position::new(5, 50)), Some(Position::new(5, 20)));
+/// ```
+#[allow(missing_debug_implementations)] // Console doesn't implement Debug
+pub struct Viewport {
+    console: ConsoleExtender<'static>,
+    view: Rectangle,
+}
+
+impl Viewport {
+    /// Creates a new viewport with an offscreen console of size `world_size`, showing an area of
+    /// size `view_size` starting at its upper-left corner.
+    pub fn new(world_size: USize, view_size: USize) -> Self {
+        Self {
+            console: ConsoleExtender::new(world_size),
+            view: Rectangle::new(Position::ORIGIN, view_size),
+        }
+    }
+
+    /// Returns the offscreen console.
+    pub fn console(&self) -> &ConsoleExtender<'static> {
+        &self.console
+    }
+
+    /// Returns the offscreen console.
+    pub fn console_mut(&mut self) -> &mut ConsoleExtender<'static> {
+        &mut self.console
+    }
+
+    /// Returns the currently visible area of the offscreen console.
+    pub fn view(&self) -> Rectangle {
+        self.view
+    }
+
+    /// Changes the size of the visible area, keeping its upper-left corner in place if possible.
+    pub fn set_view_size(&mut self, view_size: USize) {
+        self.view.size = view_size;
+        self.scroll_to(self.view.position);
+    }
+
+    /// Scrolls the view so its upper-left corner is at `position`, or as close to it as possible.
+    pub fn scroll_to(&mut self, position: Position) {
+        let world = self.console.get_size();
+        let max_x = world.width.saturating_sub(self.view.size.width) as i32;
+        let max_y = world.height.saturating_sub(self.view.size.height) as i32;
+
+        self.view.position = Position::new(position.x.clamp(0, max_x), position.y.clamp(0, max_y));
+    }
+
+    /// Scrolls the view by `offset`, stopping at the edges of the offscreen console.
+    pub fn scroll_by(&mut self, offset: Position) {
+        self.scroll_to(self.view.position + offset);
+    }
+
+    /// Scrolls the view so `position` is in its center, or as close to it as possible.
+    pub fn center_on(&mut self, position: Position) {
+        let half_size = Position::new(
+            (self.view.size.width / 2) as i32,
+            (self.view.size.height / 2) as i32,
+        );
+        self.scroll_to(position - half_size);
+    }
+
+    /// Scrolls the view just enough to keep `position` at least `margin` cells away from its
+    /// edges, e.g. to follow the player around without re-centering on every step.
+    ///
+    /// If the view is too small for the margin, `position` is centered instead.
+    pub fn follow(&mut self, position: Position, margin: u32) {
+        let USize { width, height } = self.view.size;
+        if margin.saturating_mul(2) >= width.min(height) {
+            self.center_on(position);
+            return;
+        }
+
+        let margin = margin as i32;
+        let Position { x, y } = self.view.position;
+        let right = x + width as i32 - 1;
+        let bottom = y + height as i32 - 1;
+        let dx = (position.x - (x + margin)).min(0) + (position.x - (right - margin)).max(0);
+        let dy = (position.y - (y + margin)).min(0) + (position.y - (bottom - margin)).max(0);
+        self.scroll_by(Position::new(dx, dy));
+    }
+
+    /// Converts a position on the offscreen console into a position relative to the view, or
+    /// `None` if the position is not currently visible.
+    pub fn world_to_view(&self, position: Position) -> Option<Position> {
+        let relative = position - self.view.position;
+        if relative.x >= 0
+            && relative.y >= 0
+            && (relative.x as u32) < self.view.size.width
+            && (relative.y as u32) < self.view.size.height
+        {
+            Some(relative)
+        } else {
+            None
+        }
+    }
+
+    /// Converts a position relative to the view into a position on the offscreen console.
+    pub fn view_to_world(&self, position: Position) -> Position {
+        position + self.view.position
+    }
+
+    /// Blits the visible area of the offscreen console onto `destination`, with its upper-left
+    /// corner at `destination_position`.
+    pub fn blit(
+        &self,
+        destination: &mut Console,
+        destination_position: Position,
+        fore_alpha: f32,
+        back_alpha: f32,
+        key_color: Option<Color>,
+    ) {
+        self.console.blit_ex(
+            self.view,
+            destination,
+            destination_position,
+            fore_alpha,
+            back_alpha,
+            key_color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrolling_is_clamped() {
+        let mut viewport = Viewport::new(USize::new(50, 30), USize::new(20, 10));
+        viewport.scroll_by(Position::new(-5, 100));
+        assert_eq!(viewport.view().position, Position::new(0, 20));
+
+        viewport.center_on(Position::new(49, 0));
+        assert_eq!(viewport.view().position, Position::new(30, 0));
+
+        viewport.set_view_size(USize::new(80, 10));
+        assert_eq!(viewport.view().position, Position::new(0, 0));
+    }
+
+    #[test]
+    fn follow() {
+        let mut viewport = Viewport::new(USize::new(100, 100), USize::new(20, 10));
+        viewport.scroll_to(Position::new(10, 10));
+
+        // Inside the margin; nothing happens
+        viewport.follow(Position::new(15, 15), 2);
+        assert_eq!(viewport.view().position, Position::new(10, 10));
+
+        // Too close to the right and bottom edges
+        viewport.follow(Position::new(29, 19), 2);
+        assert_eq!(viewport.view().position, Position::new(12, 12));
+        assert_eq!(
+            viewport.world_to_view(Position::new(29, 19)),
+            Some(Position::new(17, 7))
+        );
+
+        // Too close to the left edge
+        viewport.follow(Position::new(12, 15), 3);
+        assert_eq!(viewport.view().position, Position::new(9, 12));
+        assert_eq!(viewport.world_to_view(Position::new(0, 0)), None);
+        assert_eq!(
+            viewport.view_to_world(Position::new(1, 1)),
+            Position::new(10, 13)
+        );
+    }
+}
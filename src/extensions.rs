@@ -32,9 +32,15 @@
 
 //! # Extension traits for doryen-rs types.
 
-use crate::FPosition;
+use crate::color::Color;
+use crate::extenders::ConsoleExtender;
+use crate::image::Image;
+use crate::{Direction, FPosition, Position, Rectangle};
 use doryen_rs::InputApi;
 
+const UPPER_HALF_BLOCK: u16 = 223;
+const LEFT_HALF_BLOCK: u16 = 221;
+
 /// Defines extension methods for the `InputApi` type.
 pub trait InputApiExtensions {
     /// return the current mouse position in console cell position
@@ -47,3 +53,321 @@ impl InputApiExtensions for &mut dyn InputApi {
         FPosition::new(mouse_pos.0, mouse_pos.1)
     }
 }
+
+/// Maps the keys held down on the keyboard to a `Direction`, e.g. for moving the player around.
+///
+/// Any number of keys can be bound to each direction. When several keys are held down at once,
+/// their directions are combined, so holding up and right gives `NorthEast`, while opposite
+/// directions cancel each other out.
+///
+/// Call `update` once per frame to get a direction when a movement should happen: right away
+/// when a direction is first pressed, and then repeatedly, after a delay, for as long as it's
+/// held down.
+///
+/// The key names are the scan codes used by `InputApi::key`, e.g. `"ArrowUp"` or `"KeyW"`.
+#[derive(Clone, Debug)]
+pub struct DirectionKeys {
+    bindings: Vec<(String, Direction)>,
+    repeat_delay: u32,
+    repeat_interval: u32,
+    held: Option<(Direction, u32)>,
+}
+
+impl DirectionKeys {
+    /// Returns a new mapping without any key bindings, which repeats a held direction after 15
+    /// frames, and then every 5 frames.
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            repeat_delay: 15,
+            repeat_interval: 5,
+            held: None,
+        }
+    }
+
+    /// Binds `key` to `direction`.
+    pub fn bind<S: Into<String>>(mut self, key: S, direction: Direction) -> Self {
+        self.bindings.push((key.into(), direction));
+        self
+    }
+
+    /// Binds the arrow keys to the four cardinal directions.
+    pub fn with_arrow_keys(self) -> Self {
+        self.bind("ArrowUp", Direction::North)
+            .bind("ArrowRight", Direction::East)
+            .bind("ArrowDown", Direction::South)
+            .bind("ArrowLeft", Direction::West)
+    }
+
+    /// Binds the number pad keys to all eight directions, laid out as on the keypad.
+    pub fn with_numpad_keys(self) -> Self {
+        self.bind("Numpad8", Direction::North)
+            .bind("Numpad9", Direction::NorthEast)
+            .bind("Numpad6", Direction::East)
+            .bind("Numpad3", Direction::SouthEast)
+            .bind("Numpad2", Direction::South)
+            .bind("Numpad1", Direction::SouthWest)
+            .bind("Numpad4", Direction::West)
+            .bind("Numpad7", Direction::NorthWest)
+    }
+
+    /// Binds the vi-keys (`hjkl` for the cardinal directions, `yubn` for the diagonals).
+    pub fn with_vi_keys(self) -> Self {
+        self.bind("KeyK", Direction::North)
+            .bind("KeyU", Direction::NorthEast)
+            .bind("KeyL", Direction::East)
+            .bind("KeyN", Direction::SouthEast)
+            .bind("KeyJ", Direction::South)
+            .bind("KeyB", Direction::SouthWest)
+            .bind("KeyH", Direction::West)
+            .bind("KeyY", Direction::NorthWest)
+    }
+
+    /// Binds the WASD keys to the four cardinal directions.
+    pub fn with_wasd_keys(self) -> Self {
+        self.bind("KeyW", Direction::North)
+            .bind("KeyD", Direction::East)
+            .bind("KeyS", Direction::South)
+            .bind("KeyA", Direction::West)
+    }
+
+    /// Sets how many frames a direction must be held down before it starts repeating, and how
+    /// many frames there are between each repeat after that.
+    pub fn with_repeat(mut self, delay: u32, interval: u32) -> Self {
+        self.repeat_delay = delay;
+        self.repeat_interval = interval.max(1);
+        self
+    }
+
+    /// Returns the direction of the currently held keys, if any, ignoring key repeat.
+    pub fn direction(&self, input: &dyn InputApi) -> Option<Direction> {
+        self.direction_with(|key| input.key(key))
+    }
+
+    /// Advances the key repeat by one frame and returns the direction to move in during this
+    /// frame, if any.
+    pub fn update(&mut self, input: &dyn InputApi) -> Option<Direction> {
+        self.update_with(|key| input.key(key))
+    }
+
+    fn direction_with<F: Fn(&str) -> bool>(&self, is_down: F) -> Option<Direction> {
+        let delta = self
+            .bindings
+            .iter()
+            .filter(|(key, _)| is_down(key))
+            .fold(Position::ORIGIN, |delta, (_, direction)| {
+                delta + direction.offset()
+            });
+
+        Direction::from_delta(delta)
+    }
+
+    fn update_with<F: Fn(&str) -> bool>(&mut self, is_down: F) -> Option<Direction> {
+        let direction = self.direction_with(is_down);
+        match (direction, self.held) {
+            (None, _) => {
+                self.held = None;
+                None
+            }
+            (Some(direction), Some((held, frames))) if held == direction => {
+                let frames = frames + 1;
+                self.held = Some((direction, frames));
+                if frames >= self.repeat_delay
+                    && (frames - self.repeat_delay) % self.repeat_interval == 0
+                {
+                    Some(direction)
+                } else {
+                    None
+                }
+            }
+            (Some(direction), _) => {
+                self.held = Some((direction, 0));
+                Some(direction)
+            }
+        }
+    }
+}
+
+impl Default for DirectionKeys {
+    /// Returns a mapping using both the arrow keys and the number pad.
+    fn default() -> Self {
+        Self::new().with_arrow_keys().with_numpad_keys()
+    }
+}
+
+/// Defines extension methods for drawing an `Image` onto a doryen console.
+pub trait ImageExtensions {
+    /// Draws the image onto `console` with its upper-left corner at `position`, at twice the
+    /// resolution of the console, so that every console cell shows 2x2 pixels of the image.
+    ///
+    /// As a cell can only show two colors, using the upper and left half block characters, each
+    /// cell is split either into an upper and a lower half, or into a left and a right half,
+    /// whichever loses the least detail, and every half shows the average color of its two
+    /// pixels. Transparent pixels, and the missing pixels at the edges of an image with an odd
+    /// width or height, show the background color of the console.
+    ///
+    /// `source` picks a part of the image to draw, or the whole image if it's `None`.
+    fn blit_2x(
+        &self,
+        console: &mut ConsoleExtender<'_>,
+        position: Position,
+        source: Option<Rectangle>,
+    );
+}
+
+impl ImageExtensions for Image {
+    fn blit_2x(
+        &self,
+        console: &mut ConsoleExtender<'_>,
+        position: Position,
+        source: Option<Rectangle>,
+    ) {
+        let source = source.unwrap_or_else(|| Rectangle::new(Position::ORIGIN, self.size()));
+        let cells_wide = (source.size.width as i32 + 1) / 2;
+        let cells_high = (source.size.height as i32 + 1) / 2;
+        for cy in 0..cells_high {
+            for cx in 0..cells_wide {
+                let cell_position = position + Position::new(cx, cy);
+                let back = match console.back(cell_position) {
+                    Some(back) => back,
+                    None => continue,
+                };
+
+                // The upper left, upper right, lower left and lower right pixels.
+                let mut pixels = [back; 4];
+                for (i, pixel) in pixels.iter_mut().enumerate() {
+                    let offset = Position::new(cx * 2 + (i % 2) as i32, cy * 2 + (i / 2) as i32);
+                    let pixel_position = source.position + offset;
+                    if offset.x < source.size.width as i32
+                        && offset.y < source.size.height as i32
+                        && !self.is_pixel_transparent(pixel_position)
+                    {
+                        *pixel = self.get_pixel(pixel_position).unwrap_or(back);
+                    }
+                }
+
+                if pixels.iter().all(|&pixel| pixel == pixels[0]) {
+                    console.cell(cell_position, Some(u16::from(b' ')), None, Some(pixels[0]));
+                    continue;
+                }
+                let (horizontal_error, upper, lower) = split(pixels, [0, 1], [2, 3]);
+                let (vertical_error, left, right) = split(pixels, [0, 2], [1, 3]);
+                if horizontal_error <= vertical_error {
+                    console.cell(
+                        cell_position,
+                        Some(UPPER_HALF_BLOCK),
+                        Some(upper),
+                        Some(lower),
+                    );
+                } else {
+                    console.cell(
+                        cell_position,
+                        Some(LEFT_HALF_BLOCK),
+                        Some(left),
+                        Some(right),
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Splits four pixels into two halves of the given pixels, returning the total squared error of
+// showing each half as its average color, and the two average colors.
+fn split(pixels: [Color; 4], first: [usize; 2], second: [usize; 2]) -> (u32, Color, Color) {
+    let average = |half: [usize; 2]| {
+        let (a, b) = (pixels[half[0]], pixels[half[1]]);
+        let mean = |x: u8, y: u8| ((u16::from(x) + u16::from(y)) / 2) as u8;
+        Color::new(mean(a.r, b.r), mean(a.g, b.g), mean(a.b, b.b))
+    };
+    let error = |half: [usize; 2], color: Color| {
+        half.iter()
+            .map(|&i| {
+                let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2) as u32;
+                let pixel = pixels[i];
+                d(pixel.r, color.r) + d(pixel.g, color.g) + d(pixel.b, color.b)
+            })
+            .sum::<u32>()
+    };
+    let (first_color, second_color) = (average(first), average(second));
+
+    (
+        error(first, first_color) + error(second, second_color),
+        first_color,
+        second_color,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combined_directions() {
+        let keys = DirectionKeys::default().with_wasd_keys();
+        assert_eq!(keys.direction_with(|_| false), None);
+        assert_eq!(
+            keys.direction_with(|key| key == "ArrowUp" || key == "KeyD"),
+            Some(Direction::NorthEast)
+        );
+        assert_eq!(
+            keys.direction_with(|key| key == "Numpad1"),
+            Some(Direction::SouthWest)
+        );
+        assert_eq!(
+            keys.direction_with(|key| key == "ArrowLeft" || key == "ArrowRight"),
+            None
+        );
+    }
+
+    #[test]
+    fn key_repeat() {
+        let mut keys = DirectionKeys::new().with_vi_keys().with_repeat(3, 2);
+        let held = |key: &str| key == "KeyH";
+        let moves: Vec<_> = (0..8).map(|_| keys.update_with(held)).collect();
+        let w = Some(Direction::West);
+        assert_eq!(moves, vec![w, None, None, w, None, w, None, w]);
+
+        // Changing direction moves right away
+        assert_eq!(
+            keys.update_with(|key| key == "KeyY"),
+            Some(Direction::NorthWest)
+        );
+        assert_eq!(keys.update_with(|_| false), None);
+        assert_eq!(keys.update_with(held), w);
+    }
+
+    #[test]
+    fn blit_2x() {
+        let (b, w, r) = (Color::BLACK, Color::WHITE, Color::RED);
+        #[rustfmt::skip]
+        let pixels = vec![
+            w, w, w, b, r, r,
+            b, b, w, b, r, r,
+            r, b, b, b, b, b,
+        ];
+        let mut image = Image::new_with_pixels(crate::USize::new(6, 3), pixels);
+        image.set_key_color(Some(r));
+
+        let mut console = ConsoleExtender::new(crate::USize::new(4, 3));
+        console.clear(None, Some(Color::BLUE), None);
+        image.blit_2x(&mut console, Position::new(1, 1), None);
+
+        let cell = |x, y| {
+            let position = Position::new(x, y);
+            (
+                console.ascii(position).unwrap(),
+                console.fore(position).unwrap(),
+                console.back(position).unwrap(),
+            )
+        };
+        assert_eq!(cell(1, 1), (UPPER_HALF_BLOCK, w, b));
+        assert_eq!(cell(2, 1), (LEFT_HALF_BLOCK, w, b));
+        assert_eq!(console.ascii(Position::new(3, 1)), Some(u16::from(b' ')));
+        assert_eq!(console.back(Position::new(3, 1)), Some(Color::BLUE));
+        let dark_blue = Color::new(0, 0, 127);
+        assert_eq!(cell(1, 2), (UPPER_HALF_BLOCK, dark_blue, Color::BLUE));
+        assert_eq!(cell(2, 2), (UPPER_HALF_BLOCK, b, Color::BLUE));
+        assert_eq!(console.back(Position::new(0, 0)), Some(Color::BLUE));
+    }
+}
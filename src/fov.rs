@@ -0,0 +1,495 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Field of view.
+//!
+//! This toolkit calculates which cells of a map are visible from a given position, the same way
+//! libtcod's fov toolkit does. Build a `FovMap` describing which cells let light through, then
+//! call `compute_fov` with one of the `FovAlgorithm`s.
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::{Position, USize};
+//! # use doryen_extra::fov::{FovAlgorithm, FovMap};
+//! let mut map = FovMap::new(USize::new(10, 10));
+//! map.clear(true, true);
+//! map.set_properties(Position::new(5, 4), false, false);
+//! map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Shadowcasting);
+//! assert!(map.is_in_fov(Position::new(5, 4)));
+//! assert!(!map.is_in_fov(Position::new(5, 2)));
+//! assert!(map.is_in_fov(Position::new(0, 9)));
+//! ```
+
+mod permissive;
+mod raycasting;
+mod restrictive;
+mod shadowcasting;
+
+use crate::grid::Grid;
+use crate::math;
+use crate::{Error, Position, USize};
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+
+/// The algorithms available to compute a field of view.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+#[non_exhaustive]
+pub enum FovAlgorithm {
+    /// Recursive shadowcasting, libtcod's `FOV_SHADOW`. Fast, with nicely rounded shadows.
+    #[default]
+    Shadowcasting,
+    /// Precise permissive field of view, libtcod's `FOV_PERMISSIVE_0` to `FOV_PERMISSIVE_8`. The
+    /// permissiveness, from 0 to 8, decides how much of a cell must be visible for it to be in
+    /// the field of view; 0 is the most restrictive, 8 the most lenient.
+    Permissive(u8),
+    /// Mingos' Restrictive Precise Angle Shadowcasting, libtcod's `FOV_RESTRICTIVE`. Symmetric
+    /// in most cases, so what's visible can usually see back.
+    Restrictive,
+    /// Diamond raycasting, libtcod's `FOV_DIAMOND`.
+    Diamond,
+    /// Rays cast from the origin to every cell of the perimeter of the view, libtcod's
+    /// `FOV_BASIC`. Simple, but leaves some artifacts.
+    Basic,
+}
+
+/// What a cell of a `FovMap` is like, as far as light and movement are concerned.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum CellKind {
+    /// Lets light through and can be walked on, like open ground.
+    Floor,
+    /// Blocks light and can't be walked on, like a mountain.
+    Wall,
+    /// Lets light through but can't be walked on, like deep water.
+    Obstacle,
+    /// Blocks light but can be walked on, like a dense forest.
+    Cover,
+}
+
+impl CellKind {
+    /// Returns whether light goes through this kind of cell.
+    pub fn is_transparent(self) -> bool {
+        matches!(self, Self::Floor | Self::Obstacle)
+    }
+
+    /// Returns whether this kind of cell can be walked on.
+    pub fn is_walkable(self) -> bool {
+        matches!(self, Self::Floor | Self::Cover)
+    }
+}
+
+/// A map of which cells let light through and can be walked on, along with the field of view
+/// last computed on it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "FovMapData")
+)]
+pub struct FovMap {
+    transparent: Grid<bool>,
+    walkable: Grid<bool>,
+    in_fov: Grid<bool>,
+}
+
+// `FovMap` as it comes out of the deserializer, before the sizes of its grids are validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct FovMapData {
+    transparent: Grid<bool>,
+    walkable: Grid<bool>,
+    in_fov: Grid<bool>,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<FovMapData> for FovMap {
+    type Error = Error;
+
+    fn try_from(data: FovMapData) -> Result<Self, Self::Error> {
+        let (width, height) = (data.transparent.width(), data.transparent.height());
+        if u32::try_from(width).is_err() || u32::try_from(height).is_err() {
+            return Err(Error::OutOfRange("the size of the map"));
+        }
+        for grid in &[&data.walkable, &data.in_fov] {
+            if grid.width() != width || grid.height() != height {
+                return Err(Error::SizeMismatch {
+                    expected: data.transparent.size(),
+                    actual: grid.size(),
+                });
+            }
+        }
+
+        Ok(Self {
+            transparent: data.transparent,
+            walkable: data.walkable,
+            in_fov: data.in_fov,
+        })
+    }
+}
+
+impl FovMap {
+    /// Returns a new map of the given size, where every cell is opaque and unwalkable.
+    pub fn new(size: USize) -> Self {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        Self {
+            transparent: Grid::new(width, height, false),
+            walkable: Grid::new(width, height, false),
+            in_fov: Grid::new(width, height, false),
+        }
+    }
+
+    /// Returns a new map with the transparency of each cell taken from `transparent`, where the
+    /// transparent cells are walkable and the others are not.
+    pub fn new_from_transparency(transparent: &Grid<bool>) -> Self {
+        Self {
+            transparent: transparent.clone(),
+            walkable: transparent.clone(),
+            in_fov: Grid::new(transparent.width(), transparent.height(), false),
+        }
+    }
+
+    /// Returns the size of the map.
+    pub fn size(&self) -> USize {
+        self.transparent.size()
+    }
+
+    /// Sets every cell to the given properties, and clears the field of view.
+    pub fn clear(&mut self, transparent: bool, walkable: bool) {
+        self.transparent.fill(transparent);
+        self.walkable.fill(walkable);
+        self.in_fov.fill(false);
+    }
+
+    /// Sets the properties of a cell. Positions outside the map are ignored.
+    pub fn set_properties(&mut self, position: Position, transparent: bool, walkable: bool) {
+        if let Some(cell) = self.transparent.get_mut(position) {
+            *cell = transparent;
+            self.walkable[position] = walkable;
+        }
+    }
+
+    /// Sets the properties of a cell to those of the given kind of cell. Positions outside the
+    /// map are ignored.
+    pub fn set_cell_kind(&mut self, position: Position, kind: CellKind) {
+        self.set_properties(position, kind.is_transparent(), kind.is_walkable());
+    }
+
+    /// Returns whether light goes through the cell. Cells outside the map are opaque.
+    pub fn is_transparent(&self, position: Position) -> bool {
+        self.transparent.get(position).copied().unwrap_or(false)
+    }
+
+    /// Returns whether the cell can be walked on. Cells outside the map can't.
+    pub fn is_walkable(&self, position: Position) -> bool {
+        self.walkable.get(position).copied().unwrap_or(false)
+    }
+
+    /// Returns whether the cell is in the field of view last computed. Cells outside the map
+    /// never are.
+    pub fn is_in_fov(&self, position: Position) -> bool {
+        self.in_fov.get(position).copied().unwrap_or(false)
+    }
+
+    /// Marks a cell as being in the field of view or not. Positions outside the map are ignored.
+    pub fn set_in_fov(&mut self, position: Position, in_fov: bool) {
+        if let Some(cell) = self.in_fov.get_mut(position) {
+            *cell = in_fov;
+        }
+    }
+
+    /// Returns the field of view last computed, where the visible cells are `true`.
+    pub fn fov(&self) -> &Grid<bool> {
+        &self.in_fov
+    }
+
+    /// Computes the field of view from `origin`, replacing the previous one.
+    ///
+    /// Only cells within `max_radius` of the origin are visible, unless it's 0, in which case
+    /// the view is unlimited. When `light_walls` is `false`, opaque cells are never visible, only
+    /// the transparent cells around them.
+    ///
+    /// # Panics
+    /// If `origin` is outside the map, or the permissiveness of a `Permissive` algorithm is
+    /// greater than 8.
+    pub fn compute_fov(
+        &mut self,
+        origin: Position,
+        max_radius: u32,
+        light_walls: bool,
+        algorithm: FovAlgorithm,
+    ) {
+        self.try_compute_fov(origin, max_radius, light_walls, algorithm)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Like `compute_fov`, but returns an error instead of panicking if `origin` is outside the
+    /// map, or the permissiveness of a `Permissive` algorithm is greater than 8.
+    pub fn try_compute_fov(
+        &mut self,
+        origin: Position,
+        max_radius: u32,
+        light_walls: bool,
+        algorithm: FovAlgorithm,
+    ) -> Result<(), Error> {
+        if !self.transparent.in_bounds(origin) {
+            return Err(Error::OutOfRange("the origin of the field of view"));
+        }
+        if let FovAlgorithm::Permissive(permissiveness) = algorithm {
+            if permissiveness > 8 {
+                return Err(Error::OutOfRange("the permissiveness of the field of view"));
+            }
+        }
+
+        self.in_fov.fill(false);
+        // A radius past the farthest corner of the map sees no more than that corner does, and
+        // clamping it keeps the squared radius of the algorithms from overflowing.
+        let unlimited_radius = unlimited_radius(self.size(), origin);
+        let max_radius = if max_radius == 0 {
+            unlimited_radius
+        } else {
+            max_radius.min(unlimited_radius)
+        };
+        match algorithm {
+            FovAlgorithm::Shadowcasting => {
+                shadowcasting::compute(self, origin, max_radius, light_walls)
+            }
+            FovAlgorithm::Permissive(permissiveness) => {
+                permissive::compute(self, origin, max_radius, light_walls, permissiveness)
+            }
+            FovAlgorithm::Restrictive => {
+                restrictive::compute(self, origin, max_radius, light_walls)
+            }
+            FovAlgorithm::Diamond => {
+                raycasting::compute_diamond(self, origin, max_radius, light_walls)
+            }
+            FovAlgorithm::Basic => raycasting::compute_basic(self, origin, max_radius, light_walls),
+        }
+        self.in_fov[origin] = true;
+
+        Ok(())
+    }
+
+    /// Computes the combined field of view from all the `origins`, replacing the previous one,
+    /// so that a cell is visible if it's visible from any of them, e.g. to show what a whole
+    /// party of adventurers can see. The other arguments are like for `compute_fov`.
+    ///
+    /// With the `parallel` feature enabled, the fields of view of the origins are computed in
+    /// parallel. The result is identical either way.
+    ///
+    /// # Panics
+    /// If any of the `origins` is outside the map, or the permissiveness of a `Permissive`
+    /// algorithm is greater than 8.
+    pub fn compute_combined_fov(
+        &mut self,
+        origins: &[Position],
+        max_radius: u32,
+        light_walls: bool,
+        algorithm: FovAlgorithm,
+    ) {
+        self.try_compute_combined_fov(origins, max_radius, light_walls, algorithm)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Like `compute_combined_fov`, but returns an error instead of panicking if any of the
+    /// `origins` is outside the map, or the permissiveness of a `Permissive` algorithm is greater
+    /// than 8. The field of view is left alone when there's an error.
+    pub fn try_compute_combined_fov(
+        &mut self,
+        origins: &[Position],
+        max_radius: u32,
+        light_walls: bool,
+        algorithm: FovAlgorithm,
+    ) -> Result<(), Error> {
+        let fov_from = |&origin: &Position| {
+            let mut map = self.clone();
+            map.try_compute_fov(origin, max_radius, light_walls, algorithm)
+                .map(|_| map.in_fov)
+        };
+        #[cfg(feature = "parallel")]
+        let fovs: Vec<_> = {
+            use rayon::prelude::*;
+            origins.par_iter().map(fov_from).collect::<Result<_, _>>()?
+        };
+        #[cfg(not(feature = "parallel"))]
+        let fovs: Vec<_> = origins.iter().map(fov_from).collect::<Result<_, _>>()?;
+
+        self.in_fov.fill(false);
+        for fov in &fovs {
+            for (in_fov, &visible) in self.in_fov.iter_mut().zip(fov.iter()) {
+                *in_fov |= visible;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Removes the cells further than `radius` from `origin` from the field of view.
+    fn clip_to_radius(&mut self, origin: Position, radius: u32) {
+        let radius_squared = i64::from(radius) * i64::from(radius);
+        let width = self.in_fov.width();
+        for (i, in_fov) in self.in_fov.iter_mut().enumerate() {
+            let x = i64::from((i % width) as i32 - origin.x);
+            let y = i64::from((i / width) as i32 - origin.y);
+            if x * x + y * y > radius_squared {
+                *in_fov = false;
+            }
+        }
+    }
+}
+
+// A radius reaching every cell of a map of the given size from `origin`, which must be inside
+// it. The distances are widened to 64 bits, since the squared distance to a corner overflows an
+// `i32` on large maps.
+fn unlimited_radius(size: USize, origin: Position) -> u32 {
+    let x = i64::from(origin.x).max(i64::from(size.width) - i64::from(origin.x));
+    let y = i64::from(origin.y).max(i64::from(size.height) - i64::from(origin.y));
+
+    let (x, y) = (x as f64, y as f64);
+
+    (math::sqrt_f64(x * x + y * y) as u32).saturating_add(1)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    // Builds a map from rows of `#` (walls) and `.` (floor).
+    pub(super) fn map_from(rows: &[&str]) -> FovMap {
+        let transparent = Grid::new_with(rows[0].len(), rows.len(), |position| {
+            rows[position.y as usize].as_bytes()[position.x as usize] != b'#'
+        });
+
+        FovMap::new_from_transparency(&transparent)
+    }
+
+    // Renders the field of view as rows of `*` (visible) and ` ` (not visible).
+    pub(super) fn visible(map: &FovMap) -> Vec<String> {
+        map.fov()
+            .rows()
+            .map(|row| row.iter().map(|&v| if v { '*' } else { ' ' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn properties_and_errors() {
+        let mut map = FovMap::new(USize::new(3, 3));
+        assert!(!map.is_transparent(Position::new(1, 1)));
+        map.set_properties(Position::new(1, 1), true, true);
+        assert!(map.is_transparent(Position::new(1, 1)));
+        assert!(map.is_walkable(Position::new(1, 1)));
+        assert!(!map.is_walkable(Position::new(5, 5)));
+        map.set_cell_kind(Position::new(0, 1), CellKind::Cover);
+        assert!(!map.is_transparent(Position::new(0, 1)));
+        assert!(map.is_walkable(Position::new(0, 1)));
+
+        assert_eq!(
+            map.try_compute_fov(Position::new(3, 0), 0, true, FovAlgorithm::default()),
+            Err(Error::OutOfRange("the origin of the field of view"))
+        );
+        assert_eq!(
+            map.try_compute_fov(Position::new(1, 1), 0, true, FovAlgorithm::Permissive(9)),
+            Err(Error::OutOfRange("the permissiveness of the field of view"))
+        );
+        map.compute_fov(Position::new(1, 1), 0, false, FovAlgorithm::default());
+        assert_eq!(visible(&map), vec!["   ", " * ", "   "]);
+        map.compute_fov(Position::new(1, 1), 0, true, FovAlgorithm::default());
+        assert_eq!(visible(&map), vec!["***", "***", "***"]);
+    }
+
+    #[test]
+    fn combined_fov_is_the_union_of_the_origins() {
+        let mut map = map_from(&[
+            "..#....", //
+            "..#....", //
+            "#####..", //
+            ".......", //
+        ]);
+        let origins = [
+            Position::new(0, 0),
+            Position::new(4, 0),
+            Position::new(1, 3),
+        ];
+        map.compute_combined_fov(&origins, 0, true, FovAlgorithm::default());
+        let combined = map.fov().clone();
+
+        for &origin in &origins {
+            map.compute_fov(origin, 0, true, FovAlgorithm::default());
+            for (position, &visible) in map.fov().iter().enumerate() {
+                assert!(!visible || combined.cells()[position]);
+            }
+        }
+        assert!(combined.cells().iter().all(|&visible| visible));
+
+        map.compute_combined_fov(&origins, 0, true, FovAlgorithm::default());
+        assert_eq!(
+            map.try_compute_combined_fov(
+                &[Position::new(0, 0), Position::new(7, 0)],
+                0,
+                true,
+                FovAlgorithm::default()
+            ),
+            Err(Error::OutOfRange("the origin of the field of view"))
+        );
+        assert_eq!(map.fov(), &combined);
+    }
+
+    #[test]
+    fn unlimited_radius_of_huge_maps() {
+        assert_eq!(unlimited_radius(USize::new(3, 4), Position::ORIGIN), 6);
+        let size = USize::new(100_000, 100_000);
+        assert_eq!(unlimited_radius(size, Position::new(0, 0)), 141_422);
+        assert_eq!(
+            unlimited_radius(USize::new(u32::MAX, u32::MAX), Position::ORIGIN),
+            u32::MAX
+        );
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_mismatched_grids() {
+        let map = map_from(&["..#", "..."]);
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(serde_json::from_value::<FovMap>(json.clone()).unwrap(), map);
+
+        let mut mismatched = json;
+        mismatched["in_fov"] = serde_json::to_value(Grid::new(2, 2, false)).unwrap();
+        assert!(serde_json::from_value::<FovMap>(mismatched).is_err());
+    }
+}
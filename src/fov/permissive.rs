@@ -0,0 +1,371 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Precise permissive field of view, a port of libtcod's `fov_permissive2.c`.
+//!
+//! Every cell is treated as a square of `STEP` by `STEP` units, and the origin as a smaller
+//! square in the middle of its cell, whose size depends on the permissiveness. A cell is visible
+//! if any line goes from a point of the origin square to a point of the cell without crossing
+//! an opaque cell.
+
+use super::FovMap;
+use crate::Position;
+
+const STEP: i32 = 16;
+
+#[derive(Copy, Clone, Debug)]
+struct Line {
+    xi: i32,
+    yi: i32,
+    xf: i32,
+    yf: i32,
+}
+
+impl Line {
+    // The coordinates are in 16ths of a cell, so the products are computed in 64 bits to keep
+    // them from overflowing on maps a few thousand cells across.
+    fn relative_slope(&self, x: i32, y: i32) -> i64 {
+        i64::from(self.yf - self.yi) * i64::from(self.xf - x)
+            - i64::from(self.xf - self.xi) * i64::from(self.yf - y)
+    }
+
+    fn is_below(&self, x: i32, y: i32) -> bool {
+        self.relative_slope(x, y) > 0
+    }
+
+    fn is_below_or_collinear(&self, x: i32, y: i32) -> bool {
+        self.relative_slope(x, y) >= 0
+    }
+
+    fn is_above(&self, x: i32, y: i32) -> bool {
+        self.relative_slope(x, y) < 0
+    }
+
+    fn is_above_or_collinear(&self, x: i32, y: i32) -> bool {
+        self.relative_slope(x, y) <= 0
+    }
+
+    fn is_collinear(&self, x: i32, y: i32) -> bool {
+        self.relative_slope(x, y) == 0
+    }
+
+    fn is_line_collinear(&self, other: &Self) -> bool {
+        self.is_collinear(other.xi, other.yi) && self.is_collinear(other.xf, other.yf)
+    }
+}
+
+// A corner of an opaque cell that narrows a view, along with the bump that was there before.
+#[derive(Copy, Clone, Debug)]
+struct Bump {
+    x: i32,
+    y: i32,
+    parent: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct View {
+    shallow_line: Line,
+    steep_line: Line,
+    shallow_bump: Option<usize>,
+    steep_bump: Option<usize>,
+}
+
+struct Permissive<'a> {
+    map: &'a mut FovMap,
+    origin: Position,
+    light_walls: bool,
+    offset: i32,
+    limit: i32,
+    views: Vec<View>,
+    bumps: Vec<Bump>,
+    current_view: usize,
+}
+
+pub(super) fn compute(
+    map: &mut FovMap,
+    origin: Position,
+    max_radius: u32,
+    light_walls: bool,
+    permissiveness: u8,
+) {
+    let size = map.size();
+    let radius = max_radius.min(i32::MAX as u32) as i32;
+    let min_x = origin.x.min(radius);
+    let max_x = (size.width as i32 - origin.x - 1).min(radius);
+    let min_y = origin.y.min(radius);
+    let max_y = (size.height as i32 - origin.y - 1).min(radius);
+
+    let mut permissive = Permissive {
+        map,
+        origin,
+        light_walls,
+        offset: 8 - i32::from(permissiveness),
+        limit: 8 + i32::from(permissiveness),
+        views: Vec::new(),
+        bumps: Vec::new(),
+        current_view: 0,
+    };
+    permissive.check_quadrant(1, 1, max_x, max_y);
+    permissive.check_quadrant(1, -1, max_x, min_y);
+    permissive.check_quadrant(-1, -1, min_x, min_y);
+    permissive.check_quadrant(-1, 1, min_x, max_y);
+
+    map.clip_to_radius(origin, max_radius);
+}
+
+impl Permissive<'_> {
+    fn check_quadrant(&mut self, dx: i32, dy: i32, extent_x: i32, extent_y: i32) {
+        self.bumps.clear();
+        self.views.clear();
+        self.views.push(View {
+            shallow_line: Line {
+                xi: self.offset,
+                yi: self.limit,
+                xf: extent_x * STEP,
+                yf: 0,
+            },
+            steep_line: Line {
+                xi: self.limit,
+                yi: self.offset,
+                xf: 0,
+                yf: extent_y * STEP,
+            },
+            shallow_bump: None,
+            steep_bump: None,
+        });
+
+        let max_i = extent_x + extent_y;
+        let mut i = 1;
+        while i <= max_i && !self.views.is_empty() {
+            let max_j = i.min(extent_y);
+            let mut j = (i - extent_x).max(0);
+            self.current_view = 0;
+            while j <= max_j && self.current_view < self.views.len() {
+                self.visit((i - j) * STEP, j * STEP, dx, dy);
+                j += 1;
+            }
+            i += 1;
+        }
+    }
+
+    fn visit(&mut self, x: i32, y: i32, dx: i32, dy: i32) {
+        let (top_left_x, top_left_y) = (x, y + STEP);
+        let (bottom_right_x, bottom_right_y) = (x + STEP, y);
+
+        while self.current_view < self.views.len()
+            && self.views[self.current_view]
+                .steep_line
+                .is_below_or_collinear(bottom_right_x, bottom_right_y)
+        {
+            self.current_view += 1;
+        }
+        if self.current_view == self.views.len()
+            || self.views[self.current_view]
+                .shallow_line
+                .is_above_or_collinear(top_left_x, top_left_y)
+        {
+            return;
+        }
+
+        let position = self.origin + Position::new(x / STEP * dx, y / STEP * dy);
+        let transparent = self.map.transparent[position];
+        if self.light_walls || transparent {
+            self.map.in_fov[position] = true;
+        }
+        if transparent {
+            return;
+        }
+
+        let view = self.views[self.current_view];
+        let above_shallow = view.shallow_line.is_above(bottom_right_x, bottom_right_y);
+        let below_steep = view.steep_line.is_below(top_left_x, top_left_y);
+        if above_shallow && below_steep {
+            self.views.remove(self.current_view);
+        } else if above_shallow {
+            self.add_shallow_bump(top_left_x, top_left_y, self.current_view);
+            self.check_view(self.current_view);
+        } else if below_steep {
+            self.add_steep_bump(bottom_right_x, bottom_right_y, self.current_view);
+            self.check_view(self.current_view);
+        } else {
+            // The cell splits the view in two.
+            let shallower = self.current_view;
+            let mut steeper = shallower + 1;
+            self.views.insert(shallower, view);
+            self.add_steep_bump(bottom_right_x, bottom_right_y, shallower);
+            if !self.check_view(shallower) {
+                steeper -= 1;
+            }
+            self.add_shallow_bump(top_left_x, top_left_y, steeper);
+            self.check_view(steeper);
+        }
+    }
+
+    fn add_shallow_bump(&mut self, x: i32, y: i32, index: usize) {
+        let view = &mut self.views[index];
+        view.shallow_line.xf = x;
+        view.shallow_line.yf = y;
+        self.bumps.push(Bump {
+            x,
+            y,
+            parent: view.shallow_bump,
+        });
+        view.shallow_bump = Some(self.bumps.len() - 1);
+
+        let bumps = &self.bumps;
+        let mut current = view.steep_bump;
+        while let Some(bump) = current.map(|bump_index| bumps[bump_index]) {
+            if view.shallow_line.is_above(bump.x, bump.y) {
+                view.shallow_line.xi = bump.x;
+                view.shallow_line.yi = bump.y;
+            }
+            current = bump.parent;
+        }
+    }
+
+    fn add_steep_bump(&mut self, x: i32, y: i32, index: usize) {
+        let view = &mut self.views[index];
+        view.steep_line.xf = x;
+        view.steep_line.yf = y;
+        self.bumps.push(Bump {
+            x,
+            y,
+            parent: view.steep_bump,
+        });
+        view.steep_bump = Some(self.bumps.len() - 1);
+
+        let bumps = &self.bumps;
+        let mut current = view.shallow_bump;
+        while let Some(bump) = current.map(|bump_index| bumps[bump_index]) {
+            if view.steep_line.is_below(bump.x, bump.y) {
+                view.steep_line.xi = bump.x;
+                view.steep_line.yi = bump.y;
+            }
+            current = bump.parent;
+        }
+    }
+
+    // Removes the view if it has been narrowed down to a line going through a corner of the
+    // origin square, returning whether it's still there.
+    fn check_view(&mut self, index: usize) -> bool {
+        let View {
+            shallow_line,
+            steep_line,
+            ..
+        } = self.views[index];
+        if shallow_line.is_line_collinear(&steep_line)
+            && (shallow_line.is_collinear(self.offset, self.limit)
+                || shallow_line.is_collinear(self.limit, self.offset))
+        {
+            self.views.remove(index);
+            false
+        } else {
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{map_from, visible};
+    use super::super::{FovAlgorithm, FovMap};
+    use crate::{Position, USize};
+
+    #[test]
+    fn through_a_door() {
+        let mut map = map_from(&[
+            "###########",
+            "#.........#",
+            "#...#.....#",
+            "#.........#",
+            "#####.#####",
+            "#.........#",
+            "###########",
+        ]);
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Permissive(0));
+        assert_eq!(
+            visible(&map),
+            vec![
+                "    ***    ",
+                "    ***    ",
+                "    ***    ",
+                "    ***    ",
+                " ********* ",
+                "***********",
+                " ********* ",
+            ]
+        );
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Permissive(8));
+        assert_eq!(
+            visible(&map),
+            vec![
+                "    *****  ",
+                "    *****  ",
+                "    ****   ",
+                "    ***    ",
+                "***********",
+                "***********",
+                "***********",
+            ]
+        );
+    }
+
+    #[test]
+    fn pillar_shadow() {
+        let mut map = map_from(&["......", "......", "..#...", "......", "......", "......"]);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Permissive(0));
+        assert_eq!(
+            visible(&map),
+            vec!["******", "******", "******", "*** **", "****  ", "****  "]
+        );
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Permissive(8));
+        assert_eq!(
+            visible(&map),
+            vec!["******", "******", "******", "*** **", "**** *", "***** "]
+        );
+        map.compute_fov(Position::new(0, 0), 3, false, FovAlgorithm::Permissive(4));
+        assert_eq!(
+            visible(&map),
+            vec!["****  ", "***   ", "**    ", "*     ", "      ", "      "]
+        );
+    }
+
+    #[test]
+    fn large_open_map() {
+        let mut map = FovMap::new(USize::new(3000, 3000));
+        map.clear(true, true);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Permissive(8));
+        assert!(map.is_in_fov(Position::new(2999, 2999)));
+        assert!(map.is_in_fov(Position::new(2999, 1)));
+        assert!(map.fov().iter().all(|&visible| visible));
+    }
+}
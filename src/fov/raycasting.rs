@@ -0,0 +1,456 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Raycasting fields of view, ports of libtcod's `fov_circular_raycasting.c` and
+//! `fov_diamond_raycasting.c`.
+
+use super::FovMap;
+use crate::bresenham::Bresenham;
+use crate::{Position, Rectangle};
+
+pub(super) fn compute_basic(
+    map: &mut FovMap,
+    origin: Position,
+    max_radius: u32,
+    light_walls: bool,
+) {
+    let bounds = bounds(map, origin, max_radius);
+    let radius_squared = i64::from(max_radius).saturating_mul(i64::from(max_radius));
+    let (min_x, min_y) = (bounds.position.x, bounds.position.y);
+    let max_x = min_x + bounds.size.width as i32 - 1;
+    let max_y = min_y + bounds.size.height as i32 - 1;
+
+    // Cast a ray to every cell of the perimeter of the bounds.
+    for x in min_x..=max_x {
+        cast_ray(
+            map,
+            origin,
+            Position::new(x, min_y),
+            radius_squared,
+            light_walls,
+        );
+    }
+    for y in min_y + 1..=max_y {
+        cast_ray(
+            map,
+            origin,
+            Position::new(max_x, y),
+            radius_squared,
+            light_walls,
+        );
+    }
+    for x in (min_x..max_x).rev() {
+        cast_ray(
+            map,
+            origin,
+            Position::new(x, max_y),
+            radius_squared,
+            light_walls,
+        );
+    }
+    for y in (min_y + 1..max_y).rev() {
+        cast_ray(
+            map,
+            origin,
+            Position::new(min_x, y),
+            radius_squared,
+            light_walls,
+        );
+    }
+
+    if light_walls {
+        light_wall_artifacts(map, origin, bounds);
+    }
+}
+
+fn cast_ray(
+    map: &mut FovMap,
+    origin: Position,
+    destination: Position,
+    radius_squared: i64,
+    light_walls: bool,
+) {
+    let mut blocked = false;
+    for position in Bresenham::init(origin, destination) {
+        let offset = position - origin;
+        if i64::from(offset.x).pow(2) + i64::from(offset.y).pow(2) > radius_squared
+            || !map.transparent.in_bounds(position)
+        {
+            return;
+        }
+        if blocked {
+            return;
+        }
+        blocked = !map.transparent[position];
+        if light_walls || !blocked {
+            map.in_fov[position] = true;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Ray {
+    offset: Position,
+    // The obscurity vector.
+    obscurity_x: i32,
+    obscurity_y: i32,
+    // The Bresenham error.
+    error_x: i32,
+    error_y: i32,
+    input_x: Option<usize>,
+    input_y: Option<usize>,
+    added: bool,
+    ignore: bool,
+}
+
+impl Ray {
+    fn is_obscure(&self) -> bool {
+        (self.error_x > 0 && self.error_x <= self.obscurity_x)
+            || (self.error_y > 0 && self.error_y <= self.obscurity_y)
+    }
+}
+
+struct Diamond<'a> {
+    map: &'a mut FovMap,
+    origin: Position,
+    rays: Vec<Ray>,
+    perimeter: Vec<usize>,
+}
+
+pub(super) fn compute_diamond(
+    map: &mut FovMap,
+    origin: Position,
+    max_radius: u32,
+    light_walls: bool,
+) {
+    let bounds = bounds(map, origin, max_radius);
+    let radius_squared = i64::from(max_radius).saturating_mul(i64::from(max_radius));
+    let cell_count = map.transparent.cells().len();
+    let mut diamond = Diamond {
+        map,
+        origin,
+        rays: vec![Ray::default(); cell_count],
+        perimeter: Vec::new(),
+    };
+
+    let origin_index = diamond.index(Position::new(0, 0)).unwrap();
+    diamond.expand_perimeter_from(origin_index);
+    let mut next = 0;
+    while next < diamond.perimeter.len() {
+        let index = diamond.perimeter[next];
+        next += 1;
+        let offset = diamond.rays[index].offset;
+        if i64::from(offset.x).pow(2) + i64::from(offset.y).pow(2) <= radius_squared {
+            diamond.merge_input(index);
+            if !diamond.rays[index].ignore {
+                diamond.expand_perimeter_from(index);
+            }
+        } else {
+            diamond.rays[index].ignore = true;
+        }
+    }
+
+    for (in_fov, ray) in diamond.map.in_fov.iter_mut().zip(&diamond.rays) {
+        *in_fov = ray.added && !ray.ignore && !ray.is_obscure();
+    }
+
+    map.in_fov[origin] = true;
+    if light_walls {
+        light_wall_artifacts(map, origin, bounds);
+    }
+}
+
+impl Diamond<'_> {
+    // Returns the index of the ray at `offset` from the origin, initializing its position, or
+    // `None` if it's outside the map.
+    fn index(&mut self, offset: Position) -> Option<usize> {
+        let position = self.origin + offset;
+        if !self.map.transparent.in_bounds(position) {
+            return None;
+        }
+        let index = position.y as usize * self.map.transparent.width() + position.x as usize;
+        self.rays[index].offset = offset;
+
+        Some(index)
+    }
+
+    fn expand_perimeter_from(&mut self, index: usize) {
+        let offset = self.rays[index].offset;
+        if offset.x >= 0 {
+            self.process_ray(offset + Position::new(1, 0), index);
+        }
+        if offset.x <= 0 {
+            self.process_ray(offset + Position::new(-1, 0), index);
+        }
+        if offset.y >= 0 {
+            self.process_ray(offset + Position::new(0, 1), index);
+        }
+        if offset.y <= 0 {
+            self.process_ray(offset + Position::new(0, -1), index);
+        }
+    }
+
+    fn process_ray(&mut self, offset: Position, input: usize) {
+        if let Some(index) = self.index(offset) {
+            let same_row = offset.y == self.rays[input].offset.y;
+            let ray = &mut self.rays[index];
+            if same_row {
+                ray.input_x = Some(input);
+            } else {
+                ray.input_y = Some(input);
+            }
+            if !ray.added {
+                ray.added = true;
+                self.perimeter.push(index);
+            }
+        }
+    }
+
+    fn merge_input(&mut self, index: usize) {
+        let input_x = self.rays[index].input_x.map(|input| self.rays[input]);
+        let input_y = self.rays[index].input_y.map(|input| self.rays[input]);
+        let ray = &mut self.rays[index];
+
+        if let Some(input) = input_x {
+            if input.obscurity_x != 0 || input.obscurity_y != 0 {
+                if input.error_x > 0 && ray.obscurity_x == 0 {
+                    ray.error_x = input.error_x - input.obscurity_y;
+                    ray.error_y = input.error_y + input.obscurity_y;
+                    ray.obscurity_x = input.obscurity_x;
+                    ray.obscurity_y = input.obscurity_y;
+                }
+                if input.error_y <= 0 && input.obscurity_y > 0 && input.error_x > 0 {
+                    ray.error_y = input.error_y + input.obscurity_y;
+                    ray.error_x = input.error_x - input.obscurity_y;
+                    ray.obscurity_x = input.obscurity_x;
+                    ray.obscurity_y = input.obscurity_y;
+                }
+            }
+        }
+        if let Some(input) = input_y {
+            if input.obscurity_x != 0 || input.obscurity_y != 0 {
+                if input.error_y > 0 && ray.obscurity_y == 0 {
+                    ray.error_y = input.error_y - input.obscurity_x;
+                    ray.error_x = input.error_x + input.obscurity_x;
+                    ray.obscurity_x = input.obscurity_x;
+                    ray.obscurity_y = input.obscurity_y;
+                }
+                if input.error_x <= 0 && input.obscurity_x > 0 && input.error_y > 0 {
+                    ray.error_y = input.error_y - input.obscurity_x;
+                    ray.error_x = input.error_x + input.obscurity_x;
+                    ray.obscurity_x = input.obscurity_x;
+                    ray.obscurity_y = input.obscurity_y;
+                }
+            }
+        }
+
+        ray.ignore = match (input_x, input_y) {
+            (Some(input_x), Some(input_y)) => input_x.is_obscure() && input_y.is_obscure(),
+            (Some(input), None) | (None, Some(input)) => input.is_obscure(),
+            (None, None) => false,
+        };
+        if !ray.ignore && !self.map.transparent[self.origin + ray.offset] {
+            ray.error_x = ray.offset.x.abs();
+            ray.obscurity_x = ray.error_x;
+            ray.error_y = ray.offset.y.abs();
+            ray.obscurity_y = ray.error_y;
+        }
+    }
+}
+
+// The part of the map within `max_radius` of `origin` on both axes.
+fn bounds(map: &FovMap, origin: Position, max_radius: u32) -> Rectangle {
+    let size = map.size();
+    let radius = max_radius.min(i32::MAX as u32) as i32;
+    let min_x = origin.x.saturating_sub(radius).max(0);
+    let min_y = origin.y.saturating_sub(radius).max(0);
+    let max_x = origin
+        .x
+        .saturating_add(radius)
+        .saturating_add(1)
+        .min(size.width as i32);
+    let max_y = origin
+        .y
+        .saturating_add(radius)
+        .saturating_add(1)
+        .min(size.height as i32);
+
+    Rectangle::new_from_raw(min_x, min_y, (max_x - min_x) as u32, (max_y - min_y) as u32)
+}
+
+// Lights the opaque cells next to a visible transparent cell, on the side facing away from the
+// origin, which rays tend to miss.
+fn light_wall_artifacts(map: &mut FovMap, origin: Position, bounds: Rectangle) {
+    let (min_x, min_y) = (bounds.position.x, bounds.position.y);
+    let max_x = min_x + bounds.size.width as i32 - 1;
+    let max_y = min_y + bounds.size.height as i32 - 1;
+    let quadrants = [
+        (min_x, min_y, origin.x, origin.y, -1, -1),
+        (origin.x, min_y, max_x, origin.y, 1, -1),
+        (min_x, origin.y, origin.x, max_y, -1, 1),
+        (origin.x, origin.y, max_x, max_y, 1, 1),
+    ];
+    for &(x0, y0, x1, y1, dx, dy) in &quadrants {
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                let position = Position::new(x, y);
+                if !map.in_fov[position] || !map.transparent[position] {
+                    continue;
+                }
+                let (x2, y2) = (x + dx, y + dy);
+                let mut light = |neighbor: Position| {
+                    if !map.transparent[neighbor] {
+                        map.in_fov[neighbor] = true;
+                    }
+                };
+                if x2 >= x0 && x2 <= x1 {
+                    light(Position::new(x2, y));
+                }
+                if y2 >= y0 && y2 <= y1 {
+                    light(Position::new(x, y2));
+                }
+                if x2 >= x0 && x2 <= x1 && y2 >= y0 && y2 <= y1 {
+                    light(Position::new(x2, y2));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{map_from, visible};
+    use super::super::{FovAlgorithm, FovMap};
+    use crate::{Position, USize};
+
+    #[test]
+    fn huge_radius_sees_the_whole_map() {
+        for &compute in &[super::compute_basic, super::compute_diamond] {
+            for &radius in &[50_000, 100_000, u32::MAX] {
+                let mut map = map_from(&[".........."; 10]);
+                compute(&mut map, Position::new(3, 4), radius, true);
+                map.in_fov[Position::new(3, 4)] = true;
+                assert!(map.fov().cells().iter().all(|&visible| visible));
+            }
+        }
+    }
+
+    #[test]
+    fn basic() {
+        let mut map = map_from(&[
+            "###########",
+            "#.........#",
+            "#...#.....#",
+            "#.........#",
+            "#####.#####",
+            "#.........#",
+            "###########",
+        ]);
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Basic);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "    *****  ",
+                "     ***   ",
+                "    ***    ",
+                "    ***    ",
+                "***********",
+                "***********",
+                "***********"
+            ]
+        );
+    }
+
+    #[test]
+    fn basic_pillar() {
+        let mut map = map_from(&["......", "......", "..#...", "......", "......", "......"]);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Basic);
+        assert_eq!(
+            visible(&map),
+            vec!["******", "******", "******", "***  *", "***   ", "****  "]
+        );
+        map.compute_fov(Position::new(0, 0), 3, false, FovAlgorithm::Basic);
+        assert_eq!(
+            visible(&map),
+            vec!["****  ", "***   ", "**    ", "*     ", "      ", "      "]
+        );
+    }
+
+    #[test]
+    fn diamond() {
+        let mut map = map_from(&[
+            "###########",
+            "#.........#",
+            "#...#.....#",
+            "#.........#",
+            "#####.#####",
+            "#.........#",
+            "###########",
+        ]);
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Diamond);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "    ****** ",
+                "     ****  ",
+                "    ****   ",
+                "    ***    ",
+                "***********",
+                "***********",
+                "***********"
+            ]
+        );
+    }
+
+    #[test]
+    fn diamond_pillar() {
+        let mut map = map_from(&["......", "......", "..#...", "......", "......", "......"]);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Diamond);
+        assert_eq!(
+            visible(&map),
+            vec!["******", "******", "******", "*** **", "**** *", "***** "]
+        );
+        map.compute_fov(Position::new(0, 0), 3, false, FovAlgorithm::Diamond);
+        assert_eq!(
+            visible(&map),
+            vec!["****  ", "***   ", "**    ", "*     ", "      ", "      "]
+        );
+    }
+
+    #[test]
+    fn diamond_long_open_corridor() {
+        let mut map = FovMap::new(USize::new(70_000, 1));
+        map.clear(true, true);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Diamond);
+        assert!(map.fov().cells().iter().all(|&visible| visible));
+    }
+}
@@ -0,0 +1,203 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Mingos' Restrictive Precise Angle Shadowcasting, a port of libtcod's `fov_restrictive.c`.
+//!
+//! Each line of cells going away from the origin is split into as many equal angle ranges as
+//! it has cells, and the ranges covered by opaque cells are remembered as obstacles for the
+//! following lines.
+
+use super::FovMap;
+use crate::Position;
+
+struct Restrictive<'a> {
+    map: &'a mut FovMap,
+    origin: Position,
+    max_radius: i32,
+    light_walls: bool,
+    start_angles: Vec<f64>,
+    end_angles: Vec<f64>,
+}
+
+pub(super) fn compute(map: &mut FovMap, origin: Position, max_radius: u32, light_walls: bool) {
+    let mut restrictive = Restrictive {
+        map,
+        origin,
+        max_radius: max_radius as i32,
+        light_walls,
+        start_angles: Vec::new(),
+        end_angles: Vec::new(),
+    };
+    for &(dx, dy) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+        restrictive.compute_octant(dx, dy, true);
+        restrictive.compute_octant(dx, dy, false);
+    }
+
+    map.clip_to_radius(origin, max_radius);
+}
+
+impl Restrictive<'_> {
+    // Scans the octant of the `(dx, dy)` quadrant that's closest to the vertical axis if
+    // `vertical` is `true`, and to the horizontal axis otherwise.
+    fn compute_octant(&mut self, dx: i32, dy: i32, vertical: bool) {
+        let origin = self.origin;
+        let position = |line: i32, cell: i32| {
+            if vertical {
+                origin + Position::new(cell * dx, line * dy)
+            } else {
+                origin + Position::new(line * dx, cell * dy)
+            }
+        };
+
+        self.start_angles.clear();
+        self.end_angles.clear();
+        let mut obstacles_in_last_line = 0;
+        let mut min_angle = 0.0;
+        let mut iteration = 1;
+        while self.map.transparent.in_bounds(position(iteration, 0)) {
+            let slopes_per_cell = 1.0 / f64::from(iteration + 1);
+            let half_slopes = slopes_per_cell * 0.5;
+            let mut processed_cell = (min_angle / slopes_per_cell) as i32;
+            let mut done = true;
+            while processed_cell <= iteration {
+                let current = position(iteration, processed_cell);
+                if !self.map.transparent.in_bounds(current) {
+                    break;
+                }
+                let transparent = self.map.transparent[current];
+                let start_slope = f64::from(processed_cell) * slopes_per_cell;
+                let centre_slope = start_slope + half_slopes;
+                let end_slope = start_slope + slopes_per_cell;
+
+                let mut visible = true;
+                if obstacles_in_last_line > 0 && !self.map.in_fov[current] {
+                    let behind = position(iteration - 1, processed_cell);
+                    let behind_diagonal = position(iteration - 1, processed_cell - 1);
+                    let blocked_behind =
+                        !self.map.is_in_fov(behind) || !self.map.is_transparent(behind);
+                    let blocked_behind_diagonal = self.map.transparent.in_bounds(behind_diagonal)
+                        && (!self.map.in_fov[behind_diagonal]
+                            || !self.map.transparent[behind_diagonal]);
+                    for obstacle in 0..obstacles_in_last_line {
+                        if !visible {
+                            break;
+                        }
+                        let (start_angle, end_angle) =
+                            (self.start_angles[obstacle], self.end_angles[obstacle]);
+                        if transparent {
+                            if centre_slope > start_angle && centre_slope < end_angle {
+                                visible = false;
+                            }
+                        } else if start_slope >= start_angle && end_slope <= end_angle {
+                            visible = false;
+                        }
+                        if visible && blocked_behind && blocked_behind_diagonal {
+                            visible = false;
+                        }
+                    }
+                }
+
+                if visible {
+                    self.map.in_fov[current] = true;
+                    done = false;
+                    // Opaque cells block the angles they cover.
+                    if !transparent {
+                        if min_angle >= start_slope {
+                            min_angle = end_slope;
+                        } else {
+                            self.start_angles.push(start_slope);
+                            self.end_angles.push(end_slope);
+                        }
+                        if !self.light_walls {
+                            self.map.in_fov[current] = false;
+                        }
+                    }
+                }
+                processed_cell += 1;
+            }
+
+            if done || iteration == self.max_radius || min_angle >= 1.0 {
+                break;
+            }
+            iteration += 1;
+            obstacles_in_last_line = self.start_angles.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{map_from, visible};
+    use super::super::FovAlgorithm;
+    use crate::Position;
+
+    #[test]
+    fn through_a_door() {
+        let mut map = map_from(&[
+            "###########",
+            "#.........#",
+            "#...#.....#",
+            "#.........#",
+            "#####.#####",
+            "#.........#",
+            "###########",
+        ]);
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Restrictive);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "    ****   ",
+                "     ***   ",
+                "    ***    ",
+                "    ***    ",
+                "***********",
+                "***********",
+                "***********",
+            ]
+        );
+    }
+
+    #[test]
+    fn pillar_shadow() {
+        let mut map = map_from(&["......", "......", "..#...", "......", "......", "......"]);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Restrictive);
+        assert_eq!(
+            visible(&map),
+            vec!["******", "******", "******", "***  *", "***   ", "****  "]
+        );
+        map.compute_fov(Position::new(0, 0), 3, false, FovAlgorithm::Restrictive);
+        assert_eq!(
+            visible(&map),
+            vec!["****  ", "***   ", "**    ", "*     ", "      ", "      "]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::FovMap;
+use crate::Position;
+
+// The transformations from octant coordinates to map coordinates, as `[xx, xy, yx, yy]`.
+const OCTANTS: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+struct Shadowcaster<'a> {
+    map: &'a mut FovMap,
+    origin: Position,
+    max_radius: i32,
+    light_walls: bool,
+}
+
+// A row of an octant that's still to be scanned, along with the slopes of the view through it
+// that's still unobstructed.
+struct Row {
+    distance: i32,
+    slope_high: f32,
+    slope_low: f32,
+}
+
+pub(super) fn compute(map: &mut FovMap, origin: Position, max_radius: u32, light_walls: bool) {
+    let mut caster = Shadowcaster {
+        map,
+        origin,
+        max_radius: max_radius.min(i32::MAX as u32) as i32,
+        light_walls,
+    };
+    for octant in &OCTANTS {
+        caster.cast_light(octant);
+    }
+}
+
+impl Shadowcaster<'_> {
+    // Scans the rows of an octant, row by row, between the slopes of the view that's still
+    // unobstructed. The rows still to be scanned are kept on a stack rather than recursed into,
+    // since there can be one for every cell between the origin and the edge of the map.
+    fn cast_light(&mut self, octant: &[i32; 4]) {
+        let [xx, xy, yx, yy] = *octant;
+        let radius_squared = i64::from(self.max_radius) * i64::from(self.max_radius);
+        let max_angle = self.extent(xx, yx);
+        let max_distance = self.extent(xy, yy).min(self.max_radius);
+
+        let mut rows = vec![Row {
+            distance: 1,
+            slope_high: 1.0,
+            slope_low: 0.0,
+        }];
+        while let Some(Row {
+            distance,
+            mut slope_high,
+            slope_low,
+        }) = rows.pop()
+        {
+            if slope_high < slope_low || distance > max_distance {
+                continue;
+            }
+
+            let mut previous_blocked = false;
+            for angle in (0..=distance.min(max_angle)).rev() {
+                let (a, d) = (angle as f32, distance as f32);
+                let tile_slope_high = (a + 0.5) / (d - 0.5);
+                let tile_slope_low = (a - 0.5) / (d + 0.5);
+                let previous_tile_slope_low = (a + 0.5) / (d + 0.5);
+                if tile_slope_low > slope_high {
+                    continue;
+                }
+                if tile_slope_high < slope_low {
+                    break;
+                }
+
+                let position = self.origin
+                    + Position::new(angle * xx + distance * xy, angle * yx + distance * yy);
+                if !self.map.transparent.in_bounds(position) {
+                    continue;
+                }
+                let transparent = self.map.transparent[position];
+                if i64::from(angle).pow(2) + i64::from(distance).pow(2) <= radius_squared
+                    && (self.light_walls || transparent)
+                {
+                    self.map.in_fov[position] = true;
+                }
+
+                if previous_blocked && transparent {
+                    slope_high = previous_tile_slope_low;
+                }
+                if !previous_blocked && !transparent {
+                    rows.push(Row {
+                        distance: distance + 1,
+                        slope_high,
+                        slope_low: tile_slope_high,
+                    });
+                }
+                previous_blocked = !transparent;
+            }
+            if !previous_blocked {
+                rows.push(Row {
+                    distance: distance + 1,
+                    slope_high,
+                    slope_low,
+                });
+            }
+        }
+    }
+
+    // How many cells there are between the origin and the edge of the map in the direction
+    // `(dx, dy)`, one of which is zero.
+    fn extent(&self, dx: i32, dy: i32) -> i32 {
+        let size = self.map.size();
+        match (dx, dy) {
+            (1, _) => size.width as i32 - self.origin.x - 1,
+            (-1, _) => self.origin.x,
+            (_, 1) => size.height as i32 - self.origin.y - 1,
+            _ => self.origin.y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{map_from, visible};
+    use super::super::{FovAlgorithm, FovMap};
+    use crate::{Position, USize};
+
+    #[test]
+    fn pillar_shadow() {
+        let mut map = map_from(&[
+            "...........",
+            "...........",
+            "...........",
+            ".....#.....",
+            "...........",
+            "...........",
+        ]);
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Shadowcasting);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "***** *****",
+                "***** *****",
+                "***** *****",
+                "***********",
+                "***********",
+                "***********",
+            ]
+        );
+    }
+
+    #[test]
+    fn huge_radius_sees_the_whole_map() {
+        let mut map = map_from(&[".........."; 10]);
+        map.compute_fov(
+            Position::new(3, 4),
+            50_000,
+            true,
+            FovAlgorithm::Shadowcasting,
+        );
+        assert!(map.fov().cells().iter().all(|&visible| visible));
+    }
+
+    #[test]
+    fn through_a_door() {
+        let mut map = map_from(&[
+            "###########",
+            "#.........#",
+            "#...#.....#",
+            "#.........#",
+            "#####.#####",
+            "#.........#",
+            "###########",
+        ]);
+        map.compute_fov(Position::new(5, 5), 0, true, FovAlgorithm::Shadowcasting);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "    ****   ",
+                "    ****   ",
+                "    ***    ",
+                "    ***    ",
+                "***********",
+                "***********",
+                "***********",
+            ]
+        );
+        map.compute_fov(Position::new(5, 5), 0, false, FovAlgorithm::Shadowcasting);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "           ",
+                "    ****   ",
+                "     **    ",
+                "    ***    ",
+                "     *     ",
+                " ********* ",
+                "           ",
+            ]
+        );
+        map.compute_fov(Position::new(5, 5), 3, true, FovAlgorithm::Shadowcasting);
+        assert_eq!(
+            visible(&map),
+            vec![
+                "           ",
+                "           ",
+                "     *     ",
+                "    ***    ",
+                "   *****   ",
+                "  *******  ",
+                "   *****   ",
+            ]
+        );
+    }
+
+    #[test]
+    fn long_open_corridor() {
+        let mut map = FovMap::new(USize::new(300_000, 1));
+        map.clear(true, true);
+        map.compute_fov(Position::new(0, 0), 0, true, FovAlgorithm::Shadowcasting);
+        assert!(map.fov().cells().iter().all(|&visible| visible));
+        map.compute_fov(
+            Position::new(150_000, 0),
+            u32::MAX,
+            true,
+            FovAlgorithm::Shadowcasting,
+        );
+        assert!(map.fov().cells().iter().all(|&visible| visible));
+    }
+}
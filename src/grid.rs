@@ -0,0 +1,451 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Generic 2D grids.
+//!
+//! This module provides `Grid<T>`, a rectangular container of cells that can be indexed using
+//! the crate's coordinate types. It's a good fit for tile maps and other per-cell data.
+
+use crate::{Error, Position, Transform2, UPosition, USize};
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+use std::ops::{Index, IndexMut};
+
+/// A rectangular grid of cells of type `T`, stored in row-major order.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "GridData<T>")
+)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+// `Grid` as it comes out of the deserializer, before its size is validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct GridData<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+#[cfg(feature = "serialization")]
+impl<T> TryFrom<GridData<T>> for Grid<T> {
+    type Error = Error;
+
+    fn try_from(data: GridData<T>) -> Result<Self, Self::Error> {
+        Self::try_new_with_values(data.width, data.height, data.cells)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Returns a new grid with the given width and height, where every cell is set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Sets every cell in the grid to `value`.
+    pub fn fill(&mut self, value: T) {
+        for cell in &mut self.cells {
+            *cell = value.clone();
+        }
+    }
+
+    /// Returns a copy of the grid with its contents rotated and/or mirrored according to
+    /// `transform`. The translation part of the transform is ignored; the returned grid always
+    /// starts at the origin.
+    pub fn transformed(&self, transform: &Transform2) -> Self {
+        let (width, height, cells) = transform.reorient_cells(self.width, self.height, &self.cells);
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+}
+
+impl<T: Default> Grid<T> {
+    /// Returns a new grid with the given width and height, where every cell is set to
+    /// `T::default()`.
+    pub fn new_default(width: usize, height: usize) -> Self {
+        Self::new_with(width, height, |_| T::default())
+    }
+}
+
+impl<T> Grid<T> {
+    /// Returns a new grid with the given width and height, where every cell is set to the value
+    /// returned by `f` when it is called with the cell's position.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::grid::Grid;
+    /// # use doryen_extra::Position;
+    /// let grid = Grid::new_with(3, 2, |p| p.x + p.y * 10);
+    /// assert_eq!(grid[Position::new(2, 1)], 12);
+    /// ```
+    pub fn new_with<F: FnMut(Position) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(Position::new(x as i32, y as i32)));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Returns a new grid with the given width and height, using the given cells, which must be
+    /// in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `cells` is not `width * height`.
+    pub fn new_with_values(width: usize, height: usize, cells: Vec<T>) -> Self {
+        Self::try_new_with_values(width, height, cells).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns a new grid with the given width and height, using the given cells, which must be
+    /// in row-major order, or an error if the length of `cells` is not `width * height`, or if
+    /// `width * height` doesn't fit in a `usize`.
+    pub fn try_new_with_values(width: usize, height: usize, cells: Vec<T>) -> Result<Self, Error> {
+        let len = width
+            .checked_mul(height)
+            .ok_or(Error::OutOfRange("the size of the grid"))?;
+        if cells.len() != len {
+            return Err(Error::LengthMismatch {
+                expected: len,
+                actual: cells.len(),
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Returns the width of the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the size of the grid.
+    pub fn size(&self) -> USize {
+        USize::new(self.width as u32, self.height as u32)
+    }
+
+    /// Returns the cells of the grid, in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Returns the cells of the grid, in row-major order.
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Consumes the grid and returns its cells, in row-major order.
+    pub fn into_cells(self) -> Vec<T> {
+        self.cells
+    }
+
+    /// Returns whether the given position lies within the grid.
+    pub fn in_bounds(&self, position: Position) -> bool {
+        self.index_of(position).is_some()
+    }
+
+    /// Returns a reference to the cell at the given position, or `None` if the position is outside
+    /// the grid.
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index_of(position).map(|i| &self.cells[i])
+    }
+
+    /// Returns a mutable reference to the cell at the given position, or `None` if the position is
+    /// outside the grid.
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index_of(position).map(move |i| &mut self.cells[i])
+    }
+
+    /// Returns a new grid of the same size, where every cell is the result of calling `f` on the
+    /// corresponding cell of this grid.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Returns an iterator over the cells of the grid, in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// Returns a mutable iterator over the cells of the grid, in row-major order.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Returns an iterator over the cells of the grid along with their positions, in row-major
+    /// order.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Position, &T)> {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (Position::new((i % width) as i32, (i / width) as i32), cell))
+    }
+
+    /// Returns an iterator over the rows of the grid, from top to bottom.
+    pub fn rows(&self) -> std::slice::ChunksExact<'_, T> {
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    /// Returns a mutable iterator over the rows of the grid, from top to bottom.
+    pub fn rows_mut(&mut self) -> std::slice::ChunksExactMut<'_, T> {
+        self.cells.chunks_exact_mut(self.width.max(1))
+    }
+
+    fn index_of(&self, position: Position) -> Option<usize> {
+        cell_index(self.width, self.height, position)
+    }
+
+    fn index_of_or_panic(&self, position: Position) -> usize {
+        self.index_of(position).unwrap_or_else(|| {
+            panic!(
+                "position {} is outside the grid of size {}x{}",
+                position, self.width, self.height
+            )
+        })
+    }
+}
+
+// Returns the index of `position` in a row-major buffer of the given size, or `None` if it's
+// outside of it.
+pub(crate) fn cell_index(width: usize, height: usize, position: Position) -> Option<usize> {
+    if position.x < 0
+        || position.y < 0
+        || position.x as usize >= width
+        || position.y as usize >= height
+    {
+        None
+    } else {
+        Some(position.x as usize + position.y as usize * width)
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &Self::Output {
+        &self.cells[self.index_of_or_panic(position)]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut Self::Output {
+        let index = self.index_of_or_panic(position);
+        &mut self.cells[index]
+    }
+}
+
+impl<T> Index<UPosition> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: UPosition) -> &Self::Output {
+        assert!(
+            (position.x as usize) < self.width && (position.y as usize) < self.height,
+            "position {} is outside the grid of size {}x{}",
+            position,
+            self.width,
+            self.height
+        );
+
+        &self.cells[position.x as usize + position.y as usize * self.width]
+    }
+}
+
+impl<T> IndexMut<UPosition> for Grid<T> {
+    fn index_mut(&mut self, position: UPosition) -> &mut Self::Output {
+        assert!(
+            (position.x as usize) < self.width && (position.y as usize) < self.height,
+            "position {} is outside the grid of size {}x{}",
+            position,
+            self.width,
+            self.height
+        );
+
+        &mut self.cells[position.x as usize + position.y as usize * self.width]
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Grid<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rotation;
+
+    #[test]
+    fn transformed() {
+        let grid = Grid::new_with(3, 2, |p| p.x + p.y * 3);
+        let rotated = grid.transformed(&Transform2::rotation(Rotation::Cw270));
+        assert_eq!(rotated.size(), USize::new(2, 3));
+        assert_eq!(rotated.cells(), &[2, 5, 1, 4, 0, 3]);
+        assert_eq!(
+            rotated.transformed(&Transform2::rotation(Rotation::Cw90)),
+            grid
+        );
+    }
+
+    #[test]
+    fn indexing() {
+        let mut grid = Grid::new(4, 3, 0);
+        grid[Position::new(1, 2)] = 5;
+        grid[UPosition::new(3, 0)] = 7;
+
+        assert_eq!(grid.get(Position::new(1, 2)), Some(&5));
+        assert_eq!(grid[UPosition::new(1, 2)], 5);
+        assert_eq!(grid[Position::new(3, 0)], 7);
+        assert_eq!(grid.cells()[3], 7);
+        assert_eq!(grid.get(Position::new(4, 0)), None);
+        assert_eq!(grid.get(Position::new(0, -1)), None);
+        assert!(grid.get_mut(Position::new(0, 3)).is_none());
+
+        *grid.get_mut(Position::new(0, 0)).unwrap() = 1;
+        assert_eq!(grid[Position::ORIGIN], 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds_panics() {
+        let grid = Grid::new(4, 3, 0);
+        let _ = grid[Position::new(-1, 0)];
+    }
+
+    #[test]
+    fn fill_map_and_rows() {
+        let mut grid = Grid::new_with(3, 2, |p| p.x + p.y * 3);
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows, vec![&[0, 1, 2][..], &[3, 4, 5][..]]);
+
+        let doubled = grid.map(|&v| v * 2);
+        assert_eq!(doubled.cells(), &[0, 2, 4, 6, 8, 10]);
+        assert_eq!(doubled.size(), USize::new(3, 2));
+
+        let (position, value) = grid.enumerate().nth(4).unwrap();
+        assert_eq!(position, Position::new(1, 1));
+        assert_eq!(*value, 4);
+
+        for row in grid.rows_mut() {
+            row[0] = -1;
+        }
+        assert_eq!(grid.cells(), &[-1, 1, 2, -1, 4, 5]);
+
+        grid.fill(9);
+        assert!(grid.iter().all(|&v| v == 9));
+
+        let empty: Grid<u8> = Grid::new_default(0, 0);
+        assert_eq!(empty.rows().count(), 0);
+    }
+
+    #[test]
+    fn values_must_match_the_size() {
+        assert!(Grid::try_new_with_values(2, 2, vec![0; 4]).is_ok());
+        assert_eq!(
+            Grid::try_new_with_values(2, 2, vec![0; 3]),
+            Err(Error::LengthMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            Grid::<u8>::try_new_with_values(usize::MAX, 2, Vec::new()),
+            Err(Error::OutOfRange("the size of the grid"))
+        );
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_inconsistent_grids() {
+        let grid = Grid::new_with(3, 2, |p| p.x + p.y * 3);
+        let json = serde_json::to_value(&grid).unwrap();
+        assert_eq!(
+            serde_json::from_value::<Grid<i32>>(json.clone()).unwrap(),
+            grid
+        );
+
+        let mut short = json.clone();
+        short["cells"] = serde_json::json!([0, 1, 2]);
+        assert!(serde_json::from_value::<Grid<i32>>(short).is_err());
+
+        let mut overflowing = json;
+        overflowing["width"] = usize::MAX.into();
+        overflowing["cells"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<Grid<i32>>(overflowing).is_err());
+    }
+}
@@ -35,15 +35,54 @@
 //!
 //! This module provides a way to create a 2D grid of float values using various algorithms.
 
+mod blur;
+mod chunked;
+mod colorize;
+mod contours;
+mod erosion;
+mod flow;
+mod masks;
+mod precise;
+mod regions;
+mod rivers;
+mod stamps;
+mod surface;
+mod terrace;
+mod view;
+
+pub use blur::EdgeMode;
+pub use chunked::{ChunkGenerator, ChunkedHeightMap, FbmChunkGenerator};
+pub use colorize::HeightMapColors;
+pub use erosion::HydraulicErosion;
+pub use flow::FlowMap;
+pub use precise::HeightMap64;
+pub use regions::{Region, Regions};
+pub use view::{BlendMode, HeightMapView, HeightMapViewMut};
+
+use crate::fov::{CellKind, FovMap};
+use crate::grid::Grid;
+use crate::math;
 use crate::noise::algorithms::Algorithm as NoiseAlgorithm;
 use crate::noise::Noise;
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::random::{Random, Rng};
-use crate::{FPosition, Position, UPosition};
+use crate::{Direction, Error, FPosition, Neighbors, Position, Transform2, UPosition, USize};
 use ilyvion_util::non_nan::NonNan;
 use impl_ops::*;
 use std::ops::{self, AddAssign, MulAssign};
 
+// The neighbors of a cell, in the order the libtcod algorithms visit them.
+const NEIGHBOR_DIRECTIONS: [Direction; 8] = [
+    Direction::NorthWest,
+    Direction::North,
+    Direction::NorthEast,
+    Direction::West,
+    Direction::East,
+    Direction::SouthWest,
+    Direction::South,
+    Direction::SouthEast,
+];
+
 /// A struct representing a height map.
 #[derive(Clone, Debug)]
 #[cfg_attr(
@@ -64,13 +103,21 @@ impl HeightMap {
     ///
     /// If the `width` or the `height` is 0.
     pub fn new(width: usize, height: usize) -> Self {
-        assert!(width > 0 && height > 0);
+        Self::try_new(width, height).unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        Self {
+    /// Returns a new height map with the given width and height, or an error if the `width` or
+    /// the `height` is 0. Initially, all the values of the height map are `0.0`.
+    pub fn try_new(width: usize, height: usize) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::EmptySize);
+        }
+
+        Ok(Self {
             width,
             height,
             values: vec![0.0; width * height],
-        }
+        })
     }
 
     /// Returns a new height map with the given width and height, and a set of values.
@@ -80,14 +127,28 @@ impl HeightMap {
     /// * If the `width` or the `height` is 0.
     /// * If the length of `values` is not `width * height`.
     pub fn new_with_values(width: usize, height: usize, values: &[f32]) -> Self {
-        assert!(width > 0 && height > 0);
-        assert_eq!(values.len(), width * height);
+        Self::try_new_with_values(width, height, values).unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        Self {
+    /// Returns a new height map with the given width and height, and a set of values, or an
+    /// error if the `width` or the `height` is 0 or if the length of `values` is not
+    /// `width * height`.
+    pub fn try_new_with_values(width: usize, height: usize, values: &[f32]) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::EmptySize);
+        }
+        if values.len() != width * height {
+            return Err(Error::LengthMismatch {
+                expected: width * height,
+                actual: values.len(),
+            });
+        }
+
+        Ok(Self {
             width,
             height,
             values: values.to_vec(),
-        }
+        })
     }
 
     /// Returns the width of the height map.
@@ -110,6 +171,128 @@ impl HeightMap {
         &mut self.values
     }
 
+    /// Returns an iterator over the values of the height map, in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, f32> {
+        self.values.iter()
+    }
+
+    /// Returns a mutable iterator over the values of the height map, in row-major order.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, f32> {
+        self.values.iter_mut()
+    }
+
+    /// Returns an iterator over the positions and values of the cells of the height map, in
+    /// row-major order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(2, 2, &[0.0, 1.0, 2.0, 3.0]);
+    /// let highest = height_map
+    ///     .enumerate_cells()
+    ///     .fold((UPosition::new(0, 0), f32::MIN), |a, b| if b.1 > a.1 { b } else { a });
+    /// assert_eq!(highest, (UPosition::new(1, 1), 3.0));
+    /// ```
+    pub fn enumerate_cells(&self) -> impl Iterator<Item = (UPosition, f32)> + '_ {
+        let width = self.width.max(1);
+        self.values.iter().enumerate().map(move |(i, &value)| {
+            (
+                UPosition::new((i % width) as u32, (i / width) as u32),
+                value,
+            )
+        })
+    }
+
+    /// Returns a grid of the same size as the height map, where every cell is set to what `f`
+    /// returns for the height of the corresponding cell of the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Position;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(2, 1, &[0.2, 0.7]);
+    /// let land = height_map.to_grid(|height| height > 0.5);
+    /// assert_eq!(land[Position::new(0, 0)], false);
+    /// assert_eq!(land[Position::new(1, 0)], true);
+    /// ```
+    pub fn to_grid<T, F: FnMut(f32) -> T>(&self, mut f: F) -> Grid<T> {
+        Grid::new_with_values(
+            self.width,
+            self.height,
+            self.values.iter().map(|&value| f(value)).collect(),
+        )
+    }
+
+    /// Returns a field of view map of the same size as the height map, where the properties of
+    /// every cell are those of the kind of cell `f` returns for the height of the corresponding
+    /// cell of the height map. The map can also be used for pathfinding, with
+    /// `Dijkstra::new_from_map`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Position;
+    /// # use doryen_extra::fov::CellKind;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(3, 1, &[0.1, 0.5, 0.9]);
+    /// let map = height_map.to_map(|height| match height {
+    ///     h if h < 0.3 => CellKind::Obstacle,
+    ///     h if h < 0.8 => CellKind::Floor,
+    ///     _ => CellKind::Wall,
+    /// });
+    /// assert!(map.is_transparent(Position::new(0, 0)) && !map.is_walkable(Position::new(0, 0)));
+    /// assert!(map.is_walkable(Position::new(1, 0)));
+    /// assert!(!map.is_transparent(Position::new(2, 0)));
+    /// ```
+    pub fn to_map<F: FnMut(f32) -> CellKind>(&self, f: F) -> FovMap {
+        let kinds = self.to_grid(f);
+        let mut map = FovMap::new(self.size());
+        for (position, &kind) in kinds.enumerate() {
+            map.set_cell_kind(position, kind);
+        }
+
+        map
+    }
+
+    /// Returns a copy of the height map with its values rotated and/or mirrored according to
+    /// `transform`. The translation part of the transform is ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rotation, Transform2};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// // 1 2 3
+    /// // 4 5 6
+    /// let height_map = HeightMap::new_with_values(3, 2, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    ///
+    /// let rotated = height_map.transformed(&Transform2::rotation(Rotation::Cw90));
+    /// assert_eq!((rotated.width(), rotated.height()), (2, 3));
+    /// assert_eq!(rotated.values(), &[4.0, 1.0, 5.0, 2.0, 6.0, 3.0]);
+    ///
+    /// let upside_down = height_map.transformed(&Transform2::rotation(Rotation::Cw180));
+    /// assert_eq!(upside_down.values(), &[6.0, 5.0, 4.0, 3.0, 2.0, 1.0]);
+    ///
+    /// // The translation is ignored.
+    /// let mirror = Transform2::new(Position::new(7, 7), Rotation::None, true);
+    /// let mirrored = height_map.transformed(&mirror);
+    /// assert_eq!((mirrored.width(), mirrored.height()), (3, 2));
+    /// assert_eq!(mirrored.values(), &[3.0, 2.0, 1.0, 6.0, 5.0, 4.0]);
+    ///
+    /// let back = rotated.transformed(&Transform2::rotation(Rotation::Cw270));
+    /// assert_eq!((back.width(), back.height()), (3, 2));
+    /// assert_eq!(back.values(), height_map.values());
+    /// ```
+    pub fn transformed(&self, transform: &Transform2) -> Self {
+        let (width, height, values) =
+            transform.reorient_cells(self.width, self.height, &self.values);
+
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
     /// Returns the value of the height map at the given position.
     ///
     /// # Panics
@@ -125,7 +308,58 @@ impl HeightMap {
     ///
     /// If the position is outside the range of the height map.
     pub fn set_value(&mut self, position: UPosition, value: f32) {
-        self.values[position.x as usize + position.y as usize * self.width] = value;
+        *self.get_value_mut(position.x as usize, position.y as usize) = value;
+    }
+
+    /// Returns the value of the height map at the given position, or `None` if the position is
+    /// outside the range of the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(2, 1, &[0.5, 1.0]);
+    /// assert_eq!(height_map.get(UPosition::new(1, 0)), Some(1.0));
+    /// assert_eq!(height_map.get(UPosition::new(2, 0)), None);
+    /// ```
+    pub fn get(&self, position: UPosition) -> Option<f32> {
+        self.index_of(position).map(|index| self.values[index])
+    }
+
+    /// Returns a mutable reference to the value of the height map at the given position, or
+    /// `None` if the position is outside the range of the height map.
+    pub fn get_mut(&mut self, position: UPosition) -> Option<&mut f32> {
+        self.index_of(position)
+            .map(move |index| &mut self.values[index])
+    }
+
+    /// Like `value`, but returns an error instead of panicking if the position is outside the
+    /// range of the height map.
+    pub fn try_value(&self, position: UPosition) -> Result<f32, Error> {
+        self.get(position).ok_or(Error::OutOfRange("position"))
+    }
+
+    /// Like `set_value`, but returns an error instead of panicking if the position is outside the
+    /// range of the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Error, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(2, 2);
+    /// assert_eq!(height_map.try_set_value(UPosition::new(1, 1), 0.5), Ok(()));
+    /// assert_eq!(
+    ///     height_map.try_set_value(UPosition::new(0, 2), 0.5),
+    ///     Err(Error::OutOfRange("position"))
+    /// );
+    /// ```
+    pub fn try_set_value(&mut self, position: UPosition, value: f32) -> Result<(), Error> {
+        let cell = self
+            .get_mut(position)
+            .ok_or(Error::OutOfRange("position"))?;
+        *cell = value;
+
+        Ok(())
     }
 
     /// Interpolates the value of the height map at the given position.
@@ -157,26 +391,24 @@ impl HeightMap {
     ///
     /// If the position is outside the range of the height map.
     pub fn slope(&self, position: UPosition) -> f32 {
-        const DIX: [i32; 8] = [-1, 0, 1, -1, 1, -1, 0, 1];
-        const DIY: [i32; 8] = [-1, -1, -1, 0, 0, 1, 1, 1];
-
         let mut min_dy = 0.0;
         let mut max_dy = 0.0;
         let v = self.value(position);
-        for (nx, ny) in Iterator::zip(DIX.iter(), DIY.iter())
-            .map(|(&dx, &dy)| (position.x as i32 + dx, position.y as i32 + dy))
+        for Position { x: nx, y: ny } in Neighbors::new(
+            Position::new(position.x as i32, position.y as i32),
+            &NEIGHBOR_DIRECTIONS,
+        )
+        .within_size(self.size())
         {
-            if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny <= self.height as i32 {
-                let n_slope = self.get_value(nx as usize, ny as usize) - v;
-                if n_slope > max_dy {
-                    max_dy = n_slope;
-                } else if n_slope < min_dy {
-                    min_dy = n_slope;
-                }
+            let n_slope = self.get_value(nx as usize, ny as usize) - v;
+            if n_slope > max_dy {
+                max_dy = n_slope;
+            } else if n_slope < min_dy {
+                min_dy = n_slope;
             }
         }
 
-        (max_dy + min_dy).atan2(1.0)
+        math::atan2(max_dy + min_dy, 1.0)
     }
 
     /// Calculates the normal at the given position.
@@ -185,10 +417,8 @@ impl HeightMap {
     ///
     /// If the position is outside the range of the height map.
     pub fn normal(&self, position: FPosition, water_level: f32) -> [f32; 3] {
-        let mut n = [0.0, 0.0, 1.0];
-
         if position.x >= self.width as f32 - 1.0 || position.y >= self.height as f32 - 1.0 {
-            return n;
+            return [0.0, 0.0, 1.0];
         }
 
         let mut h0 = self.interpolated_value(position);
@@ -206,12 +436,16 @@ impl HeightMap {
             hy = water_level;
         }
 
-        n[0] = 255.0 * (h0 - hx);
-        n[1] = 255.0 * (h0 - hy);
-        n[2] = 16.0;
+        Self::normalized_normal(h0 - hx, h0 - hy)
+    }
+
+    // Returns the normal of a surface that drops by `dx` going one cell right and by `dy`
+    // going one cell down.
+    fn normalized_normal(dx: f32, dy: f32) -> [f32; 3] {
+        let mut n = [255.0 * dx, 255.0 * dy, 16.0];
 
         // normalize
-        let inv_len = 1.0 / (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        let inv_len = 1.0 / math::sqrt(n[0] * n[0] + n[1] * n[1] + n[2] * n[2]);
         n[0] *= inv_len;
         n[1] *= inv_len;
         n[2] *= inv_len;
@@ -251,7 +485,7 @@ impl HeightMap {
     pub fn min_max(&self) -> MinMax {
         self.values
             .iter()
-            .fold((std::f32::MAX, std::f32::MIN), |(min, max), &v| {
+            .fold((f32::MAX, f32::MIN), |(min, max), &v| {
                 (min.min(v), max.max(v))
             })
             .into()
@@ -265,9 +499,7 @@ impl HeightMap {
     pub fn clamp(&mut self, min: f32, max: f32) {
         assert!(min <= max);
 
-        self.values
-            .iter_mut()
-            .for_each(|v| *v = v.max(min).min(max))
+        self.update_values(|_, v| *v = v.max(min).min(max));
     }
 
     /// Normalizes the values in the height map by scaling them proportionally such that the map's
@@ -303,7 +535,7 @@ impl HeightMap {
         };
 
         // normalize
-        self.values.iter_mut().for_each(|v| {
+        self.update_values(|_, v| {
             *v = (f64::from(min) + (f64::from(*v) - f64::from(cur_min)) * inv_max) as f32
         });
     }
@@ -316,21 +548,30 @@ impl HeightMap {
     }
 
     /// Linearly interpolate two height maps together.
+    ///
+    /// # Panics
+    ///
+    /// If the height maps don't have the same size, or if `coefficient` isn't between 0 and 1.
     pub fn lerp(&self, other: &Self, coefficient: f32) -> Self {
-        assert_eq!(self.width, other.width);
-        assert_eq!(self.height, other.height);
-        assert!(coefficient >= 0.0 && coefficient <= 1.0);
+        self.try_lerp(other, coefficient)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Linearly interpolate two height maps together, or returns an error if the height maps
+    /// don't have the same size, or if `coefficient` isn't between 0 and 1.
+    pub fn try_lerp(&self, other: &Self, coefficient: f32) -> Result<Self, Error> {
+        self.check_same_size(other)?;
+        if !(0.0..=1.0).contains(&coefficient) {
+            return Err(Error::OutOfRange("coefficient"));
+        }
 
         let mut result = Self::new(self.width, self.height);
-        for (v, (&sv, &ov)) in result
-            .values
-            .iter_mut()
-            .zip(Iterator::zip(self.values.iter(), other.values.iter()))
-        {
+        result.update_values(|position, v| {
+            let (sv, ov) = (self.value(position), other.value(position));
             *v = sv + (ov - sv) * coefficient;
-        }
+        });
 
-        result
+        Ok(result)
     }
 
     /// Adds a hill (a half spheroid) at the given position, with a `radius` and a `height`.
@@ -439,9 +680,6 @@ impl HeightMap {
         aggregation_coefficient: f32,
         random: &mut Random<A>,
     ) {
-        const DX: [i32; 8] = [-1, 0, 1, -1, 1, -1, 0, 1];
-        const DY: [i32; 8] = [-1, -1, -1, 0, 0, 1, 1, 1];
-
         while drops > 0 {
             let mut cur_x = random.get_i32(0, (self.width - 1) as i32);
             let mut cur_y = random.get_i32(0, (self.height - 1) as i32);
@@ -453,16 +691,15 @@ impl HeightMap {
                 let mut next_y = 0;
                 let v = self.get_value(cur_x as usize, cur_y as usize);
                 slope = 0.0;
-                for (nx, ny) in
-                    Iterator::zip(DX.iter(), DY.iter()).map(|(&dx, &dy)| (cur_x + dx, cur_y + dy))
+                for Position { x: nx, y: ny } in
+                    Neighbors::new(Position::new(cur_x, cur_y), &NEIGHBOR_DIRECTIONS)
+                        .within_size(self.size())
                 {
-                    if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny < self.height as i32 {
-                        let n_slope = v - self.get_value(nx as usize, ny as usize);
-                        if n_slope > slope {
-                            slope = n_slope;
-                            next_x = nx;
-                            next_y = ny;
-                        }
+                    let n_slope = v - self.get_value(nx as usize, ny as usize);
+                    if n_slope > slope {
+                        slope = n_slope;
+                        next_x = nx;
+                        next_y = ny;
                     }
                 }
                 if slope > 0.0 {
@@ -560,7 +797,7 @@ impl HeightMap {
                 for coefficient in coefficients {
                     let min_dist_point = points.iter_mut().min_by_key(|p| p.dist).unwrap();
                     self.values[offset] += coefficient * *min_dist_point.dist;
-                    min_dist_point.dist = std::f32::MAX.into();
+                    min_dist_point.dist = f32::MAX.into();
                 }
                 offset += self.width;
             }
@@ -674,25 +911,90 @@ impl HeightMap {
         delta: f32,
         scale: f32,
     ) {
-        assert_eq!(
-            noise.dimensions, 2,
-            "add_fbm requires a 2D noise generator."
-        );
+        self.try_add_fbm(noise, octaves, coordinates, delta, scale)
+            .unwrap_or_else(|error| panic!("add_fbm requires a 2D noise generator: {}", error));
+    }
 
-        let x_coefficient = coordinates.mul_x / self.width as f32;
-        let y_coefficient = coordinates.mul_y / self.height as f32;
+    /// Like `add_fbm`, but returns an error instead of panicking if the `noise` provided
+    /// isn't 2D.
+    pub fn try_add_fbm<A: NoiseAlgorithm>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        delta: f32,
+        scale: f32,
+    ) -> Result<(), Error> {
+        check_noise_dimensions(noise, 2)?;
 
-        for x in 0..self.width {
-            let mut f = [0.0; 2];
-            let mut offset = x;
-            f[0] = (x as f32 + coordinates.add_x) * x_coefficient;
-            for y in 0..self.height {
-                f[1] = (y as f32 + coordinates.add_y) * y_coefficient;
-                let value = delta + noise.fbm(&f, octaves) * scale;
-                self.values[offset] += value;
-                offset += self.width;
-            }
-        }
+        let sample = self.fbm_sampler(noise, octaves, coordinates, None);
+        self.update_values_serially(|position, v| *v += delta + sample(position) * scale);
+
+        Ok(())
+    }
+
+    /// Add an FBM to the height map, sampled from a 3D noise generator at the given `time`.
+    ///
+    /// The noise coordinates for map cell `(x, y)` are the same as for `add_fbm`, with `time` as
+    /// the third coordinate, so calling this once per frame with a slowly increasing `time` gives
+    /// smoothly animated layers, e.g. for water or clouds.
+    ///
+    /// # Panics
+    ///
+    /// If the `noise` provided isn't 3D.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::{FbmCoordinateParameters, HeightMap};
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::noise::algorithms::Simplex;
+    /// let mut noise = Noise::<Simplex>::from_seed(1, 3);
+    /// let coordinates = FbmCoordinateParameters {
+    ///     mul_x: 4.0,
+    ///     mul_y: 4.0,
+    ///     add_x: 0.0,
+    ///     add_y: 0.0,
+    /// };
+    /// let mut frames = Vec::new();
+    /// for frame in 0..3 {
+    ///     let mut clouds = HeightMap::new(32, 32);
+    ///     clouds.add_fbm_3d(&mut noise, 4.0, coordinates, frame as f32 * 0.05, 0.0, 1.0);
+    ///     frames.push(clouds);
+    /// }
+    /// let cell = UPosition::new(5, 5);
+    /// assert_ne!(frames[0].value(cell), frames[1].value(cell));
+    /// ```
+    pub fn add_fbm_3d<A: NoiseAlgorithm>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        time: f32,
+        delta: f32,
+        scale: f32,
+    ) {
+        self.try_add_fbm_3d(noise, octaves, coordinates, time, delta, scale)
+            .unwrap_or_else(|error| panic!("add_fbm_3d requires a 3D noise generator: {}", error));
+    }
+
+    /// Like `add_fbm_3d`, but returns an error instead of panicking if the `noise` provided
+    /// isn't 3D.
+    pub fn try_add_fbm_3d<A: NoiseAlgorithm>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        time: f32,
+        delta: f32,
+        scale: f32,
+    ) -> Result<(), Error> {
+        check_noise_dimensions(noise, 3)?;
+
+        let sample = self.fbm_sampler(noise, octaves, coordinates, Some(time));
+        self.update_values_serially(|position, v| *v += delta + sample(position) * scale);
+
+        Ok(())
     }
 
     /// Scale the map by an FBM.
@@ -714,24 +1016,238 @@ impl HeightMap {
         delta: f32,
         scale: f32,
     ) {
-        assert_eq!(
-            noise.dimensions, 2,
-            "scale_fbm requires a 2D noise generator."
-        );
+        self.try_scale_fbm(noise, coordinates, octaves, delta, scale)
+            .unwrap_or_else(|error| panic!("scale_fbm requires a 2D noise generator: {}", error));
+    }
 
+    /// Like `scale_fbm`, but returns an error instead of panicking if the `noise` generator
+    /// provided isn't 2D.
+    pub fn try_scale_fbm<A: NoiseAlgorithm>(
+        &mut self,
+        noise: &mut Noise<A>,
+        coordinates: FbmCoordinateParameters,
+        octaves: f32,
+        delta: f32,
+        scale: f32,
+    ) -> Result<(), Error> {
+        check_noise_dimensions(noise, 2)?;
+
+        let sample = self.fbm_sampler(noise, octaves, coordinates, None);
+        self.update_values_serially(|position, v| *v *= delta + sample(position) * scale);
+
+        Ok(())
+    }
+
+    /// Like `add_fbm`, but the rows of the map are computed in parallel. The result is identical
+    /// to the one of `add_fbm`.
+    ///
+    /// # Panics
+    ///
+    /// If the `noise` provided isn't 2D.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::heightmap::{FbmCoordinateParameters, HeightMap};
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::noise::algorithms::Simplex;
+    /// let mut noise = Noise::<Simplex>::from_seed(1, 2);
+    /// let coordinates = FbmCoordinateParameters {
+    ///     mul_x: 4.0,
+    ///     mul_y: 4.0,
+    ///     add_x: 0.0,
+    ///     add_y: 0.0,
+    /// };
+    /// let mut serial = HeightMap::new(40, 30);
+    /// serial.add_fbm(&mut noise, 4.0, coordinates, 0.5, 2.0);
+    /// let mut parallel = HeightMap::new(40, 30);
+    /// parallel.par_add_fbm(&mut noise, 4.0, coordinates, 0.5, 2.0);
+    /// assert_eq!(serial.values(), parallel.values());
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn par_add_fbm<A: NoiseAlgorithm + Sync>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        delta: f32,
+        scale: f32,
+    ) {
+        self.try_par_add_fbm(noise, octaves, coordinates, delta, scale)
+            .unwrap_or_else(|error| panic!("par_add_fbm requires a 2D noise generator: {}", error));
+    }
+
+    /// Like `par_add_fbm`, but returns an error instead of panicking if the `noise` provided
+    /// isn't 2D.
+    #[cfg(feature = "parallel")]
+    pub fn try_par_add_fbm<A: NoiseAlgorithm + Sync>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        delta: f32,
+        scale: f32,
+    ) -> Result<(), Error> {
+        check_noise_dimensions(noise, 2)?;
+
+        let sample = self.fbm_sampler(noise, octaves, coordinates, None);
+        self.update_values(|position, v| *v += delta + sample(position) * scale);
+
+        Ok(())
+    }
+
+    /// Like `add_fbm_3d`, but the rows of the map are computed in parallel. The result is
+    /// identical to the one of `add_fbm_3d`.
+    ///
+    /// # Panics
+    ///
+    /// If the `noise` provided isn't 3D.
+    #[cfg(feature = "parallel")]
+    pub fn par_add_fbm_3d<A: NoiseAlgorithm + Sync>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        time: f32,
+        delta: f32,
+        scale: f32,
+    ) {
+        self.try_par_add_fbm_3d(noise, octaves, coordinates, time, delta, scale)
+            .unwrap_or_else(|error| {
+                panic!("par_add_fbm_3d requires a 3D noise generator: {}", error)
+            });
+    }
+
+    /// Like `par_add_fbm_3d`, but returns an error instead of panicking if the `noise` provided
+    /// isn't 3D.
+    #[cfg(feature = "parallel")]
+    pub fn try_par_add_fbm_3d<A: NoiseAlgorithm + Sync>(
+        &mut self,
+        noise: &mut Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        time: f32,
+        delta: f32,
+        scale: f32,
+    ) -> Result<(), Error> {
+        check_noise_dimensions(noise, 3)?;
+
+        let sample = self.fbm_sampler(noise, octaves, coordinates, Some(time));
+        self.update_values(|position, v| *v += delta + sample(position) * scale);
+
+        Ok(())
+    }
+
+    /// Like `scale_fbm`, but the rows of the map are computed in parallel. The result is
+    /// identical to the one of `scale_fbm`.
+    ///
+    /// # Panics
+    ///
+    /// If the `noise` generator provided isn't 2D.
+    #[cfg(feature = "parallel")]
+    pub fn par_scale_fbm<A: NoiseAlgorithm + Sync>(
+        &mut self,
+        noise: &mut Noise<A>,
+        coordinates: FbmCoordinateParameters,
+        octaves: f32,
+        delta: f32,
+        scale: f32,
+    ) {
+        self.try_par_scale_fbm(noise, coordinates, octaves, delta, scale)
+            .unwrap_or_else(|error| {
+                panic!("par_scale_fbm requires a 2D noise generator: {}", error)
+            });
+    }
+
+    /// Like `par_scale_fbm`, but returns an error instead of panicking if the `noise` generator
+    /// provided isn't 2D.
+    #[cfg(feature = "parallel")]
+    pub fn try_par_scale_fbm<A: NoiseAlgorithm + Sync>(
+        &mut self,
+        noise: &mut Noise<A>,
+        coordinates: FbmCoordinateParameters,
+        octaves: f32,
+        delta: f32,
+        scale: f32,
+    ) -> Result<(), Error> {
+        check_noise_dimensions(noise, 2)?;
+
+        let sample = self.fbm_sampler(noise, octaves, coordinates, None);
+        self.update_values(|position, v| *v *= delta + sample(position) * scale);
+
+        Ok(())
+    }
+
+    // Returns the FBM value for each map cell, as used by `add_fbm` and friends, with `time` as
+    // the third coordinate if there is one.
+    fn fbm_sampler<'a, A: NoiseAlgorithm>(
+        &self,
+        noise: &'a Noise<A>,
+        octaves: f32,
+        coordinates: FbmCoordinateParameters,
+        time: Option<f32>,
+    ) -> impl Fn(UPosition) -> f32 + 'a {
         let x_coefficient = coordinates.mul_x / self.width as f32;
         let y_coefficient = coordinates.mul_y / self.height as f32;
+        let dimensions = if time.is_some() { 3 } else { 2 };
+
+        move |position| {
+            let f = [
+                (position.x as f32 + coordinates.add_x) * x_coefficient,
+                (position.y as f32 + coordinates.add_y) * y_coefficient,
+                time.unwrap_or(0.0),
+            ];
+            noise.fbm(&f[..dimensions], octaves)
+        }
+    }
 
-        for x in 0..self.width {
-            let mut f = [0.0; 2];
-            let mut offset = x;
-            f[0] = (x as f32 + coordinates.add_x) * x_coefficient;
-            for y in 0..self.height {
-                f[1] = (y as f32 + coordinates.add_y) * y_coefficient;
-                let value = delta + noise.fbm(&f, octaves) * scale;
-                self.values[offset] *= value;
-                offset += self.width;
+    // Updates every value of the height map with `f`, which is given the position of the value.
+    // With the `parallel` feature enabled, the rows are updated in parallel; since `f` only
+    // depends on its position and value, the result is identical to the serial one.
+    fn update_values<F>(&mut self, f: F)
+    where
+        F: Fn(UPosition, &mut f32) + Send + Sync,
+    {
+        update_rows(&mut self.values, self.width, |y, row| {
+            for (x, v) in row.iter_mut().enumerate() {
+                f(UPosition::new(x as u32, y as u32), v);
             }
+        });
+    }
+
+    // Like `update_values`, but always on the calling thread, for the closures that can't be
+    // shared between threads.
+    fn update_values_serially<F>(&mut self, f: F)
+    where
+        F: Fn(UPosition, &mut f32),
+    {
+        for (y, row) in self.values.chunks_mut(self.width).enumerate() {
+            for (x, v) in row.iter_mut().enumerate() {
+                f(UPosition::new(x as u32, y as u32), v);
+            }
+        }
+    }
+
+    fn check_same_size(&self, other: &Self) -> Result<(), Error> {
+        if self.width == other.width && self.height == other.height {
+            Ok(())
+        } else {
+            Err(Error::SizeMismatch {
+                expected: self.size(),
+                actual: other.size(),
+            })
+        }
+    }
+
+    fn size(&self) -> USize {
+        USize::new(self.width as u32, self.height as u32)
+    }
+
+    fn index_of(&self, position: UPosition) -> Option<usize> {
+        let (x, y) = (position.x as usize, position.y as usize);
+        if x < self.width && y < self.height {
+            Some(x + y * self.width)
+        } else {
+            None
         }
     }
 
@@ -795,6 +1311,43 @@ impl HeightMap {
     }
 }
 
+// Calls `f` with the index and the contents of each row of `values`, which has rows of `width`
+// elements. With the `parallel` feature enabled, the rows are visited in parallel, so `f` must
+// only touch the row it's given.
+fn update_rows<T, F>(values: &mut [T], width: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        values
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| f(y, row));
+    }
+    #[cfg(not(feature = "parallel"))]
+    values
+        .chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| f(y, row));
+}
+
+fn check_noise_dimensions<A: NoiseAlgorithm>(
+    noise: &Noise<A>,
+    dimensions: usize,
+) -> Result<(), Error> {
+    if noise.dimensions == dimensions {
+        Ok(())
+    } else {
+        Err(Error::DimensionMismatch {
+            expected: dimensions,
+            actual: noise.dimensions,
+        })
+    }
+}
+
 impl_op_ex!(+ |a: &HeightMap, b: &HeightMap| -> HeightMap {
     assert_eq!(a.width, b.width);
     assert_eq!(a.height, b.height);
@@ -831,6 +1384,57 @@ impl MulAssign<f32> for HeightMap {
     }
 }
 
+impl ops::Index<UPosition> for HeightMap {
+    type Output = f32;
+
+    /// Returns the value of the height map at the given position.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(3, 3);
+    /// height_map[UPosition::new(1, 2)] = 0.5;
+    /// height_map[UPosition::new(1, 2)] += 0.25;
+    /// assert_eq!(height_map[UPosition::new(1, 2)], 0.75);
+    /// ```
+    fn index(&self, position: UPosition) -> &Self::Output {
+        let (x, y) = (position.x as usize, position.y as usize);
+        assert!(x < self.width);
+        assert!(y < self.height);
+
+        &self.values[x + y * self.width]
+    }
+}
+
+impl ops::IndexMut<UPosition> for HeightMap {
+    fn index_mut(&mut self, position: UPosition) -> &mut Self::Output {
+        self.get_value_mut(position.x as usize, position.y as usize)
+    }
+}
+
+impl<'a> IntoIterator for &'a HeightMap {
+    type Item = &'a f32;
+    type IntoIter = std::slice::Iter<'a, f32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut HeightMap {
+    type Item = &'a mut f32;
+    type IntoIter = std::slice::IterMut<'a, f32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 /// Represents a result of minimum and maximum values in a height map.
 #[derive(Copy, Clone, Debug)]
 pub struct MinMax {
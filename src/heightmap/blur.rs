@@ -0,0 +1,220 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::math;
+
+/// How `HeightMap::smooth_box` and `HeightMap::smooth_gaussian` treat the cells beyond the
+/// edges of the map.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum EdgeMode {
+    /// Leaves the cells beyond the edges out, averaging only the cells inside the map, like
+    /// `HeightMap::kernel_transform` does.
+    Ignore,
+    /// Treats the cells beyond the edges as copies of the nearest edge cell.
+    Clamp,
+    /// Treats the map as repeating, so the cells beyond one edge are those along the opposite
+    /// edge. Use this for maps that tile.
+    Wrap,
+    /// Treats the cells beyond the edges as a mirror image of the cells inside them.
+    Mirror,
+}
+
+impl Default for EdgeMode {
+    /// Returns `EdgeMode::Ignore`.
+    fn default() -> Self {
+        Self::Ignore
+    }
+}
+
+impl EdgeMode {
+    // Maps `index` onto `0..length`, or returns `None` if the cell should be left out.
+    fn resolve(self, index: isize, length: usize) -> Option<usize> {
+        let length = length as isize;
+        if (0..length).contains(&index) {
+            return Some(index as usize);
+        }
+        match self {
+            Self::Ignore => None,
+            Self::Clamp => Some(index.max(0).min(length - 1) as usize),
+            Self::Wrap => Some(index.rem_euclid(length) as usize),
+            Self::Mirror => {
+                let period = 2 * length;
+                let index = index.rem_euclid(period);
+                Some(if index < length {
+                    index
+                } else {
+                    period - 1 - index
+                } as usize)
+            }
+        }
+    }
+}
+
+impl HeightMap {
+    /// Smooths the height map by replacing every value with the average of the values within
+    /// `radius` cells of it, horizontally and vertically, i.e. a box blur. A `radius` of 0
+    /// leaves the map as it is. Radii larger than the map's width and height are treated as if
+    /// they were that large.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::heightmap::{EdgeMode, HeightMap};
+    /// let mut height_map = HeightMap::new_with_values(4, 1, &[0.0, 3.0, 0.0, 3.0]);
+    /// height_map.smooth_box(1, EdgeMode::Wrap);
+    /// assert_eq!(height_map.values(), &[2.0, 1.0, 2.0, 1.0]);
+    /// ```
+    pub fn smooth_box(&mut self, radius: u32, edges: EdgeMode) {
+        let radius = (radius as usize).min(self.max_kernel_radius());
+        let kernel = vec![1.0; 2 * radius + 1];
+        self.convolve_separable(&kernel, edges);
+    }
+
+    /// Smooths the height map with a gaussian blur whose standard deviation is `sigma` cells.
+    /// A gaussian blur looks softer and more natural than a box blur with the same reach. A
+    /// `sigma` of 0.0 or less leaves the map as it is. The blur never reaches further than the
+    /// map's width and height, however large `sigma` is.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::{EdgeMode, HeightMap};
+    /// let mut height_map = HeightMap::new(9, 9);
+    /// height_map.set_value(UPosition::new(4, 4), 1.0);
+    /// height_map.smooth_gaussian(1.0, EdgeMode::Clamp);
+    /// let center = height_map.value(UPosition::new(4, 4));
+    /// assert!(center < 1.0 && center > height_map.value(UPosition::new(4, 5)));
+    /// ```
+    pub fn smooth_gaussian(&mut self, sigma: f32, edges: EdgeMode) {
+        if sigma <= 0.0 {
+            return;
+        }
+        let radius = (sigma * 3.0).ceil().min(self.max_kernel_radius() as f32) as i32;
+        let kernel: Vec<f32> = (-radius..=radius)
+            .map(|offset| math::exp(-((offset * offset) as f32) / (2.0 * sigma * sigma)))
+            .collect();
+        self.convolve_separable(&kernel, edges);
+    }
+
+    // How far a kernel may reach; the cells further away than this are all beyond the edges, so
+    // this keeps huge radii from allocating huge kernels for no benefit.
+    fn max_kernel_radius(&self) -> usize {
+        self.width.max(self.height)
+    }
+
+    // Convolves the map with `kernel` horizontally and then vertically.
+    fn convolve_separable(&mut self, kernel: &[f32], edges: EdgeMode) {
+        let (width, height) = (self.width, self.height);
+
+        let mut horizontal = vec![0.0; self.values.len()];
+        for y in 0..height {
+            let row = &self.values[y * width..(y + 1) * width];
+            for x in 0..width {
+                horizontal[x + y * width] = convolve_at(kernel, edges, x, width, |i| row[i]);
+            }
+        }
+        for x in 0..width {
+            for y in 0..height {
+                self.values[x + y * width] =
+                    convolve_at(kernel, edges, y, height, |i| horizontal[x + i * width]);
+            }
+        }
+    }
+}
+
+// Returns the weighted average of the cells around `index` on a line of `length` cells, where
+// `sample` returns the value of a cell, normalizing the weights of the cells that were used.
+fn convolve_at<F: Fn(usize) -> f32>(
+    kernel: &[f32],
+    edges: EdgeMode,
+    index: usize,
+    length: usize,
+    sample: F,
+) -> f32 {
+    let radius = (kernel.len() / 2) as isize;
+    let mut sum = 0.0;
+    let mut total_weight = 0.0;
+    for (k, &weight) in kernel.iter().enumerate() {
+        if let Some(i) = edges.resolve(index as isize + k as isize - radius, length) {
+            sum += weight * sample(i);
+            total_weight += weight;
+        }
+    }
+
+    sum / total_weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UPosition;
+
+    #[test]
+    fn edge_modes_resolve_cells_beyond_the_edges() {
+        assert_eq!(EdgeMode::Ignore.resolve(-1, 4), None);
+        assert_eq!(EdgeMode::Clamp.resolve(-2, 4), Some(0));
+        assert_eq!(EdgeMode::Clamp.resolve(5, 4), Some(3));
+        assert_eq!(EdgeMode::Wrap.resolve(-1, 4), Some(3));
+        assert_eq!(EdgeMode::Wrap.resolve(5, 4), Some(1));
+        assert_eq!(EdgeMode::Mirror.resolve(-1, 4), Some(0));
+        assert_eq!(EdgeMode::Mirror.resolve(5, 4), Some(2));
+    }
+
+    #[test]
+    fn blurring_preserves_the_total_when_wrapping() {
+        let mut height_map = HeightMap::new(8, 6);
+        height_map.set_value(UPosition::new(0, 0), 10.0);
+        height_map.set_value(UPosition::new(5, 3), 4.0);
+        height_map.smooth_gaussian(1.5, EdgeMode::Wrap);
+
+        let total: f32 = height_map.values().iter().sum();
+        assert!((total - 14.0).abs() < 1e-4);
+        assert!(height_map.value(UPosition::new(7, 5)) > 0.0);
+    }
+
+    #[test]
+    fn huge_radii_are_capped() {
+        let mut boxed = HeightMap::new_with_values(4, 1, &[0.0, 4.0, 0.0, 0.0]);
+        boxed.smooth_box(u32::MAX, EdgeMode::Ignore);
+        assert_eq!(boxed.values(), &[1.0; 4]);
+
+        let mut gaussian = HeightMap::new_with_values(4, 1, &[0.0, 4.0, 0.0, 0.0]);
+        gaussian.smooth_gaussian(f32::MAX, EdgeMode::Ignore);
+        for &value in gaussian.values() {
+            assert!((value - 1.0).abs() < 1e-5, "{}", value);
+        }
+    }
+}
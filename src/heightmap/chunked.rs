@@ -0,0 +1,389 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use super::HeightMap;
+use crate::noise::algorithms::Simplex;
+use crate::noise::{Noise, DEFAULT_LACUNARITY};
+use crate::random::Random;
+use crate::util::FloorRem;
+use crate::{Error, FPosition, Position, Rectangle, UPosition};
+use std::collections::HashMap;
+
+/// Generates the chunks of a `ChunkedHeightMap`.
+///
+/// A generator must be deterministic: generating the same chunk twice must give the same values,
+/// since chunks may be evicted and generated again later. To make the terrain seamless, the
+/// values should be derived from world coordinates rather than from coordinates within the chunk.
+pub trait ChunkGenerator {
+    /// Generates the chunk at the given chunk coordinates, which covers the world cells from
+    /// `chunk * chunk_size` up to, but not including, `(chunk + 1) * chunk_size`. The returned
+    /// height map must be `chunk_size` by `chunk_size` cells.
+    fn generate(&self, chunk: Position, chunk_size: usize) -> HeightMap;
+}
+
+/// Any function taking the chunk coordinates and the chunk size can be used as a chunk
+/// generator.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, UPosition};
+/// # use doryen_extra::heightmap::{ChunkedHeightMap, HeightMap};
+/// // A world that slopes gently towards the east.
+/// let mut world = ChunkedHeightMap::new(16, 4, |chunk: Position, chunk_size: usize| {
+///     let mut heightmap = HeightMap::new(chunk_size, chunk_size);
+///     for y in 0..chunk_size {
+///         for x in 0..chunk_size {
+///             let world_x = chunk.x * chunk_size as i32 + x as i32;
+///             heightmap[UPosition::new(x as u32, y as u32)] = world_x as f32;
+///         }
+///     }
+///     heightmap
+/// });
+/// assert_eq!(world.value(Position::new(-20, 3)), -20.0);
+/// ```
+impl<F: Fn(Position, usize) -> HeightMap> ChunkGenerator for F {
+    fn generate(&self, chunk: Position, chunk_size: usize) -> HeightMap {
+        self(chunk, chunk_size)
+    }
+}
+
+/// A chunk generator that fills chunks with simplex FBM noise sampled at world coordinates,
+/// making the terrain continuous across chunk boundaries.
+///
+/// The value of world cell `(x, y)` is `delta + fbm(x / scale, y / scale) * amplitude`.
+#[derive(Debug)]
+pub struct FbmChunkGenerator {
+    noise: Noise<Simplex>,
+    /// The number of cells per noise unit; larger values give larger features
+    pub scale: f32,
+    /// The number of octaves of the FBM
+    pub octaves: f32,
+    /// The value added to the noise
+    pub delta: f32,
+    /// The value the noise is multiplied by
+    pub amplitude: f32,
+}
+
+impl FbmChunkGenerator {
+    /// Returns a generator whose noise is seeded with `seed`, with a `delta` of 0 and an
+    /// `amplitude` of 1.
+    pub fn new(seed: u32, scale: f32, octaves: f32) -> Self {
+        Self {
+            noise: Noise::new_simplex(2, DEFAULT_LACUNARITY, Random::new_mt_from_seed(seed)),
+            scale,
+            octaves,
+            delta: 0.0,
+            amplitude: 1.0,
+        }
+    }
+}
+
+impl ChunkGenerator for FbmChunkGenerator {
+    fn generate(&self, chunk: Position, chunk_size: usize) -> HeightMap {
+        let origin = chunk * chunk_size as i32;
+        let mut heightmap = HeightMap::new(chunk_size, chunk_size);
+        heightmap.update_values(|position, v| {
+            let x = (origin.x + position.x as i32) as f32 / self.scale;
+            let y = (origin.y + position.y as i32) as f32 / self.scale;
+            *v = self.delta + self.noise.fbm(&[x, y], self.octaves) * self.amplitude;
+        });
+
+        heightmap
+    }
+}
+
+/// An effectively infinite height map, split into square chunks that are generated on demand
+/// and cached.
+///
+/// At most `capacity` chunks are kept in memory; when another one is needed, the least recently
+/// used chunk is evicted, and generated again if it's needed later. Since chunk generators are
+/// deterministic, this is invisible, apart from the time it takes.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{FPosition, Position};
+/// # use doryen_extra::heightmap::{ChunkedHeightMap, FbmChunkGenerator};
+/// let mut world = ChunkedHeightMap::new(32, 16, FbmChunkGenerator::new(42, 20.0, 4.0));
+/// let here = world.value(Position::new(-1_000, 5_000));
+/// assert!(here >= -1.0 && here <= 1.0);
+///
+/// // Sampling between cells blends across chunk boundaries.
+/// let left = world.value(Position::new(31, 0));
+/// let right = world.value(Position::new(32, 0));
+/// let between = world.interpolated_value(FPosition::new(31.5, 0.0));
+/// assert!((between - (left + right) / 2.0).abs() < 1e-6);
+/// assert_eq!(world.loaded_chunks(), 3);
+/// ```
+#[derive(Debug)]
+pub struct ChunkedHeightMap<G: ChunkGenerator> {
+    generator: G,
+    chunk_size: usize,
+    capacity: usize,
+    chunks: HashMap<Position, (HeightMap, u64)>,
+    tick: u64,
+}
+
+impl<G: ChunkGenerator> ChunkedHeightMap<G> {
+    /// Returns a chunked height map with chunks of `chunk_size` by `chunk_size` cells, keeping at
+    /// most `capacity` chunks in memory at once.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` or `capacity` is 0.
+    pub fn new(chunk_size: usize, capacity: usize, generator: G) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+        assert!(capacity > 0, "capacity must be greater than 0");
+
+        Self {
+            generator,
+            chunk_size,
+            capacity,
+            chunks: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the width and height of the chunks.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the maximum number of chunks kept in memory.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the generator.
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// Returns the number of chunks currently in memory.
+    pub fn loaded_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns whether the chunk at the given chunk coordinates is in memory.
+    pub fn is_loaded(&self, chunk: Position) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// Returns the coordinates of the chunk containing the world position, and the position
+    /// within that chunk.
+    pub fn chunk_of(&self, position: Position) -> (Position, UPosition) {
+        let size = self.chunk_size as i32;
+        let chunk = Position::new(
+            (position.x - position.x.floor_modulo(size)) / size,
+            (position.y - position.y.floor_modulo(size)) / size,
+        );
+        let local = UPosition::new(
+            position.x.floor_modulo(size) as u32,
+            position.y.floor_modulo(size) as u32,
+        );
+
+        (chunk, local)
+    }
+
+    /// Returns the chunk at the given chunk coordinates, generating it if it isn't in memory.
+    pub fn chunk(&mut self, chunk: Position) -> &HeightMap {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.chunks.get_mut(&chunk) {
+            entry.1 = tick;
+        } else {
+            if self.chunks.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            let heightmap = self.generator.generate(chunk, self.chunk_size);
+            assert!(
+                heightmap.width() == self.chunk_size && heightmap.height() == self.chunk_size,
+                "the chunk generator returned a chunk of the wrong size"
+            );
+            self.chunks.insert(chunk, (heightmap, tick));
+        }
+
+        &self.chunks[&chunk].0
+    }
+
+    /// Returns the chunk at the given chunk coordinates if it's in memory, without generating it
+    /// or counting it as used.
+    pub fn loaded_chunk(&self, chunk: Position) -> Option<&HeightMap> {
+        self.chunks.get(&chunk).map(|(heightmap, _)| heightmap)
+    }
+
+    /// Returns the value at the given world position, generating its chunk if needed.
+    pub fn value(&mut self, position: Position) -> f32 {
+        let (chunk, local) = self.chunk_of(position);
+        self.chunk(chunk).value(local)
+    }
+
+    /// Returns the value at the given world position if its chunk is in memory.
+    pub fn loaded_value(&self, position: Position) -> Option<f32> {
+        let (chunk, local) = self.chunk_of(position);
+        self.loaded_chunk(chunk)
+            .map(|heightmap| heightmap.value(local))
+    }
+
+    /// Returns the value at the given world position, bilinearly interpolated between the four
+    /// surrounding cells, which may belong to different chunks.
+    pub fn interpolated_value(&mut self, position: FPosition) -> f32 {
+        let x = position.x.floor();
+        let y = position.y.floor();
+        let dx = position.x - x;
+        let dy = position.y - y;
+        let cell = Position::new(x as i32, y as i32);
+
+        let c1 = self.value(cell);
+        let c2 = self.value(cell + (1, 0));
+        let c3 = self.value(cell + (0, 1));
+        let c4 = self.value(cell + (1, 1));
+        let top = (1.0 - dx) * c1 + dx * c2;
+        let bottom = (1.0 - dx) * c3 + dx * c4;
+
+        (1.0 - dy) * top + dy * bottom
+    }
+
+    /// Returns a height map with a copy of the values within `area` of the world, which is
+    /// treated as a block of `width` by `height` cells and may span any number of chunks,
+    /// generating them as needed. This makes it possible to use the rest of the height map
+    /// toolkit on any part of the world.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle, UPosition};
+    /// # use doryen_extra::heightmap::{ChunkedHeightMap, FbmChunkGenerator};
+    /// let mut world = ChunkedHeightMap::new(16, 8, FbmChunkGenerator::new(42, 20.0, 4.0));
+    /// let area = world.region(Rectangle::new_from_raw(-10, -10, 20, 20));
+    /// assert_eq!(area.value(UPosition::new(10, 10)), world.value(Position::new(0, 0)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the `area`'s width or height is 0.
+    pub fn region(&mut self, area: Rectangle) -> HeightMap {
+        self.try_region(area)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns a height map with a copy of the values within `area` of the world, or an error if
+    /// the `area`'s width or height is 0. See `region` for details.
+    pub fn try_region(&mut self, area: Rectangle) -> Result<HeightMap, Error> {
+        let width = area.size.width as usize;
+        let height = area.size.height as usize;
+        let mut region = HeightMap::try_new(width, height)?;
+        for y in 0..height {
+            for x in 0..width {
+                let position = area.position + (x as i32, y as i32);
+                region[UPosition::new(x as u32, y as u32)] = self.value(position);
+            }
+        }
+
+        Ok(region)
+    }
+
+    /// Removes the chunk at the given chunk coordinates from memory, returning it if it was
+    /// loaded.
+    pub fn unload(&mut self, chunk: Position) -> Option<HeightMap> {
+        self.chunks.remove(&chunk).map(|(heightmap, _)| heightmap)
+    }
+
+    /// Removes every chunk from memory.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .chunks
+            .iter()
+            .min_by_key(|(_, &(_, tick))| tick)
+            .map(|(&chunk, _)| chunk);
+        if let Some(chunk) = oldest {
+            self.chunks.remove(&chunk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_are_seamless_and_evicted() {
+        let mut world = ChunkedHeightMap::new(8, 2, FbmChunkGenerator::new(7, 10.0, 3.0));
+        let generator = FbmChunkGenerator::new(7, 10.0, 3.0);
+        for &(x, y) in &[(0, 0), (7, 7), (8, 0), (-1, -1), (-9, 20), (100, -100)] {
+            let expected = generator
+                .noise
+                .fbm(&[x as f32 / 10.0, y as f32 / 10.0], 3.0);
+            assert_eq!(world.value(Position::new(x, y)), expected);
+        }
+        assert_eq!(world.loaded_chunks(), 2);
+        assert!(world.is_loaded(Position::new(12, -13)));
+        assert!(world.is_loaded(Position::new(-2, 2)));
+        assert_eq!(world.loaded_value(Position::new(0, 0)), None);
+
+        assert_eq!(
+            world.chunk_of(Position::new(-1, 8)),
+            (Position::new(-1, 1), UPosition::new(7, 0))
+        );
+
+        // Touching a chunk makes it the most recently used one.
+        world.chunk(Position::new(12, -13));
+        world.value(Position::new(0, 0));
+        assert!(world.is_loaded(Position::new(12, -13)));
+        assert!(!world.is_loaded(Position::new(-2, 2)));
+
+        assert!(world.unload(Position::new(0, 0)).is_some());
+        world.clear();
+        assert_eq!(world.loaded_chunks(), 0);
+    }
+
+    #[test]
+    fn empty_regions_are_rejected() {
+        let mut world = ChunkedHeightMap::new(8, 2, FbmChunkGenerator::new(7, 10.0, 3.0));
+        assert_eq!(
+            world
+                .try_region(Rectangle::new_from_raw(3, 3, 0, 5))
+                .unwrap_err(),
+            Error::EmptySize
+        );
+        assert_eq!(
+            world
+                .try_region(Rectangle::new_from_raw(-3, 3, 2, 1))
+                .unwrap()
+                .values()
+                .len(),
+            2
+        );
+    }
+}
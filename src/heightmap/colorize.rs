@@ -0,0 +1,212 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::color::Color;
+use crate::heightmap::HeightMap;
+use crate::image::Image;
+use crate::{FPosition, USize};
+
+/// Describes how `HeightMap::to_image` colors a height map.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::color::Color;
+/// # use doryen_extra::heightmap::{HeightMap, HeightMapColors};
+/// # use doryen_extra::FPosition;
+/// let mut height_map = HeightMap::new(32, 32);
+/// height_map.add_hill(FPosition::new(16.0, 16.0), 12.0, 1.0);
+///
+/// let colors = HeightMapColors {
+///     gradient: vec![
+///         (0.0, Color::new(0, 0, 100)),
+///         (0.3, Color::new(60, 60, 255)),
+///         (0.31, Color::new(190, 180, 130)),
+///         (0.6, Color::new(40, 120, 40)),
+///         (1.0, Color::WHITE),
+///     ],
+///     sea_level: Some(0.3),
+///     light: Some([1.0, 1.0, 1.0]),
+/// };
+/// let image = height_map.to_image(&colors);
+/// assert_eq!(image.size().width, 32);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct HeightMapColors {
+    /// The colors of the heights, as pairs of a height and its color, sorted by height. Heights
+    /// between two pairs get a color between theirs, and heights below the first or above the
+    /// last pair get its color.
+    pub gradient: Vec<(f32, Color)>,
+    /// The height of the surface of the water, if there is any. Water is flat, so the cells
+    /// below it aren't shaded.
+    pub sea_level: Option<f32>,
+    /// The direction the light comes from, as an `[x, y, z]` vector pointing towards the light,
+    /// where `z` points up out of the map, or `None` to not shade the map. Slopes facing the
+    /// light get brighter and the ones facing away from it get darker, while flat areas keep
+    /// their color.
+    pub light: Option<[f32; 3]>,
+}
+
+impl Default for HeightMapColors {
+    /// Returns colors going from black at 0.0 to white at 1.0, without water or shading.
+    fn default() -> Self {
+        Self {
+            gradient: vec![(0.0, Color::BLACK), (1.0, Color::WHITE)],
+            sea_level: None,
+            light: None,
+        }
+    }
+}
+
+impl HeightMapColors {
+    /// Returns the color of the given height, without shading.
+    pub fn color(&self, height: f32) -> Color {
+        let gradient = &self.gradient;
+        match gradient.iter().position(|&(key, _)| key > height) {
+            None => gradient.last().map_or(Color::BLACK, |&(_, color)| color),
+            Some(0) => gradient[0].1,
+            Some(index) => {
+                let (low, low_color) = gradient[index - 1];
+                let (high, high_color) = gradient[index];
+                low_color.lerp_rgb(high_color, (height - low) / (high - low))
+            }
+        }
+    }
+}
+
+impl HeightMap {
+    /// Converts the height map into an image of the same size, coloring every cell by its
+    /// height, and shading it by the direction its slope faces, as described by `colors`.
+    pub fn to_image(&self, colors: &HeightMapColors) -> Image {
+        let light = colors.light.and_then(|[x, y, z]| {
+            let length = crate::math::sqrt(x * x + y * y + z * z);
+            if length > 0.0 && z > 0.0 {
+                Some([x / length, y / length, z / length])
+            } else {
+                None
+            }
+        });
+        let sea_level = colors.sea_level.unwrap_or(f32::NEG_INFINITY);
+
+        let mut pixels = Vec::with_capacity(self.values().len());
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let height = self.values()[x + y * self.width()];
+                let color = colors.color(height);
+                pixels.push(match light {
+                    Some(light) if height >= sea_level => {
+                        let normal = self.normal(FPosition::new(x as f32, y as f32), sea_level);
+                        let lit =
+                            normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2];
+                        // Relative to a flat cell, whose normal is straight up.
+                        shade(color, (lit / light[2]).clamp(0.0, 2.0))
+                    }
+                    _ => color,
+                });
+            }
+        }
+
+        Image::new_with_pixels(
+            USize::new(self.width() as u32, self.height() as u32),
+            pixels,
+        )
+    }
+}
+
+fn shade(color: Color, factor: f32) -> Color {
+    let channel = |c: u8| (f32::from(c) * factor).clamp(0.0, 255.0) as u8;
+    Color::new_with_alpha(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        color.a,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient() {
+        let colors = HeightMapColors {
+            gradient: vec![(0.0, Color::BLACK), (0.5, Color::RED), (1.0, Color::WHITE)],
+            ..HeightMapColors::default()
+        };
+        assert_eq!(colors.color(-1.0), Color::BLACK);
+        assert_eq!(colors.color(0.25), Color::new(127, 0, 0));
+        assert_eq!(colors.color(0.5), Color::RED);
+        assert_eq!(colors.color(2.0), Color::WHITE);
+        assert_eq!(
+            HeightMapColors {
+                gradient: Vec::new(),
+                ..colors
+            }
+            .color(0.5),
+            Color::BLACK
+        );
+    }
+
+    #[test]
+    fn shading() {
+        // A slope rising towards the east, so facing west, with flat water on its low end.
+        let values: Vec<f32> = (0..8).map(|x| x as f32 * 0.1).cycle().take(64).collect();
+        let height_map = HeightMap::new_with_values(8, 8, &values);
+        let gray = HeightMapColors {
+            gradient: vec![(0.0, Color::new(100, 100, 100))],
+            sea_level: Some(0.25),
+            light: None,
+        };
+        assert!(height_map
+            .to_image(&gray)
+            .pixels()
+            .iter()
+            .all(|&color| color == Color::new(100, 100, 100)));
+
+        let from_east = height_map.to_image(&HeightMapColors {
+            light: Some([1.0, 0.0, 1.0]),
+            ..gray.clone()
+        });
+        let from_west = height_map.to_image(&HeightMapColors {
+            light: Some([-1.0, 0.0, 1.0]),
+            ..gray
+        });
+        let at = |image: &Image, x| image.get_pixel(crate::Position::new(x, 3)).unwrap().r;
+        assert_eq!(at(&from_east, 1), 100);
+        assert_eq!(at(&from_west, 1), 100);
+        assert!(at(&from_east, 4) < 100);
+        assert!(at(&from_west, 4) > 100);
+    }
+}
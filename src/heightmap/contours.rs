@@ -0,0 +1,231 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::FPosition;
+use std::collections::HashMap;
+
+// An edge between two neighboring cells: `Horizontal(x, y)` connects `(x, y)` and `(x + 1, y)`,
+// `Vertical(x, y)` connects `(x, y)` and `(x, y + 1)`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Edge {
+    Horizontal(usize, usize),
+    Vertical(usize, usize),
+}
+
+impl HeightMap {
+    /// Returns the iso-lines of the height map at every one of the given `levels`, i.e. the lines
+    /// separating the cells above a level from those at or below it, found with the marching
+    /// squares algorithm. The lines of the first level come first, then those of the second
+    /// one, and so on; use `contour` to get the lines of a single level.
+    ///
+    /// The cell at `(x, y)` is at the position `(x, y)`, and the lines pass between cells where
+    /// the level would be if the values were interpolated linearly.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(3, 3);
+    /// height_map.set_value(UPosition::new(1, 1), 1.0);
+    /// let lines = height_map.contours(&[0.5, 0.75]);
+    /// assert_eq!(lines.len(), 2);
+    /// // A closed loop around the middle cell, which starts and ends at the same position.
+    /// assert_eq!(lines[0].len(), 5);
+    /// assert_eq!(lines[0].first(), lines[0].last());
+    /// assert!(lines[0].contains(&FPosition::new(1.5, 1.0)));
+    /// assert!(lines[1].contains(&FPosition::new(1.25, 1.0)));
+    /// ```
+    pub fn contours(&self, levels: &[f32]) -> Vec<Vec<FPosition>> {
+        levels
+            .iter()
+            .flat_map(|&level| self.contour(level))
+            .collect()
+    }
+
+    /// Returns the iso-lines of the height map at the given `level`. See `contours`.
+    ///
+    /// Lines that go all the way around an area start and end at the same position; the others
+    /// start and end at the edges of the height map.
+    pub fn contour(&self, level: f32) -> Vec<Vec<FPosition>> {
+        let inside = |x: usize, y: usize| self.get_value(x, y) > level;
+
+        let mut segments = Vec::new();
+        for y in 0..self.height.saturating_sub(1) {
+            for x in 0..self.width.saturating_sub(1) {
+                let case = u8::from(inside(x, y)) << 3
+                    | u8::from(inside(x + 1, y)) << 2
+                    | u8::from(inside(x + 1, y + 1)) << 1
+                    | u8::from(inside(x, y + 1));
+                let top = Edge::Horizontal(x, y);
+                let right = Edge::Vertical(x + 1, y);
+                let bottom = Edge::Horizontal(x, y + 1);
+                let left = Edge::Vertical(x, y);
+
+                // Saddles are resolved by whether the center of the square is inside.
+                let center_inside = || {
+                    (self.get_value(x, y)
+                        + self.get_value(x + 1, y)
+                        + self.get_value(x + 1, y + 1)
+                        + self.get_value(x, y + 1))
+                        / 4.0
+                        > level
+                };
+                match case {
+                    1 | 14 => segments.push((left, bottom)),
+                    2 | 13 => segments.push((bottom, right)),
+                    3 | 12 => segments.push((left, right)),
+                    4 | 11 => segments.push((top, right)),
+                    6 | 9 => segments.push((top, bottom)),
+                    7 | 8 => segments.push((left, top)),
+                    5 if center_inside() => {
+                        segments.push((left, top));
+                        segments.push((bottom, right));
+                    }
+                    10 if !center_inside() => {
+                        segments.push((left, top));
+                        segments.push((bottom, right));
+                    }
+                    5 | 10 => {
+                        segments.push((left, bottom));
+                        segments.push((top, right));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.join_segments(&segments, level)
+    }
+
+    // Joins segments that share an edge into lines.
+    fn join_segments(&self, segments: &[(Edge, Edge)], level: f32) -> Vec<Vec<FPosition>> {
+        let mut by_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+        for (index, &(a, b)) in segments.iter().enumerate() {
+            by_edge.entry(a).or_default().push(index);
+            by_edge.entry(b).or_default().push(index);
+        }
+
+        let mut used = vec![false; segments.len()];
+        let next_from = |edge: Edge, taken: &mut Vec<bool>| {
+            let index = *by_edge.get(&edge)?.iter().find(|&&index| !taken[index])?;
+            taken[index] = true;
+            let (a, b) = segments[index];
+            Some(if a == edge { b } else { a })
+        };
+
+        let mut lines = Vec::new();
+        for (index, &(start, end)) in segments.iter().enumerate() {
+            if used[index] {
+                continue;
+            }
+            used[index] = true;
+
+            let mut forward = vec![start, end];
+            while let Some(edge) = next_from(forward[forward.len() - 1], &mut used) {
+                forward.push(edge);
+            }
+            let mut backward = Vec::new();
+            if forward.first() != forward.last() {
+                let mut current = start;
+                while let Some(edge) = next_from(current, &mut used) {
+                    backward.push(edge);
+                    current = edge;
+                }
+            }
+
+            lines.push(
+                backward
+                    .iter()
+                    .rev()
+                    .chain(&forward)
+                    .map(|&edge| self.edge_crossing(edge, level))
+                    .collect(),
+            );
+        }
+
+        lines
+    }
+
+    // Returns where the value along `edge` crosses `level`.
+    fn edge_crossing(&self, edge: Edge, level: f32) -> FPosition {
+        let ((x1, y1), (x2, y2)) = match edge {
+            Edge::Horizontal(x, y) => ((x, y), (x + 1, y)),
+            Edge::Vertical(x, y) => ((x, y), (x, y + 1)),
+        };
+        let (v1, v2) = (self.get_value(x1, y1), self.get_value(x2, y2));
+        let t = (level - v1) / (v2 - v1);
+
+        FPosition::new(
+            x1 as f32 + (x2 as f32 - x1 as f32) * t,
+            y1 as f32 + (y2 as f32 - y1 as f32) * t,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_and_closed_lines() {
+        #[rustfmt::skip]
+        let values = [
+            0.0, 0.0, 0.0, 0.0, 2.0,
+            0.0, 2.0, 0.0, 0.0, 2.0,
+            0.0, 0.0, 0.0, 0.0, 2.0,
+        ];
+        let height_map = HeightMap::new_with_values(5, 3, &values);
+        let lines = height_map.contour(1.0);
+        assert_eq!(lines.len(), 2);
+
+        let closed = &lines[0];
+        assert_eq!(closed.first(), closed.last());
+        assert_eq!(closed.len(), 5);
+        for point in &closed[..4] {
+            let distance = (point.x - 1.0).abs() + (point.y - 1.0).abs();
+            assert_eq!(distance, 0.5);
+        }
+
+        // A straight line along the right edge, from the top of the map to the bottom.
+        let mut open = lines[1].clone();
+        open.sort_by(|a, b| a.y.partial_cmp(&b.y).unwrap());
+        assert_eq!(
+            open,
+            vec![
+                FPosition::new(3.5, 0.0),
+                FPosition::new(3.5, 1.0),
+                FPosition::new(3.5, 2.0),
+            ]
+        );
+    }
+}
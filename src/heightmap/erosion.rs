@@ -0,0 +1,409 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::{update_rows, HeightMap, NEIGHBOR_DIRECTIONS};
+use crate::math;
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use crate::random::{Random, Rng};
+
+/// The parameters of `HeightMap::hydraulic_erosion`.
+///
+/// The defaults are meant for height maps whose values are between 0.0 and 1.0.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct HydraulicErosion {
+    /// How much a droplet keeps going in the direction it was going in, rather than turning
+    /// downhill, from 0.0 to 1.0.
+    pub inertia: f32,
+    /// How much sediment a droplet can carry, relative to its speed, its amount of water and
+    /// the steepness of the slope it's going down.
+    pub sediment_capacity: f32,
+    /// The least amount of sediment a droplet can carry, which keeps droplets eroding on
+    /// nearly flat ground.
+    pub min_sediment_capacity: f32,
+    /// How much of the sediment a droplet has room for it picks up each step, from 0.0 to 1.0.
+    pub erode_speed: f32,
+    /// How much of the sediment a droplet has too much of it drops each step, from 0.0 to 1.0.
+    pub deposit_speed: f32,
+    /// How much of its water a droplet loses each step, from 0.0 to 1.0.
+    pub evaporate_speed: f32,
+    /// How much a droplet speeds up going downhill and slows down going uphill.
+    pub gravity: f32,
+    /// The radius of the area a droplet erodes around itself. Larger radiuses make smoother
+    /// valleys.
+    pub erosion_radius: u32,
+    /// The most steps a droplet takes before it has evaporated.
+    pub max_lifetime: u32,
+}
+
+impl Default for HydraulicErosion {
+    /// Returns the parameters of Hans Theobald Beyer's "Implementation of a method for
+    /// hydraulic erosion": an inertia of 0.05, a sediment capacity of 4.0 and at least 0.01,
+    /// erode and deposit speeds of 0.3, an evaporate speed of 0.01, a gravity of 4.0, an erosion
+    /// radius of 3 and a lifetime of 30 steps.
+    fn default() -> Self {
+        Self {
+            inertia: 0.05,
+            sediment_capacity: 4.0,
+            min_sediment_capacity: 0.01,
+            erode_speed: 0.3,
+            deposit_speed: 0.3,
+            evaporate_speed: 0.01,
+            gravity: 4.0,
+            erosion_radius: 3,
+            max_lifetime: 30,
+        }
+    }
+}
+
+impl HeightMap {
+    /// Simulates the erosion caused by water flowing over the terrain, carving valleys into
+    /// slopes and filling in the bottoms with sediment. This is a much more thorough version of
+    /// `rain_erosion`.
+    ///
+    /// Each of the `droplets` starts at a random position and runs downhill, speeding up and
+    /// picking up sediment as it goes, and drops sediment when it slows down, goes uphill or
+    /// carries more than it can, until it evaporates or leaves the map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::FPosition;
+    /// # use doryen_extra::heightmap::{HeightMap, HydraulicErosion};
+    /// # use doryen_extra::random::Random;
+    /// let mut height_map = HeightMap::new(64, 64);
+    /// height_map.add_hill(FPosition::new(32.0, 32.0), 30.0, 1.0);
+    /// let mut random = Random::new_mt_from_seed(1);
+    /// height_map.hydraulic_erosion(10_000, &HydraulicErosion::default(), &mut random);
+    /// ```
+    pub fn hydraulic_erosion<A: RandomAlgorithm>(
+        &mut self,
+        droplets: u32,
+        parameters: &HydraulicErosion,
+        random: &mut Random<A>,
+    ) {
+        if self.width < 2 || self.height < 2 {
+            return;
+        }
+
+        // The droplets must start strictly inside the last column and row, so that the cell to
+        // their lower right exists. The start is uniform whatever the distribution of `random`.
+        let (max_x, max_y) = ((self.width - 1) as f32, (self.height - 1) as f32);
+        let below = |limit: f32| f32::from_bits(limit.to_bits() - 1);
+        let brush = erosion_brush(parameters.erosion_radius);
+        for _ in 0..droplets {
+            let mut x = (random.get_uniform_f32() * max_x).min(below(max_x));
+            let mut y = (random.get_uniform_f32() * max_y).min(below(max_y));
+            let (mut direction_x, mut direction_y) = (0.0, 0.0);
+            let mut speed = 1.0;
+            let mut water = 1.0;
+            let mut sediment = 0.0;
+
+            for _ in 0..parameters.max_lifetime {
+                let (cell_x, cell_y) = (x as usize, y as usize);
+                let (u, v) = (x - cell_x as f32, y - cell_y as f32);
+                let (height, gradient_x, gradient_y) = self.height_and_gradient(x, y);
+
+                direction_x =
+                    direction_x * parameters.inertia - gradient_x * (1.0 - parameters.inertia);
+                direction_y =
+                    direction_y * parameters.inertia - gradient_y * (1.0 - parameters.inertia);
+                let length = math::hypot(direction_x, direction_y);
+                if length <= f32::EPSILON {
+                    break;
+                }
+                direction_x /= length;
+                direction_y /= length;
+                x += direction_x;
+                y += direction_y;
+                if x < 0.0
+                    || y < 0.0
+                    || x >= (self.width - 1) as f32
+                    || y >= (self.height - 1) as f32
+                {
+                    break;
+                }
+
+                let delta_height = self.height_and_gradient(x, y).0 - height;
+                let capacity = (-delta_height * speed * water * parameters.sediment_capacity)
+                    .max(parameters.min_sediment_capacity);
+                if sediment > capacity || delta_height > 0.0 {
+                    // Fill in the pit that was just left, or drop the excess sediment, spreading
+                    // it over the corners of the cell the droplet was in.
+                    let amount = if delta_height > 0.0 {
+                        delta_height.min(sediment)
+                    } else {
+                        (sediment - capacity) * parameters.deposit_speed
+                    };
+                    sediment -= amount;
+                    *self.get_value_mut(cell_x, cell_y) += amount * (1.0 - u) * (1.0 - v);
+                    *self.get_value_mut(cell_x + 1, cell_y) += amount * u * (1.0 - v);
+                    *self.get_value_mut(cell_x, cell_y + 1) += amount * (1.0 - u) * v;
+                    *self.get_value_mut(cell_x + 1, cell_y + 1) += amount * u * v;
+                } else {
+                    // Never erode more than the height difference, so as to not dig pits.
+                    let amount =
+                        ((capacity - sediment) * parameters.erode_speed).min(-delta_height);
+                    sediment += amount;
+                    self.erode_around(cell_x, cell_y, amount, &brush);
+                }
+
+                speed = math::sqrt((speed * speed + delta_height * parameters.gravity).max(0.0));
+                water *= 1.0 - parameters.evaporate_speed;
+            }
+        }
+    }
+
+    /// Simulates the erosion caused by loose material sliding down slopes that are too steep for
+    /// it to stay on, like scree piling up at the foot of a cliff.
+    ///
+    /// In each of the `iterations`, every cell that is higher than a neighbor by more than
+    /// `talus_angle` allows, where the slope between two cells is measured like in `slope`,
+    /// sends half of the excess height downhill, shared among its too low neighbors by how much
+    /// lower they are. All the cells are updated at once, so the result doesn't depend on the
+    /// order they're visited in, and the total height of the map never changes. With the
+    /// `parallel` feature enabled, the rows are processed in parallel, with identical results.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(5, 5);
+    /// height_map.set_value(UPosition::new(2, 2), 1.0);
+    /// height_map.thermal_erosion(0.1, 50);
+    /// assert!(height_map.value(UPosition::new(2, 2)) < 0.5);
+    /// ```
+    pub fn thermal_erosion(&mut self, talus_angle: f32, iterations: u32) {
+        let (sin, cos) = math::sin_cos(talus_angle);
+        let talus = sin / cos;
+        let (width, height) = (self.width, self.height);
+        let neighbors = move |x: usize, y: usize| thermal_neighbors(x, y, width, height);
+
+        // Each iteration is done in two passes over the rows, each of which only writes to the
+        // row it's visiting: first every cell works out how much it sends downhill, then every
+        // cell gathers what it receives from its neighbors, always in the same order. This keeps
+        // the result identical whether the rows are visited in parallel or not.
+        let mut outflows = vec![(0.0_f32, 0.0_f32); self.values.len()];
+        let mut next = self.values.clone();
+        for _ in 0..iterations {
+            let values = &self.values;
+            update_rows(&mut outflows, width, |y, row| {
+                for (x, outflow) in row.iter_mut().enumerate() {
+                    let here = values[x + y * width];
+                    let mut total_excess = 0.0;
+                    let mut max_excess = 0.0_f32;
+                    for (neighbor, distance) in neighbors(x, y) {
+                        let excess = here - values[neighbor] - talus * distance;
+                        if excess > 0.0 {
+                            total_excess += excess;
+                            max_excess = max_excess.max(excess);
+                        }
+                    }
+                    *outflow = (max_excess / 2.0, total_excess);
+                }
+            });
+            if outflows.iter().all(|&(moved, _)| moved == 0.0) {
+                break;
+            }
+
+            let outflows = &outflows;
+            update_rows(&mut next, width, |y, row| {
+                for (x, value) in row.iter_mut().enumerate() {
+                    let here = x + y * width;
+                    let mut delta = -outflows[here].0;
+                    for (neighbor, distance) in neighbors(x, y) {
+                        let (moved, total_excess) = outflows[neighbor];
+                        let excess = values[neighbor] - values[here] - talus * distance;
+                        if moved > 0.0 && excess > 0.0 {
+                            delta += moved * excess / total_excess;
+                        }
+                    }
+                    *value = values[here] + delta;
+                }
+            });
+            std::mem::swap(&mut self.values, &mut next);
+        }
+    }
+
+    // Returns the bilinearly interpolated height and its gradient at the given position, which
+    // must be at least one cell away from the right and bottom edges.
+    fn height_and_gradient(&self, x: f32, y: f32) -> (f32, f32, f32) {
+        let (cell_x, cell_y) = (x as usize, y as usize);
+        let (u, v) = (x - cell_x as f32, y - cell_y as f32);
+        let north_west = self.get_value(cell_x, cell_y);
+        let north_east = self.get_value(cell_x + 1, cell_y);
+        let south_west = self.get_value(cell_x, cell_y + 1);
+        let south_east = self.get_value(cell_x + 1, cell_y + 1);
+
+        let gradient_x = (north_east - north_west) * (1.0 - v) + (south_east - south_west) * v;
+        let gradient_y = (south_west - north_west) * (1.0 - u) + (south_east - north_east) * u;
+        let height = north_west * (1.0 - u) * (1.0 - v)
+            + north_east * u * (1.0 - v)
+            + south_west * (1.0 - u) * v
+            + south_east * u * v;
+
+        (height, gradient_x, gradient_y)
+    }
+
+    // Lowers the cells around the given one by a total of `amount`, spread out by the brush.
+    fn erode_around(&mut self, x: usize, y: usize, amount: f32, brush: &[(i32, i32, f32)]) {
+        let in_bounds = |&&(dx, dy, _): &&(i32, i32, f32)| {
+            let (bx, by) = (x as i32 + dx, y as i32 + dy);
+            bx >= 0 && by >= 0 && (bx as usize) < self.width && (by as usize) < self.height
+        };
+        let total_weight: f32 = brush.iter().filter(in_bounds).map(|&(.., w)| w).sum();
+        let cells: Vec<_> = brush.iter().filter(in_bounds).copied().collect();
+        for (dx, dy, weight) in cells {
+            let (bx, by) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+            *self.get_value_mut(bx, by) -= amount * weight / total_weight;
+        }
+    }
+}
+
+// Returns the index of each neighbor of cell `(x, y)` in a map of the given size, along with its
+// distance from the cell, for `thermal_erosion`.
+fn thermal_neighbors(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = (usize, f32)> {
+    NEIGHBOR_DIRECTIONS.iter().filter_map(move |direction| {
+        let offset = direction.offset();
+        let (nx, ny) = (x as i32 + offset.x, y as i32 + offset.y);
+        if nx < 0 || ny < 0 || nx as usize >= width || ny as usize >= height {
+            return None;
+        }
+        let distance = if direction.is_diagonal() {
+            std::f32::consts::SQRT_2
+        } else {
+            1.0
+        };
+        Some((nx as usize + ny as usize * width, distance))
+    })
+}
+
+// Returns the offsets of the cells within `radius` of a cell, along with weights that fall off
+// linearly with the distance.
+fn erosion_brush(radius: u32) -> Vec<(i32, i32, f32)> {
+    let radius = radius.max(1) as i32;
+    let mut brush = Vec::new();
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let distance = math::hypot(dx as f32, dy as f32);
+            if distance < radius as f32 {
+                brush.push((dx, dy, radius as f32 - distance));
+            }
+        }
+    }
+
+    brush
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+    use crate::FPosition;
+
+    fn hill() -> HeightMap {
+        let mut height_map = HeightMap::new(48, 48);
+        height_map.add_hill(FPosition::new(24.0, 24.0), 20.0, 1.0);
+        height_map
+    }
+
+    #[test]
+    fn hydraulic_erosion_moves_material_downhill() {
+        let original = hill();
+        let mut eroded = hill();
+        let parameters = HydraulicErosion::default();
+        eroded.hydraulic_erosion(5000, &parameters, &mut Random::new_mt_from_seed(5));
+
+        let total = |height_map: &HeightMap| height_map.values().iter().sum::<f32>();
+        assert!(eroded.values().iter().all(|value| value.is_finite()));
+        assert!(total(&eroded) <= total(&original) + 1e-3);
+        assert!(eroded.min_max().max < original.min_max().max);
+        assert_ne!(eroded.values(), original.values());
+
+        let mut again = hill();
+        again.hydraulic_erosion(5000, &parameters, &mut Random::new_mt_from_seed(5));
+        assert_eq!(again.values(), eroded.values());
+    }
+
+    #[test]
+    fn hydraulic_erosion_works_with_any_distribution() {
+        for &distribution in &[
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut height_map = HeightMap::new(16, 16);
+            height_map.add_hill(FPosition::new(8.0, 8.0), 6.0, 1.0);
+            let mut random = Random::new_mt_from_seed(3);
+            random.distribution = distribution;
+            height_map.hydraulic_erosion(2000, &HydraulicErosion::default(), &mut random);
+            assert!(height_map.values().iter().all(|value| value.is_finite()));
+        }
+    }
+
+    #[test]
+    fn thermal_erosion_flattens_steep_slopes() {
+        let mut height_map = HeightMap::new(9, 9);
+        for y in 0..9 {
+            for x in 5..9 {
+                height_map.set_value(crate::UPosition::new(x, y), 2.0);
+            }
+        }
+        let total = height_map.values().iter().sum::<f32>();
+        let talus_angle = 0.3;
+        height_map.thermal_erosion(talus_angle, 500);
+
+        assert!((height_map.values().iter().sum::<f32>() - total).abs() < 1e-3);
+        let (sin, cos) = math::sin_cos(talus_angle);
+        for y in 0..9 {
+            for x in 0..8 {
+                let step = height_map.value(crate::UPosition::new(x + 1, y))
+                    - height_map.value(crate::UPosition::new(x, y));
+                assert!(step.abs() <= sin / cos + 0.05);
+            }
+        }
+
+        // Slopes that are gentle enough are left alone.
+        let gentle: Vec<f32> = (0..9).map(|x| x as f32 * 0.1).cycle().take(81).collect();
+        let mut slope = HeightMap::new_with_values(9, 9, &gentle);
+        slope.thermal_erosion(talus_angle, 10);
+        assert_eq!(slope.values(), &gentle[..]);
+    }
+}
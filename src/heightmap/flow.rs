@@ -0,0 +1,238 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::{HeightMap, NEIGHBOR_DIRECTIONS};
+use crate::{Direction, Position, UPosition};
+use ilyvion_util::non_nan::NonNan;
+
+/// The way water flows over a height map, as computed by `HeightMap::flow_map`.
+///
+/// Water flows from each cell to whichever of its eight neighbors it drops the most steeply
+/// toward (the "D8" method), unless none of them are lower, in which case the cell is a sink,
+/// where water pools into a lake. The accumulation of a cell is how many cells, counting itself,
+/// drain through it; the cells with the most accumulation are where rivers would form.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct FlowMap {
+    width: usize,
+    height: usize,
+    directions: Vec<Option<Direction>>,
+    accumulation: Vec<u32>,
+}
+
+impl FlowMap {
+    /// Returns the width of the flow map.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the flow map.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the direction water flows in from the cell at `position`, or `None` if it's a sink.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the flow map.
+    pub fn direction(&self, position: UPosition) -> Option<Direction> {
+        self.directions[self.index(position)]
+    }
+
+    /// Returns the cell water flows into from the cell at `position`, or `None` if it's a sink.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the flow map.
+    pub fn downstream(&self, position: UPosition) -> Option<UPosition> {
+        self.direction(position).map(|direction| {
+            let offset = direction.offset();
+            UPosition::new(
+                (position.x as i32 + offset.x) as u32,
+                (position.y as i32 + offset.y) as u32,
+            )
+        })
+    }
+
+    /// Returns how many cells, counting itself, drain through the cell at `position`.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the flow map.
+    pub fn accumulation(&self, position: UPosition) -> u32 {
+        self.accumulation[self.index(position)]
+    }
+
+    /// Returns whether the cell at `position` is a sink, i.e. has no lower neighbor for water to
+    /// flow into.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the flow map.
+    pub fn is_sink(&self, position: UPosition) -> bool {
+        self.direction(position).is_none()
+    }
+
+    /// Returns all the sinks of the flow map, row by row.
+    pub fn sinks(&self) -> impl Iterator<Item = UPosition> + '_ {
+        let width = self.width;
+        self.directions
+            .iter()
+            .enumerate()
+            .filter(|(_, direction)| direction.is_none())
+            .map(move |(i, _)| UPosition::new((i % width) as u32, (i / width) as u32))
+    }
+
+    /// Returns the cells water flows through from `position` until it reaches a sink, starting
+    /// with `position` itself and ending with the sink.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the flow map.
+    pub fn trace(&self, position: UPosition) -> Vec<UPosition> {
+        let mut course = vec![position];
+        let mut current = position;
+        while let Some(next) = self.downstream(current) {
+            course.push(next);
+            current = next;
+        }
+
+        course
+    }
+
+    fn index(&self, position: UPosition) -> usize {
+        assert!(
+            (position.x as usize) < self.width && (position.y as usize) < self.height,
+            "position is outside the flow map"
+        );
+        position.x as usize + position.y as usize * self.width
+    }
+}
+
+impl HeightMap {
+    /// Computes how water flows over the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Direction, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(3, 1);
+    /// height_map.set_value(UPosition::new(0, 0), 2.0);
+    /// height_map.set_value(UPosition::new(1, 0), 1.0);
+    /// let flow_map = height_map.flow_map();
+    /// assert_eq!(flow_map.direction(UPosition::new(0, 0)), Some(Direction::East));
+    /// assert!(flow_map.is_sink(UPosition::new(2, 0)));
+    /// assert_eq!(flow_map.accumulation(UPosition::new(2, 0)), 3);
+    /// ```
+    pub fn flow_map(&self) -> FlowMap {
+        let mut directions = Vec::with_capacity(self.values.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = self.get_value(x, y);
+                let mut steepest = 0.0;
+                let mut direction = None;
+                for &neighbor_direction in &NEIGHBOR_DIRECTIONS {
+                    let offset = neighbor_direction.offset();
+                    let neighbor = Position::new(x as i32 + offset.x, y as i32 + offset.y);
+                    if neighbor.x < 0
+                        || neighbor.y < 0
+                        || neighbor.x as usize >= self.width
+                        || neighbor.y as usize >= self.height
+                    {
+                        continue;
+                    }
+                    let mut drop = value - self.get_value(neighbor.x as usize, neighbor.y as usize);
+                    if neighbor_direction.is_diagonal() {
+                        drop /= std::f32::consts::SQRT_2;
+                    }
+                    if drop > steepest {
+                        steepest = drop;
+                        direction = Some(neighbor_direction);
+                    }
+                }
+                directions.push(direction);
+            }
+        }
+
+        // Water only ever flows downhill, so going from the highest cell to the lowest, every
+        // cell has received everything that drains into it before it passes it on.
+        let mut order: Vec<usize> = (0..self.values.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(NonNan::new(self.values[i])));
+        let mut accumulation = vec![1; self.values.len()];
+        for i in order {
+            if let Some(direction) = directions[i] {
+                let offset = direction.offset();
+                let x = (i % self.width) as i32 + offset.x;
+                let y = (i / self.width) as i32 + offset.y;
+                accumulation[x as usize + y as usize * self.width] += accumulation[i];
+            }
+        }
+
+        FlowMap {
+            width: self.width,
+            height: self.height,
+            directions,
+            accumulation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn everything_drains_into_the_pit() {
+        let values: Vec<f32> = (0..81)
+            .map(|i| crate::math::hypot((i % 9 - 4) as f32, (i / 9 - 4) as f32))
+            .collect();
+        let height_map = HeightMap::new_with_values(9, 9, &values);
+        let flow_map = height_map.flow_map();
+
+        assert_eq!(
+            flow_map.sinks().collect::<Vec<_>>(),
+            vec![UPosition::new(4, 4)]
+        );
+        assert_eq!(flow_map.accumulation(UPosition::new(4, 4)), 81);
+        assert_eq!(flow_map.accumulation(UPosition::new(0, 0)), 1);
+        let course = flow_map.trace(UPosition::new(0, 0));
+        assert_eq!(course.first(), Some(&UPosition::new(0, 0)));
+        assert_eq!(course.last(), Some(&UPosition::new(4, 4)));
+        for pair in course.windows(2) {
+            assert!(height_map.value(pair[1]) < height_map.value(pair[0]));
+        }
+    }
+}
@@ -0,0 +1,149 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::math;
+use crate::UPosition;
+
+impl HeightMap {
+    /// Multiplies the value of every cell by what `mask` returns for its position.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new_with_values(2, 1, &[1.0, 1.0]);
+    /// height_map.apply_mask(|position| if position.x == 0 { 0.5 } else { 2.0 });
+    /// assert_eq!(height_map.values(), &[0.5, 2.0]);
+    /// ```
+    pub fn apply_mask<F: Fn(UPosition) -> f32>(&mut self, mask: F) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                *self.get_value_mut(x, y) *= mask(UPosition::new(x as u32, y as u32));
+            }
+        }
+    }
+
+    /// Multiplies the map by a radial falloff, which is 1.0 in the center of the map and goes
+    /// down to 0.0 at the middle of each of its edges and beyond, making an island shaped map
+    /// with no land on the border.
+    ///
+    /// The falloff at a normalized distance `d` from the center is `1 - d^exponent`; the higher
+    /// the `exponent`, the more of the map stays untouched and the steeper the coast.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new_with_values(3, 3, &[1.0; 9]);
+    /// height_map.apply_radial_mask(2.0);
+    /// assert_eq!(height_map.value(UPosition::new(1, 1)), 1.0);
+    /// assert!(!height_map.has_land_on_border(0.0));
+    /// ```
+    pub fn apply_radial_mask(&mut self, exponent: f32) {
+        let (half_width, half_height) = self.half_size();
+        self.apply_mask(|position| {
+            let distance = math::hypot(
+                normalized_offset(position.x, half_width),
+                normalized_offset(position.y, half_height),
+            );
+            falloff(distance, exponent)
+        });
+    }
+
+    /// Multiplies the map by a square falloff, which is 1.0 in the center of the map and goes
+    /// down to 0.0 along all of its edges, making an island shaped map with no land on the
+    /// border that fills more of the map than `apply_radial_mask` does.
+    ///
+    /// The falloff at a normalized distance `d` from the center, measured along whichever axis
+    /// it's the greatest, is `1 - d^exponent`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new_with_values(5, 5, &[1.0; 25]);
+    /// height_map.apply_square_mask(1.0);
+    /// assert_eq!(height_map.value(UPosition::new(2, 2)), 1.0);
+    /// assert_eq!(height_map.value(UPosition::new(1, 3)), 0.5);
+    /// assert!(!height_map.has_land_on_border(0.0));
+    /// ```
+    pub fn apply_square_mask(&mut self, exponent: f32) {
+        let (half_width, half_height) = self.half_size();
+        self.apply_mask(|position| {
+            let distance = normalized_offset(position.x, half_width)
+                .abs()
+                .max(normalized_offset(position.y, half_height).abs());
+            falloff(distance, exponent)
+        });
+    }
+
+    // Returns the distances from the center of the map to the centers of its edge cells.
+    fn half_size(&self) -> (f32, f32) {
+        (
+            (self.width - 1) as f32 / 2.0,
+            (self.height - 1) as f32 / 2.0,
+        )
+    }
+}
+
+// Returns how far `coordinate` is from the center, relative to `half`, so that the edges are at
+// -1.0 and 1.0.
+fn normalized_offset(coordinate: u32, half: f32) -> f32 {
+    if half > 0.0 {
+        (coordinate as f32 - half) / half
+    } else {
+        0.0
+    }
+}
+
+fn falloff(distance: f32, exponent: f32) -> f32 {
+    1.0 - math::powf(distance.min(1.0), exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radial_mask_falls_off_from_the_center() {
+        let mut height_map = HeightMap::new_with_values(9, 5, &[2.0; 45]);
+        height_map.apply_radial_mask(1.0);
+
+        assert_eq!(height_map.value(UPosition::new(4, 2)), 2.0);
+        assert_eq!(height_map.value(UPosition::new(6, 2)), 1.0);
+        assert_eq!(height_map.value(UPosition::new(4, 1)), 1.0);
+        assert_eq!(height_map.value(UPosition::new(8, 2)), 0.0);
+        assert_eq!(height_map.value(UPosition::new(0, 0)), 0.0);
+        assert!(!height_map.has_land_on_border(0.0));
+    }
+}
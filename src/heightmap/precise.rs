@@ -0,0 +1,370 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::{Error, FPosition, UPosition, USize};
+use impl_ops::*;
+use std::ops::{self, AddAssign, MulAssign};
+
+/// A height map that stores its values as `f64` instead of `f32`.
+///
+/// Long pipelines that normalize, scale and combine maps over and over, e.g. for planetary scale
+/// terrain, slowly lose precision with `f32`. A `HeightMap64` supports the arithmetic those
+/// passes are made of; convert to and from a `HeightMap` with `From` and `to_height_map` to use
+/// the rest of the toolkit.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::UPosition;
+/// # use doryen_extra::heightmap::{HeightMap, HeightMap64};
+/// let map = HeightMap::new_with_values(2, 1, &[0.0, 1.0]);
+/// let mut precise = HeightMap64::from(&map);
+/// precise *= 1e-9;
+/// precise += 1.0;
+/// precise.normalize(0.0, 1.0);
+/// assert_eq!(precise.value(UPosition::new(1, 0)), 1.0);
+/// assert_eq!(precise.to_height_map().values(), map.values());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct HeightMap64 {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl HeightMap64 {
+    /// Returns a new height map with the given width and height. Initially, all the values of the
+    /// height map are `0.0`.
+    ///
+    /// # Panics
+    ///
+    /// If the `width` or the `height` is 0.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::try_new(width, height).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns a new height map with the given width and height, or an error if the `width` or
+    /// the `height` is 0. Initially, all the values of the height map are `0.0`.
+    pub fn try_new(width: usize, height: usize) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::EmptySize);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            values: vec![0.0; width * height],
+        })
+    }
+
+    /// Returns a new height map with the given width and height, and a set of values.
+    ///
+    /// # Panics
+    ///
+    /// * If the `width` or the `height` is 0.
+    /// * If the length of `values` is not `width * height`.
+    pub fn new_with_values(width: usize, height: usize, values: &[f64]) -> Self {
+        Self::try_new_with_values(width, height, values).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns a new height map with the given width and height, and a set of values, or an
+    /// error if the `width` or the `height` is 0 or if the length of `values` is not
+    /// `width * height`.
+    pub fn try_new_with_values(width: usize, height: usize, values: &[f64]) -> Result<Self, Error> {
+        let mut result = Self::try_new(width, height)?;
+        if values.len() != width * height {
+            return Err(Error::SizeMismatch {
+                expected: result.size(),
+                actual: USize::new(values.len() as u32, 1),
+            });
+        }
+        result.values.copy_from_slice(values);
+
+        Ok(result)
+    }
+
+    /// Returns the width of the height map.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the height map.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the values of the height map.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Returns the values of the height map.
+    pub fn values_mut(&mut self) -> &mut [f64] {
+        &mut self.values
+    }
+
+    /// Returns the value of the height map at the given position.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the height map.
+    pub fn value(&self, position: UPosition) -> f64 {
+        self.get(position)
+            .unwrap_or_else(|| panic!("position {} is outside the height map", position))
+    }
+
+    /// Returns the value of the height map at the given position, or `None` if the position is
+    /// outside the range of the height map.
+    pub fn get(&self, position: UPosition) -> Option<f64> {
+        self.index_of(position).map(|index| self.values[index])
+    }
+
+    /// Sets the value of the height map at the given position.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the height map.
+    pub fn set_value(&mut self, position: UPosition, value: f64) {
+        let index = self
+            .index_of(position)
+            .unwrap_or_else(|| panic!("position {} is outside the height map", position));
+        self.values[index] = value;
+    }
+
+    /// Returns the lowest and highest height value in the height map.
+    pub fn min_max(&self) -> (f64, f64) {
+        self.values
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            })
+    }
+
+    /// Clamps the values in the height map to be between `min` and `max`, inclusive.
+    ///
+    /// # Panics
+    ///
+    /// If `max` > `min`.
+    pub fn clamp(&mut self, min: f64, max: f64) {
+        assert!(min <= max);
+
+        for v in &mut self.values {
+            *v = v.clamp(min, max);
+        }
+    }
+
+    /// Normalizes the values in the height map by scaling them proportionally such that the
+    /// map's current smallest value will be set to `min`, and its largest value will be set to
+    /// `max`.
+    ///
+    /// # Panics
+    ///
+    /// If `max` > `min`.
+    pub fn normalize(&mut self, min: f64, max: f64) {
+        assert!(min <= max);
+
+        let (cur_min, cur_max) = self.min_max();
+        let scale = if cur_max - cur_min == 0.0 {
+            0.0
+        } else {
+            (max - min) / (cur_max - cur_min)
+        };
+        for v in &mut self.values {
+            *v = min + (*v - cur_min) * scale;
+        }
+    }
+
+    /// Linearly interpolate two height maps together.
+    ///
+    /// # Panics
+    ///
+    /// If the height maps don't have the same size, or if `coefficient` isn't between 0 and 1.
+    pub fn lerp(&self, other: &Self, coefficient: f64) -> Self {
+        self.try_lerp(other, coefficient)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Linearly interpolate two height maps together, or returns an error if the height maps
+    /// don't have the same size, or if `coefficient` isn't between 0 and 1.
+    pub fn try_lerp(&self, other: &Self, coefficient: f64) -> Result<Self, Error> {
+        self.check_same_size(other)?;
+        if !(0.0..=1.0).contains(&coefficient) {
+            return Err(Error::OutOfRange("coefficient"));
+        }
+
+        let mut result = self.clone();
+        for (v, &o) in result.values.iter_mut().zip(&other.values) {
+            *v += (o - *v) * coefficient;
+        }
+
+        Ok(result)
+    }
+
+    /// Adds a hill (a half spheroid) at the given position, with a `radius` and a `height`.
+    /// If `height == radius` or `-radius`, the hill will be a half-sphere.
+    pub fn add_hill(&mut self, position: FPosition, radius: f64, height: f64) {
+        let radius2 = radius * radius;
+        let coefficient = height / radius2;
+        let (px, py) = (f64::from(position.x), f64::from(position.y));
+
+        let min_x = (px - radius).max(0.0) as usize;
+        let max_x = (px + radius).min(self.width as f64) as usize;
+        let min_y = (py - radius).max(0.0) as usize;
+        let max_y = (py + radius).min(self.height as f64) as usize;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let z = radius2 - (x as f64 - px).powi(2) - (y as f64 - py).powi(2);
+                if z > 0.0 {
+                    self.values[x + y * self.width] += z * coefficient;
+                }
+            }
+        }
+    }
+
+    /// Returns a height map with the values of this one rounded to `f32`.
+    pub fn to_height_map(&self) -> HeightMap {
+        let values: Vec<f32> = self.values.iter().map(|&v| v as f32).collect();
+        HeightMap::new_with_values(self.width, self.height, &values)
+    }
+
+    fn size(&self) -> USize {
+        USize::new(self.width as u32, self.height as u32)
+    }
+
+    fn index_of(&self, position: UPosition) -> Option<usize> {
+        let (x, y) = (position.x as usize, position.y as usize);
+        if x < self.width && y < self.height {
+            Some(x + y * self.width)
+        } else {
+            None
+        }
+    }
+
+    fn check_same_size(&self, other: &Self) -> Result<(), Error> {
+        if self.width == other.width && self.height == other.height {
+            Ok(())
+        } else {
+            Err(Error::SizeMismatch {
+                expected: self.size(),
+                actual: other.size(),
+            })
+        }
+    }
+}
+
+impl From<&HeightMap> for HeightMap64 {
+    fn from(map: &HeightMap) -> Self {
+        Self {
+            width: map.width(),
+            height: map.height(),
+            values: map.values().iter().map(|&v| f64::from(v)).collect(),
+        }
+    }
+}
+
+impl From<HeightMap> for HeightMap64 {
+    fn from(map: HeightMap) -> Self {
+        Self::from(&map)
+    }
+}
+
+impl From<&HeightMap64> for HeightMap {
+    fn from(map: &HeightMap64) -> Self {
+        map.to_height_map()
+    }
+}
+
+impl From<HeightMap64> for HeightMap {
+    fn from(map: HeightMap64) -> Self {
+        map.to_height_map()
+    }
+}
+
+impl_op_ex!(+ |a: &HeightMap64, b: &HeightMap64| -> HeightMap64 {
+    assert_eq!(a.width, b.width);
+    assert_eq!(a.height, b.height);
+
+    let mut result = a.clone();
+    for (r, &o) in result.values.iter_mut().zip(b.values.iter()) {
+        *r += o
+    }
+
+    result
+});
+
+impl AddAssign<f64> for HeightMap64 {
+    fn add_assign(&mut self, rhs: f64) {
+        self.values.iter_mut().for_each(|v| *v += rhs);
+    }
+}
+
+impl_op_ex!(*|a: &HeightMap64, b: &HeightMap64| -> HeightMap64 {
+    assert_eq!(a.width, b.width);
+    assert_eq!(a.height, b.height);
+
+    let mut result = a.clone();
+    for (r, &o) in result.values.iter_mut().zip(b.values.iter()) {
+        *r *= o
+    }
+
+    result
+});
+
+impl MulAssign<f64> for HeightMap64 {
+    fn mul_assign(&mut self, rhs: f64) {
+        self.values.iter_mut().for_each(|v| *v *= rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_rescaling_keeps_small_differences() {
+        let mut map = HeightMap64::new_with_values(3, 1, &[0.0, 0.5, 1.0]);
+        for _ in 0..20 {
+            map *= 1e-6;
+            map += 1e6;
+            map.normalize(0.0, 1.0);
+        }
+        assert_eq!(map.values(), &[0.0, 0.5, 1.0]);
+
+        let sum = &map + &map;
+        assert_eq!(sum.min_max(), (0.0, 2.0));
+    }
+}
@@ -0,0 +1,239 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::grid::Grid;
+use crate::heightmap::HeightMap;
+use crate::{Position, Rectangle, UPosition, USize};
+
+/// A connected area of a height map, as found by `HeightMap::label_regions` or
+/// `HeightMap::label_water_regions`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Region {
+    /// The number of cells in the region.
+    pub area: usize,
+    /// The smallest block of cells that contains the whole region.
+    pub bounds: Rectangle,
+    /// The position of the highest cell in the region. If several cells are equally high, this
+    /// is the first of them, row by row.
+    pub highest: UPosition,
+    /// The height of the highest cell in the region.
+    pub highest_value: f32,
+}
+
+/// The connected areas of a height map, and which of them every cell belongs to.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Regions {
+    labels: Grid<Option<usize>>,
+    regions: Vec<Region>,
+}
+
+impl Regions {
+    /// Returns the index of the region the cell at `position` belongs to, or `None` if it isn't
+    /// part of any region.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the height map.
+    pub fn label(&self, position: UPosition) -> Option<usize> {
+        self.labels[position]
+    }
+
+    /// Returns the region the cell at `position` belongs to, or `None` if it isn't part of any
+    /// region.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is outside the height map.
+    pub fn region_at(&self, position: UPosition) -> Option<&Region> {
+        self.label(position).map(|label| &self.regions[label])
+    }
+
+    /// Returns the index of the region of every cell of the height map.
+    pub fn labels(&self) -> &Grid<Option<usize>> {
+        &self.labels
+    }
+
+    /// Returns the regions, in the order their first cells appear in, row by row.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Returns the region with the largest area, or `None` if there are no regions.
+    pub fn largest(&self) -> Option<&Region> {
+        self.regions.iter().max_by_key(|region| region.area)
+    }
+
+    /// Returns the number of regions.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Returns whether there are no regions at all.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+impl HeightMap {
+    /// Finds the connected areas of land, i.e. the cells above `water_level`, such as islands
+    /// and continents. Cells are connected to the cells next to them horizontally and
+    /// vertically, not diagonally.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(5, 1, &[1.0, 2.0, 0.0, 0.0, 3.0]);
+    /// let islands = height_map.label_regions(0.5);
+    /// assert_eq!(islands.len(), 2);
+    /// assert_eq!(islands.regions()[0].area, 2);
+    /// assert_eq!(islands.regions()[0].highest, UPosition::new(1, 0));
+    /// assert_eq!(islands.label(UPosition::new(4, 0)), Some(1));
+    /// assert_eq!(islands.label(UPosition::new(2, 0)), None);
+    /// ```
+    pub fn label_regions(&self, water_level: f32) -> Regions {
+        self.label_regions_where(|value| value > water_level)
+    }
+
+    /// Finds the connected areas of water, i.e. the cells at or below `water_level`, such as
+    /// lakes and seas. Cells are connected to the cells next to them horizontally and
+    /// vertically, not diagonally.
+    pub fn label_water_regions(&self, water_level: f32) -> Regions {
+        self.label_regions_where(|value| value <= water_level)
+    }
+
+    fn label_regions_where<F: Fn(f32) -> bool>(&self, included: F) -> Regions {
+        let mut labels = Grid::new(self.width, self.height, None);
+        let mut regions = Vec::new();
+        let mut stack = Vec::new();
+
+        for start_y in 0..self.height {
+            for start_x in 0..self.width {
+                let start = UPosition::new(start_x as u32, start_y as u32);
+                if labels[start].is_some() || !included(self.get_value(start_x, start_y)) {
+                    continue;
+                }
+
+                let label = regions.len();
+                let (mut min, mut max) = (start, start);
+                let mut region = Region {
+                    area: 0,
+                    bounds: Rectangle::default(),
+                    highest: start,
+                    highest_value: self.get_value(start_x, start_y),
+                };
+                labels[start] = Some(label);
+                stack.push(start);
+                while let Some(position) = stack.pop() {
+                    let value = self.value(position);
+                    region.area += 1;
+                    if value > region.highest_value
+                        || (value == region.highest_value
+                            && (position.y, position.x) < (region.highest.y, region.highest.x))
+                    {
+                        region.highest = position;
+                        region.highest_value = value;
+                    }
+                    min = UPosition::new(min.x.min(position.x), min.y.min(position.y));
+                    max = UPosition::new(max.x.max(position.x), max.y.max(position.y));
+
+                    let (x, y) = (position.x as i32, position.y as i32);
+                    for &(nx, ny) in &[(x, y - 1), (x - 1, y), (x + 1, y), (x, y + 1)] {
+                        if nx < 0
+                            || ny < 0
+                            || nx as usize >= self.width
+                            || ny as usize >= self.height
+                        {
+                            continue;
+                        }
+                        let neighbor = UPosition::new(nx as u32, ny as u32);
+                        if labels[neighbor].is_none() && included(self.value(neighbor)) {
+                            labels[neighbor] = Some(label);
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+                region.bounds = Rectangle::new(
+                    Position::new(min.x as i32, min.y as i32),
+                    USize::new(max.x - min.x + 1, max.y - min.y + 1),
+                );
+                regions.push(region);
+            }
+        }
+
+        Regions { labels, regions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_are_connected_horizontally_and_vertically() {
+        #[rustfmt::skip]
+        let values = [
+            1.0, 1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 1.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 2.0, 1.0, 0.0,
+        ];
+        let height_map = HeightMap::new_with_values(4, 4, &values);
+        let land = height_map.label_regions(0.5);
+
+        assert_eq!(land.len(), 3);
+        assert_eq!(
+            land.regions()[2],
+            Region {
+                area: 3,
+                bounds: Rectangle::new_from_raw(1, 2, 2, 2),
+                highest: UPosition::new(1, 3),
+                highest_value: 2.0,
+            }
+        );
+        assert_eq!(land.largest().map(|region| region.area), Some(3));
+        assert_eq!(land.label(UPosition::new(3, 1)), Some(1));
+
+        let water = height_map.label_water_regions(0.5);
+        assert_eq!(water.len(), 3);
+        assert_eq!(water.regions()[0].area, 3);
+        assert_eq!(water.regions()[1].area, 4);
+    }
+}
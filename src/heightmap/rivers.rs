@@ -0,0 +1,197 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::grid::Grid;
+use crate::heightmap::HeightMap;
+use crate::path::Dijkstra;
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use crate::random::{Random, Rng};
+use crate::{Position, UPosition};
+
+// How many steps of level ground climbing by 1.0 is worth when tracing a river.
+const UPHILL_COST: f32 = 100.0;
+// The most extra cost of flowing through a cell, randomly picked for each cell, which makes the
+// river meander rather than run straight.
+const MEANDER_COST: f32 = 0.5;
+
+impl HeightMap {
+    /// Traces a river from `source` to `mouth` and carves its channel into the map, returning the
+    /// cells of its course, starting with `source` and ending with `mouth`.
+    ///
+    /// The course follows the terrain downhill wherever it can, only climbing when there's no
+    /// other way to reach the mouth, and meanders randomly. The channel is carved `radius` cells
+    /// wide on either side of the course and `depth` deep, and its bottom never rises along the
+    /// way, so water would flow all the way from the source to the mouth.
+    ///
+    /// # Panics
+    ///
+    /// If `source` or `mouth` is outside the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// # use doryen_extra::random::Random;
+    /// let mut height_map = HeightMap::new(40, 40);
+    /// height_map.add_hill(FPosition::new(10.0, 10.0), 20.0, 1.0);
+    /// let mut random = Random::new_mt_from_seed(3);
+    /// let course = height_map.generate_river(
+    ///     UPosition::new(10, 10),
+    ///     UPosition::new(39, 39),
+    ///     1.5,
+    ///     0.05,
+    ///     &mut random,
+    /// );
+    /// assert_eq!(course.first(), Some(&UPosition::new(10, 10)));
+    /// assert_eq!(course.last(), Some(&UPosition::new(39, 39)));
+    /// ```
+    pub fn generate_river<A: RandomAlgorithm>(
+        &mut self,
+        source: UPosition,
+        mouth: UPosition,
+        radius: f32,
+        depth: f32,
+        random: &mut Random<A>,
+    ) -> Vec<UPosition> {
+        assert!(
+            (source.x as usize) < self.width && (source.y as usize) < self.height,
+            "the source is outside the height map"
+        );
+        assert!(
+            (mouth.x as usize) < self.width && (mouth.y as usize) < self.height,
+            "the mouth is outside the height map"
+        );
+
+        let heights = Grid::new_with_values(self.width, self.height, self.values.clone());
+        let mut meander = Grid::new(self.width, self.height, 0.0);
+        for cost in &mut meander {
+            *cost = random.get_uniform_f32() * MEANDER_COST;
+        }
+        let mut dijkstra = Dijkstra::new_with_cost(self.size(), 1.0, move |from, to| {
+            let climb = (heights[to] - heights[from]).max(0.0);
+            1.0 + climb * UPHILL_COST + meander[to]
+        });
+        let source_position = Position::new(source.x as i32, source.y as i32);
+        dijkstra.compute(source_position);
+        dijkstra.set_path(Position::new(mouth.x as i32, mouth.y as i32));
+
+        let course: Vec<UPosition> = std::iter::once(source_position)
+            .chain(dijkstra.path())
+            .map(|position| UPosition::new(position.x as u32, position.y as u32))
+            .collect();
+
+        // Carve from the source down, never letting the bottom of the channel rise.
+        let mut bottom = f32::INFINITY;
+        for &position in &course {
+            bottom = bottom.min(self.value(position) - depth);
+            self.carve_channel(position, radius, depth, bottom);
+        }
+
+        course
+    }
+
+    // Lowers the cells within `radius` of `position` to a parabolic channel whose bottom is at
+    // `bottom` and whose banks rise by `depth`.
+    fn carve_channel(&mut self, position: UPosition, radius: f32, depth: f32, bottom: f32) {
+        let radius = radius.max(0.5);
+        let reach = radius.ceil() as i32;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (x, y) = (position.x as i32 + dx, position.y as i32 + dy);
+                if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+                    continue;
+                }
+                let distance2 = (dx * dx + dy * dy) as f32;
+                if distance2 <= radius * radius {
+                    let level = bottom + depth * distance2 / (radius * radius);
+                    let value = self.get_value_mut(x as usize, y as usize);
+                    *value = value.min(level);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+    use crate::FPosition;
+
+    #[test]
+    fn rivers_flow_downhill_to_the_mouth() {
+        let mut height_map = HeightMap::new(30, 30);
+        height_map.add_hill(FPosition::new(5.0, 5.0), 25.0, 1.0);
+        // A ridge across the way, which the river has to climb over.
+        for y in 0..30 {
+            height_map.set_value(UPosition::new(20, y), 0.9);
+        }
+        let source = UPosition::new(5, 5);
+        let mouth = UPosition::new(29, 29);
+        let mut random = Random::new_mt_from_seed(8);
+        let course = height_map.generate_river(source, mouth, 1.0, 0.1, &mut random);
+
+        assert_eq!(course.first(), Some(&source));
+        assert_eq!(course.last(), Some(&mouth));
+        for pair in course.windows(2) {
+            let (dx, dy) = (
+                pair[0].x as i32 - pair[1].x as i32,
+                pair[0].y as i32 - pair[1].y as i32,
+            );
+            assert!(dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0));
+            assert!(height_map.value(pair[1]) <= height_map.value(pair[0]));
+        }
+    }
+
+    #[test]
+    fn rivers_reach_the_mouth_with_any_distribution() {
+        let source = UPosition::new(10, 10);
+        let mouth = UPosition::new(39, 39);
+        for &distribution in &[
+            Distribution::Linear,
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            for seed in 0..20 {
+                let mut height_map = HeightMap::new(40, 40);
+                height_map.add_hill(FPosition::new(10.0, 10.0), 20.0, 1.0);
+                let mut random = Random::new_mt_from_seed(seed);
+                random.distribution = distribution;
+                let course = height_map.generate_river(source, mouth, 1.5, 0.05, &mut random);
+                assert_eq!(course.first(), Some(&source));
+                assert_eq!(course.last(), Some(&mouth));
+            }
+        }
+    }
+}
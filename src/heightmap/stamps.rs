@@ -0,0 +1,240 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::math;
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use crate::random::{Random, Rng};
+use crate::FPosition;
+use std::f32::consts::PI;
+
+// How many points around a crater's rim get a random radius; the radius is interpolated
+// between them.
+const CRATER_RIM_POINTS: usize = 16;
+// How much a crater's radius varies around its rim, relative to the radius.
+const CRATER_JITTER: f32 = 0.15;
+// How far outside a crater's radius its rim slopes down to the surrounding terrain, relative to
+// the radius.
+const CRATER_RIM_WIDTH: f32 = 0.5;
+// How many times a ridge's crest line is split in two and its midpoint moved sideways.
+const RIDGE_SUBDIVISIONS: u32 = 4;
+// How far the midpoint of a ridge's crest is moved sideways at most, relative to its length.
+const RIDGE_JITTER: f32 = 0.15;
+
+impl HeightMap {
+    /// Adds an impact crater at the given position: a bowl `depth` deep, surrounded by a rim
+    /// `rim_height` high that slopes back down to the surrounding terrain. The radius varies
+    /// randomly around the rim, so that no two craters look quite the same.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// # use doryen_extra::random::Random;
+    /// let mut height_map = HeightMap::new(40, 40);
+    /// let mut random = Random::new_mt_from_seed(1);
+    /// height_map.add_crater(FPosition::new(20.0, 20.0), 10.0, 2.0, 0.5, &mut random);
+    /// assert_eq!(height_map.value(UPosition::new(20, 20)), -2.0);
+    /// assert!(height_map.value(UPosition::new(30, 20)) > 0.0);
+    /// assert_eq!(height_map.value(UPosition::new(0, 0)), 0.0);
+    /// ```
+    pub fn add_crater<A: RandomAlgorithm>(
+        &mut self,
+        position: FPosition,
+        radius: f32,
+        depth: f32,
+        rim_height: f32,
+        random: &mut Random<A>,
+    ) {
+        let rim: Vec<f32> = (0..CRATER_RIM_POINTS)
+            .map(|_| radius * (1.0 + (random.get_uniform_f32() * 2.0 - 1.0) * CRATER_JITTER))
+            .collect();
+        let reach = radius * (1.0 + CRATER_JITTER) * (1.0 + CRATER_RIM_WIDTH);
+
+        self.update_area(position, reach, |dx, dy| {
+            let angle = math::atan2(dy, dx) + PI;
+            let slot = angle / (2.0 * PI) * CRATER_RIM_POINTS as f32;
+            let index = (slot as usize).min(CRATER_RIM_POINTS - 1);
+            let t = slot - index as f32;
+            let local_radius = rim[index] + (rim[(index + 1) % CRATER_RIM_POINTS] - rim[index]) * t;
+
+            let r = math::hypot(dx, dy) / local_radius;
+            if r < 1.0 {
+                let r2 = r * r;
+                -depth * (1.0 - r2) + rim_height * r2 * r2
+            } else if r < 1.0 + CRATER_RIM_WIDTH {
+                let falloff = 1.0 - (r - 1.0) / CRATER_RIM_WIDTH;
+                rim_height * falloff * falloff
+            } else {
+                0.0
+            }
+        });
+    }
+
+    /// Adds a ridge of mountains going from `from` to `to`, whose crest is `height` high and
+    /// which slopes down to the surrounding terrain `width` cells away from the crest. The crest
+    /// wanders randomly from side to side, and its height varies randomly along the way.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// # use doryen_extra::random::Random;
+    /// let mut height_map = HeightMap::new(40, 40);
+    /// let mut random = Random::new_mt_from_seed(1);
+    /// height_map.add_ridge(FPosition::new(5.0, 20.0), FPosition::new(35.0, 20.0), 6.0, 1.0, &mut random);
+    /// assert!(height_map.value(UPosition::new(20, 20)) > 0.0);
+    /// assert_eq!(height_map.value(UPosition::new(20, 0)), 0.0);
+    /// ```
+    pub fn add_ridge<A: RandomAlgorithm>(
+        &mut self,
+        from: FPosition,
+        to: FPosition,
+        width: f32,
+        height: f32,
+        random: &mut Random<A>,
+    ) {
+        // The crest is a line of (x, y, height) points, made by midpoint displacement.
+        let mut crest = vec![(from.x, from.y, height), (to.x, to.y, height)];
+        let mut jitter = math::hypot(to.x - from.x, to.y - from.y) * RIDGE_JITTER;
+        for _ in 0..RIDGE_SUBDIVISIONS {
+            let mut next = Vec::with_capacity(crest.len() * 2 - 1);
+            for pair in crest.windows(2) {
+                let ((x1, y1, h1), (x2, y2, h2)) = (pair[0], pair[1]);
+                let length = math::hypot(x2 - x1, y2 - y1).max(f32::EPSILON);
+                let offset = (random.get_uniform_f32() * 2.0 - 1.0) * jitter;
+                let crest_height = (h1 + h2) / 2.0 * (0.8 + random.get_uniform_f32() * 0.3);
+                next.push(pair[0]);
+                next.push((
+                    (x1 + x2) / 2.0 - (y2 - y1) / length * offset,
+                    (y1 + y2) / 2.0 + (x2 - x1) / length * offset,
+                    crest_height.min(height),
+                ));
+            }
+            next.push(crest[crest.len() - 1]);
+            crest = next;
+            jitter /= 2.0;
+        }
+
+        let (min_x, max_x, min_y, max_y) = crest.iter().fold(
+            (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+            |(min_x, max_x, min_y, max_y), &(x, y, _)| {
+                (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+            },
+        );
+        let center = FPosition::new((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+        let reach = math::hypot(max_x - min_x, max_y - min_y) / 2.0 + width;
+
+        self.update_area(center, reach, |dx, dy| {
+            let (px, py) = (center.x + dx, center.y + dy);
+            crest
+                .windows(2)
+                .map(|pair| {
+                    let ((x1, y1, h1), (x2, y2, h2)) = (pair[0], pair[1]);
+                    let (sx, sy) = (x2 - x1, y2 - y1);
+                    let length2 = (sx * sx + sy * sy).max(f32::EPSILON);
+                    let t = (((px - x1) * sx + (py - y1) * sy) / length2).clamp(0.0, 1.0);
+                    let distance = math::hypot(px - (x1 + sx * t), py - (y1 + sy * t));
+                    let falloff = (1.0 - distance / width).max(0.0);
+                    (h1 + (h2 - h1) * t) * falloff * falloff
+                })
+                .fold(0.0, f32::max)
+        });
+    }
+
+    // Adds what `f` returns for the offset of every cell within `reach` of `center`.
+    fn update_area<F: Fn(f32, f32) -> f32>(&mut self, center: FPosition, reach: f32, f: F) {
+        let min_x = (center.x - reach).floor().max(0.0) as usize;
+        let max_x = ((center.x + reach).ceil() + 1.0).clamp(0.0, self.width as f32) as usize;
+        let min_y = (center.y - reach).floor().max(0.0) as usize;
+        let max_y = ((center.y + reach).ceil() + 1.0).clamp(0.0, self.height as f32) as usize;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                *self.get_value_mut(x, y) += f(x as f32 - center.x, y as f32 - center.y);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UPosition;
+
+    #[test]
+    fn craters_have_a_bowl_and_a_rim() {
+        let mut height_map = HeightMap::new(60, 60);
+        let mut random = Random::new_mt_from_seed(9);
+        height_map.add_crater(FPosition::new(30.0, 30.0), 15.0, 3.0, 1.0, &mut random);
+
+        let profile: Vec<f32> = (30..60)
+            .map(|x| height_map.value(UPosition::new(x, 30)))
+            .collect();
+        let (rim, &highest) = profile
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+        assert!(highest > 0.5 && highest <= 1.0);
+        assert!((12..=18).contains(&rim), "rim at {}", rim);
+        // Down from the rim to the bottom of the bowl.
+        assert!(profile[..rim].windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(profile[29], 0.0);
+    }
+
+    #[test]
+    fn ridges_are_highest_along_their_crest() {
+        let mut height_map = HeightMap::new(50, 30);
+        let mut random = Random::new_mt_from_seed(4);
+        height_map.add_ridge(
+            FPosition::new(5.0, 15.0),
+            FPosition::new(45.0, 15.0),
+            5.0,
+            2.0,
+            &mut random,
+        );
+
+        assert!(height_map
+            .values()
+            .iter()
+            .all(|&v| (0.0..=2.0).contains(&v)));
+        for x in 10..40 {
+            let column: Vec<f32> = (0..30)
+                .map(|y| height_map.value(UPosition::new(x, y)))
+                .collect();
+            assert!(column.iter().any(|&v| v > 1.0));
+            assert_eq!(column[0], 0.0);
+            assert_eq!(column[29], 0.0);
+        }
+    }
+}
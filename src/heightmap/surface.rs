@@ -0,0 +1,139 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::grid::Grid;
+use crate::heightmap::HeightMap;
+
+impl HeightMap {
+    /// Returns the normal of every cell of the height map, the same as calling `normal` with
+    /// the position of each cell, but in a single pass.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{FPosition, Position, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(4, 4);
+    /// height_map.set_value(UPosition::new(2, 1), 0.1);
+    /// let normals = height_map.normal_map(0.0);
+    /// assert_eq!(normals[Position::new(1, 1)], height_map.normal(FPosition::new(1.0, 1.0), 0.0));
+    /// ```
+    pub fn normal_map(&self, water_level: f32) -> Grid<[f32; 3]> {
+        let mut normals = Grid::new(self.width, self.height, [0.0; 3]);
+        self.normal_map_into(water_level, &mut normals);
+
+        normals
+    }
+
+    /// Like `normal_map`, but writes the normals into an existing grid, which avoids allocating
+    /// a new one when the normals are computed again and again, e.g. every frame.
+    ///
+    /// # Panics
+    ///
+    /// If `normals` isn't the same size as the height map.
+    pub fn normal_map_into(&self, water_level: f32, normals: &mut Grid<[f32; 3]>) {
+        assert_eq!(
+            normals.size(),
+            self.size(),
+            "the grid must be the same size as the height map"
+        );
+
+        for (i, normal) in normals.iter_mut().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            if x + 1 >= self.width || y + 1 >= self.height {
+                *normal = [0.0, 0.0, 1.0];
+                continue;
+            }
+
+            let h0 = self.get_value(x, y).max(water_level);
+            let hx = self.get_value(x + 1, y).max(water_level);
+            let hy = self.get_value(x, y + 1).max(water_level);
+            *normal = Self::normalized_normal(h0 - hx, h0 - hy);
+        }
+    }
+
+    /// Returns a height map with the slope of every cell of this height map, the same as
+    /// calling `slope` with the position of each cell.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::UPosition;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(3, 1, &[0.0, 0.5, 2.0]);
+    /// let slopes = height_map.slope_map();
+    /// assert_eq!(slopes.value(UPosition::new(1, 0)), height_map.slope(UPosition::new(1, 0)));
+    /// ```
+    pub fn slope_map(&self) -> Self {
+        let mut slopes = Self::new(self.width, self.height);
+        self.slope_map_into(&mut slopes);
+
+        slopes
+    }
+
+    /// Like `slope_map`, but writes the slopes into an existing height map, which avoids
+    /// allocating a new one when the slopes are computed again and again.
+    ///
+    /// # Panics
+    ///
+    /// If `slopes` isn't the same size as this height map.
+    pub fn slope_map_into(&self, slopes: &mut Self) {
+        assert_eq!(
+            slopes.size(),
+            self.size(),
+            "the slope map must be the same size as the height map"
+        );
+
+        slopes.update_values(|position, v| *v = self.slope(position));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FPosition, Position, UPosition};
+
+    #[test]
+    fn bulk_results_match_single_cells() {
+        let values: Vec<f32> = (0..30).map(|i| ((i * 7) % 11) as f32 / 10.0).collect();
+        let height_map = HeightMap::new_with_values(6, 5, &values);
+        let normals = height_map.normal_map(0.3);
+        let slopes = height_map.slope_map();
+
+        for y in 0..5 {
+            for x in 0..6 {
+                let normal = height_map.normal(FPosition::new(x as f32, y as f32), 0.3);
+                assert_eq!(normals[Position::new(x, y)], normal);
+                let position = UPosition::new(x as u32, y as u32);
+                assert_eq!(slopes.value(position), height_map.slope(position));
+            }
+        }
+    }
+}
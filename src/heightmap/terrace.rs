@@ -0,0 +1,143 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::{HeightMap, MinMax};
+
+impl HeightMap {
+    /// Snaps the values of the height map to `levels` evenly spaced plateaus, going from the
+    /// map's current lowest value to its highest.
+    ///
+    /// Every value is snapped down to the plateau at or below it. With a `smoothness` of 0.0, the
+    /// plateaus are separated by sheer cliffs; with anything higher, up to 1.0, that much of each
+    /// step is a slope leading up to the next plateau. A `smoothness` of 1.0 leaves the map as it
+    /// was, unless there's only one level, which flattens the whole map to its lowest value.
+    ///
+    /// # Panics
+    ///
+    /// If `levels` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new_with_values(5, 1, &[0.0, 0.3, 0.5, 0.8, 1.0]);
+    /// height_map.terrace(3, 0.0);
+    /// assert_eq!(height_map.values(), &[0.0, 0.0, 0.5, 0.5, 1.0]);
+    /// ```
+    pub fn terrace(&mut self, levels: u32, smoothness: f32) {
+        assert!(levels > 0, "there must be at least one level");
+
+        let MinMax { min, max } = self.min_max();
+        let range = max - min;
+        if range == 0.0 {
+            return;
+        }
+
+        let last_level = (levels - 1) as f32;
+        let step_height = range / last_level.max(1.0);
+        let smoothness = smoothness.clamp(0.0, 1.0);
+        self.update_values(|_, v| {
+            let position = (*v - min) / range * last_level;
+            let step = position.floor().min(last_level);
+            let t = position - step;
+            let rise = if t > 1.0 - smoothness {
+                (t - (1.0 - smoothness)) / smoothness
+            } else {
+                0.0
+            };
+            *v = min + (step + rise) * step_height;
+        });
+    }
+
+    /// Snaps every value of the height map down to the highest of the given `levels` that isn't
+    /// above it, or to the lowest of them if they're all above it. This maps heights onto a
+    /// fixed set of thresholds, e.g. those that decide which kind of tile a cell becomes.
+    ///
+    /// # Panics
+    ///
+    /// If `levels` is empty or isn't sorted in increasing order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new_with_values(5, 1, &[-1.0, 0.1, 0.3, 0.8, 2.0]);
+    /// height_map.quantize(&[0.0, 0.25, 0.75]);
+    /// assert_eq!(height_map.values(), &[0.0, 0.0, 0.25, 0.75, 0.75]);
+    /// ```
+    pub fn quantize(&mut self, levels: &[f32]) {
+        assert!(!levels.is_empty(), "there must be at least one level");
+        assert!(
+            levels.windows(2).all(|pair| pair[0] <= pair[1]),
+            "the levels must be sorted in increasing order"
+        );
+
+        self.update_values(|_, v| {
+            let above = levels.iter().take_while(|&&level| level <= *v).count();
+            *v = levels[above.max(1) - 1];
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_values(height_map: &HeightMap, expected: &[f32]) {
+        for (value, target) in height_map.values().iter().zip(expected) {
+            assert!((value - target).abs() < 1e-5, "{} != {}", value, target);
+        }
+    }
+
+    #[test]
+    fn smooth_terraces_ramp_between_plateaus() {
+        let mut height_map = HeightMap::new_with_values(6, 1, &[0.0, 0.2, 0.5, 0.6, 0.7, 0.8]);
+        height_map.terrace(2, 0.5);
+
+        assert_values(&height_map, &[0.0, 0.0, 0.2, 0.4, 0.6, 0.8]);
+    }
+
+    #[test]
+    fn full_smoothness_leaves_the_map_unchanged() {
+        let values = [0.0, 1.0, 2.0, 3.0, 4.0, 0.5, 3.7];
+        let mut height_map = HeightMap::new_with_values(7, 1, &values);
+        height_map.terrace(4, 1.0);
+
+        assert_values(&height_map, &values);
+    }
+
+    #[test]
+    fn a_single_level_flattens_the_map() {
+        let mut height_map = HeightMap::new_with_values(3, 1, &[1.0, 2.0, 3.0]);
+        height_map.terrace(1, 1.0);
+
+        assert_values(&height_map, &[1.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,387 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::{Position, Rectangle, UPosition, USize};
+
+/// How `HeightMap::blend` combines the values being pasted with those already in the map.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum BlendMode {
+    /// Replaces the values in the map with the pasted values.
+    Replace,
+    /// Adds the pasted values to the values in the map.
+    Add,
+    /// Multiplies the values in the map by the pasted values.
+    Multiply,
+    /// Keeps the highest of the two values.
+    Max,
+    /// Keeps the lowest of the two values.
+    Min,
+    /// Interpolates between the value in the map, at 0.0, and the pasted value, at 1.0.
+    Lerp(f32),
+}
+
+impl BlendMode {
+    /// Returns the result of blending `source` into `destination`.
+    pub fn blend(self, destination: f32, source: f32) -> f32 {
+        match self {
+            Self::Replace => source,
+            Self::Add => destination + source,
+            Self::Multiply => destination * source,
+            Self::Max => destination.max(source),
+            Self::Min => destination.min(source),
+            Self::Lerp(coefficient) => destination + (source - destination) * coefficient,
+        }
+    }
+}
+
+/// A borrowed rectangular area of a height map, as returned by `HeightMap::view`. Positions
+/// are relative to the upper-left corner of the area.
+#[derive(Copy, Clone, Debug)]
+pub struct HeightMapView<'a> {
+    map: &'a HeightMap,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> HeightMapView<'a> {
+    /// Returns the width of the view.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the view.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the value at the given position of the view.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the view.
+    pub fn value(&self, position: UPosition) -> f32 {
+        self.get(position).expect("position is outside the view")
+    }
+
+    /// Returns the value at the given position of the view, or `None` if the position is
+    /// outside the view.
+    pub fn get(&self, position: UPosition) -> Option<f32> {
+        offset_in(position, self.width, self.height)
+            .map(|(x, y)| self.map.get_value(self.x + x, self.y + y))
+    }
+
+    /// Returns an iterator over the rows of the view, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [f32]> {
+        let map = self.map;
+        let (x, width) = (self.x, self.width);
+        (self.y..self.y + self.height).map(move |y| {
+            let start = x + y * map.width;
+            &map.values[start..start + width]
+        })
+    }
+
+    /// Returns a new height map with a copy of the values of the view.
+    pub fn to_height_map(&self) -> HeightMap {
+        let values: Vec<f32> = self.rows().flatten().copied().collect();
+        HeightMap::new_with_values(self.width, self.height, &values)
+    }
+}
+
+/// A mutably borrowed rectangular area of a height map, as returned by `HeightMap::view_mut`.
+/// Positions are relative to the upper-left corner of the area.
+#[derive(Debug)]
+pub struct HeightMapViewMut<'a> {
+    map: &'a mut HeightMap,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> HeightMapViewMut<'a> {
+    /// Returns the width of the view.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the view.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the value at the given position of the view.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the view.
+    pub fn value(&self, position: UPosition) -> f32 {
+        self.get(position).expect("position is outside the view")
+    }
+
+    /// Returns the value at the given position of the view, or `None` if the position is
+    /// outside the view.
+    pub fn get(&self, position: UPosition) -> Option<f32> {
+        offset_in(position, self.width, self.height)
+            .map(|(x, y)| self.map.get_value(self.x + x, self.y + y))
+    }
+
+    /// Returns a mutable reference to the value at the given position of the view, or `None` if
+    /// the position is outside the view.
+    pub fn get_mut(&mut self, position: UPosition) -> Option<&mut f32> {
+        let (x, y) = offset_in(position, self.width, self.height)?;
+        Some(self.map.get_value_mut(self.x + x, self.y + y))
+    }
+
+    /// Sets the value at the given position of the view.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the view.
+    pub fn set_value(&mut self, position: UPosition, value: f32) {
+        *self
+            .get_mut(position)
+            .expect("position is outside the view") = value;
+    }
+
+    /// Sets every value of the view to `value`.
+    pub fn fill(&mut self, value: f32) {
+        for row in self.rows_mut() {
+            row.iter_mut().for_each(|v| *v = value);
+        }
+    }
+
+    /// Returns an iterator over the rows of the view, from top to bottom.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [f32]> {
+        let (x, width) = (self.x, self.width);
+        let map_width = self.map.width;
+        self.map.values[self.y * map_width..(self.y + self.height) * map_width]
+            .chunks_exact_mut(map_width.max(1))
+            .map(move |row| &mut row[x..x + width])
+    }
+
+    /// Returns a read-only view of the same area.
+    pub fn as_view(&self) -> HeightMapView<'_> {
+        HeightMapView {
+            map: self.map,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl HeightMap {
+    /// Returns a view of the cells of the height map within `area`, which is treated as a block
+    /// of `width` by `height` cells.
+    ///
+    /// # Panics
+    ///
+    /// If any part of `area` is outside the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Rectangle, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let height_map = HeightMap::new_with_values(3, 2, &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// let view = height_map.view(Rectangle::new_from_raw(1, 0, 2, 2));
+    /// assert_eq!(view.value(UPosition::new(0, 1)), 4.0);
+    /// assert_eq!(view.to_height_map().values(), &[1.0, 2.0, 4.0, 5.0]);
+    /// ```
+    pub fn view(&self, area: Rectangle) -> HeightMapView<'_> {
+        let (x, y, width, height) = self.checked_area(area);
+        HeightMapView {
+            map: self,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns a mutable view of the cells of the height map within `area`, which is treated as
+    /// a block of `width` by `height` cells.
+    ///
+    /// # Panics
+    ///
+    /// If any part of `area` is outside the height map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Rectangle;
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let mut height_map = HeightMap::new(3, 2);
+    /// height_map.view_mut(Rectangle::new_from_raw(1, 1, 2, 1)).fill(1.0);
+    /// assert_eq!(height_map.values(), &[0.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+    /// ```
+    pub fn view_mut(&mut self, area: Rectangle) -> HeightMapViewMut<'_> {
+        let (x, y, width, height) = self.checked_area(area);
+        HeightMapViewMut {
+            map: self,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Copies the values of `other` within `source`, which is treated as a block of `width` by
+    /// `height` cells, into this height map with their upper-left corner at `destination`. The
+    /// parts of the area that are outside either map are left out.
+    pub fn copy_from(&mut self, other: &Self, source: Rectangle, destination: Position) {
+        self.blend_area(other, source, destination, BlendMode::Replace);
+    }
+
+    /// Pastes all of `other` into this height map with its upper-left corner at `destination`,
+    /// replacing the values that were there. The parts of `other` that end up outside this map
+    /// are left out, so stamps can hang over the edges.
+    pub fn paste(&mut self, other: &Self, destination: Position) {
+        self.blend(other, destination, BlendMode::Replace);
+    }
+
+    /// Pastes all of `other` into this height map with its upper-left corner at `destination`,
+    /// combining its values with the values that were there according to `mode`. The parts of
+    /// `other` that end up outside this map are left out, so stamps can hang over the edges.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Position;
+    /// # use doryen_extra::heightmap::{BlendMode, HeightMap};
+    /// let mut height_map = HeightMap::new_with_values(3, 1, &[1.0, 1.0, 1.0]);
+    /// let stamp = HeightMap::new_with_values(2, 1, &[0.5, 3.0]);
+    /// height_map.blend(&stamp, Position::new(-1, 0), BlendMode::Max);
+    /// assert_eq!(height_map.values(), &[3.0, 1.0, 1.0]);
+    /// height_map.blend(&stamp, Position::new(1, 0), BlendMode::Lerp(0.5));
+    /// assert_eq!(height_map.values(), &[3.0, 0.75, 2.0]);
+    /// ```
+    pub fn blend(&mut self, other: &Self, destination: Position, mode: BlendMode) {
+        let source = Rectangle::new(Position::ORIGIN, other.size());
+        self.blend_area(other, source, destination, mode);
+    }
+
+    fn blend_area(
+        &mut self,
+        other: &Self,
+        source: Rectangle,
+        destination: Position,
+        mode: BlendMode,
+    ) {
+        let source_end = (
+            i64::from(source.position.x) + i64::from(source.size.width),
+            i64::from(source.position.y) + i64::from(source.size.height),
+        );
+        for source_y in i64::from(source.position.y).max(0)..source_end.1.min(other.height as i64) {
+            let y = i64::from(destination.y) + source_y - i64::from(source.position.y);
+            if y < 0 || y >= self.height as i64 {
+                continue;
+            }
+            for source_x in
+                i64::from(source.position.x).max(0)..source_end.0.min(other.width as i64)
+            {
+                let x = i64::from(destination.x) + source_x - i64::from(source.position.x);
+                if x < 0 || x >= self.width as i64 {
+                    continue;
+                }
+                let value = other.get_value(source_x as usize, source_y as usize);
+                let cell = self.get_value_mut(x as usize, y as usize);
+                *cell = mode.blend(*cell, value);
+            }
+        }
+    }
+
+    fn checked_area(&self, area: Rectangle) -> (usize, usize, usize, usize) {
+        let Rectangle {
+            position: Position { x, y },
+            size: USize { width, height },
+        } = area;
+        assert!(
+            x >= 0
+                && y >= 0
+                && x as usize + width as usize <= self.width
+                && y as usize + height as usize <= self.height,
+            "the area is outside the height map"
+        );
+
+        (x as usize, y as usize, width as usize, height as usize)
+    }
+}
+
+// Returns the coordinates of `position` if it's within a `width` by `height` area.
+fn offset_in(position: UPosition, width: usize, height: usize) -> Option<(usize, usize)> {
+    let (x, y) = (position.x as usize, position.y as usize);
+    if x < width && y < height {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copying_clips_to_both_maps() {
+        let source =
+            HeightMap::new_with_values(3, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+        let mut destination = HeightMap::new(3, 3);
+        destination.copy_from(
+            &source,
+            Rectangle::new_from_raw(-1, 1, 3, 5),
+            Position::new(1, 0),
+        );
+
+        assert_eq!(
+            destination.values(),
+            &[0.0, 0.0, 4.0, 0.0, 0.0, 7.0, 0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn mutable_views_only_touch_their_area() {
+        let mut height_map = HeightMap::new(4, 3);
+        let mut view = height_map.view_mut(Rectangle::new_from_raw(1, 1, 2, 2));
+        view.set_value(UPosition::new(1, 1), 2.0);
+        assert_eq!(view.get(UPosition::new(2, 0)), None);
+        assert_eq!(view.as_view().rows().count(), 2);
+
+        assert_eq!(height_map.value(UPosition::new(2, 2)), 2.0);
+        assert_eq!(height_map.values().iter().sum::<f32>(), 2.0);
+    }
+}
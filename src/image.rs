@@ -0,0 +1,644 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Image toolkit
+//!
+//! An in-memory image, stored as a buffer of colors, with the operations of libtcod's image
+//! toolkit: filling, flipping, rotating and scaling, averaging over areas through mipmaps, and
+//! transparency through a key color.
+//!
+//! With the `image_io` feature, images can be loaded from and saved to PNG and BMP files, and
+//! converted to and from the `RgbaImage` type of the [`image`] crate.
+//!
+//! [`image`]: https://crates.io/crates/image
+
+use crate::color::Color;
+use crate::grid::cell_index;
+use crate::heightmap::HeightMap;
+use crate::{Error, FRectangle, Position, USize};
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+#[cfg(feature = "image_io")]
+use std::io;
+
+/// An image stored in memory as a row-major buffer of colors.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "ImageData")
+)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    key_color: Option<Color>,
+    // Successively halved versions of the image, starting at half its size, built when first
+    // needed. Emptied whenever the pixels change.
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    mipmaps: Vec<Mipmap>,
+}
+
+// `Image` as it comes out of the deserializer, before its size is validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct ImageData {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    key_color: Option<Color>,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<ImageData> for Image {
+    type Error = Error;
+
+    fn try_from(data: ImageData) -> Result<Self, Self::Error> {
+        let size = match (u32::try_from(data.width), u32::try_from(data.height)) {
+            (Ok(width), Ok(height)) => USize::new(width, height),
+            _ => return Err(Error::OutOfRange("the size of the image")),
+        };
+        let mut image = Self::try_new_with_pixels(size, data.pixels)?;
+        image.key_color = data.key_color;
+
+        Ok(image)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Mipmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.pixels == other.pixels
+            && self.key_color == other.key_color
+    }
+}
+
+impl Eq for Image {}
+
+impl Image {
+    /// Returns a new image with the given size, where every pixel is black.
+    pub fn new(size: USize) -> Self {
+        Self::new_with_color(size, Color::BLACK)
+    }
+
+    /// Returns a new image with the given size, where every pixel is `color`.
+    pub fn new_with_color(size: USize, color: Color) -> Self {
+        let width = size.width as usize;
+        let height = size.height as usize;
+
+        Self {
+            width,
+            height,
+            pixels: vec![color; width * height],
+            key_color: None,
+            mipmaps: Vec::new(),
+        }
+    }
+
+    /// Returns a new image with the given size and pixels, in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `pixels` is not `width * height`.
+    pub fn new_with_pixels(size: USize, pixels: Vec<Color>) -> Self {
+        Self::try_new_with_pixels(size, pixels).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns a new image with the given size and pixels, in row-major order, or an error if
+    /// the length of `pixels` is not `width * height`.
+    pub fn try_new_with_pixels(size: USize, pixels: Vec<Color>) -> Result<Self, Error> {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        let len = width
+            .checked_mul(height)
+            .ok_or(Error::OutOfRange("the size of the image"))?;
+        if pixels.len() != len {
+            return Err(Error::LengthMismatch {
+                expected: len,
+                actual: pixels.len(),
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            key_color: None,
+            mipmaps: Vec::new(),
+        })
+    }
+
+    /// Returns the size of the image.
+    pub fn size(&self) -> USize {
+        USize::new(self.width as u32, self.height as u32)
+    }
+
+    /// Returns the pixels of the image, in row-major order.
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// Returns the pixels of the image, in row-major order.
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        self.mipmaps.clear();
+        &mut self.pixels
+    }
+
+    /// Returns the color of the pixel at `position`, or `None` if it's outside the image.
+    pub fn get_pixel(&self, position: Position) -> Option<Color> {
+        self.index(position).map(|index| self.pixels[index])
+    }
+
+    /// Sets the color of the pixel at `position`. Positions outside the image are ignored.
+    pub fn put_pixel(&mut self, position: Position, color: Color) {
+        if let Some(index) = self.index(position) {
+            self.pixels[index] = color;
+            self.mipmaps.clear();
+        }
+    }
+
+    /// Sets every pixel of the image to `color`.
+    pub fn fill(&mut self, color: Color) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = color);
+        self.mipmaps.clear();
+    }
+
+    /// Returns the color used for transparent pixels, if there is one.
+    pub fn key_color(&self) -> Option<Color> {
+        self.key_color
+    }
+
+    /// Sets the color used for transparent pixels; pixels of this color aren't drawn when the
+    /// image is blitted. `None` makes only pixels with an opacity of 0 transparent.
+    pub fn set_key_color(&mut self, key_color: Option<Color>) {
+        self.key_color = key_color;
+    }
+
+    /// Returns whether the pixel at `position` is transparent, i.e. is the key color or has an
+    /// opacity of 0. Pixels outside the image are transparent.
+    pub fn is_pixel_transparent(&self, position: Position) -> bool {
+        match self.get_pixel(position) {
+            Some(color) => color.a == 0 || self.key_color == Some(color),
+            None => true,
+        }
+    }
+
+    /// Returns the average color of the pixels in `area`, in pixel coordinates, taken from the
+    /// mipmap whose pixels are closest in size to the area. This is a cheap way to get the color
+    /// a part of the image should have when scaled down. The mipmaps are built the first time
+    /// they're needed after the image has changed.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// # use doryen_extra::image::Image;
+    /// # use doryen_extra::{FRectangle, Position, USize};
+    /// let mut image = Image::new(USize::new(4, 4));
+    /// image.put_pixel(Position::new(0, 0), Color::WHITE);
+    /// image.put_pixel(Position::new(1, 1), Color::WHITE);
+    /// let average = image.get_mipmap_pixel(FRectangle::new_from_raw(0.0, 0.0, 2.0, 2.0));
+    /// assert_eq!(average, Color::new(127, 127, 127));
+    /// ```
+    pub fn get_mipmap_pixel(&mut self, area: FRectangle) -> Color {
+        if self.pixels.is_empty() {
+            return Color::BLACK;
+        }
+        if self.mipmaps.is_empty() {
+            self.generate_mipmaps();
+        }
+
+        let texel_size = area.size.width.max(area.size.height);
+        let mut level = 0;
+        let mut level_size = 1.0;
+        while level < self.mipmaps.len() && level_size < texel_size {
+            level += 1;
+            level_size *= 2.0;
+        }
+        // The level whose pixels are at most as large as the area.
+        if level_size > texel_size && level > 0 {
+            level -= 1;
+        }
+
+        let (width, height, pixels) = if level == 0 {
+            (self.width, self.height, &self.pixels)
+        } else {
+            let mipmap = &self.mipmaps[level - 1];
+            (mipmap.width, mipmap.height, &mipmap.pixels)
+        };
+        let scale = |coordinate: f32, size: usize, image_size: usize| {
+            let scaled = coordinate * size as f32 / image_size as f32;
+            (scaled.max(0.0) as usize).min(size - 1)
+        };
+        let x = scale(area.position.x, width, self.width);
+        let y = scale(area.position.y, height, self.height);
+
+        pixels[x + y * width]
+    }
+
+    /// Scales the image to `size`, averaging the pixels through mipmaps when shrinking and
+    /// repeating them when growing.
+    pub fn scale(&mut self, size: USize) {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        let texel_width = self.width as f32 / width as f32;
+        let texel_height = self.height as f32 / height as f32;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(self.get_mipmap_pixel(FRectangle::new_from_raw(
+                    x as f32 * texel_width,
+                    y as f32 * texel_height,
+                    texel_width,
+                    texel_height,
+                )));
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.pixels = pixels;
+        self.mipmaps.clear();
+    }
+
+    /// Flips the image horizontally, so that its left side becomes its right side.
+    pub fn hflip(&mut self) {
+        for row in self.pixels.chunks_mut(self.width.max(1)) {
+            row.reverse();
+        }
+        self.mipmaps.clear();
+    }
+
+    /// Flips the image vertically, so that its top becomes its bottom.
+    pub fn vflip(&mut self) {
+        for y in 0..self.height / 2 {
+            for x in 0..self.width {
+                self.pixels
+                    .swap(x + y * self.width, x + (self.height - 1 - y) * self.width);
+            }
+        }
+        self.mipmaps.clear();
+    }
+
+    /// Rotates the image clockwise by 90 degrees `rotations` times. Odd numbers of rotations swap
+    /// the width and height of the image.
+    pub fn rotate90(&mut self, rotations: u32) {
+        match rotations % 4 {
+            1 => {
+                let (width, height) = (self.width, self.height);
+                let mut pixels = Vec::with_capacity(self.pixels.len());
+                // The new row y is the old column y, read from the bottom up.
+                for y in 0..width {
+                    for x in 0..height {
+                        pixels.push(self.pixels[y + (height - 1 - x) * width]);
+                    }
+                }
+                self.width = height;
+                self.height = width;
+                self.pixels = pixels;
+                self.mipmaps.clear();
+            }
+            2 => {
+                self.pixels.reverse();
+                self.mipmaps.clear();
+            }
+            3 => {
+                self.rotate90(2);
+                self.rotate90(1);
+            }
+            _ => {}
+        }
+    }
+
+    /// Inverts the colors of every pixel, keeping their opacity.
+    pub fn invert(&mut self) {
+        for pixel in &mut self.pixels {
+            pixel.r = 255 - pixel.r;
+            pixel.g = 255 - pixel.g;
+            pixel.b = 255 - pixel.b;
+        }
+        self.mipmaps.clear();
+    }
+
+    /// Loads an image from a PNG or BMP file. The format is decided by the contents of the file.
+    /// Files that can't be decoded give an error of the `InvalidData` kind.
+    #[cfg(feature = "image_io")]
+    pub fn load_from_file<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let image = ::image::open(path).map_err(image_error_to_io)?;
+        Ok(Self::from(&image.to_rgba8()))
+    }
+
+    /// Loads an image from the contents of a PNG or BMP file.
+    #[cfg(feature = "image_io")]
+    pub fn load_from_memory(bytes: &[u8]) -> io::Result<Self> {
+        let image = ::image::load_from_memory(bytes).map_err(image_error_to_io)?;
+        Ok(Self::from(&image.to_rgba8()))
+    }
+
+    /// Saves the image to a file, as a PNG or a BMP depending on the extension of the path.
+    #[cfg(feature = "image_io")]
+    pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
+        ::image::RgbaImage::from(self)
+            .save(path)
+            .map_err(image_error_to_io)
+    }
+
+    fn generate_mipmaps(&mut self) {
+        self.mipmaps.clear();
+        let (mut width, mut height) = (self.width, self.height);
+        while width > 1 || height > 1 {
+            let (source_width, source_height) = (width, height);
+            let source = self.mipmaps.last().map_or(&self.pixels, |m| &m.pixels);
+            width = (width / 2).max(1);
+            height = (height / 2).max(1);
+
+            let mut pixels = Vec::with_capacity(width * height);
+            for y in 0..height {
+                for x in 0..width {
+                    let mut sum = [0_u32; 4];
+                    let mut count = 0;
+                    for sy in y * 2..(y * 2 + 2).min(source_height) {
+                        for sx in x * 2..(x * 2 + 2).min(source_width) {
+                            let color = source[sx + sy * source_width];
+                            sum[0] += u32::from(color.r);
+                            sum[1] += u32::from(color.g);
+                            sum[2] += u32::from(color.b);
+                            sum[3] += u32::from(color.a);
+                            count += 1;
+                        }
+                    }
+                    pixels.push(Color::new_with_alpha(
+                        (sum[0] / count) as u8,
+                        (sum[1] / count) as u8,
+                        (sum[2] / count) as u8,
+                        (sum[3] / count) as u8,
+                    ));
+                }
+            }
+            self.mipmaps.push(Mipmap {
+                width,
+                height,
+                pixels,
+            });
+        }
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        cell_index(self.width, self.height, position)
+    }
+}
+
+impl From<&HeightMap> for Image {
+    /// Converts a height map into a grayscale image of the same size, where the lowest value of
+    /// the height map is black and the highest one is white.
+    fn from(height_map: &HeightMap) -> Self {
+        let min_max = height_map.min_max();
+        let range = min_max.max - min_max.min;
+        let pixels = height_map
+            .values()
+            .iter()
+            .map(|&value| {
+                let level = if range > 0.0 {
+                    ((value - min_max.min) / range * 255.0).round() as u8
+                } else {
+                    0
+                };
+                Color::new(level, level, level)
+            })
+            .collect();
+
+        Self::new_with_pixels(
+            USize::new(height_map.width() as u32, height_map.height() as u32),
+            pixels,
+        )
+    }
+}
+
+impl From<HeightMap> for Image {
+    /// Converts a height map into a grayscale image; see the `&HeightMap` conversion.
+    fn from(height_map: HeightMap) -> Self {
+        Self::from(&height_map)
+    }
+}
+
+#[cfg(feature = "image_io")]
+impl From<&Image> for ::image::RgbaImage {
+    fn from(image: &Image) -> Self {
+        let bytes = image
+            .pixels
+            .iter()
+            .flat_map(|color| vec![color.r, color.g, color.b, color.a])
+            .collect();
+        Self::from_raw(image.width as u32, image.height as u32, bytes)
+            .expect("the buffer has the size of the image")
+    }
+}
+
+#[cfg(feature = "image_io")]
+impl From<&::image::RgbaImage> for Image {
+    fn from(image: &::image::RgbaImage) -> Self {
+        let pixels = image
+            .pixels()
+            .map(|&::image::Rgba([r, g, b, a])| Color::new_with_alpha(r, g, b, a))
+            .collect();
+        Self::new_with_pixels(USize::new(image.width(), image.height()), pixels)
+    }
+}
+
+#[cfg(feature = "image_io")]
+fn image_error_to_io(error: ::image::ImageError) -> io::Error {
+    match error {
+        ::image::ImageError::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_put_pixel() {
+        let mut image = Image::new(USize::new(3, 2));
+        image.put_pixel(Position::new(2, 1), Color::RED);
+        image.put_pixel(Position::new(3, 1), Color::RED);
+        assert_eq!(image.get_pixel(Position::new(2, 1)), Some(Color::RED));
+        assert_eq!(image.get_pixel(Position::new(0, 0)), Some(Color::BLACK));
+        assert_eq!(image.get_pixel(Position::new(-1, 0)), None);
+        assert_eq!(image.pixels()[5], Color::RED);
+    }
+
+    // A 3x2 image with a distinct color in every pixel, numbered from 0 in row-major order.
+    fn numbered() -> Image {
+        let pixels = (0..6).map(|i| Color::new(i, 0, 0)).collect();
+        Image::new_with_pixels(USize::new(3, 2), pixels)
+    }
+
+    fn numbers(image: &Image) -> Vec<u8> {
+        image.pixels().iter().map(|color| color.r).collect()
+    }
+
+    #[test]
+    fn flips() {
+        let mut image = numbered();
+        image.hflip();
+        assert_eq!(numbers(&image), [2, 1, 0, 5, 4, 3]);
+
+        let mut flipped = numbered();
+        flipped.vflip();
+        assert_eq!(numbers(&flipped), [3, 4, 5, 0, 1, 2]);
+    }
+
+    #[test]
+    fn rotations() {
+        let mut image = numbered();
+        image.rotate90(1);
+        assert_eq!(image.size(), USize::new(2, 3));
+        assert_eq!(numbers(&image), [3, 0, 4, 1, 5, 2]);
+
+        image.rotate90(1);
+        assert_eq!(image.size(), USize::new(3, 2));
+        assert_eq!(numbers(&image), [5, 4, 3, 2, 1, 0]);
+
+        image.rotate90(2);
+        assert_eq!(image, numbered());
+        image.rotate90(3);
+        assert_eq!(numbers(&image), [2, 5, 1, 4, 0, 3]);
+    }
+
+    #[test]
+    fn mipmaps_and_scaling() {
+        let mut image = Image::new(USize::new(4, 4));
+        for y in 0..4 {
+            for x in 0..2 {
+                image.put_pixel(Position::new(x, y), Color::WHITE);
+            }
+        }
+        let whole = FRectangle::new_from_raw(0.0, 0.0, 4.0, 4.0);
+        assert_eq!(image.get_mipmap_pixel(whole), Color::new(127, 127, 127));
+        let single = FRectangle::new_from_raw(3.0, 0.0, 1.0, 1.0);
+        assert_eq!(image.get_mipmap_pixel(single), Color::BLACK);
+
+        image.fill(Color::RED);
+        assert_eq!(image.get_mipmap_pixel(whole), Color::RED);
+
+        let mut scaled = numbered();
+        scaled.scale(USize::new(6, 4));
+        assert_eq!(
+            numbers(&scaled),
+            [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 3, 3, 4, 4, 5, 5]
+        );
+        scaled.scale(USize::new(3, 2));
+        assert_eq!(scaled, numbered());
+    }
+
+    #[test]
+    fn transparency() {
+        let mut image = numbered();
+        image.put_pixel(Position::new(1, 0), Color::new_with_alpha(1, 0, 0, 0));
+        assert!(!image.is_pixel_transparent(Position::new(0, 0)));
+        assert!(image.is_pixel_transparent(Position::new(1, 0)));
+        assert!(image.is_pixel_transparent(Position::new(5, 0)));
+
+        image.set_key_color(Some(Color::new(2, 0, 0)));
+        assert!(image.is_pixel_transparent(Position::new(2, 0)));
+        assert_eq!(image.key_color(), Some(Color::new(2, 0, 0)));
+
+        image.invert();
+        assert_eq!(
+            image.get_pixel(Position::new(0, 0)),
+            Some(Color::new(255, 255, 255))
+        );
+    }
+
+    #[test]
+    fn from_height_map() {
+        let height_map = HeightMap::new_with_values(3, 1, &[-1.0, 0.0, 1.0]);
+        let image = Image::from(height_map);
+        assert_eq!(image.size(), USize::new(3, 1));
+        assert_eq!(
+            image.pixels(),
+            [Color::BLACK, Color::new(128, 128, 128), Color::WHITE]
+        );
+    }
+
+    #[cfg(feature = "image_io")]
+    #[test]
+    fn png_and_bmp_files() {
+        let mut image = numbered();
+        image.put_pixel(Position::new(1, 1), Color::new_with_alpha(1, 2, 3, 4));
+        for extension in &["png", "bmp"] {
+            let path = std::env::temp_dir().join(format!(
+                "doryen-extra-image-{}.{}",
+                std::process::id(),
+                extension
+            ));
+            image.save_to_file(&path).unwrap();
+            let loaded = Image::load_from_file(&path).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded, image);
+            assert_eq!(Image::load_from_memory(&bytes).unwrap(), image);
+        }
+        assert_eq!(
+            Image::load_from_memory(b"not an image").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_inconsistent_images() {
+        let mut image = Image::new_with_color(USize::new(3, 2), Color::RED);
+        image.set_key_color(Some(Color::RED));
+        let json = serde_json::to_value(&image).unwrap();
+        assert_eq!(
+            serde_json::from_value::<Image>(json.clone()).unwrap(),
+            image
+        );
+
+        let mut short = json.clone();
+        short["pixels"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<Image>(short).is_err());
+
+        let mut too_wide = json;
+        too_wide["width"] = (u64::from(u32::MAX) + 1).into();
+        assert!(serde_json::from_value::<Image>(too_wide).is_err());
+    }
+}
@@ -61,36 +61,99 @@
 //! ```
 //! in your `Cargo.toml` file, which removes the default `doryen` feature.
 //!
+//! ## `compression`
+//!
+//! With this feature enabled, the buffers of the [`zip`] toolkit can be saved to and loaded from
+//! gzip compressed files, using the [`flate2`] crate, which is also the format libtcod uses.
+//!
+//! ## `deterministic-math`
+//!
+//! With this feature enabled, the transcendental functions used by the noise generators, the
+//! gaussian random number distributions, the height map and the map generators are computed by
+//! the [`libm`] crate rather than the platform's math library, so they give bit-identical results
+//! on every platform and with every compiler version, at some cost in speed. Enable it if worlds
+//! generated from a seed must be the same everywhere, e.g. for multiplayer games or for tests
+//! comparing generated output.
+//!
+//! ## `glam_support`
+//!
+//! With this feature enabled, the position and size types can be converted to and from the
+//! corresponding vector types of the [`glam`] crate, e.g. `Position` to and from `glam::IVec2`.
+//! Any [`glam`] release from 0.24 to 0.30 works, so the feature can share the version a project
+//! already uses, and none of them needs a newer compiler than the crate's minimum of Rust 1.73.
+//!
+//! ## `image_io`
+//!
+//! With this feature enabled, an `Image` can be loaded from and saved to PNG and BMP files, and
+//! converted to and from the `RgbaImage` type of the [`image`] crate.
+//!
 //! ## `libtcod-compat`
 //!
 //! This feature restores (on a best-effort basis) the functionality of the original
 //! `libtcod` library, where it has been modified. At the time of writing, the only change that
 //! will happen is to the float generation of the `ComplementaryMultiplyWithCarry` RNG algorithm.
 //!
+//! ## `mint_support`
+//!
+//! With this feature enabled, the position and size types can be converted to and from the
+//! [`mint`] crate's `Point2`/`Vector2` (and `Point3`/`Vector3` for the 3D types), which lets them
+//! interoperate with any math library that supports [`mint`].
+//!
+//! ## `parallel`
+//!
+//! With this feature enabled, some operations spread their work out over multiple threads using
+//! the [`rayon`] crate, always with results identical to the ones computed without the feature:
+//!
+//! * the height map operations that work on every cell independently, namely `clamp`,
+//!   `normalize`, `lerp`, `terrace`, `quantize` and `slope_map_into`, as well as
+//!   `thermal_erosion` and the chunks made by `FbmChunkGenerator`;
+//! * `FovMap::compute_combined_fov`, which computes the field of view of each origin on its own
+//!   thread.
+//!
+//! Operations that share a noise generator between threads have separate `par_` variants, which
+//! require the noise algorithm to be `Sync`: `HeightMap::par_add_fbm`, `par_add_fbm_3d` and
+//! `par_scale_fbm`, and `Noise::par_fill_2d`. The other variants, and everything else, have the
+//! same bounds with or without the feature. `hydraulic_erosion` and the other operations that draw
+//! from a `Random` generator always run on the calling thread, since the order of the draws
+//! decides their result.
+//!
 //! ## `rng_support`
 //!
 //! With this feature enabled, the [`Random`] struct implements [`rand_core::RngCore`] and
 //! [`rand_core::SeedableRng`], which lets it be used in any place that accepts the `rand` crate RNGs.
 //!
+//! ## `simd`
+//!
+//! With this feature enabled, the `Perlin` and `Simplex` algorithms evaluate 2D noise for eight
+//! coordinates at once with SIMD instructions, using the [`wide`] crate, which speeds up bulk
+//! generation with `Noise::fill_2d` and `NoiseMapBuilder`. The results are always identical to the
+//! ones computed without the feature.
+//!
 //! ## `serialization`
 //!
 //! With this feature enabled, all types for which it makes sense to serialize will implement
 //! [`serde::ser::Serialize`] and [`serde::de::Deserialize`]. NOTE: More types may get implementations
 //! for this in the future.
 //!
+//! This includes the internal state of `Random` and `Noise`, so a generator saved in the middle of
+//! generating a world picks up exactly where it left off when it's loaded again.
+//!
 //! # Missing Features / Toolkits
 //!
 //! The following toolkits from [`libtcod`] have not yet been converted, with possible reason given in parenthesis:
-//! * `bsp` toolkit: 2D Binary Space Partition
-//! * `fov` toolkit: Easily calculate the potential visible set of map cells from the player position
-//! * `image` toolkit: Some image manipulation utilities (undecided on whether to convert this one; other crates may already serve this purpose)
 //! * `list` toolkit: A fast, lightweight and generic container, that provides array, list and stack paradigms (use `Vec` instead)
-//! * `namegen` toolkit: Allows one to generate random names out of custom made syllable sets (parts requires `parse` toolkit)
-//! * `parse` toolkit: An easy way to parse complex text configuration files
 //!
 //! [`libtcod`]: https://github.com/libtcod/libtcod
 //! [`doryen-rs`]: https://crates.io/crates/doryen-rs
 //! [`tcod`]: https://crates.io/crates/tcod
+//! [`flate2`]: https://crates.io/crates/flate2
+//! [`glam`]: https://crates.io/crates/glam
+//! [`image`]: https://crates.io/crates/image
+//! [`libm`]: https://crates.io/crates/libm
+//! [`mint`]: https://crates.io/crates/mint
+//! [`rayon`]: https://crates.io/crates/rayon
+//! [`wide`]: https://crates.io/crates/wide
+//! [`zip`]: ./zip/index.html
 //!
 //! [`Random`]: ./random/struct.Random.html
 //! [`rand_core::RngCore`]: ../rand_core/trait.RngCore.html
@@ -128,14 +191,14 @@
 // Deny (don't do this)
 #![deny(clippy::cast_lossless)]
 #![deny(clippy::default_trait_access)]
-#![deny(clippy::empty_enum)]
+#![deny(clippy::empty_enums)]
 #![deny(clippy::enum_glob_use)]
 #![deny(clippy::expl_impl_clone_on_copy)]
 #![deny(clippy::explicit_into_iter_loop)]
 #![deny(clippy::explicit_iter_loop)]
-#![deny(clippy::filter_map)]
+#![deny(clippy::manual_filter_map)]
 #![deny(clippy::filter_map_next)]
-#![deny(clippy::find_map)]
+#![deny(clippy::manual_find_map)]
 #![deny(clippy::if_not_else)]
 #![deny(clippy::invalid_upcast_comparisons)]
 #![deny(clippy::items_after_statements)]
@@ -158,25 +221,53 @@
 // Warn (try not to do this)
 //#![warn(clippy::must_use_candidate)]
 #![deny(clippy::new_without_default)]
-#![warn(clippy::pub_enum_variant_names)]
 #![warn(clippy::shadow_unrelated)]
 #![warn(clippy::similar_names)]
 #![warn(clippy::too_many_lines)]
+// Tests are written for clarity first
+#![cfg_attr(
+    test,
+    allow(
+        clippy::manual_div_ceil,
+        clippy::shadow_unrelated,
+        clippy::similar_names
+    )
+)]
 
 #[macro_use]
 mod util;
 
+mod math;
+
 mod base;
 pub use base::*;
 
+mod error;
+pub use error::Error;
+
 #[cfg(feature = "doryen")]
 pub mod extenders;
 #[cfg(feature = "doryen")]
 pub mod extensions;
 
 pub mod bresenham;
+pub mod bsp;
 pub mod color;
+pub mod console;
+pub mod fov;
+pub mod grid;
 
 pub mod heightmap;
+pub mod image;
+pub mod mapgen;
+pub mod namegen;
 pub mod noise;
+pub mod parser;
+pub mod path;
+pub mod prelude;
 pub mod random;
+pub mod raster;
+pub mod text;
+pub mod voronoi;
+pub mod worldgen;
+pub mod zip;
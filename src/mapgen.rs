@@ -0,0 +1,135 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Map generation
+//!
+//! Procedural generators for dungeon and cave maps.
+//!
+//! The generators produce a `Grid<bool>` walkability map, where `true` is a floor cell and
+//! `false` is a wall. They take a `Random` to draw from, so the same seed always gives the same
+//! map.
+
+mod bsp_dungeon;
+mod cellular;
+mod corridors;
+mod drunkard;
+mod poisson;
+mod wfc;
+
+pub use bsp_dungeon::{BspDungeon, Dungeon};
+pub use cellular::CellularAutomata;
+pub use corridors::{delaunay_edges, minimum_spanning_tree, plan_corridors};
+pub use drunkard::DrunkardsWalk;
+pub use poisson::PoissonDisk;
+pub use wfc::{OverlappingModel, TiledModel};
+
+use crate::grid::Grid;
+use crate::Position;
+
+/// Returns the 4-connected regions of floor (`true`) cells in `map`, each as a list of
+/// positions, in the order their first cell appears in row-major order.
+pub fn floor_regions(map: &Grid<bool>) -> Vec<Vec<Position>> {
+    let mut visited = Grid::new(map.width(), map.height(), false);
+    let mut regions = Vec::new();
+
+    for (start, &floor) in map.enumerate() {
+        if !floor || visited[start] {
+            continue;
+        }
+
+        let mut region = Vec::new();
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(position) = stack.pop() {
+            region.push(position);
+            for neighbor in position.neighbors4() {
+                if map.get(neighbor) == Some(&true) && !visited[neighbor] {
+                    visited[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// Turns every floor cell that isn't part of the largest 4-connected floor region of `map` into
+/// a wall, so that every remaining floor cell can be reached from every other one.
+pub fn keep_largest_region(map: &mut Grid<bool>) {
+    let mut regions = floor_regions(map);
+    let largest = regions
+        .iter()
+        .enumerate()
+        .max_by_key(|(i, region)| (region.len(), std::cmp::Reverse(*i)))
+        .map(|(i, _)| i);
+
+    for (i, region) in regions.iter_mut().enumerate() {
+        if Some(i) != largest {
+            for &position in region.iter() {
+                map[position] = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions() {
+        // ##..
+        // #..#
+        // ##.#
+        // .###
+        let mut map = Grid::new_with_values(
+            4,
+            4,
+            vec![
+                false, false, true, true, //
+                false, true, true, false, //
+                false, false, true, false, //
+                true, false, false, false,
+            ],
+        );
+        let regions = floor_regions(&map);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].len(), 5);
+        assert_eq!(regions[1], vec![Position::new(0, 3)]);
+
+        keep_largest_region(&mut map);
+        assert!(!map[Position::new(0, 3)]);
+        assert_eq!(map.iter().filter(|&&floor| floor).count(), 5);
+    }
+}
@@ -0,0 +1,347 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::bsp::Bsp;
+use crate::grid::Grid;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{Position, Rectangle, USize};
+
+/// A dungeon generator based on binary space partitioning, like the one in libtcod's samples.
+///
+/// The map is split recursively into smaller and smaller parts, a room is carved out in each
+/// leaf of the resulting tree that isn't entirely on the border of the map, and the two halves
+/// of every split are then connected with a corridor, from the bottom of the tree up. The border
+/// of the map is never dug out, and every room can be reached from every other one.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::USize;
+/// # use doryen_extra::mapgen::BspDungeon;
+/// # use doryen_extra::random::Random;
+/// let mut random = Random::new_mt_from_seed(42);
+/// let dungeon = BspDungeon::default().generate(USize::new(80, 50), &mut random);
+/// assert!(dungeon.rooms.len() > 4);
+/// assert!(dungeon.map[dungeon.rooms[0].center()]);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct BspDungeon {
+    /// The maximum number of times the map is split recursively
+    pub depth: u32,
+    /// The minimum width and height of a room
+    pub min_room_size: u32,
+    /// The maximum ratio between the longer and the shorter side of a part of the map before it
+    /// is always split across its longer side
+    pub max_ratio: f32,
+    /// Whether rooms get a random size within their part of the map, rather than filling it
+    pub random_room_size: bool,
+}
+
+impl Default for BspDungeon {
+    /// Returns a generator with a depth of 8, rooms of at least 4 by 4 cells with random sizes,
+    /// and a maximum ratio of 1.5.
+    fn default() -> Self {
+        Self {
+            depth: 8,
+            min_room_size: 4,
+            max_ratio: 1.5,
+            random_room_size: true,
+        }
+    }
+}
+
+/// A dungeon generated by `BspDungeon`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Dungeon {
+    /// The walkability map, where `true` is a floor cell and `false` is a wall
+    pub map: Grid<bool>,
+    /// The rooms, in the order of the leaves of `tree`, from left to right; leaves that are
+    /// entirely on the border of the map have no room
+    pub rooms: Vec<Rectangle>,
+    /// The cells dug out for each corridor; cells that were already floor aren't included
+    pub corridors: Vec<Vec<Position>>,
+    /// The partition of the map the dungeon was built on
+    pub tree: Bsp,
+}
+
+impl BspDungeon {
+    /// Generates a dungeon of the given size.
+    pub fn generate<A: Algorithm>(&self, size: USize, random: &mut Random<A>) -> Dungeon {
+        let mut tree = Bsp::new(Rectangle::new(Position::new(0, 0), size));
+        let split_size = self.min_room_size + 1;
+        tree.split_recursive(
+            random,
+            self.depth,
+            USize::new(split_size, split_size),
+            self.max_ratio,
+            self.max_ratio,
+        );
+
+        let mut carver = Carver {
+            generator: self,
+            random,
+            map: Grid::new(size.width as usize, size.height as usize, false),
+            rooms: Vec::new(),
+            corridors: Vec::new(),
+        };
+        carver.carve(&tree);
+
+        Dungeon {
+            map: carver.map,
+            rooms: carver.rooms,
+            corridors: carver.corridors,
+            tree,
+        }
+    }
+}
+
+struct Carver<'a, A: Algorithm> {
+    generator: &'a BspDungeon,
+    random: &'a mut Random<A>,
+    map: Grid<bool>,
+    rooms: Vec<Rectangle>,
+    corridors: Vec<Vec<Position>>,
+}
+
+// An inclusive range of cells, as `(min_x, min_y, max_x, max_y)`.
+type Bounds = (i32, i32, i32, i32);
+
+impl<A: Algorithm> Carver<'_, A> {
+    // Carves the rooms of the node's subtree and connects them, returning the bounds of
+    // everything that was dug out, or `None` if there was no room for anything.
+    fn carve(&mut self, node: &Bsp) -> Option<Bounds> {
+        match (node.left(), node.right()) {
+            (Some(left), Some(right)) => match (self.carve(left), self.carve(right)) {
+                (Some(left), Some(right)) => {
+                    self.connect(node.is_horizontal(), left, right);
+                    Some((
+                        left.0.min(right.0),
+                        left.1.min(right.1),
+                        left.2.max(right.2),
+                        left.3.max(right.3),
+                    ))
+                }
+                (left, right) => left.or(right),
+            },
+            _ => self.carve_room(node.rectangle()),
+        }
+    }
+
+    // Carves a room within `area`, unless the area only covers the border of the map.
+    fn carve_room(&mut self, area: Rectangle) -> Option<Bounds> {
+        let Rectangle { position, size } = area;
+        let map_size = self.map.size();
+        let mut min_x = position.x + 1;
+        let mut min_y = position.y + 1;
+        let mut max_x = (position.x + size.width as i32 - 1).min(map_size.width as i32 - 2);
+        let mut max_y = (position.y + size.height as i32 - 1).min(map_size.height as i32 - 2);
+        if max_x < min_x || max_y < min_y {
+            return None;
+        }
+        if self.generator.random_room_size {
+            let room_size = self.generator.min_room_size as i32;
+            min_x = self.get_between(min_x, (max_x - room_size + 1).max(min_x));
+            min_y = self.get_between(min_y, (max_y - room_size + 1).max(min_y));
+            max_x = self.get_between((min_x + room_size - 1).min(max_x), max_x);
+            max_y = self.get_between((min_y + room_size - 1).min(max_y), max_y);
+        }
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                self.map[Position::new(x, y)] = true;
+            }
+        }
+        self.rooms.push(Rectangle::new_from_raw(
+            min_x,
+            min_y,
+            (max_x - min_x + 1) as u32,
+            (max_y - min_y + 1) as u32,
+        ));
+
+        Some((min_x, min_y, max_x, max_y))
+    }
+
+    // Digs a corridor between the dug out areas on either side of a split; `horizontal` tells
+    // whether `left` is above `right`, rather than to the left of it.
+    fn connect(&mut self, horizontal: bool, left: Bounds, right: Bounds) {
+        // Work in coordinates where `left` is to the left of `right`, swapping the axes for
+        // horizontal splits.
+        let flip = |(x, y): (i32, i32)| if horizontal { (y, x) } else { (x, y) };
+        let (_, left_min_y) = flip((left.0, left.1));
+        let (left_max_x, left_max_y) = flip((left.2, left.3));
+        let (right_min_x, right_min_y) = flip((right.0, right.1));
+        let (_, right_max_y) = flip((right.2, right.3));
+        let to_position = |(x, y): (i32, i32)| {
+            let (x, y) = flip((x, y));
+            Position::new(x, y)
+        };
+
+        let mut corridor = Vec::new();
+        if left_max_y < right_min_y || right_max_y < left_min_y {
+            // The areas don't overlap across the split, so a Z shaped corridor is needed.
+            let y1 = self.get_between(left_min_y, left_max_y);
+            let y2 = self.get_between(right_min_y, right_max_y);
+            let x = self.get_between(left_max_x + 1, right_min_x);
+            self.dig_until_floor(&mut corridor, (x - 1, y1), -1, &to_position);
+            for y in y1.min(y2)..=y1.max(y2) {
+                self.dig(&mut corridor, to_position((x, y)));
+            }
+            self.dig_until_floor(&mut corridor, (x + 1, y2), 1, &to_position);
+        } else {
+            let y = self.get_between(left_min_y.max(right_min_y), left_max_y.min(right_max_y));
+            self.dig_until_floor(&mut corridor, (right_min_x, y), 1, &to_position);
+            self.dig_until_floor(&mut corridor, (right_min_x - 1, y), -1, &to_position);
+        }
+
+        if !corridor.is_empty() {
+            self.corridors.push(corridor);
+        }
+    }
+
+    // Digs from `start` in the `step` direction along the x axis of the flipped coordinates,
+    // until reaching a floor cell or the edge of the map.
+    fn dig_until_floor<F: Fn((i32, i32)) -> Position>(
+        &mut self,
+        corridor: &mut Vec<Position>,
+        (mut x, y): (i32, i32),
+        step: i32,
+        to_position: &F,
+    ) {
+        loop {
+            let position = to_position((x, y));
+            match self.map.get(position) {
+                Some(false) => self.dig(corridor, position),
+                _ => break,
+            }
+            x += step;
+        }
+    }
+
+    // Returns a number between `min` and `max`, inclusive, every number being equally likely
+    // whatever the generator's distribution.
+    fn get_between(&mut self, min: i32, max: i32) -> i32 {
+        let (min, max) = (min.min(max), min.max(max));
+        min + self
+            .random
+            .get_index((i64::from(max) - i64::from(min) + 1) as usize) as i32
+    }
+
+    fn dig(&mut self, corridor: &mut Vec<Position>, position: Position) {
+        if let Some(cell) = self.map.get_mut(position) {
+            if !*cell {
+                *cell = true;
+                corridor.push(position);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::floor_regions;
+    use crate::random::Distribution;
+
+    #[test]
+    fn connected_and_deterministic() {
+        let size = USize::new(60, 40);
+        for &random_room_size in &[true, false] {
+            let generator = BspDungeon {
+                random_room_size,
+                ..BspDungeon::default()
+            };
+            let dungeon = generator.generate(size, &mut Random::new_mt_from_seed(3));
+            assert_eq!(floor_regions(&dungeon.map).len(), 1);
+            assert_eq!(dungeon.rooms.len(), dungeon.tree.iter_leaves().count());
+            assert_eq!(dungeon.corridors.len(), dungeon.rooms.len() - 1);
+            for x in 0..60 {
+                assert!(!dungeon.map[Position::new(x, 0)]);
+                assert!(!dungeon.map[Position::new(x, 39)]);
+            }
+            assert_eq!(
+                dungeon,
+                generator.generate(size, &mut Random::new_mt_from_seed(3))
+            );
+        }
+    }
+
+    #[test]
+    fn parts_on_the_border_get_no_room() {
+        let generator = BspDungeon {
+            min_room_size: 1,
+            ..BspDungeon::default()
+        };
+        for &(width, height) in &[(1, 1), (2, 2), (3, 5), (12, 9), (31, 17)] {
+            for seed in 0..20 {
+                let mut random = Random::new_mt_from_seed(seed);
+                let dungeon = generator.generate(USize::new(width, height), &mut random);
+                if width < 3 || height < 3 {
+                    assert!(dungeon.rooms.is_empty());
+                    assert!(dungeon.map.iter().all(|&floor| !floor));
+                }
+                for room in &dungeon.rooms {
+                    assert!(room.size.width >= 1 && room.size.height >= 1);
+                    assert!(room.position.x >= 1 && room.position.y >= 1);
+                    assert!(room.position.x + (room.size.width as i32) < width as i32);
+                    assert!(room.position.y + (room.size.height as i32) < height as i32);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn works_with_any_distribution() {
+        let size = USize::new(60, 40);
+        for &distribution in &[
+            Distribution::Linear,
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut random = Random::new_mt_from_seed(0);
+            random.distribution = distribution;
+            let dungeon = BspDungeon::default().generate(size, &mut random);
+            assert_eq!(floor_regions(&dungeon.map).len(), 1);
+            for room in &dungeon.rooms {
+                assert!(room.size.width >= 1 && room.size.height >= 1);
+                assert!(room.position.x >= 1 && room.position.y >= 1);
+                assert!(room.position.x + (room.size.width as i32) < size.width as i32);
+                assert!(room.position.y + (room.size.height as i32) < size.height as i32);
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::grid::Grid;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{Position, USize};
+
+/// A cave generator based on cellular automata.
+///
+/// The map starts out as random noise, and is then smoothed by repeatedly applying birth and
+/// survival rules based on how many of each cell's eight neighbors are walls; cells outside the
+/// map count as walls, and the border of the map is always a wall.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::USize;
+/// # use doryen_extra::mapgen::CellularAutomata;
+/// # use doryen_extra::random::Random;
+/// let mut random = Random::new_mt_from_seed(42);
+/// let cave = CellularAutomata::default().generate(USize::new(60, 40), &mut random);
+/// assert_eq!(cave.width(), 60);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct CellularAutomata {
+    /// The probability, between 0 and 1, that a cell starts out as a wall
+    pub fill_probability: f32,
+    /// A floor cell turns into a wall when at least this many of its neighbors are walls
+    pub birth_limit: u8,
+    /// A wall stays a wall when at least this many of its neighbors are walls
+    pub survival_limit: u8,
+    /// The number of times the rules are applied
+    pub iterations: u32,
+    /// Whether to fill in every floor region except the largest one once the rules have been
+    /// applied, so the whole cave is connected
+    pub remove_disconnected: bool,
+}
+
+impl Default for CellularAutomata {
+    /// Returns the classic "4-5 rule" generator: 45% initial walls, four iterations, with
+    /// disconnected pockets removed.
+    fn default() -> Self {
+        Self {
+            fill_probability: 0.45,
+            birth_limit: 5,
+            survival_limit: 4,
+            iterations: 4,
+            remove_disconnected: true,
+        }
+    }
+}
+
+impl CellularAutomata {
+    /// Generates a cave of the given size.
+    pub fn generate<A: Algorithm>(&self, size: USize, random: &mut Random<A>) -> Grid<bool> {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        let is_border = |position: Position| {
+            position.x == 0
+                || position.y == 0
+                || position.x as usize == width - 1
+                || position.y as usize == height - 1
+        };
+
+        let mut map = Grid::new_with(width, height, |position| {
+            !is_border(position) && random.get_uniform_f32() >= self.fill_probability
+        });
+
+        for _ in 0..self.iterations {
+            map = Grid::new_with(width, height, |position| {
+                if is_border(position) {
+                    return false;
+                }
+
+                let walls = position
+                    .neighbors8()
+                    .filter(|&neighbor| !map.get(neighbor).copied().unwrap_or(false))
+                    .count();
+                if map[position] {
+                    walls < usize::from(self.birth_limit)
+                } else {
+                    walls < usize::from(self.survival_limit)
+                }
+            });
+        }
+
+        if self.remove_disconnected {
+            super::keep_largest_region(&mut map);
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic_and_connected() {
+        let generator = CellularAutomata::default();
+        let size = USize::new(40, 30);
+        let cave = generator.generate(size, &mut Random::new_mt_from_seed(7));
+        assert_eq!(
+            cave,
+            generator.generate(size, &mut Random::new_mt_from_seed(7))
+        );
+
+        assert_eq!(super::super::floor_regions(&cave).len(), 1);
+        assert!((0..40).all(|x| !cave[Position::new(x, 0)] && !cave[Position::new(x, 29)]));
+        let floor = cave.iter().filter(|&&floor| floor).count();
+        assert!(floor > 100, "only {} floor cells", floor);
+    }
+
+    #[test]
+    fn full_walls() {
+        let generator = CellularAutomata {
+            fill_probability: 1.0,
+            ..CellularAutomata::default()
+        };
+        let cave = generator.generate(USize::new(10, 10), &mut Random::new_mt_from_seed(1));
+        assert!(cave.iter().all(|&floor| !floor));
+    }
+}
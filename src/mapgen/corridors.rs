@@ -0,0 +1,341 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::math;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::FPosition;
+
+/// Returns the edges of the Delaunay triangulation of `points`, as pairs of indices `(a, b)`
+/// with `a < b`, in ascending order.
+///
+/// The triangulation connects every point to its natural neighbors without any edges crossing,
+/// which makes it a good candidate set of corridors between room centers. Points that all lie on
+/// a line are connected in order along it, and duplicate points are connected to their first
+/// occurrence.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::FPosition;
+/// # use doryen_extra::mapgen::delaunay_edges;
+/// let points = [
+///     FPosition::new(0.0, 0.0),
+///     FPosition::new(10.0, 0.0),
+///     FPosition::new(0.0, 10.0),
+///     FPosition::new(10.0, 11.0),
+/// ];
+/// // The shorter diagonal is the one that gets picked.
+/// assert_eq!(delaunay_edges(&points), vec![(0, 1), (0, 2), (1, 2), (1, 3), (2, 3)]);
+/// ```
+pub fn delaunay_edges(points: &[FPosition]) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+
+    // Duplicates would break the triangulation, so only the first occurrence takes part in it.
+    let mut unique = Vec::new();
+    for (index, point) in points.iter().enumerate() {
+        match unique
+            .iter()
+            .find(|&&other: &&usize| points[other] == *point)
+        {
+            Some(&first) => edges.push((first, index)),
+            None => unique.push(index),
+        }
+    }
+
+    let vertices: Vec<(f64, f64)> = unique
+        .iter()
+        .map(|&index| (f64::from(points[index].x), f64::from(points[index].y)))
+        .collect();
+    let triangles = triangulate(&vertices);
+    if triangles.is_empty() {
+        // Fewer than three points, or all of them on a line.
+        let mut order: Vec<usize> = (0..vertices.len()).collect();
+        order.sort_by(|&a, &b| {
+            vertices[a]
+                .partial_cmp(&vertices[b])
+                .expect("points must not be NaN")
+        });
+        edges.extend(
+            order
+                .windows(2)
+                .map(|pair| (unique[pair[0]], unique[pair[1]])),
+        );
+    } else {
+        for triangle in triangles {
+            for &(a, b) in &[
+                (triangle[0], triangle[1]),
+                (triangle[1], triangle[2]),
+                (triangle[2], triangle[0]),
+            ] {
+                edges.push((unique[a], unique[b]));
+            }
+        }
+    }
+
+    for edge in &mut edges {
+        if edge.0 > edge.1 {
+            *edge = (edge.1, edge.0);
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+
+    edges
+}
+
+/// Returns the edges of a minimum spanning tree of the graph made up of `points` and `edges`,
+/// using the distance between points as the weight of an edge, in ascending order.
+///
+/// If the graph isn't connected, a minimum spanning tree of each connected part is returned.
+///
+/// # Panics
+///
+/// If an edge refers to a point that doesn't exist.
+pub fn minimum_spanning_tree(
+    points: &[FPosition],
+    edges: &[(usize, usize)],
+) -> Vec<(usize, usize)> {
+    let length = |&(a, b): &(usize, usize)| {
+        let d = points[a] - points[b];
+        math::hypot(d.x, d.y)
+    };
+    let mut sorted = edges.to_vec();
+    sorted.sort_by(|a, b| {
+        length(a)
+            .partial_cmp(&length(b))
+            .expect("points must not be NaN")
+    });
+
+    // Kruskal's algorithm, using a union-find structure with path halving.
+    let mut parents: Vec<usize> = (0..points.len()).collect();
+    let mut tree = Vec::new();
+    for (a, b) in sorted {
+        let root_a = find_root(&mut parents, a);
+        let root_b = find_root(&mut parents, b);
+        if root_a != root_b {
+            parents[root_a] = root_b;
+            tree.push((a.min(b), a.max(b)));
+        }
+    }
+    tree.sort_unstable();
+
+    tree
+}
+
+/// Plans which rooms to connect with corridors, given their centers.
+///
+/// Every room is reachable through a minimum spanning tree of the Delaunay triangulation of the
+/// centers, which keeps corridors short. On top of that, each of the remaining Delaunay edges is
+/// added with a probability of `extra_edge_probability`, so the dungeon gets a few loops instead
+/// of being a tree of dead ends.
+///
+/// Returns the corridors as pairs of indices `(a, b)` with `a < b`, in ascending order.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::FPosition;
+/// # use doryen_extra::mapgen::plan_corridors;
+/// # use doryen_extra::random::Random;
+/// let mut random = Random::new_mt_from_seed(42);
+/// let rooms: Vec<_> = (0..10)
+///     .map(|i| FPosition::new((i * 7 % 10) as f32 * 8.0, (i * 3 % 10) as f32 * 5.0))
+///     .collect();
+/// let corridors = plan_corridors(&rooms, 0.15, &mut random);
+/// assert!(corridors.len() >= rooms.len() - 1);
+/// ```
+pub fn plan_corridors<A: Algorithm>(
+    centers: &[FPosition],
+    extra_edge_probability: f32,
+    random: &mut Random<A>,
+) -> Vec<(usize, usize)> {
+    let edges = delaunay_edges(centers);
+    let mut corridors = minimum_spanning_tree(centers, &edges);
+    for edge in edges {
+        if corridors.binary_search(&edge).is_err()
+            && random.get_uniform_f32() < extra_edge_probability
+        {
+            corridors.push(edge);
+        }
+    }
+    corridors.sort_unstable();
+
+    corridors
+}
+
+// Triangulates distinct vertices with the Bowyer-Watson algorithm, returning triangles as
+// counter-clockwise triples of vertex indices. Returns no triangles if the vertices are collinear.
+fn triangulate(vertices: &[(f64, f64)]) -> Vec<[usize; 3]> {
+    if vertices.len() < 3 {
+        return Vec::new();
+    }
+
+    let (mut min_x, mut min_y) = vertices[0];
+    let (mut max_x, mut max_y) = vertices[0];
+    for &(x, y) in vertices {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let delta = (max_x - min_x).max(max_y - min_y).max(1.0) * 100.0;
+    let center_x = (min_x + max_x) / 2.0;
+    let center_y = (min_y + max_y) / 2.0;
+
+    // The super triangle encloses every vertex, and is removed again at the end.
+    let n = vertices.len();
+    let mut all = vertices.to_vec();
+    all.push((center_x - delta, center_y - delta));
+    all.push((center_x + delta, center_y - delta));
+    all.push((center_x, center_y + delta));
+    let mut triangles = vec![[n, n + 1, n + 2]];
+
+    for (index, &point) in vertices.iter().enumerate() {
+        let (bad, good): (Vec<_>, Vec<_>) = triangles
+            .into_iter()
+            .partition(|triangle| in_circumcircle(&all, *triangle, point));
+        triangles = good;
+
+        // The boundary of the hole left by the bad triangles is made up of the edges that
+        // only belong to one of them.
+        let mut boundary: Vec<(usize, usize)> = Vec::new();
+        for triangle in &bad {
+            for &(a, b) in &[
+                (triangle[0], triangle[1]),
+                (triangle[1], triangle[2]),
+                (triangle[2], triangle[0]),
+            ] {
+                if let Some(shared) = boundary.iter().position(|&(c, d)| c == b && d == a) {
+                    boundary.swap_remove(shared);
+                } else {
+                    boundary.push((a, b));
+                }
+            }
+        }
+        triangles.extend(boundary.into_iter().map(|(a, b)| [a, b, index]));
+    }
+
+    triangles.retain(|triangle| triangle.iter().all(|&vertex| vertex < n));
+
+    triangles
+}
+
+fn find_root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+
+    node
+}
+
+fn orientation(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn in_circumcircle(vertices: &[(f64, f64)], triangle: [usize; 3], point: (f64, f64)) -> bool {
+    let [a, b, c] = triangle;
+    let (ax, ay) = (vertices[a].0 - point.0, vertices[a].1 - point.1);
+    let (bx, by) = (vertices[b].0 - point.0, vertices[b].1 - point.1);
+    let (cx, cy) = (vertices[c].0 - point.0, vertices[c].1 - point.1);
+    let determinant = (ax * ax + ay * ay) * (bx * cy - cx * by)
+        - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay);
+
+    if orientation(vertices[a], vertices[b], vertices[c]) > 0.0 {
+        determinant > 0.0
+    } else {
+        determinant < 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_connected(count: usize, edges: &[(usize, usize)]) -> bool {
+        let mut reached = vec![false; count];
+        let mut stack = vec![0];
+        reached[0] = true;
+        while let Some(node) = stack.pop() {
+            for &(a, b) in edges {
+                for &(from, to) in &[(a, b), (b, a)] {
+                    if from == node && !reached[to] {
+                        reached[to] = true;
+                        stack.push(to);
+                    }
+                }
+            }
+        }
+        reached.into_iter().all(|r| r)
+    }
+
+    #[test]
+    fn triangulation_of_random_points() {
+        let mut random = Random::new_mt_from_seed(11);
+        let points: Vec<_> = (0..40)
+            .map(|_| FPosition::new(random.get_f32(0.0, 80.0), random.get_f32(0.0, 50.0)))
+            .collect();
+        let edges = delaunay_edges(&points);
+
+        // A planar triangulation has at most 3n - 6 edges.
+        assert!(edges.len() <= 3 * points.len() - 6);
+        assert!(is_connected(points.len(), &edges));
+
+        let tree = minimum_spanning_tree(&points, &edges);
+        assert_eq!(tree.len(), points.len() - 1);
+        assert!(is_connected(points.len(), &tree));
+        assert!(tree.iter().all(|edge| edges.contains(edge)));
+
+        let corridors = plan_corridors(&points, 0.5, &mut random);
+        assert!(tree.iter().all(|edge| corridors.contains(edge)));
+        assert!(corridors.len() > tree.len());
+        assert!(corridors.iter().all(|edge| edges.contains(edge)));
+    }
+
+    #[test]
+    fn degenerate_inputs() {
+        assert!(delaunay_edges(&[]).is_empty());
+        assert!(delaunay_edges(&[FPosition::new(1.0, 1.0)]).is_empty());
+
+        let line = [
+            FPosition::new(4.0, 0.0),
+            FPosition::new(0.0, 0.0),
+            FPosition::new(2.0, 0.0),
+            FPosition::new(0.0, 0.0),
+        ];
+        assert_eq!(delaunay_edges(&line), vec![(0, 2), (1, 2), (1, 3)]);
+        assert_eq!(
+            minimum_spanning_tree(&line, &delaunay_edges(&line)).len(),
+            3
+        );
+    }
+}
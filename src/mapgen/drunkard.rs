@@ -0,0 +1,192 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::grid::Grid;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{Direction, Position, USize};
+
+/// A cave digger based on the drunkard's walk.
+///
+/// Starting from solid rock, one or more walkers stumble around the map, digging out every cell
+/// they step on, until the requested share of the map has been turned into floor. The border of
+/// the map is never dug out.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::USize;
+/// # use doryen_extra::mapgen::DrunkardsWalk;
+/// # use doryen_extra::random::Random;
+/// let mut random = Random::new_mt_from_seed(42);
+/// let cave = DrunkardsWalk::default().generate(USize::new(60, 40), &mut random);
+/// let floor = cave.iter().filter(|&&floor| floor).count();
+/// assert!(floor >= 58 * 38 * 4 / 10);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct DrunkardsWalk {
+    /// The share, between 0 and 1, of the map's inside (everything but the border) to dig out
+    pub floor_percentage: f32,
+    /// The number of walkers, which take turns taking a step. The first walker starts in the
+    /// center of the map, and the others start at random cells that have already been dug out.
+    pub walkers: u32,
+    /// The probability, between 0 and 1, that a step is taken towards the center of the map
+    /// rather than in a random direction, which keeps the cave from hugging the edges
+    pub center_weight: f32,
+}
+
+impl Default for DrunkardsWalk {
+    /// Returns a generator that digs out 40% of the map with a single walker and a slight pull
+    /// towards the center.
+    fn default() -> Self {
+        Self {
+            floor_percentage: 0.4,
+            walkers: 1,
+            center_weight: 0.1,
+        }
+    }
+}
+
+impl DrunkardsWalk {
+    /// Generates a cave of the given size.
+    pub fn generate<A: Algorithm>(&self, size: USize, random: &mut Random<A>) -> Grid<bool> {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        let mut map = Grid::new(width, height, false);
+        if width < 3 || height < 3 {
+            return map;
+        }
+
+        let max_x = width as i32 - 2;
+        let max_y = height as i32 - 2;
+        let inside = (width - 2) * (height - 2);
+        let target = ((inside as f32 * self.floor_percentage.clamp(0.0, 1.0)).ceil() as usize)
+            .clamp(1, inside);
+        let center = Position::new((width / 2) as i32, (height / 2) as i32);
+
+        let mut walkers = vec![center];
+        map[center] = true;
+        let mut floor_cells = vec![center];
+        while walkers.len() < self.walkers.max(1) as usize {
+            let start = floor_cells[random.get_index(floor_cells.len())];
+            walkers.push(start);
+        }
+
+        // Every walker gets a generous number of steps; the limit only matters if the walk
+        // somehow gets stuck.
+        let max_steps = inside * 100;
+        let walker_count = walkers.len();
+        for step in 0..max_steps {
+            if floor_cells.len() >= target {
+                break;
+            }
+
+            let walker = &mut walkers[step % walker_count];
+            let direction = if random.get_uniform_f32() < self.center_weight {
+                Direction::from_delta(center - *walker)
+            } else {
+                None
+            }
+            .unwrap_or_else(|| Direction::CARDINAL[random.get_index(Direction::CARDINAL.len())]);
+
+            let next = *walker + direction.offset();
+            *walker = Position::new(next.x.clamp(1, max_x), next.y.clamp(1, max_y));
+            if !map[*walker] {
+                map[*walker] = true;
+                floor_cells.push(*walker);
+            }
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+
+    #[test]
+    fn deterministic_connected_and_bounded() {
+        let generator = DrunkardsWalk {
+            walkers: 3,
+            ..DrunkardsWalk::default()
+        };
+        let size = USize::new(30, 20);
+        let cave = generator.generate(size, &mut Random::new_mt_from_seed(3));
+        assert_eq!(
+            cave,
+            generator.generate(size, &mut Random::new_mt_from_seed(3))
+        );
+
+        assert_eq!(super::super::floor_regions(&cave).len(), 1);
+        assert!((0..20).all(|y| !cave[Position::new(0, y)] && !cave[Position::new(29, y)]));
+        let floor = cave.iter().filter(|&&floor| floor).count();
+        assert_eq!(floor, (28.0 * 18.0 * 0.4_f32).ceil() as usize);
+    }
+
+    #[test]
+    fn tiny_maps() {
+        let mut random = Random::new_mt_from_seed(1);
+        let cave = DrunkardsWalk::default().generate(USize::new(2, 5), &mut random);
+        assert!(cave.iter().all(|&floor| !floor));
+
+        let full = DrunkardsWalk {
+            floor_percentage: 1.0,
+            ..DrunkardsWalk::default()
+        };
+        let dug_out = full.generate(USize::new(4, 4), &mut random);
+        assert_eq!(dug_out.iter().filter(|&&floor| floor).count(), 4);
+    }
+
+    #[test]
+    fn works_with_any_distribution() {
+        for &distribution in &[
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut random = Random::new_mt_from_seed(5);
+            random.distribution = distribution;
+            let generator = DrunkardsWalk {
+                walkers: 4,
+                ..DrunkardsWalk::default()
+            };
+            let cave = generator.generate(USize::new(30, 20), &mut random);
+            assert_eq!(super::super::floor_regions(&cave).len(), 1);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::math;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{FPosition, Rectangle, UPosition};
+use std::ops::RangeInclusive;
+
+/// Bridson's Poisson-disk sampling, for scattering points that are evenly spread out without
+/// clumping together, such as trees, monsters or loot.
+///
+/// Every point is at least `min_distance` away from every other point, and the area is filled
+/// until there's no room left for another one.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::Rectangle;
+/// # use doryen_extra::mapgen::PoissonDisk;
+/// # use doryen_extra::random::Random;
+/// let mut random = Random::new_mt_from_seed(42);
+/// let trees = PoissonDisk::new(3.0).sample(Rectangle::new_from_raw(0, 0, 40, 20), &mut random);
+/// for (i, a) in trees.iter().enumerate() {
+///     for b in &trees[i + 1..] {
+///         assert!((a.x - b.x).hypot(a.y - b.y) >= 3.0);
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct PoissonDisk {
+    /// The smallest allowed distance between two points
+    pub min_distance: f32,
+    /// The number of candidates tried around a point before giving up on it
+    pub attempts: u32,
+}
+
+impl PoissonDisk {
+    /// Returns a sampler with the given minimum distance, which tries 30 candidates around
+    /// each point.
+    ///
+    /// # Panics
+    ///
+    /// If `min_distance` isn't positive.
+    pub fn new(min_distance: f32) -> Self {
+        assert!(min_distance > 0.0, "min_distance must be positive");
+
+        Self {
+            min_distance,
+            attempts: 30,
+        }
+    }
+
+    /// Returns evenly spread out points within `area`, where every point is at least
+    /// `min_distance` away from every other point.
+    pub fn sample<A: Algorithm>(&self, area: Rectangle, random: &mut Random<A>) -> Vec<FPosition> {
+        let min_distance = self.min_distance;
+        self.sample_with_distance(area, min_distance, |_| min_distance, random)
+    }
+
+    /// Returns evenly spread out points within `area`, where the minimum distance between
+    /// points varies with the value of `heightmap`: a value of 0 or less gives `min_distance`
+    /// and a value of 1 or more gives `max_distance`, with a linear interpolation in between.
+    /// This allows dense forests in some regions and sparse ones in others.
+    ///
+    /// The height map is laid over `area`, with its first cell at the area's top-left corner.
+    ///
+    /// # Panics
+    ///
+    /// If the height map is smaller than `area`, or if `max_distance` is smaller than
+    /// `min_distance`.
+    pub fn sample_with_heightmap<A: Algorithm>(
+        &self,
+        area: Rectangle,
+        heightmap: &HeightMap,
+        max_distance: f32,
+        random: &mut Random<A>,
+    ) -> Vec<FPosition> {
+        assert!(
+            heightmap.width() >= area.size.width as usize
+                && heightmap.height() >= area.size.height as usize,
+            "the height map must cover the area"
+        );
+
+        let min_distance = self.min_distance;
+        self.sample_with_distance(
+            area,
+            max_distance,
+            |position| {
+                let value = height_at(heightmap, area, position).clamp(0.0, 1.0);
+                min_distance + (max_distance - min_distance) * value
+            },
+            random,
+        )
+    }
+
+    /// Returns evenly spread out points within `area`, like `sample`, leaving out the points
+    /// where the value of `heightmap` is outside `heights`. This keeps e.g. trees out of the
+    /// water and off the mountain tops.
+    ///
+    /// The height map is laid over `area`, with its first cell at the area's top-left corner.
+    ///
+    /// # Panics
+    ///
+    /// If the height map is smaller than `area`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Rectangle, UPosition};
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// # use doryen_extra::mapgen::PoissonDisk;
+    /// # use doryen_extra::random::Random;
+    /// let mut heightmap = HeightMap::new(20, 20);
+    /// heightmap.view_mut(Rectangle::new_from_raw(0, 0, 10, 20)).fill(1.0);
+    /// let mut random = Random::new_mt_from_seed(42);
+    /// let area = Rectangle::new_from_raw(0, 0, 20, 20);
+    /// let trees = PoissonDisk::new(2.0).sample_within_heights(area, &heightmap, 0.5..=1.0, &mut random);
+    /// assert!(!trees.is_empty());
+    /// assert!(trees.iter().all(|tree| tree.x < 10.0));
+    /// ```
+    pub fn sample_within_heights<A: Algorithm>(
+        &self,
+        area: Rectangle,
+        heightmap: &HeightMap,
+        heights: RangeInclusive<f32>,
+        random: &mut Random<A>,
+    ) -> Vec<FPosition> {
+        assert!(
+            heightmap.width() >= area.size.width as usize
+                && heightmap.height() >= area.size.height as usize,
+            "the height map must cover the area"
+        );
+
+        let mut points = self.sample(area, random);
+        points.retain(|&position| heights.contains(&height_at(heightmap, area, position)));
+
+        points
+    }
+
+    /// Returns evenly spread out points within `area`, where the minimum distance around each
+    /// point is given by the `distance` function. The function must return values between
+    /// `min_distance` and `max_distance`.
+    ///
+    /// # Panics
+    ///
+    /// If `max_distance` is smaller than `min_distance`.
+    pub fn sample_with_distance<A, F>(
+        &self,
+        area: Rectangle,
+        max_distance: f32,
+        distance: F,
+        random: &mut Random<A>,
+    ) -> Vec<FPosition>
+    where
+        A: Algorithm,
+        F: Fn(FPosition) -> f32,
+    {
+        assert!(
+            max_distance >= self.min_distance,
+            "max_distance must not be smaller than min_distance"
+        );
+        if area.size.area() == 0 {
+            return Vec::new();
+        }
+
+        // Since no two points are closer than the minimum distance, a grid cell with that
+        // distance as its diagonal holds at most one point.
+        let cell_size = self.min_distance / std::f32::consts::SQRT_2;
+        let left = area.position.x as f32;
+        let top = area.position.y as f32;
+        let right = left + area.size.width as f32;
+        let bottom = top + area.size.height as f32;
+        let columns = (area.size.width as f32 / cell_size).ceil() as usize;
+        let rows = (area.size.height as f32 / cell_size).ceil() as usize;
+        let cell_of = |position: FPosition| {
+            (
+                (((position.x - left) / cell_size) as usize).min(columns - 1),
+                (((position.y - top) / cell_size) as usize).min(rows - 1),
+            )
+        };
+        let reach = (max_distance / cell_size).ceil() as usize;
+
+        let mut cells: Vec<Option<usize>> = vec![None; columns * rows];
+        let mut points = Vec::new();
+        let mut active = Vec::new();
+
+        let first = loop {
+            let position = FPosition::new(
+                left + random.get_uniform_f32() * (right - left),
+                top + random.get_uniform_f32() * (bottom - top),
+            );
+            if position.x < right && position.y < bottom {
+                break position;
+            }
+        };
+        let (first_column, first_row) = cell_of(first);
+        cells[first_row * columns + first_column] = Some(0);
+        points.push(first);
+        active.push(0);
+
+        while !active.is_empty() {
+            let active_index = random.get_index(active.len());
+            let center = points[active[active_index]];
+            let radius = distance(center);
+
+            let mut found = false;
+            for _ in 0..self.attempts {
+                let angle = random.get_uniform_f32() * 2.0 * std::f32::consts::PI;
+                let length = radius + random.get_uniform_f32() * radius;
+                let (sin, cos) = math::sin_cos(angle);
+                let candidate = center + FPosition::new(cos * length, sin * length);
+                if candidate.x < left
+                    || candidate.x >= right
+                    || candidate.y < top
+                    || candidate.y >= bottom
+                {
+                    continue;
+                }
+
+                let candidate_radius = distance(candidate);
+                let (column, row) = cell_of(candidate);
+                let too_close = (row.saturating_sub(reach)..(row + reach + 1).min(rows))
+                    .flat_map(|r| {
+                        (column.saturating_sub(reach)..(column + reach + 1).min(columns))
+                            .map(move |c| r * columns + c)
+                    })
+                    .filter_map(|index| cells[index])
+                    .any(|other| {
+                        let other = points[other];
+                        let other_radius = distance(other);
+                        let d = candidate - other;
+                        math::hypot(d.x, d.y) < candidate_radius.max(other_radius)
+                    });
+                if too_close {
+                    continue;
+                }
+
+                cells[row * columns + column] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+
+            if !found {
+                active.swap_remove(active_index);
+            }
+        }
+
+        points
+    }
+}
+
+// Returns the value of the cell of `heightmap` that `position` is in, when the height map is
+// laid over `area`.
+fn height_at(heightmap: &HeightMap, area: Rectangle, position: FPosition) -> f32 {
+    let origin = FPosition::new(area.position.x as f32, area.position.y as f32);
+    let cell = (position - origin).trunc_u();
+    heightmap.value(UPosition::new(
+        cell.x.min(area.size.width - 1),
+        cell.y.min(area.size.height - 1),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+
+    fn assert_spaced(points: &[FPosition], distance: impl Fn(FPosition) -> f32) {
+        for (i, &a) in points.iter().enumerate() {
+            for &b in &points[i + 1..] {
+                let d = a - b;
+                assert!(d.x.hypot(d.y) >= distance(a).max(distance(b)));
+            }
+        }
+    }
+
+    #[test]
+    fn points_are_spaced_and_fill_the_area() {
+        let mut random = Random::new_mt_from_seed(7);
+        let area = Rectangle::new_from_raw(-10, 5, 50, 30);
+        let points = PoissonDisk::new(2.5).sample(area, &mut random);
+
+        assert_spaced(&points, |_| 2.5);
+        assert!(points
+            .iter()
+            .all(|p| p.x >= -10.0 && p.x < 40.0 && p.y >= 5.0 && p.y < 35.0));
+        // A maximal packing covers the area well; far fewer points would leave large gaps.
+        assert!(points.len() > 150, "only {} points", points.len());
+    }
+
+    #[test]
+    fn heightmap_controls_density() {
+        let mut random = Random::new_mt_from_seed(3);
+        let mut heightmap = HeightMap::new(40, 20);
+        for x in 20..40 {
+            for y in 0..20 {
+                heightmap.set_value(UPosition::new(x, y), 1.0);
+            }
+        }
+
+        let sampler = PoissonDisk::new(1.5);
+        let points = sampler.sample_with_heightmap(
+            Rectangle::new_from_raw(0, 0, 40, 20),
+            &heightmap,
+            5.0,
+            &mut random,
+        );
+        assert_spaced(&points, |p| if p.x >= 20.0 { 5.0 } else { 1.5 });
+
+        let dense = points.iter().filter(|p| p.x < 20.0).count();
+        let sparse = points.len() - dense;
+        assert!(dense > sparse * 4, "{} dense, {} sparse", dense, sparse);
+    }
+
+    #[test]
+    fn empty_area() {
+        let mut random = Random::new_mt_from_seed(1);
+        assert!(PoissonDisk::new(1.0)
+            .sample(Rectangle::new_from_raw(0, 0, 0, 10), &mut random)
+            .is_empty());
+    }
+
+    #[test]
+    fn works_with_any_distribution() {
+        for &distribution in &[
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut random = Random::new_mt_from_seed(5);
+            random.distribution = distribution;
+            let points =
+                PoissonDisk::new(2.0).sample(Rectangle::new_from_raw(10, 10, 30, 20), &mut random);
+            assert_spaced(&points, |_| 2.0);
+            assert!(points
+                .iter()
+                .all(|p| p.x >= 10.0 && p.x < 40.0 && p.y >= 10.0 && p.y < 30.0));
+            assert!(points.len() > 50, "only {} points", points.len());
+        }
+    }
+}
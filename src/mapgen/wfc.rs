@@ -0,0 +1,626 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::grid::Grid;
+use crate::math;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{Direction, Position, Rotation, Transform2, USize};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A simple tiled model for Wave Function Collapse.
+///
+/// The model is a set of weighted tiles together with rules for which tiles may be placed next
+/// to each other. Generating a grid places one tile in every cell such that every pair of
+/// neighboring tiles is allowed, choosing tiles in proportion to their weights.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Direction, Position, USize};
+/// # use doryen_extra::mapgen::TiledModel;
+/// # use doryen_extra::random::Random;
+/// let mut model = TiledModel::new();
+/// let land = model.add_tile('.', 3.0);
+/// let coast = model.add_tile(',', 1.0);
+/// let sea = model.add_tile('~', 3.0);
+/// for &direction in &Direction::CARDINAL {
+///     model.allow(land, direction, land);
+///     model.allow(land, direction, coast);
+///     model.allow(coast, direction, coast);
+///     model.allow(coast, direction, sea);
+///     model.allow(sea, direction, sea);
+/// }
+///
+/// let mut random = Random::new_mt_from_seed(1);
+/// let map = model.generate(USize::new(20, 10), &mut random, 10).unwrap();
+/// // Land never touches the sea directly
+/// for (position, &tile) in map.enumerate() {
+///     if tile == '.' {
+///         assert!(position.neighbors4().all(|n| map.get(n) != Some(&'~')));
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TiledModel<T> {
+    tiles: Vec<T>,
+    weights: Vec<f64>,
+    propagator: Propagator,
+}
+
+impl<T: Clone> TiledModel<T> {
+    /// Returns a new model without any tiles.
+    pub fn new() -> Self {
+        Self {
+            tiles: Vec::new(),
+            weights: Vec::new(),
+            propagator: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
+        }
+    }
+
+    /// Adds a tile with the given weight to the model and returns its index, which is used to
+    /// refer to it in `allow`.
+    ///
+    /// # Panics
+    ///
+    /// If `weight` isn't positive.
+    pub fn add_tile(&mut self, tile: T, weight: f32) -> usize {
+        assert!(weight > 0.0, "tile weights must be positive");
+        self.tiles.push(tile);
+        self.weights.push(f64::from(weight));
+        for adjacent in &mut self.propagator {
+            adjacent.push(Vec::new());
+        }
+
+        self.tiles.len() - 1
+    }
+
+    /// Allows the tile with index `b` to be placed next to the tile with index `a`, on its
+    /// `direction` side. The reverse relation is allowed as well.
+    ///
+    /// # Panics
+    ///
+    /// * If `direction` isn't a cardinal direction.
+    /// * If either index doesn't refer to a tile in the model.
+    pub fn allow(&mut self, a: usize, direction: Direction, b: usize) {
+        assert!(
+            a < self.tiles.len() && b < self.tiles.len(),
+            "tile index out of range"
+        );
+        let d = cardinal_index(direction);
+        if !self.propagator[d][a].contains(&b) {
+            self.propagator[d][a].push(b);
+            self.propagator[(d + 2) % 4][b].push(a);
+        }
+    }
+
+    /// Generates a grid of the given size, retrying up to `attempts` times if the solver runs
+    /// into a contradiction, i.e. a cell where no tile fits. Returns `None` if every attempt
+    /// failed.
+    pub fn generate<A: Algorithm>(
+        &self,
+        size: USize,
+        random: &mut Random<A>,
+        attempts: u32,
+    ) -> Option<Grid<T>> {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        let wave = solve(
+            width,
+            height,
+            &self.weights,
+            &self.propagator,
+            random,
+            attempts,
+        )?;
+
+        Some(Grid::new_with_values(
+            width,
+            height,
+            wave.into_iter()
+                .map(|tile| self.tiles[tile].clone())
+                .collect(),
+        ))
+    }
+}
+
+impl<T: Clone> Default for TiledModel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An overlapping model for Wave Function Collapse.
+///
+/// The model learns every `n` by `n` pattern of a sample grid, along with how often each one
+/// occurs. Generated grids are made up only of those patterns, overlapping each other, and so
+/// locally resemble the sample.
+#[derive(Clone, Debug)]
+pub struct OverlappingModel<T> {
+    n: usize,
+    patterns: Vec<Vec<T>>,
+    weights: Vec<f64>,
+    propagator: Propagator,
+}
+
+impl<T: Clone + Eq + Hash> OverlappingModel<T> {
+    /// Learns the patterns of `sample`.
+    ///
+    /// # Parameters
+    /// * `sample` - The grid to learn patterns from.
+    /// * `n` - The width and height of the patterns; 2 or 3 are the usual choices.
+    /// * `periodic_sample` - Whether patterns may wrap around the edges of the sample.
+    /// * `symmetry` - Whether the rotated and mirrored versions of each pattern are also used.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0, or the sample is too small to contain a single pattern.
+    pub fn new(sample: &Grid<T>, n: usize, periodic_sample: bool, symmetry: bool) -> Self {
+        assert!(n > 0, "the pattern size must be at least 1");
+        let (max_x, max_y) = if periodic_sample {
+            (sample.width(), sample.height())
+        } else {
+            (
+                (sample.width() + 1).saturating_sub(n),
+                (sample.height() + 1).saturating_sub(n),
+            )
+        };
+        assert!(
+            max_x > 0
+                && max_y > 0
+                && sample.width() >= n.min(sample.width())
+                && n <= sample.width().max(sample.height()),
+            "the sample is too small for the pattern size"
+        );
+
+        let orientations: Vec<Transform2> = if symmetry {
+            Rotation::ALL
+                .iter()
+                .flat_map(|&rotation| {
+                    [false, true]
+                        .iter()
+                        .map(move |&mirror| Transform2::new(Position::ORIGIN, rotation, mirror))
+                })
+                .collect()
+        } else {
+            vec![Transform2::IDENTITY]
+        };
+
+        let mut patterns = Vec::new();
+        let mut weights = Vec::new();
+        let mut indices = HashMap::new();
+        for y in 0..max_y {
+            for x in 0..max_x {
+                let pattern: Vec<T> = (0..n * n)
+                    .map(|i| {
+                        let px = (x + i % n) % sample.width();
+                        let py = (y + i / n) % sample.height();
+                        sample[Position::new(px as i32, py as i32)].clone()
+                    })
+                    .collect();
+                for orientation in &orientations {
+                    let (_, _, oriented) = orientation.reorient_cells(n, n, &pattern);
+                    let index = *indices.entry(oriented.clone()).or_insert_with(|| {
+                        patterns.push(oriented);
+                        weights.push(0.0);
+                        patterns.len() - 1
+                    });
+                    weights[index] += 1.0;
+                }
+            }
+        }
+
+        let mut propagator: Propagator = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        for (d, &direction) in Direction::CARDINAL.iter().enumerate() {
+            let offset = direction.offset();
+            propagator[d] = patterns
+                .iter()
+                .map(|a| {
+                    (0..patterns.len())
+                        .filter(|&b| agrees(a, &patterns[b], n, offset))
+                        .collect()
+                })
+                .collect();
+        }
+
+        Self {
+            n,
+            patterns,
+            weights,
+            propagator,
+        }
+    }
+
+    /// Returns the number of distinct patterns the model has learned.
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Generates a grid of the given size, retrying up to `attempts` times if the solver runs
+    /// into a contradiction. Returns `None` if every attempt failed, or if `size` is smaller
+    /// than the pattern size.
+    pub fn generate<A: Algorithm>(
+        &self,
+        size: USize,
+        random: &mut Random<A>,
+        attempts: u32,
+    ) -> Option<Grid<T>> {
+        let width = size.width as usize;
+        let height = size.height as usize;
+        if width < self.n || height < self.n {
+            return None;
+        }
+
+        // Each wave cell decides the pattern whose upper-left corner is at that cell; the last
+        // row and column of patterns also cover the cells beyond them.
+        let wave_width = width - self.n + 1;
+        let wave_height = height - self.n + 1;
+        let wave = solve(
+            wave_width,
+            wave_height,
+            &self.weights,
+            &self.propagator,
+            random,
+            attempts,
+        )?;
+
+        Some(Grid::new_with(width, height, |position| {
+            let x = position.x as usize;
+            let y = position.y as usize;
+            let wx = x.min(wave_width - 1);
+            let wy = y.min(wave_height - 1);
+            let pattern = &self.patterns[wave[wx + wy * wave_width]];
+            pattern[(x - wx) + (y - wy) * self.n].clone()
+        }))
+    }
+}
+
+// For each cardinal direction (in the order of `Direction::CARDINAL`) and each pattern, the
+// patterns that may be placed next to it in that direction.
+type Propagator = [Vec<Vec<usize>>; 4];
+
+fn cardinal_index(direction: Direction) -> usize {
+    Direction::CARDINAL
+        .iter()
+        .position(|&cardinal| cardinal == direction)
+        .expect("only cardinal directions can be used for adjacency")
+}
+
+// Whether pattern `b`, placed at `offset` from pattern `a`, agrees with `a` where they overlap.
+fn agrees<T: Eq>(a: &[T], b: &[T], n: usize, offset: Position) -> bool {
+    let n = n as i32;
+    let (dx, dy) = (offset.x, offset.y);
+    for y in dy.max(0)..(n + dy).min(n) {
+        for x in dx.max(0)..(n + dx).min(n) {
+            if a[(x + y * n) as usize] != b[((x - dx) + (y - dy) * n) as usize] {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn solve<A: Algorithm>(
+    width: usize,
+    height: usize,
+    weights: &[f64],
+    propagator: &Propagator,
+    random: &mut Random<A>,
+    attempts: u32,
+) -> Option<Vec<usize>> {
+    if weights.is_empty() {
+        return None;
+    }
+
+    for _ in 0..attempts {
+        let mut solver = Solver::new(width, height, weights, propagator);
+        if let Some(result) = solver.run(random) {
+            return Some(result);
+        }
+    }
+
+    None
+}
+
+// The classic Wave Function Collapse solver, which keeps track of how many of each pattern's
+// neighbors still support it, so patterns can be banned as soon as they lose all support.
+struct Solver<'a> {
+    width: usize,
+    height: usize,
+    weights: &'a [f64],
+    propagator: &'a Propagator,
+    wave: Vec<Vec<bool>>,
+    compatible: Vec<Vec<[usize; 4]>>,
+    counts: Vec<usize>,
+    sums_of_weights: Vec<f64>,
+    sums_of_weight_log_weights: Vec<f64>,
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(width: usize, height: usize, weights: &'a [f64], propagator: &'a Propagator) -> Self {
+        let cells = width * height;
+        let patterns = weights.len();
+        let initial_compatible: Vec<[usize; 4]> = (0..patterns)
+            .map(|t| {
+                let mut compatible = [0; 4];
+                for (d, count) in compatible.iter_mut().enumerate() {
+                    *count = propagator[(d + 2) % 4][t].len();
+                }
+                compatible
+            })
+            .collect();
+        let sum_of_weights: f64 = weights.iter().sum();
+        let sum_of_weight_log_weights: f64 = weights.iter().map(|w| w * math::ln_f64(*w)).sum();
+
+        Self {
+            width,
+            height,
+            weights,
+            propagator,
+            wave: vec![vec![true; patterns]; cells],
+            compatible: vec![initial_compatible; cells],
+            counts: vec![patterns; cells],
+            sums_of_weights: vec![sum_of_weights; cells],
+            sums_of_weight_log_weights: vec![sum_of_weight_log_weights; cells],
+            stack: Vec::new(),
+        }
+    }
+
+    fn run<A: Algorithm>(&mut self, random: &mut Random<A>) -> Option<Vec<usize>> {
+        // Patterns that can't have a neighbor in some direction are impossible anywhere that
+        // neighbor exists.
+        for cell in 0..self.wave.len() {
+            for t in 0..self.weights.len() {
+                let unsupported = (0..4).any(|d| {
+                    self.compatible[cell][t][d] == 0 && self.neighbor(cell, (d + 2) % 4).is_some()
+                });
+                if unsupported && self.wave[cell][t] {
+                    self.ban(cell, t);
+                }
+            }
+        }
+        if !self.propagate() {
+            return None;
+        }
+
+        loop {
+            match self.observe(random) {
+                Observation::Done => break,
+                Observation::Contradiction => return None,
+                Observation::Collapsed => {
+                    if !self.propagate() {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        Some(
+            self.wave
+                .iter()
+                .map(|patterns| patterns.iter().position(|&allowed| allowed).unwrap())
+                .collect(),
+        )
+    }
+
+    fn observe<A: Algorithm>(&mut self, random: &mut Random<A>) -> Observation {
+        let mut min_entropy = f64::MAX;
+        let mut chosen = None;
+        for cell in 0..self.wave.len() {
+            match self.counts[cell] {
+                0 => return Observation::Contradiction,
+                1 => continue,
+                _ => {}
+            }
+
+            let sum = self.sums_of_weights[cell];
+            let entropy = math::ln_f64(sum) - self.sums_of_weight_log_weights[cell] / sum;
+            let noisy = entropy + 1e-6 * random.get_uniform_f64();
+            if noisy < min_entropy {
+                min_entropy = noisy;
+                chosen = Some(cell);
+            }
+        }
+
+        let cell = match chosen {
+            Some(cell) => cell,
+            None => return Observation::Done,
+        };
+
+        let mut target = random.get_uniform_f64() * self.sums_of_weights[cell];
+        let mut pick = None;
+        for (t, &weight) in self.weights.iter().enumerate() {
+            if self.wave[cell][t] {
+                pick = Some(t);
+                if target < weight {
+                    break;
+                }
+                target -= weight;
+            }
+        }
+        let pick = pick.expect("a cell with several possibilities has at least one");
+
+        for t in 0..self.weights.len() {
+            if t != pick && self.wave[cell][t] {
+                self.ban(cell, t);
+            }
+        }
+
+        Observation::Collapsed
+    }
+
+    fn propagate(&mut self) -> bool {
+        while let Some((cell, t)) = self.stack.pop() {
+            for d in 0..4 {
+                let neighbor = match self.neighbor(cell, d) {
+                    Some(neighbor) => neighbor,
+                    None => continue,
+                };
+
+                for &t2 in &self.propagator[d][t] {
+                    let compatible = &mut self.compatible[neighbor][t2][d];
+                    if *compatible == 0 {
+                        continue;
+                    }
+                    *compatible -= 1;
+                    if *compatible == 0 && self.wave[neighbor][t2] {
+                        self.ban(neighbor, t2);
+                    }
+                }
+            }
+        }
+
+        self.counts.iter().all(|&count| count > 0)
+    }
+
+    fn ban(&mut self, cell: usize, t: usize) {
+        self.wave[cell][t] = false;
+        self.compatible[cell][t] = [0; 4];
+        self.stack.push((cell, t));
+
+        let weight = self.weights[t];
+        self.counts[cell] -= 1;
+        self.sums_of_weights[cell] -= weight;
+        self.sums_of_weight_log_weights[cell] -= weight * math::ln_f64(weight);
+    }
+
+    fn neighbor(&self, cell: usize, d: usize) -> Option<usize> {
+        let x = (cell % self.width) as i32;
+        let y = (cell / self.width) as i32;
+        let offset = Direction::CARDINAL[d].offset();
+        let nx = x + offset.x;
+        let ny = y + offset.y;
+        if nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.height {
+            None
+        } else {
+            Some(nx as usize + ny as usize * self.width)
+        }
+    }
+}
+
+enum Observation {
+    Collapsed,
+    Done,
+    Contradiction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+
+    #[test]
+    fn tiled_checkerboard() {
+        let mut model = TiledModel::new();
+        let black = model.add_tile(false, 1.0);
+        let white = model.add_tile(true, 1.0);
+        model.allow(black, Direction::East, white);
+        model.allow(white, Direction::East, black);
+        model.allow(black, Direction::South, white);
+        model.allow(white, Direction::South, black);
+
+        let mut random = Random::new_mt_from_seed(5);
+        let grid = model.generate(USize::new(7, 5), &mut random, 1).unwrap();
+        let first = grid[Position::ORIGIN];
+        for (position, &cell) in grid.enumerate() {
+            assert_eq!(cell, first == ((position.x + position.y) % 2 == 0));
+        }
+    }
+
+    #[test]
+    fn tiled_contradiction() {
+        let mut model = TiledModel::new();
+        let a = model.add_tile('a', 1.0);
+        let b = model.add_tile('b', 1.0);
+        model.allow(a, Direction::East, b);
+        // Nothing may be placed to the east of b, nor above or below anything
+        let mut random = Random::new_mt_from_seed(5);
+        assert_eq!(model.generate(USize::new(3, 1), &mut random, 3), None);
+        assert!(model.generate(USize::new(2, 1), &mut random, 3).is_some());
+    }
+
+    #[test]
+    fn weights_hold_with_any_distribution() {
+        let mut model = TiledModel::new();
+        let rare = model.add_tile(true, 1.0);
+        let common = model.add_tile(false, 9.0);
+        for &a in &[rare, common] {
+            for &b in &[rare, common] {
+                model.allow(a, Direction::East, b);
+                model.allow(a, Direction::South, b);
+            }
+        }
+
+        for &distribution in &[
+            Distribution::Linear,
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut random = Random::new_mt_from_seed(5);
+            random.distribution = distribution;
+            let grid = model.generate(USize::new(30, 30), &mut random, 1).unwrap();
+            // 90 of the 900 cells are expected to be rare, give or take 9.
+            let rare_count = grid.iter().filter(|&&cell| cell).count();
+            assert!(
+                (50..130).contains(&rare_count),
+                "{} rare cells with {:?}",
+                rare_count,
+                distribution
+            );
+        }
+    }
+
+    #[test]
+    fn overlapping_stripes() {
+        let sample = Grid::new_with(4, 4, |p| p.x % 2 == 0);
+        let model = OverlappingModel::new(&sample, 2, true, false);
+        assert_eq!(model.pattern_count(), 2);
+
+        let mut random = Random::new_mt_from_seed(9);
+        let grid = model.generate(USize::new(9, 6), &mut random, 5).unwrap();
+        for (position, &cell) in grid.enumerate() {
+            if let Some(&east) = grid.get(position + Position::new(1, 0)) {
+                assert_ne!(cell, east);
+            }
+            if let Some(&south) = grid.get(position + Position::new(0, 1)) {
+                assert_eq!(cell, south);
+            }
+        }
+
+        let symmetric = OverlappingModel::new(&sample, 2, true, true);
+        assert_eq!(symmetric.pattern_count(), 4);
+    }
+}
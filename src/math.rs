@@ -0,0 +1,126 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+// Floating point functions whose results may differ between platforms and compiler versions.
+//
+// With the `deterministic-math` feature enabled, they're computed by the `libm` crate, whose
+// software implementations give bit-identical results everywhere; otherwise, they use the
+// standard library, which is usually faster. Everything whose output should be reproducible from
+// a seed, such as noise, random number distributions and map generators, goes through these.
+
+#[cfg(feature = "deterministic-math")]
+mod imp {
+    pub(crate) fn sqrt(x: f32) -> f32 {
+        libm::sqrtf(x)
+    }
+
+    pub(crate) fn sqrt_f64(x: f64) -> f64 {
+        libm::sqrt(x)
+    }
+
+    pub(crate) fn ln_f64(x: f64) -> f64 {
+        libm::log(x)
+    }
+
+    pub(crate) fn ln_1p_f64(x: f64) -> f64 {
+        libm::log1p(x)
+    }
+
+    pub(crate) fn exp(x: f32) -> f32 {
+        libm::expf(x)
+    }
+
+    pub(crate) fn powf(x: f32, n: f32) -> f32 {
+        libm::powf(x, n)
+    }
+
+    pub(crate) fn exp_f64(x: f64) -> f64 {
+        libm::exp(x)
+    }
+
+    pub(crate) fn atan2(y: f32, x: f32) -> f32 {
+        libm::atan2f(y, x)
+    }
+
+    pub(crate) fn sin_cos(x: f32) -> (f32, f32) {
+        libm::sincosf(x)
+    }
+
+    pub(crate) fn hypot(x: f32, y: f32) -> f32 {
+        libm::hypotf(x, y)
+    }
+}
+
+#[cfg(not(feature = "deterministic-math"))]
+mod imp {
+    pub(crate) fn sqrt(x: f32) -> f32 {
+        x.sqrt()
+    }
+
+    pub(crate) fn sqrt_f64(x: f64) -> f64 {
+        x.sqrt()
+    }
+
+    pub(crate) fn ln_f64(x: f64) -> f64 {
+        x.ln()
+    }
+
+    pub(crate) fn ln_1p_f64(x: f64) -> f64 {
+        x.ln_1p()
+    }
+
+    pub(crate) fn exp(x: f32) -> f32 {
+        x.exp()
+    }
+
+    pub(crate) fn powf(x: f32, n: f32) -> f32 {
+        x.powf(n)
+    }
+
+    pub(crate) fn exp_f64(x: f64) -> f64 {
+        x.exp()
+    }
+
+    pub(crate) fn atan2(y: f32, x: f32) -> f32 {
+        y.atan2(x)
+    }
+
+    pub(crate) fn sin_cos(x: f32) -> (f32, f32) {
+        x.sin_cos()
+    }
+
+    pub(crate) fn hypot(x: f32, y: f32) -> f32 {
+        x.hypot(y)
+    }
+}
+
+pub(crate) use imp::*;
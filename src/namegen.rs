@@ -0,0 +1,461 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Name generation.
+//!
+//! Generates random names out of syllable sets, the way libtcod's namegen toolkit does. A
+//! [`SyllableSet`] holds lists of syllables and a list of rules that tell how to put them
+//! together; a [`NameGenerator`] holds any number of named syllable sets and generates names
+//! from them using any [`Rng`].
+//!
+//! # Rules
+//!
+//! A rule is a string where letters, apostrophes and dashes are copied as-is to the name, an
+//! underscore is turned into a space, a slash copies the character following it, and a dollar
+//! sign followed by one of these characters is replaced by a random entry of a list of the set:
+//!
+//! * `$P`: `pre`, the syllables used before the name, e.g. titles
+//! * `$s`: `start`, the syllables starting the name
+//! * `$m`: `middle`, the syllables in the middle of the name
+//! * `$e`: `end`, the syllables ending the name
+//! * `$p`: `post`, the syllables used after the name, e.g. epithets
+//! * `$v`: `vocals`, single vowels
+//! * `$c`: `consonants`, single consonants
+//! * `$?`: either `vocals` or `consonants`
+//!
+//! A number between the dollar sign and the character, like in `$25m`, is the chance in percent
+//! that the list is used at all. A rule can start with a chance too, like in `%50$s$e`, which is
+//! the chance that the rule is picked when it comes up.
+//!
+//! Names with three equal letters in a row, with the same three or more letters repeated back to
+//! back, or containing any of the set's `illegal` strings, are thrown away and generated anew.
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::namegen::{NameGenerator, SyllableSet};
+//! # use doryen_extra::random::Random;
+//! let mut generator = NameGenerator::new();
+//! generator.add(SyllableSet {
+//!     name: "orc".to_string(),
+//!     start: SyllableSet::list("Gor, Ur, Mog, Shag"),
+//!     end: SyllableSet::list("bag, ash, nak, rat"),
+//!     rules: SyllableSet::list("$s$e"),
+//!     ..SyllableSet::default()
+//! });
+//!
+//! let mut random = Random::new_mt_from_seed(42);
+//! let name = generator.generate("orc", &mut random);
+//! assert!(["Gor", "Ur", "Mog", "Shag"].iter().any(|start| name.starts_with(start)));
+//! ```
+//!
+//! # Syllable set files
+//!
+//! The syllable set files that come with libtcod, and the ones made for it by the community,
+//! can be loaded as they are with [`NameGenerator::parse_str`] and
+//! [`NameGenerator::load_from_file`]. They hold any number of sets written like this, where
+//! `/* */` and `//` comments are allowed, and adjacent strings are joined together:
+//! ```text
+//! name "dwarf" {
+//!   phonemesVocals = "a, o, u"
+//!   phonemesConsonants = "b, d, g, k, r"
+//!   syllablesPre = "Lord"
+//!   syllablesStart = "Thor, Dur, Bal, Gim"
+//!   syllablesMiddle = "in, ar, o"
+//!   syllablesEnd = "in, li, grim, dor"
+//!   syllablesPost = "the Bold, Ironfist"
+//!   illegal = "dd"
+//!   rules = "$s$e, %20$s$m$e, $P_$s$e, $s$e_$p"
+//! }
+//! ```
+//!
+//! [`SyllableSet`]: ./struct.SyllableSet.html
+//! [`NameGenerator::parse_str`]: ./struct.NameGenerator.html#method.parse_str
+//! [`NameGenerator::load_from_file`]: ./struct.NameGenerator.html#method.load_from_file
+//! [`NameGenerator`]: ./struct.NameGenerator.html
+//! [`Rng`]: ../random/trait.Rng.html
+
+mod cfg;
+
+use crate::random::Rng;
+use crate::Error;
+use std::io;
+
+/// The number of times a name is generated anew after failing the checks before giving up.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// A named set of syllables and the rules for putting them together into names.
+///
+/// See the [module documentation](./index.html) for how the lists are used.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct SyllableSet {
+    /// The name the set is generated from with
+    pub name: String,
+    /// Single vowels, used by `$v` and `$?`
+    pub vocals: Vec<String>,
+    /// Single consonants, used by `$c` and `$?`
+    pub consonants: Vec<String>,
+    /// Syllables used before the name, used by `$P`
+    pub pre: Vec<String>,
+    /// Syllables starting the name, used by `$s`
+    pub start: Vec<String>,
+    /// Syllables in the middle of the name, used by `$m`
+    pub middle: Vec<String>,
+    /// Syllables ending the name, used by `$e`
+    pub end: Vec<String>,
+    /// Syllables used after the name, used by `$p`
+    pub post: Vec<String>,
+    /// Strings a name must not contain, compared without regard to case
+    pub illegal: Vec<String>,
+    /// The rules names are generated from
+    pub rules: Vec<String>,
+}
+
+impl SyllableSet {
+    /// Splits a comma separated list, like `"ka, ki, ku"`, into its trimmed, non-empty entries,
+    /// which is the way libtcod's syllable set files write their lists.
+    pub fn list(s: &str) -> Vec<String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Generates a name from a randomly picked rule of this set.
+    ///
+    /// # Panics
+    /// If the set has no rules, or see `generate_custom`.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        self.try_generate(rng)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Generates a name from the given rule rather than one of the set's rules.
+    ///
+    /// # Panics
+    /// If the rule uses an unknown wildcard or one whose list is empty, or if no acceptable name
+    /// could be generated.
+    pub fn generate_custom<R: Rng>(&self, rule: &str, rng: &mut R) -> String {
+        self.try_generate_custom(rule, rng)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `generate`, but returns an error instead of panicking.
+    pub fn try_generate<R: Rng>(&self, rng: &mut R) -> Result<String, Error> {
+        if self.rules.is_empty() {
+            return Err(Error::InvalidData("the syllable set has no rules"));
+        }
+
+        let rule = loop {
+            let rule = pick(&self.rules, rng);
+            let (chance, rule) = match rule.strip_prefix('%') {
+                Some(rest) => parse_chance(rest),
+                None => (100, rule),
+            };
+            if rng.get_i32(0, 100) <= chance {
+                break rule;
+            }
+        };
+
+        self.try_generate_custom(rule, rng)
+    }
+
+    /// Like `generate_custom`, but returns an error instead of panicking.
+    pub fn try_generate_custom<R: Rng>(&self, rule: &str, rng: &mut R) -> Result<String, Error> {
+        for _ in 0..MAX_ATTEMPTS {
+            let name = self.apply_rule(rule, rng)?;
+            if self.is_acceptable(&name) {
+                return Ok(name.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+        }
+
+        Err(Error::InvalidData("no acceptable name could be generated"))
+    }
+
+    fn apply_rule<R: Rng>(&self, rule: &str, rng: &mut R) -> Result<String, Error> {
+        let mut name = String::new();
+        let mut chars = rule.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '_' => name.push(' '),
+                '/' => name.extend(chars.next()),
+                '$' => {
+                    let (chance, rest) = parse_chance(chars.as_str());
+                    chars = rest.chars();
+                    let list = match chars.next() {
+                        Some('P') => &self.pre,
+                        Some('s') => &self.start,
+                        Some('m') => &self.middle,
+                        Some('e') => &self.end,
+                        Some('p') => &self.post,
+                        Some('v') => &self.vocals,
+                        Some('?') if rng.get_i32(0, 1) == 0 => &self.vocals,
+                        Some('c') | Some('?') => &self.consonants,
+                        _ => return Err(Error::InvalidData("unknown wildcard in the rule")),
+                    };
+                    if chance >= rng.get_i32(0, 100) {
+                        if list.is_empty() {
+                            return Err(Error::InvalidData(
+                                "a wildcard in the rule refers to an empty list",
+                            ));
+                        }
+                        name.push_str(pick(list, rng));
+                    }
+                }
+                c if c.is_alphabetic() || c == '\'' || c == '-' => name.push(c),
+                _ => {}
+            }
+        }
+
+        Ok(name)
+    }
+
+    fn is_acceptable(&self, name: &str) -> bool {
+        let lowercase: Vec<char> = name.to_lowercase().chars().collect();
+        let has_triples = lowercase
+            .windows(3)
+            .any(|w| w[0].is_alphabetic() && w[0] == w[1] && w[1] == w[2]);
+        let has_repeats = (3..=lowercase.len() / 2).any(|length| {
+            lowercase
+                .windows(length * 2)
+                .any(|w| w[..length] == w[length..] && !w.iter().any(|c| c.is_whitespace()))
+        });
+        let lowercase: String = lowercase.into_iter().collect();
+        let has_illegal = self
+            .illegal
+            .iter()
+            .any(|illegal| lowercase.contains(&illegal.to_lowercase()));
+
+        !has_triples && !has_repeats && !has_illegal
+    }
+}
+
+/// A collection of syllable sets, generating names from them by their name.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct NameGenerator {
+    sets: Vec<SyllableSet>,
+}
+
+impl NameGenerator {
+    /// Returns a new name generator without any syllable sets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a syllable set, replacing any set with the same name.
+    pub fn add(&mut self, set: SyllableSet) {
+        match self.sets.iter_mut().find(|s| s.name == set.name) {
+            Some(existing) => *existing = set,
+            None => self.sets.push(set),
+        }
+    }
+
+    /// Adds the syllable sets of a libtcod syllable set file, given as a string, replacing any
+    /// sets with the same names. Nothing is added if the file has errors.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::namegen::NameGenerator;
+    /// let mut generator = NameGenerator::new();
+    /// generator
+    ///     .parse_str(r#"name "elf" { syllablesStart = "Ela, Fin" syllablesEnd = "rond, dil" rules = "$s$e" }"#)
+    ///     .unwrap();
+    /// assert_eq!(generator.names().collect::<Vec<_>>(), ["elf"]);
+    /// ```
+    pub fn parse_str(&mut self, s: &str) -> Result<(), Error> {
+        for set in cfg::parse(s)? {
+            self.add(set);
+        }
+
+        Ok(())
+    }
+
+    /// Adds the syllable sets of a libtcod syllable set file, like `parse_str`. Errors in the
+    /// file are returned as errors of the `InvalidData` kind.
+    pub fn load_from_file<P: AsRef<std::path::Path>>(&mut self, path: P) -> io::Result<()> {
+        let s = std::fs::read_to_string(path)?;
+        self.parse_str(&s)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Returns the syllable set with the given name, if there is one.
+    pub fn set(&self, name: &str) -> Option<&SyllableSet> {
+        self.sets.iter().find(|s| s.name == name)
+    }
+
+    /// Returns an iterator over the names of the syllable sets, in the order they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.sets.iter().map(|s| s.name.as_str())
+    }
+
+    /// Generates a name from the syllable set with the given name.
+    ///
+    /// # Panics
+    /// If there is no syllable set with the given name, or see `SyllableSet::generate`.
+    pub fn generate<R: Rng>(&self, name: &str, rng: &mut R) -> String {
+        self.try_generate(name, rng)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Generates a name from the syllable set with the given name, using the given rule rather
+    /// than one of the set's rules.
+    ///
+    /// # Panics
+    /// If there is no syllable set with the given name, or see `SyllableSet::generate_custom`.
+    pub fn generate_custom<R: Rng>(&self, name: &str, rule: &str, rng: &mut R) -> String {
+        self.try_generate_custom(name, rule, rng)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `generate`, but returns an error instead of panicking.
+    pub fn try_generate<R: Rng>(&self, name: &str, rng: &mut R) -> Result<String, Error> {
+        self.try_set(name)?.try_generate(rng)
+    }
+
+    /// Like `generate_custom`, but returns an error instead of panicking.
+    pub fn try_generate_custom<R: Rng>(
+        &self,
+        name: &str,
+        rule: &str,
+        rng: &mut R,
+    ) -> Result<String, Error> {
+        self.try_set(name)?.try_generate_custom(rule, rng)
+    }
+
+    fn try_set(&self, name: &str) -> Result<&SyllableSet, Error> {
+        self.set(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))
+    }
+}
+
+fn pick<'a, R: Rng>(list: &'a [String], rng: &mut R) -> &'a str {
+    &list[rng.get_index(list.len())]
+}
+
+// Splits the leading digits off of `s`, returning the number they make up, or 100 if there are
+// none, and the rest of the string.
+fn parse_chance(s: &str) -> (i32, &str) {
+    let digits = s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let chance = s[..digits].parse().unwrap_or(100);
+
+    (chance, &s[digits..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    fn dwarf() -> SyllableSet {
+        SyllableSet {
+            name: "dwarf".to_string(),
+            vocals: SyllableSet::list("a, o, u"),
+            consonants: SyllableSet::list("b, d, g, k, r"),
+            pre: SyllableSet::list("Lord"),
+            start: SyllableSet::list("Thor, Dur, Bal, Gim"),
+            middle: SyllableSet::list("in, ar, o"),
+            end: SyllableSet::list("in, li, grim, dor"),
+            post: SyllableSet::list("the Bold, Ironfist"),
+            illegal: SyllableSet::list("dd"),
+            rules: SyllableSet::list("$s$e, %20$s$m$e, $P_$s$e, $s$e_$p"),
+        }
+    }
+
+    #[test]
+    fn names_are_deterministic_and_follow_the_rules() {
+        let mut generator = NameGenerator::new();
+        generator.add(dwarf());
+        let mut random = Random::new_mt_from_seed(7);
+        let names: Vec<String> = (0..50)
+            .map(|_| generator.generate("dwarf", &mut random))
+            .collect();
+        let mut replay = Random::new_mt_from_seed(7);
+        for expected in &names {
+            assert_eq!(&generator.generate("dwarf", &mut replay), expected);
+        }
+
+        for name in &names {
+            assert!(!name.to_lowercase().contains("dd"));
+            assert!(!name.starts_with(' ') && !name.contains("  "));
+            assert!(["Lord ", "Thor", "Dur", "Bal", "Gim"]
+                .iter()
+                .any(|start| name.starts_with(start)));
+        }
+        assert!(names.iter().any(|name| name.starts_with("Lord ")));
+        assert!(names.iter().any(|name| name.ends_with("the Bold")));
+    }
+
+    #[test]
+    fn custom_rules() {
+        let set = dwarf();
+        let mut random = Random::new_mt_from_seed(1);
+        assert_eq!(set.generate_custom("Mr/._$0s", &mut random), "Mr.");
+        let name = set.generate_custom("$v$c$v", &mut random);
+        assert_eq!(name.len(), 3);
+        assert!(SyllableSet::list("a, o, u").contains(&name[..1].to_string()));
+    }
+
+    #[test]
+    fn rejected_names() {
+        let set = dwarf();
+        assert!(!set.is_acceptable("Baaal"));
+        assert!(!set.is_acceptable("Gimgim"));
+        assert!(!set.is_acceptable("Oddin"));
+        assert!(set.is_acceptable("Thorin"));
+    }
+
+    #[test]
+    fn errors() {
+        let mut generator = NameGenerator::new();
+        generator.add(dwarf());
+        let mut random = Random::new_mt_from_seed(1);
+        assert_eq!(
+            generator.try_generate("elf", &mut random),
+            Err(Error::NotFound("elf".to_string()))
+        );
+        assert!(generator
+            .try_generate_custom("dwarf", "$x", &mut random)
+            .is_err());
+        assert!(SyllableSet::default().try_generate(&mut random).is_err());
+        assert!(SyllableSet::default()
+            .try_generate_custom("$s", &mut random)
+            .is_err());
+    }
+}
@@ -0,0 +1,142 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+// A reader for libtcod's namegen syllable set files, which look like this:
+//
+//     name "dwarf" {
+//       syllablesStart = "Thor, Dur, Bal"
+//       syllablesEnd = "in, li, " "grim"  // adjacent strings are joined
+//       rules = "$s$e"
+//     }
+
+use crate::namegen::SyllableSet;
+use crate::parser::{Parser, Value, ValueType};
+use crate::Error;
+
+const PROPERTIES: [&str; 9] = [
+    "phonemesVocals",
+    "phonemesConsonants",
+    "syllablesPre",
+    "syllablesStart",
+    "syllablesMiddle",
+    "syllablesEnd",
+    "syllablesPost",
+    "illegal",
+    "rules",
+];
+
+pub(super) fn parse(s: &str) -> Result<Vec<SyllableSet>, Error> {
+    let mut parser = Parser::new();
+    let name = parser.new_struct("name");
+    for &property in &PROPERTIES {
+        name.add_property(property, ValueType::String, false);
+    }
+
+    parser
+        .parse_str(s)?
+        .iter()
+        .map(|entity| {
+            let list = |property| {
+                entity
+                    .get(property)
+                    .and_then(Value::as_str)
+                    .map(SyllableSet::list)
+                    .unwrap_or_default()
+            };
+            Ok(SyllableSet {
+                name: entity
+                    .name()
+                    .ok_or(Error::InvalidData("a syllable set has no name"))?
+                    .to_string(),
+                vocals: list("phonemesVocals"),
+                consonants: list("phonemesConsonants"),
+                pre: list("syllablesPre"),
+                start: list("syllablesStart"),
+                middle: list("syllablesMiddle"),
+                end: list("syllablesEnd"),
+                post: list("syllablesPost"),
+                illegal: list("illegal"),
+                rules: list("rules"),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sets() {
+        let sets = parse(
+            r#"
+            /* Names for dwarves. */
+            name "dwarf" {
+                syllablesStart = "Thor, Dur,"
+                                 " Bal" // continued
+                syllablesEnd = "in, li"
+                illegal = "dd"
+                rules = "$s$e, %10$s_\"the Bold\""
+            }
+            name "orc" { phonemesVocals = "a, u" rules = "$v" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(sets.len(), 2);
+        assert_eq!(sets[0].name, "dwarf");
+        assert_eq!(sets[0].start, SyllableSet::list("Thor, Dur, Bal"));
+        assert_eq!(sets[0].end, SyllableSet::list("in, li"));
+        assert_eq!(sets[0].illegal, SyllableSet::list("dd"));
+        assert_eq!(sets[0].rules[1], "%10$s_\"the Bold\"");
+        assert!(sets[0].middle.is_empty());
+        assert_eq!(sets[1].vocals, SyllableSet::list("a, u"));
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let error = |s| match parse(s) {
+            Err(Error::Syntax { line, column, .. }) => (line, column),
+            other => panic!("expected a syntax error, got {:?}", other),
+        };
+
+        assert_eq!(error("name \"a\" {\n  rules \"$s\"\n}"), (2, 9));
+        assert_eq!(error("name \"a\" {\n  color = \"red\"\n}"), (2, 3));
+        assert_eq!(error("name \"a\" {\n  rules = \"$s\n}"), (2, 11));
+        assert_eq!(error("names"), (1, 1));
+        assert_eq!(error("name \"a\" { /* }"), (1, 12));
+        assert_eq!(
+            parse("name { rules = \"$s\" }"),
+            Err(Error::InvalidData("a syllable set has no name"))
+        );
+    }
+}
@@ -48,18 +48,33 @@
 //!
 //! ## Choosing a noise type
 //! The default choice should be Simplex. It's much faster than Perlin, especially in 4 dimensions.
-//! It has a better contrast too.
+//! It has a better contrast too. Value noise is the fastest of them all, but blockier, which
+//! doesn't matter much at low frequencies, e.g. for biome masks.
+//! OpenSimplex2 has about the cost of Simplex but none of its axis-aligned artifacts in 2D, so it's
+//! the better pick when those show, e.g. on large height maps.
 
 pub mod algorithms;
+pub mod combinators;
+mod map_builder;
+
+pub use map_builder::NoiseMapBuilder;
 
 use crate::noise::algorithms::Algorithm;
 use crate::noise::algorithms::AlgorithmInitializer;
+use crate::noise::algorithms::AnalyticDerivative;
+use crate::noise::algorithms::OpenSimplex2;
 use crate::noise::algorithms::Perlin;
 use crate::noise::algorithms::Simplex;
+use crate::noise::algorithms::Value;
 use crate::noise::algorithms::Wavelet;
+use crate::noise::algorithms::LANES;
+use crate::noise::algorithms::{AlgorithmKind, AnyAlgorithm};
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::random::Random;
+use crate::{Error, Rectangle};
 use derivative::Derivative;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
 
 /// The maximum number of octaves supported.
 pub const MAX_OCTAVES: usize = 128;
@@ -68,19 +83,97 @@ pub const MAX_DIMENSIONS: usize = 4;
 //pub const DEFAULT_HURST: f32 = 0.5;
 /// The default lacunarity value.
 pub const DEFAULT_LACUNARITY: f32 = 2.0;
+/// The default gain value of the ridged multifractal.
+pub const DEFAULT_GAIN: f32 = 2.0;
+/// The default offset value of the ridged multifractal.
+pub const DEFAULT_OFFSET: f32 = 1.0;
 
 const DELTA: f32 = 1.0e-6;
+// Where the warp noise is sampled for each axis, so the axes aren't displaced by the same amount.
+const WARP_OFFSETS: [f32; MAX_DIMENSIONS] = [0.0, 5.2, 13.7, 27.1];
+
+/// The ways the octaves of a noise generator can be combined, for the bulk evaluation methods.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum FractalMode {
+    /// A single octave, see `Noise::flat`. The octaves are ignored.
+    Flat,
+    /// Fractal Brownian Motion, see `Noise::fbm`.
+    #[default]
+    Fbm,
+    /// Turbulence, see `Noise::turbulence`.
+    Turbulence,
+    /// Ridged multifractal, see `Noise::ridged_multifractal`.
+    RidgedMultifractal,
+    /// Billow, see `Noise::billow`.
+    Billow,
+}
 
 /// A struct representing a noise generator algorithm and its parameters.
+///
+/// A `Noise` created from a seed, with `Noise::from_seed`, always gives the same values for that
+/// seed and the same dimensions, so a world seed can be shared between players. With the
+/// `deterministic-math` feature enabled, this also holds across platforms and compiler versions;
+/// without it, the few transcendental functions involved come from the platform's math library
+/// and may differ in the last bits.
 
-#[derive(Derivative)]
+#[derive(Clone, Derivative)]
 #[derivative(Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "NoiseData<A>")
+)]
 pub struct Noise<A: Algorithm> {
     pub(crate) dimensions: usize,
     algorithm: A,
     #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
+    exponent: [f32; MAX_OCTAVES],
+    lacunarity: f32,
+    gain: f32,
+    offset: f32,
+    #[cfg_attr(feature = "serialization", serde(default))]
+    seed: Option<u32>,
+}
+
+// `Noise` as it comes out of the deserializer, before its dimensions are validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct NoiseData<A> {
+    dimensions: usize,
+    algorithm: A,
+    #[serde(with = "crate::util::big_array")]
     exponent: [f32; MAX_OCTAVES],
     lacunarity: f32,
+    gain: f32,
+    offset: f32,
+    #[serde(default)]
+    seed: Option<u32>,
+}
+
+#[cfg(feature = "serialization")]
+impl<A: Algorithm> TryFrom<NoiseData<A>> for Noise<A> {
+    type Error = Error;
+
+    fn try_from(data: NoiseData<A>) -> Result<Self, Self::Error> {
+        if !(1..=MAX_DIMENSIONS).contains(&data.dimensions) {
+            return Err(Error::OutOfRange("dimensions"));
+        }
+
+        Ok(Self {
+            dimensions: data.dimensions,
+            algorithm: data.algorithm,
+            exponent: data.exponent,
+            lacunarity: data.lacunarity,
+            gain: data.gain,
+            offset: data.offset,
+            seed: data.seed,
+        })
+    }
 }
 
 impl<A: Algorithm> Noise<A> {
@@ -134,7 +227,7 @@ impl<A: Algorithm> Noise<A> {
                 f64::from(octaves * self.algorithm.generate(&tf)) * f64::from(self.exponent[exp_i]);
         }
 
-        value.max(-0.99999).min(0.99999) as f32
+        value.clamp(-0.99999, 0.99999) as f32
     }
 
     /// Returns the turbulence function value between -1.0 and 1.0 at the given
@@ -172,14 +265,653 @@ impl<A: Algorithm> Noise<A> {
                 * f64::from(self.exponent[exp_i]);
         }
 
-        value.max(-0.99999).min(0.99999) as f32
+        value.clamp(-0.99999, 0.99999) as f32
+    }
+
+    /// Like `flat`, but with `f64` coordinates, which keeps the noise smooth far away from the
+    /// origin, where `f32` coordinates can't hold much of a fraction any more.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::random::Random;
+    /// let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+    /// // As `f32`s, both of these are 100000000.0.
+    /// let a = noise.flat_f64(&[100_000_000.25, 3.0]);
+    /// let b = noise.flat_f64(&[100_000_000.75, 3.0]);
+    /// assert_ne!(a, b);
+    /// ```
+    pub fn flat_f64(&self, f: &[f64]) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        self.algorithm.generate_f64(f)
+    }
+
+    /// Like `fbm`, but with `f64` coordinates, which keeps the noise smooth far away from the
+    /// origin, where `f32` coordinates can't hold much of a fraction any more.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn fbm_f64(&self, f: &[f64], mut octaves: f32) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        let mut tf = [0.0_f64; MAX_DIMENSIONS];
+        tf[0..self.dimensions].copy_from_slice(f);
+        let tf = &mut tf[0..self.dimensions];
+
+        let mut value: f64 = 0.0;
+        /* Inner loop of spectral construction, where the fractal is built */
+        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
+            value += f64::from(self.algorithm.generate_f64(tf)) * f64::from(e);
+            for tfe in tf.iter_mut() {
+                *tfe *= f64::from(self.lacunarity);
+            }
+        }
+
+        /* Take care of remainder in octaves */
+        let exp_i = octaves.trunc() as usize;
+        octaves -= octaves.trunc();
+        if octaves > DELTA {
+            value += f64::from(octaves * self.algorithm.generate_f64(tf))
+                * f64::from(self.exponent[exp_i]);
+        }
+
+        value.clamp(-0.99999, 0.99999) as f32
+    }
+
+    /// Like `turbulence`, but with `f64` coordinates, which keeps the noise smooth far away from
+    /// the origin, where `f32` coordinates can't hold much of a fraction any more.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn turbulence_f64(&self, f: &[f64], mut octaves: f32) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        let mut tf = [0.0_f64; MAX_DIMENSIONS];
+        tf[0..self.dimensions].copy_from_slice(f);
+        let tf = &mut tf[0..self.dimensions];
+
+        let mut value: f64 = 0.0;
+        /* Inner loop of spectral construction, where the fractal is built */
+        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
+            value += f64::from(self.algorithm.generate_f64(tf).abs()) * f64::from(e);
+            for tfe in tf.iter_mut() {
+                *tfe *= f64::from(self.lacunarity);
+            }
+        }
+
+        /* Take care of remainder in octaves */
+        let exp_i = octaves.trunc() as usize;
+        octaves -= octaves.trunc();
+        if octaves > DELTA {
+            value += f64::from(octaves * self.algorithm.generate_f64(tf).abs())
+                * f64::from(self.exponent[exp_i]);
+        }
+
+        value.clamp(-0.99999, 0.99999) as f32
+    }
+
+    /// Returns the ridged multifractal function value between -1.0 and 1.0 at the given
+    /// coordinates, using the lacunarity defined when the noise generator was created.
+    /// The same array of coordinates will always return the same value.
+    ///
+    /// Each octave folds the noise around zero into sharp ridges, and is weighted by the
+    /// octave before it times the gain, so detail builds up along the ridgelines and the
+    /// valleys stay smooth. The offset raises the ridges; with the default offset of 1.0, the
+    /// whole -1.0 to 1.0 range is used. See `with_gain` and `with_offset`.
+    ///
+    /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::random::Random;
+    /// let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+    /// let value = noise.ridged_multifractal(&[1.3, 2.7], 6.0);
+    /// assert!((-1.0..=1.0).contains(&value));
+    /// ```
+    pub fn ridged_multifractal(&self, f: &[f32], mut octaves: f32) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        let mut tf = [0.0_f32; MAX_DIMENSIONS];
+        tf[0..self.dimensions].copy_from_slice(f);
+
+        let mut value: f64 = 0.0;
+        let mut total: f64 = 0.0;
+        let mut weight = 1.0;
+        let mut ridge = |coordinates: &[f32], e: f32, amount: f32| {
+            let folded = self.offset - self.algorithm.generate(coordinates).abs();
+            let signal = folded * folded * weight;
+            weight = (signal * self.gain).clamp(0.0, 1.0);
+            value += f64::from(amount * signal) * f64::from(e);
+            total += f64::from(amount) * f64::from(e);
+        };
+
+        /* Inner loop of spectral construction, where the fractal is built */
+        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
+            ridge(&tf, e, 1.0);
+            for tfe in tf.iter_mut().take(f.len()) {
+                *tfe *= self.lacunarity;
+            }
+        }
+
+        /* Take care of remainder in octaves */
+        let exp_i = octaves.trunc() as usize;
+        octaves -= octaves.trunc();
+        if octaves > DELTA {
+            ridge(&tf, self.exponent[exp_i], octaves);
+        }
+
+        if total <= 0.0 {
+            return 0.0;
+        }
+
+        (value / total * 2.0 - 1.0).clamp(-0.99999, 0.99999) as f32
+    }
+
+    /// Returns the billow function value between -1.0 and 1.0 at the given coordinates, using
+    /// the lacunarity defined when the noise generator was created.
+    /// The same array of coordinates will always return the same value.
+    ///
+    /// Like `turbulence`, but each octave is rescaled back to the -1.0 to 1.0 range, which
+    /// gives rounded, puffy shapes, like clouds or rolling hills.
+    ///
+    /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn billow(&self, f: &[f32], mut octaves: f32) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        let mut tf = [0.0_f32; MAX_DIMENSIONS];
+        tf[0..self.dimensions].copy_from_slice(f);
+
+        let mut value: f64 = 0.0;
+        /* Inner loop of spectral construction, where the fractal is built */
+        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
+            value += f64::from(2.0 * self.algorithm.generate(&tf).abs() - 1.0) * f64::from(e);
+            for tfe in tf.iter_mut().take(f.len()) {
+                *tfe *= self.lacunarity;
+            }
+        }
+
+        /* Take care of remainder in octaves */
+        let exp_i = octaves.trunc() as usize;
+        octaves -= octaves.trunc();
+        if octaves > DELTA {
+            value += f64::from(octaves * (2.0 * self.algorithm.generate(&tf).abs() - 1.0))
+                * f64::from(self.exponent[exp_i]);
+        }
+
+        value.clamp(-0.99999, 0.99999) as f32
+    }
+
+    /// Returns the noise function value between -1.0 and 1.0 at the given coordinates, after
+    /// displacing them by the `warp` noise generator, which gives swirling, organic patterns.
+    /// The same array of coordinates will always return the same value.
+    ///
+    /// Each coordinate is moved by up to `strength` units, by sampling `warp` at a different
+    /// place for each axis.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the dimensions of both `Noise`s.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::random::Random;
+    /// let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+    /// let warp = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(2));
+    /// let value = noise.warped(&[1.3, 2.7], &warp, 4.0);
+    /// assert_ne!(value, noise.flat(&[1.3, 2.7]));
+    /// assert_eq!(noise.warped(&[1.3, 2.7], &warp, 0.0), noise.flat(&[1.3, 2.7]));
+    /// ```
+    pub fn warped<B: Algorithm>(&self, f: &[f32], warp: &Noise<B>, strength: f32) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+        assert_eq!(
+            warp.dimensions,
+            f.len(),
+            "Number of coordinates given in 'f' must match the dimensions of 'warp'."
+        );
+
+        let mut wf = [0.0_f32; MAX_DIMENSIONS];
+        let mut tf = [0.0_f32; MAX_DIMENSIONS];
+        for (tfe, &offset) in tf.iter_mut().zip(&WARP_OFFSETS).take(self.dimensions) {
+            for (wfe, &fe) in wf.iter_mut().zip(f) {
+                *wfe = fe + offset;
+            }
+            *tfe = warp.flat(&wf[..self.dimensions]) * strength;
+        }
+        for (tfe, &fe) in tf.iter_mut().zip(f) {
+            *tfe += fe;
+        }
+
+        self.algorithm.generate(&tf)
+    }
+
+    /// Fills `buffer`, in row-major order, with the values of the noise over the cells of
+    /// `area`. The noise coordinates for cell `(x, y)` are `(x * scale, y * scale)`.
+    ///
+    /// This is much faster than sampling cell by cell, since the coordinates are computed once
+    /// per row and column, and the cells are evaluated `LANES` at a time, with SIMD instructions
+    /// if the `simd` feature is enabled.
+    ///
+    /// # Panics
+    /// * If the noise generator isn't 2D.
+    /// * If the length of `buffer` isn't the number of cells in `area`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle, USize};
+    /// # use doryen_extra::noise::{FractalMode, Noise};
+    /// # use doryen_extra::random::Random;
+    /// let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+    /// let area = Rectangle::new(Position::new(10, 20), USize::new(4, 3));
+    /// let mut buffer = [0.0; 12];
+    /// noise.fill_2d(&mut buffer, area, 0.5, 4.0, FractalMode::Fbm);
+    /// assert_eq!(buffer[5], noise.fbm(&[5.5, 10.5], 4.0));
+    /// ```
+    pub fn fill_2d(
+        &self,
+        buffer: &mut [f32],
+        area: Rectangle,
+        scale: f32,
+        octaves: f32,
+        mode: FractalMode,
+    ) {
+        self.try_fill_2d(buffer, area, scale, octaves, mode)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Like `fill_2d`, but returns an error instead of panicking if the noise generator isn't 2D
+    /// or if the length of `buffer` isn't the number of cells in `area`.
+    pub fn try_fill_2d(
+        &self,
+        buffer: &mut [f32],
+        area: Rectangle,
+        scale: f32,
+        octaves: f32,
+        mode: FractalMode,
+    ) -> Result<(), Error> {
+        let xs = self.fill_2d_columns(buffer, area, scale)?;
+        let width = (area.size.width as usize).max(1);
+        for (y, row) in buffer.chunks_exact_mut(width).enumerate() {
+            self.fill_2d_row(row, &xs, area, y, scale, octaves, mode);
+        }
+
+        Ok(())
+    }
+
+    /// Like `fill_2d`, but the rows of `buffer` are filled in parallel. The values are identical
+    /// to the ones of `fill_2d`.
+    ///
+    /// # Panics
+    /// * If the noise generator isn't 2D.
+    /// * If the length of `buffer` isn't the number of cells in `area`.
+    #[cfg(feature = "parallel")]
+    pub fn par_fill_2d(
+        &self,
+        buffer: &mut [f32],
+        area: Rectangle,
+        scale: f32,
+        octaves: f32,
+        mode: FractalMode,
+    ) where
+        A: Sync,
+    {
+        self.try_par_fill_2d(buffer, area, scale, octaves, mode)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Like `par_fill_2d`, but returns an error instead of panicking if the noise generator isn't
+    /// 2D or if the length of `buffer` isn't the number of cells in `area`.
+    #[cfg(feature = "parallel")]
+    pub fn try_par_fill_2d(
+        &self,
+        buffer: &mut [f32],
+        area: Rectangle,
+        scale: f32,
+        octaves: f32,
+        mode: FractalMode,
+    ) -> Result<(), Error>
+    where
+        A: Sync,
+    {
+        use rayon::prelude::*;
+
+        let xs = self.fill_2d_columns(buffer, area, scale)?;
+        let width = (area.size.width as usize).max(1);
+        buffer
+            .par_chunks_exact_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| self.fill_2d_row(row, &xs, area, y, scale, octaves, mode));
+
+        Ok(())
+    }
+
+    // Checks the arguments of `fill_2d` and returns the noise coordinates of the columns of
+    // `area`, padded to a whole number of lanes by repeating the last one. There are none if
+    // `area` is empty.
+    fn fill_2d_columns(
+        &self,
+        buffer: &[f32],
+        area: Rectangle,
+        scale: f32,
+    ) -> Result<Vec<f32>, Error> {
+        self.check_dimensions(&[0.0; 2])?;
+        let (width, height) = (area.size.width as usize, area.size.height as usize);
+        if buffer.len() != width * height {
+            return Err(Error::LengthMismatch {
+                expected: width * height,
+                actual: buffer.len(),
+            });
+        }
+        if width == 0 {
+            return Ok(Vec::new());
+        }
+
+        let columns = width.div_ceil(LANES) * LANES;
+        Ok((0..columns)
+            .map(|x| (area.position.x as f32 + x.min(width - 1) as f32) * scale)
+            .collect())
+    }
+
+    // Fills row `y` of a `fill_2d` buffer, given the coordinates of its columns.
+    #[allow(clippy::too_many_arguments)]
+    fn fill_2d_row(
+        &self,
+        row: &mut [f32],
+        xs: &[f32],
+        area: Rectangle,
+        y: usize,
+        scale: f32,
+        octaves: f32,
+        mode: FractalMode,
+    ) {
+        let fy = [(area.position.y as f32 + y as f32) * scale; LANES];
+        for (values, fx) in row.chunks_mut(LANES).zip(xs.chunks_exact(LANES)) {
+            let mut lanes = [0.0; LANES];
+            lanes.copy_from_slice(fx);
+            let samples = self.fractal_2d_lanes(&lanes, &fy, octaves, mode);
+            values.copy_from_slice(&samples[..values.len()]);
+        }
+    }
+
+    // Evaluates the fractal of the given mode at `LANES` 2D coordinates at once. The values are
+    // exactly the same as those of the matching methods, one coordinate at a time.
+    fn fractal_2d_lanes(
+        &self,
+        x: &[f32; LANES],
+        y: &[f32; LANES],
+        mut octaves: f32,
+        mode: FractalMode,
+    ) -> [f32; LANES] {
+        if mode == FractalMode::Flat {
+            return self.algorithm.generate_2d_lanes(x, y);
+        }
+
+        let (mut tx, mut ty) = (*x, *y);
+        let mut sums = [0.0_f64; LANES];
+        let mut totals = [0.0_f64; LANES];
+        let mut weights = [1.0_f32; LANES];
+        let mut octave = |fx: &[f32; LANES], fy: &[f32; LANES], e: f32, amount: f32| {
+            let samples = self.algorithm.generate_2d_lanes(fx, fy);
+            for lane in 0..LANES {
+                let sample = samples[lane];
+                let contribution = match mode {
+                    FractalMode::Flat => unreachable!(),
+                    FractalMode::Fbm => amount * sample,
+                    FractalMode::Turbulence => amount * sample.abs(),
+                    FractalMode::Billow => amount * (2.0 * sample.abs() - 1.0),
+                    FractalMode::RidgedMultifractal => {
+                        let folded = self.offset - sample.abs();
+                        let signal = folded * folded * weights[lane];
+                        weights[lane] = (signal * self.gain).clamp(0.0, 1.0);
+                        totals[lane] += f64::from(amount) * f64::from(e);
+                        amount * signal
+                    }
+                };
+                sums[lane] += f64::from(contribution) * f64::from(e);
+            }
+        };
+
+        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
+            octave(&tx, &ty, e, 1.0);
+            for (fx, fy) in tx.iter_mut().zip(&mut ty) {
+                *fx *= self.lacunarity;
+                *fy *= self.lacunarity;
+            }
+        }
+
+        let exp_i = octaves.trunc() as usize;
+        octaves -= octaves.trunc();
+        if octaves > DELTA {
+            octave(&tx, &ty, self.exponent[exp_i], octaves);
+        }
+
+        let mut values = [0.0; LANES];
+        for ((value, &sum), &total) in values.iter_mut().zip(&sums).zip(&totals) {
+            *value = match mode {
+                FractalMode::RidgedMultifractal if total <= 0.0 => 0.0,
+                FractalMode::RidgedMultifractal => {
+                    (sum / total * 2.0 - 1.0).clamp(-0.99999, 0.99999) as f32
+                }
+                _ => sum.clamp(-0.99999, 0.99999) as f32,
+            };
+        }
+
+        values
+    }
+
+    /// Returns the gain used by `ridged_multifractal`.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets the gain used by `ridged_multifractal`, i.e. how strongly each octave is weighted
+    /// by the one before it. Higher values give more detail along the ridges. Defaults to
+    /// `DEFAULT_GAIN`.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Returns the offset used by `ridged_multifractal`.
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// Sets the offset used by `ridged_multifractal`, which is added to each octave before it's
+    /// squared. Defaults to `DEFAULT_OFFSET`.
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Initializes a noise generator with the given number of dimensions (from 1 to 4) and the
+    /// default lacunarity, from a Mersenne Twister random number generator seeded with `seed`.
+    ///
+    /// The same seed always yields the same noise; see the `Noise` documentation for the exact
+    /// guarantee.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::noise::algorithms::Simplex;
+    /// let noise = Noise::<Simplex>::from_seed(1234, 2);
+    /// let copy = Noise::<Simplex>::from_seed(noise.seed().unwrap(), 2);
+    /// assert_eq!(noise.fbm(&[1.3, 2.7], 4.0), copy.fbm(&[1.3, 2.7], 4.0));
+    /// ```
+    pub fn from_seed(seed: u32, dimensions: usize) -> Self {
+        let mut noise = Self::new(
+            dimensions,
+            DEFAULT_LACUNARITY,
+            Random::new_mt_from_seed(seed),
+        );
+        noise.seed = Some(seed);
+
+        noise
+    }
+
+    /// Returns the seed the noise generator was created or re-seeded with, or `None` if it was
+    /// created from a `Random` given by hand, whose seed isn't known.
+    pub fn seed(&self) -> Option<u32> {
+        self.seed
+    }
+
+    /// Re-initializes the noise generator's algorithm from a Mersenne Twister random number
+    /// generator seeded with `seed`, keeping its dimensions and parameters. Afterwards, it gives
+    /// the same values as a generator created with `from_seed` and the same parameters.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::noise::algorithms::Perlin;
+    /// let mut noise = Noise::<Perlin>::from_seed(1, 3);
+    /// let before = noise.flat(&[0.3, 0.6, 0.9]);
+    /// noise.reseed(2);
+    /// assert_eq!(noise.seed(), Some(2));
+    /// assert_ne!(noise.flat(&[0.3, 0.6, 0.9]), before);
+    /// ```
+    pub fn reseed(&mut self, seed: u32) {
+        let initializer = AlgorithmInitializer::new(Random::new_mt_from_seed(seed));
+        self.algorithm = self.algorithm.renew(self.dimensions, initializer);
+        self.seed = Some(seed);
+    }
+
+    /// Like `flat`, but returns an error instead of panicking if the `f` slice's length isn't
+    /// equal to the `Noise`'s dimensions.
+    pub fn try_flat(&self, f: &[f32]) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.flat(f))
+    }
+
+    /// Like `fbm`, but returns an error instead of panicking if the `f` slice's length isn't
+    /// equal to the `Noise`'s dimensions.
+    pub fn try_fbm(&self, f: &[f32], octaves: f32) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.fbm(f, octaves))
+    }
+
+    /// Like `turbulence`, but returns an error instead of panicking if the `f` slice's length
+    /// isn't equal to the `Noise`'s dimensions.
+    pub fn try_turbulence(&self, f: &[f32], octaves: f32) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.turbulence(f, octaves))
+    }
+
+    /// Like `flat_f64`, but returns an error instead of panicking if the `f` slice's length
+    /// isn't equal to the `Noise`'s dimensions.
+    pub fn try_flat_f64(&self, f: &[f64]) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.flat_f64(f))
+    }
+
+    /// Like `fbm_f64`, but returns an error instead of panicking if the `f` slice's length isn't
+    /// equal to the `Noise`'s dimensions.
+    pub fn try_fbm_f64(&self, f: &[f64], octaves: f32) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.fbm_f64(f, octaves))
+    }
+
+    /// Like `turbulence_f64`, but returns an error instead of panicking if the `f` slice's
+    /// length isn't equal to the `Noise`'s dimensions.
+    pub fn try_turbulence_f64(&self, f: &[f64], octaves: f32) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.turbulence_f64(f, octaves))
+    }
+
+    /// Like `ridged_multifractal`, but returns an error instead of panicking if the `f` slice's
+    /// length isn't equal to the `Noise`'s dimensions.
+    pub fn try_ridged_multifractal(&self, f: &[f32], octaves: f32) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.ridged_multifractal(f, octaves))
+    }
+
+    /// Like `billow`, but returns an error instead of panicking if the `f` slice's length isn't
+    /// equal to the `Noise`'s dimensions.
+    pub fn try_billow(&self, f: &[f32], octaves: f32) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+
+        Ok(self.billow(f, octaves))
+    }
+
+    /// Like `warped`, but returns an error instead of panicking if the `f` slice's length isn't
+    /// equal to the dimensions of both `Noise`s.
+    pub fn try_warped<B: Algorithm>(
+        &self,
+        f: &[f32],
+        warp: &Noise<B>,
+        strength: f32,
+    ) -> Result<f32, Error> {
+        self.check_dimensions(f)?;
+        warp.check_dimensions(f)?;
+
+        Ok(self.warped(f, warp, strength))
+    }
+
+    fn check_dimensions<T>(&self, f: &[T]) -> Result<(), Error> {
+        if f.len() == self.dimensions {
+            Ok(())
+        } else {
+            Err(Error::DimensionMismatch {
+                expected: self.dimensions,
+                actual: f.len(),
+            })
+        }
     }
 
     fn new<R: RandomAlgorithm>(
-        mut dimensions: usize,
+        dimensions: usize,
         //hurst: f32,
         lacunarity: f32,
         random: Random<R>,
+    ) -> Self {
+        Self::new_from(dimensions, lacunarity, random, A::new)
+    }
+
+    fn new_from<R: RandomAlgorithm, F: FnOnce(usize, AlgorithmInitializer<R>) -> A>(
+        mut dimensions: usize,
+        lacunarity: f32,
+        random: Random<R>,
+        algorithm: F,
     ) -> Self {
         dimensions = dimensions.min(4);
 
@@ -187,16 +919,19 @@ impl<A: Algorithm> Noise<A> {
 
         Self {
             dimensions,
-            algorithm: A::new(dimensions, initializer),
+            algorithm: algorithm(dimensions, initializer),
             exponent: Self::exponent(lacunarity),
             lacunarity,
+            gain: DEFAULT_GAIN,
+            offset: DEFAULT_OFFSET,
+            seed: None,
         }
     }
 
     fn exponent(lacunarity: f32) -> [f32; MAX_OCTAVES] {
         let mut exponent = [0.0; MAX_OCTAVES];
         let mut f = 1.0;
-        for e in exponent.iter_mut() {
+        for e in &mut exponent {
             *e = 1.0 / f;
             f *= lacunarity;
         }
@@ -205,6 +940,88 @@ impl<A: Algorithm> Noise<A> {
     }
 }
 
+impl<A: AnalyticDerivative> Noise<A> {
+    /// Returns the noise function value, like `flat`, along with its partial derivatives along
+    /// each axis, which are computed analytically.
+    ///
+    /// # Panics
+    /// If `N` isn't equal to the `Noise`'s dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::Noise;
+    /// # use doryen_extra::random::Random;
+    /// let noise = Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(1));
+    /// let (value, [dx, dy]) = noise.get_with_derivative(&[1.3, 2.7]);
+    /// assert_eq!(value, noise.flat(&[1.3, 2.7]));
+    /// let normal = [-dx, -dy, 1.0];
+    /// ```
+    pub fn get_with_derivative<const N: usize>(&self, f: &[f32; N]) -> (f32, [f32; N]) {
+        assert_eq!(
+            self.dimensions, N,
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        self.algorithm.get_with_derivative(f)
+    }
+
+    /// Returns the Fractal Brownian Motion function value, like `fbm`, along with its partial
+    /// derivatives along each axis, which are computed analytically.
+    ///
+    /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
+    ///
+    /// # Panics
+    /// If `N` isn't equal to the `Noise`'s dimensions.
+    pub fn fbm_with_derivative<const N: usize>(
+        &self,
+        f: &[f32; N],
+        mut octaves: f32,
+    ) -> (f32, [f32; N]) {
+        assert_eq!(
+            self.dimensions, N,
+            "Number of coordinates given in 'f' must match the dimensions."
+        );
+
+        let mut tf = [0.0_f32; MAX_DIMENSIONS];
+        tf[0..self.dimensions].copy_from_slice(f);
+
+        let mut value: f64 = 0.0;
+        let mut derivative = [0.0_f64; N];
+        // Each octave is sampled at the coordinates times this, so its derivatives are too.
+        let mut frequency = 1.0_f64;
+        let mut add_octave = |coordinates: &[f32], e: f32, amount: f32, scale: f64| {
+            let (octave, octave_derivative) = self.algorithm.generate_with_derivative(coordinates);
+            value += f64::from(amount * octave) * f64::from(e);
+            for (d, &od) in derivative.iter_mut().zip(&octave_derivative) {
+                *d += f64::from(amount * od) * f64::from(e) * scale;
+            }
+        };
+
+        /* Inner loop of spectral construction, where the fractal is built */
+        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
+            add_octave(&tf, e, 1.0, frequency);
+            for tfe in tf.iter_mut().take(N) {
+                *tfe *= self.lacunarity;
+            }
+            frequency *= f64::from(self.lacunarity);
+        }
+
+        /* Take care of remainder in octaves */
+        let exp_i = octaves.trunc() as usize;
+        octaves -= octaves.trunc();
+        if octaves > DELTA {
+            add_octave(&tf, self.exponent[exp_i], octaves, frequency);
+        }
+
+        let mut result = [0.0; N];
+        for (r, &d) in result.iter_mut().zip(&derivative) {
+            *r = d as f32;
+        }
+
+        (value.clamp(-0.99999, 0.99999) as f32, result)
+    }
+}
+
 impl Noise<Perlin> {
     /// Initializes a Perlin noise generator with the given number of dimensions (from 1 to 4),
     /// the lacunarity parameter and a random number generator.
@@ -217,6 +1034,108 @@ impl Noise<Perlin> {
     }
 }
 
+/// A noise generator whose algorithm is picked at runtime.
+pub type DynNoise = Noise<AnyAlgorithm>;
+
+impl Noise<AnyAlgorithm> {
+    /// Initializes a noise generator of the given kind with the given number of dimensions (from
+    /// 1 to 4), the lacunarity parameter and a random number generator.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::algorithms::AlgorithmKind;
+    /// # use doryen_extra::noise::DynNoise;
+    /// # use doryen_extra::random::Random;
+    /// let kind: AlgorithmKind = "open_simplex2".parse().unwrap();
+    /// let noise = DynNoise::new_with_kind(kind, 2, 2.0, Random::new_mt_from_seed(1));
+    /// assert_eq!(noise.kind(), AlgorithmKind::OpenSimplex2);
+    /// let value = noise.fbm(&[1.3, 2.7], 4.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Not here, but when generating values, if the kind doesn't support that many dimensions,
+    /// which is the case of `Wavelet` with 4 dimensions. Use `try_new_with_kind` when the kind
+    /// and the dimensions come from outside the program.
+    pub fn new_with_kind<R: RandomAlgorithm>(
+        kind: AlgorithmKind,
+        dimensions: usize,
+        lacunarity: f32,
+        random: Random<R>,
+    ) -> Self {
+        Self::new_from(dimensions, lacunarity, random, |dimensions, initializer| {
+            AnyAlgorithm::new_with_kind(kind, dimensions, initializer)
+        })
+    }
+
+    /// Like `new_with_kind`, but returns an error if the kind doesn't support the given number
+    /// of dimensions, or if it's 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Error;
+    /// # use doryen_extra::noise::algorithms::AlgorithmKind;
+    /// # use doryen_extra::noise::DynNoise;
+    /// # use doryen_extra::random::Random;
+    /// let random = Random::new_mt_from_seed(1);
+    /// let noise = DynNoise::try_new_with_kind(AlgorithmKind::Wavelet, 4, 2.0, random);
+    /// assert_eq!(
+    ///     noise.err(),
+    ///     Some(Error::DimensionMismatch {
+    ///         expected: 3,
+    ///         actual: 4
+    ///     })
+    /// );
+    /// ```
+    pub fn try_new_with_kind<R: RandomAlgorithm>(
+        kind: AlgorithmKind,
+        dimensions: usize,
+        lacunarity: f32,
+        random: Random<R>,
+    ) -> Result<Self, Error> {
+        kind.check_dimensions(dimensions)?;
+
+        Ok(Self::new_with_kind(kind, dimensions, lacunarity, random))
+    }
+
+    /// Initializes a noise generator of the given kind with the given number of dimensions (from
+    /// 1 to 4) and the default lacunarity, from a Mersenne Twister random number generator
+    /// seeded with `seed`. See `from_seed`.
+    ///
+    /// # Panics
+    ///
+    /// Like `new_with_kind`, when generating values, if the kind doesn't support that many
+    /// dimensions.
+    pub fn from_seed_with_kind(kind: AlgorithmKind, seed: u32, dimensions: usize) -> Self {
+        let mut noise = Self::new_with_kind(
+            kind,
+            dimensions,
+            DEFAULT_LACUNARITY,
+            Random::new_mt_from_seed(seed),
+        );
+        noise.seed = Some(seed);
+
+        noise
+    }
+
+    /// Returns the kind of the noise generator's algorithm.
+    pub fn kind(&self) -> AlgorithmKind {
+        self.algorithm.kind()
+    }
+}
+
+impl Noise<OpenSimplex2> {
+    /// Initializes an OpenSimplex2 noise generator with the given number of dimensions (from 1
+    /// to 4), the lacunarity parameter and a random number generator.
+    pub fn new_open_simplex2<R: RandomAlgorithm>(
+        dimensions: usize,
+        lacunarity: f32,
+        random: Random<R>,
+    ) -> Self {
+        Self::new(dimensions, lacunarity, random)
+    }
+}
+
 impl Noise<Simplex> {
     /// Initializes a Simplex noise generator with the given number of dimensions (from 1 to 4),
     /// the lacunarity parameter and a random number generator.
@@ -229,6 +1148,18 @@ impl Noise<Simplex> {
     }
 }
 
+impl Noise<Value> {
+    /// Initializes a Value noise generator with the given number of dimensions (from 1 to 4),
+    /// the lacunarity parameter and a random number generator.
+    pub fn new_value<R: RandomAlgorithm>(
+        dimensions: usize,
+        lacunarity: f32,
+        random: Random<R>,
+    ) -> Self {
+        Self::new(dimensions, lacunarity, random)
+    }
+}
+
 impl Noise<Wavelet> {
     /// Initializes a Wavelet noise generator with the given number of dimensions (from 1 to 4),
     /// the lacunarity parameter and a random number generator.
@@ -240,3 +1171,93 @@ impl Noise<Wavelet> {
         Self::new(dimensions, lacunarity, random)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> impl Iterator<Item = [f32; 2]> {
+        (0..200).map(|i| [i as f32 * 0.173, i as f32 * 0.061 - 3.0])
+    }
+
+    #[test]
+    fn single_octave_billow_folds_the_noise() {
+        let noise = Noise::<Simplex>::from_seed(5, 2);
+        for f in points() {
+            let expected = 2.0 * noise.flat(&f).abs() - 1.0;
+            assert!((noise.billow(&f, 1.0) - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn ridged_without_gain_only_keeps_the_first_octave() {
+        let offset = 0.8;
+        let noise = Noise::<Simplex>::from_seed(5, 2)
+            .with_gain(0.0)
+            .with_offset(offset);
+        // The later octaves get no weight, but still count towards the total they're scaled by.
+        let total: f32 = noise.exponent[..4].iter().sum();
+        for f in points() {
+            let ridge = (offset - noise.flat(&f).abs()).powi(2);
+            let expected = (2.0 * ridge / total - 1.0).clamp(-0.99999, 0.99999);
+            assert!((noise.ridged_multifractal(&f, 4.0) - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn gain_and_offset_change_ridges() {
+        let noise = Noise::<Simplex>::from_seed(5, 2);
+        let ridged = |generator: &Noise<Simplex>| -> Vec<f32> {
+            points()
+                .map(|f| generator.ridged_multifractal(&f, 4.0))
+                .collect()
+        };
+
+        let default = ridged(&noise);
+        assert_ne!(ridged(&noise.clone().with_gain(0.5)), default);
+        assert_ne!(ridged(&noise.clone().with_offset(0.7)), default);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_fill_matches_the_serial_one() {
+        let noise = Noise::<Simplex>::from_seed(5, 2);
+        let area = Rectangle::new(crate::Position::new(-7, 3), crate::USize::new(37, 23));
+        for &mode in &[FractalMode::Flat, FractalMode::Fbm, FractalMode::Turbulence] {
+            let mut serial = vec![0.0; 37 * 23];
+            let mut parallel = vec![0.0; 37 * 23];
+            noise.fill_2d(&mut serial, area, 0.13, 4.0, mode);
+            noise.par_fill_2d(&mut parallel, area, 0.13, 4.0, mode);
+            assert_eq!(serial, parallel);
+        }
+        assert!(noise
+            .try_par_fill_2d(&mut [0.0; 3], area, 0.13, 4.0, FractalMode::Fbm)
+            .is_err());
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloaded_noise_gives_the_same_values() {
+        let noise = Noise::<Simplex>::from_seed(7, 3);
+        let json = serde_json::to_string(&noise).unwrap();
+        let reloaded: Noise<Simplex> = serde_json::from_str(&json).unwrap();
+        for i in 0..100 {
+            let f = [i as f32 * 0.37, i as f32 * 0.11, 1.5];
+            assert_eq!(
+                noise.fbm(&f, 4.0).to_bits(),
+                reloaded.fbm(&f, 4.0).to_bits()
+            );
+        }
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_unsupported_dimensions() {
+        let noise = Noise::<Simplex>::from_seed(7, 3);
+        for &dimensions in &[0, MAX_DIMENSIONS + 1] {
+            let mut json = serde_json::to_value(&noise).unwrap();
+            json["dimensions"] = dimensions.into();
+            assert!(serde_json::from_value::<Noise<Simplex>>(json).is_err());
+        }
+    }
+}
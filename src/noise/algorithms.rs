@@ -33,14 +33,21 @@
 
 //! Noise generator algorithms.
 
+mod dynamic;
+mod open_simplex2;
 mod perlin;
 mod simplex;
+mod value;
 mod wavelet;
 
+pub use dynamic::{AlgorithmKind, AnyAlgorithm};
+pub use open_simplex2::OpenSimplex2;
 pub use perlin::Perlin;
 pub use simplex::Simplex;
+pub use value::Value;
 pub use wavelet::Wavelet;
 
+use crate::math;
 use crate::noise::MAX_DIMENSIONS;
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::random::{Random, Rng};
@@ -51,8 +58,134 @@ pub trait Algorithm {
     /// Creates a new noise algorithm instance.
     fn new<R: RandomAlgorithm>(dimensions: usize, initializer: AlgorithmInitializer<R>) -> Self;
 
+    /// Creates a new instance of the same algorithm as this one, e.g. to re-seed it. The default
+    /// implementation calls `new`; algorithms picked at runtime keep their kind.
+    fn renew<R: RandomAlgorithm>(
+        &self,
+        dimensions: usize,
+        initializer: AlgorithmInitializer<R>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(dimensions, initializer)
+    }
+
     /// Generates the noise value at the given coordinates.
     fn generate(&self, f: &[f32]) -> f32;
+
+    /// Generates the 2D noise values at `LANES` coordinates at once, the `x` coordinates in one
+    /// array and the `y` coordinates in the other. The values are the same as those
+    /// `generate` would give one coordinate at a time.
+    ///
+    /// The `Perlin` and `Simplex` algorithms evaluate the coordinates side by side with SIMD
+    /// instructions when the `simd` feature is enabled.
+    fn generate_2d_lanes(&self, x: &[f32; LANES], y: &[f32; LANES]) -> [f32; LANES] {
+        generate_2d_lanes_one_by_one(self, x, y)
+    }
+
+    /// Generates the noise value at the given `f64` coordinates.
+    ///
+    /// Far from the origin, `f32` coordinates can't hold much of a fraction, which shows up as
+    /// blocky artifacts. The algorithms of this crate repeat themselves on their lattice, so
+    /// they wrap the coordinates back close to the origin, in `f64`, before generating the
+    /// value. The default implementation converts the coordinates to `f32` as they are.
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        let mut tf = [0.0; MAX_DIMENSIONS];
+        for (tfe, &fe) in tf.iter_mut().zip(f) {
+            *tfe = fe as f32;
+        }
+
+        self.generate(&tf[..f.len().min(MAX_DIMENSIONS)])
+    }
+}
+
+/// Noise algorithms that can compute the derivatives of the noise analytically, which is both
+/// faster and more accurate than finite differences, e.g. for normal mapping or for fractals
+/// that depend on the slope of the noise.
+pub trait AnalyticDerivative: Algorithm {
+    /// Generates the noise value at the given coordinates, the same as `generate`, along with
+    /// its partial derivatives along each axis. Only as many derivatives as the algorithm has
+    /// dimensions are used; the rest are 0.0.
+    fn generate_with_derivative(&self, f: &[f32]) -> (f32, [f32; MAX_DIMENSIONS]);
+
+    /// Generates the noise value at the given coordinates along with its partial derivatives
+    /// along each axis.
+    ///
+    /// # Panics
+    /// If `N` is less than the dimensions of the algorithm.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::noise::algorithms::{Algorithm, AlgorithmInitializer, AnalyticDerivative, Simplex};
+    /// # use doryen_extra::random::Random;
+    /// let simplex = Simplex::new(2, AlgorithmInitializer::new(Random::new_mt_from_seed(1)));
+    /// let (value, [dx, dy]) = simplex.get_with_derivative(&[1.3, 2.7]);
+    /// let step = simplex.generate(&[1.3 + 0.001, 2.7]) - value;
+    /// assert!((step / 0.001 - dx).abs() < 0.05);
+    /// ```
+    fn get_with_derivative<const N: usize>(&self, f: &[f32; N]) -> (f32, [f32; N]) {
+        let (value, derivative) = self.generate_with_derivative(f);
+        let mut result = [0.0; N];
+        for (r, &d) in result.iter_mut().zip(&derivative) {
+            *r = d;
+        }
+
+        (value, result)
+    }
+}
+
+/// The number of coordinates `Algorithm::generate_2d_lanes` evaluates at once.
+pub const LANES: usize = 8;
+
+// Wraps coordinates that repeat every `period` along each axis back close to the origin.
+// Coordinates within half a period of the origin are left as they are.
+fn wrap_axes(f: &[f64], period: f64) -> [f32; MAX_DIMENSIONS] {
+    let mut wrapped = [0.0; MAX_DIMENSIONS];
+    for (w, &fe) in wrapped.iter_mut().zip(f) {
+        *w = (fe - (fe / period).round() * period) as f32;
+    }
+
+    wrapped
+}
+
+// Wraps coordinates that repeat every `period` cells along each axis of a skewed lattice back
+// close to the origin. The lattice coordinates are `scale * (f + skew * sum(f))`, and the other
+// way around, `f = (l + unskew * sum(l)) / scale`. Coordinates within half a period of the origin
+// are left as they are.
+fn wrap_skewed(
+    f: &[f64],
+    scale: f64,
+    skew: f64,
+    unskew: f64,
+    period: f64,
+) -> [f32; MAX_DIMENSIONS] {
+    let sum: f64 = f.iter().sum();
+    let mut cells = [0.0; MAX_DIMENSIONS];
+    for (cell, &fe) in cells.iter_mut().zip(f) {
+        *cell = (scale * (fe + skew * sum) / period).round() * period;
+    }
+    let cell_sum: f64 = cells.iter().sum();
+
+    let mut wrapped = [0.0; MAX_DIMENSIONS];
+    for ((w, &fe), &cell) in wrapped.iter_mut().zip(f).zip(&cells) {
+        *w = (fe - (cell + unskew * cell_sum) / scale) as f32;
+    }
+
+    wrapped
+}
+
+fn generate_2d_lanes_one_by_one<A: Algorithm + ?Sized>(
+    algorithm: &A,
+    x: &[f32; LANES],
+    y: &[f32; LANES],
+) -> [f32; LANES] {
+    let mut values = [0.0; LANES];
+    for (value, (&fx, &fy)) in values.iter_mut().zip(x.iter().zip(y)) {
+        *value = algorithm.generate(&[fx, fy]);
+    }
+
+    values
 }
 
 /// Noise algorithm initializer.
@@ -99,14 +232,147 @@ impl<R: RandomAlgorithm> AlgorithmInitializer<R> {
         buffer
     }
 
+    /// Generate a table of random values between -1.0 and 1.0.
+    pub fn values(&mut self) -> [f32; 256] {
+        let mut values = [0.0; 256];
+        for value in &mut values {
+            *value = self.random.get_f32(-1.0, 1.0);
+        }
+
+        values
+    }
+
     fn normalize(dimensions: usize, f: &mut [f32]) {
         let mut magnitude = 0.0;
         for &i in f.iter().take(dimensions) {
             magnitude += i * i;
         }
-        magnitude = 1.0 / magnitude.sqrt();
+        magnitude = 1.0 / math::sqrt(magnitude);
         for i in f.iter_mut().take(dimensions) {
             *i *= magnitude;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generates the noise at the same coordinates with both `generate` and `generate_f64`.
+    type Generate = Box<dyn Fn(&[f32], &[f64]) -> (f32, f32)>;
+
+    fn algorithms(dimensions: usize) -> Vec<Generate> {
+        fn both<A: Algorithm + 'static>(dimensions: usize) -> Generate {
+            let algorithm = A::new(
+                dimensions,
+                AlgorithmInitializer::new(Random::new_mt_from_seed(17)),
+            );
+            Box::new(move |f, f64| (algorithm.generate(f), algorithm.generate_f64(f64)))
+        }
+
+        let mut algorithms = vec![
+            both::<Perlin>(dimensions),
+            both::<Simplex>(dimensions),
+            both::<OpenSimplex2>(dimensions),
+            both::<Value>(dimensions),
+        ];
+        if dimensions <= 3 {
+            algorithms.push(both::<Wavelet>(dimensions));
+        }
+
+        algorithms
+    }
+
+    #[test]
+    fn generate_f64_matches_generate_near_origin() {
+        for dimensions in 1..=MAX_DIMENSIONS {
+            for (a, generate) in algorithms(dimensions).iter().enumerate() {
+                for i in 0..50 {
+                    let f = [i as f32 * 0.37, 2.1 - i as f32 * 0.13, 0.7, 1.9];
+                    let f64 = [f64::from(f[0]), f64::from(f[1]), 0.7, 1.9];
+                    let (value, value_f64) = generate(&f[..dimensions], &f64[..dimensions]);
+                    assert_eq!(value, value_f64, "algorithm {} in {}D", a, dimensions);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_f64_stays_smooth_far_from_origin() {
+        for dimensions in 1..=MAX_DIMENSIONS {
+            for (a, generate) in algorithms(dimensions).iter().enumerate() {
+                let values: Vec<f32> = (0..20)
+                    .map(|i| {
+                        let f = [10_000_000.0 + f64::from(i) * 0.05; MAX_DIMENSIONS];
+                        generate(&[0.0; MAX_DIMENSIONS][..dimensions], &f[..dimensions]).1
+                    })
+                    .collect();
+                let mut distinct = values.clone();
+                distinct.sort_by(|x, y| x.partial_cmp(y).unwrap());
+                distinct.dedup();
+                assert!(
+                    distinct.len() >= 10,
+                    "algorithm {} in {}D: {:?}",
+                    a,
+                    dimensions,
+                    values
+                );
+                for pair in values.windows(2) {
+                    assert!((pair[0] - pair[1]).abs() < 0.5);
+                }
+            }
+        }
+    }
+
+    fn assert_derivative_matches_differences<A: AnalyticDerivative>() {
+        const STEP: f32 = 2e-4;
+        for dimensions in 1..=MAX_DIMENSIONS {
+            let algorithm = A::new(
+                dimensions,
+                AlgorithmInitializer::new(Random::new_mt_from_seed(5)),
+            );
+            for i in 0..40 {
+                let f = [
+                    0.3 + i as f32 * 0.47,
+                    1.1 - i as f32 * 0.29,
+                    2.3 + i as f32 * 0.11,
+                    0.7 - i as f32 * 0.37,
+                ];
+                let (value, derivative) = algorithm.generate_with_derivative(&f[..dimensions]);
+                assert_eq!(value, algorithm.generate(&f[..dimensions]));
+                // Simplex noise isn't quite continuous across the edges of its simplices, so
+                // one of the one-sided differences has to match.
+                for axis in 0..dimensions {
+                    let (mut before, mut after) = (f, f);
+                    before[axis] -= STEP;
+                    after[axis] += STEP;
+                    let forward = (algorithm.generate(&after[..dimensions]) - value) / STEP;
+                    let backward = (value - algorithm.generate(&before[..dimensions])) / STEP;
+                    let error = (forward - derivative[axis])
+                        .abs()
+                        .min((backward - derivative[axis]).abs());
+                    assert!(
+                        error < 0.02,
+                        "{}D, axis {} at {:?}: {} or {} != {}",
+                        dimensions,
+                        axis,
+                        f,
+                        forward,
+                        backward,
+                        derivative[axis]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn perlin_derivative_matches_differences() {
+        assert_derivative_matches_differences::<Perlin>();
+    }
+
+    #[test]
+    fn simplex_derivative_matches_differences() {
+        assert_derivative_matches_differences::<Simplex>();
+    }
+}
@@ -0,0 +1,303 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::noise::algorithms::{
+    Algorithm, AlgorithmInitializer, OpenSimplex2, Perlin, Simplex, Value, Wavelet, LANES,
+};
+use crate::noise::MAX_DIMENSIONS;
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use crate::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// The noise algorithms that can be picked at runtime, e.g. from a configuration file, with
+/// `AnyAlgorithm`.
+///
+/// Kinds are parsed from, and displayed as, their names in lower case, with an underscore
+/// between words, e.g. `"open_simplex2"`.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::noise::algorithms::AlgorithmKind;
+/// let kind: AlgorithmKind = "perlin".parse().unwrap();
+/// assert_eq!(kind, AlgorithmKind::Perlin);
+/// assert_eq!(AlgorithmKind::OpenSimplex2.to_string(), "open_simplex2");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum AlgorithmKind {
+    /// The `Perlin` algorithm.
+    Perlin,
+    /// The `Simplex` algorithm.
+    #[default]
+    Simplex,
+    /// The `OpenSimplex2` algorithm.
+    OpenSimplex2,
+    /// The `Value` algorithm.
+    Value,
+    /// The `Wavelet` algorithm.
+    Wavelet,
+}
+
+impl AlgorithmKind {
+    /// All the algorithm kinds.
+    pub const ALL: [Self; 5] = [
+        Self::Perlin,
+        Self::Simplex,
+        Self::OpenSimplex2,
+        Self::Value,
+        Self::Wavelet,
+    ];
+
+    /// Returns the name of the algorithm kind.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Perlin => "perlin",
+            Self::Simplex => "simplex",
+            Self::OpenSimplex2 => "open_simplex2",
+            Self::Value => "value",
+            Self::Wavelet => "wavelet",
+        }
+    }
+
+    /// Returns the largest number of dimensions the algorithm kind supports: 3 for `Wavelet`,
+    /// and 4 for the others.
+    pub fn max_dimensions(self) -> usize {
+        match self {
+            Self::Wavelet => 3,
+            _ => MAX_DIMENSIONS,
+        }
+    }
+
+    // Checks that the algorithm kind supports the given number of dimensions.
+    pub(crate) fn check_dimensions(self, dimensions: usize) -> Result<(), Error> {
+        if (1..=self.max_dimensions()).contains(&dimensions) {
+            Ok(())
+        } else {
+            Err(Error::DimensionMismatch {
+                expected: self.max_dimensions(),
+                actual: dimensions,
+            })
+        }
+    }
+}
+
+impl Display for AlgorithmKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for AlgorithmKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::NotFound(s.to_string()))
+    }
+}
+
+/// A noise algorithm picked at runtime.
+///
+/// Use `Noise::new_with_kind` to create a `Noise<AnyAlgorithm>`, i.e. a `DynNoise`. Created
+/// through `Algorithm::new`, it's a `Simplex` algorithm, the default `AlgorithmKind`.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum AnyAlgorithm {
+    /// A `Perlin` algorithm.
+    Perlin(Box<Perlin>),
+    /// A `Simplex` algorithm.
+    Simplex(Simplex),
+    /// An `OpenSimplex2` algorithm.
+    OpenSimplex2(OpenSimplex2),
+    /// A `Value` algorithm.
+    Value(Box<Value>),
+    /// A `Wavelet` algorithm.
+    Wavelet(Wavelet),
+}
+
+impl AnyAlgorithm {
+    /// Creates a new noise algorithm instance of the given kind.
+    ///
+    /// # Panics
+    ///
+    /// Not here, but when generating values, if the kind doesn't support that many dimensions,
+    /// which is the case of `Wavelet` with 4 dimensions. Use `try_new_with_kind` when the kind
+    /// and the dimensions come from outside the program.
+    pub fn new_with_kind<R: RandomAlgorithm>(
+        kind: AlgorithmKind,
+        dimensions: usize,
+        initializer: AlgorithmInitializer<R>,
+    ) -> Self {
+        match kind {
+            AlgorithmKind::Perlin => Self::Perlin(Box::new(Perlin::new(dimensions, initializer))),
+            AlgorithmKind::Simplex => Self::Simplex(Simplex::new(dimensions, initializer)),
+            AlgorithmKind::OpenSimplex2 => {
+                Self::OpenSimplex2(OpenSimplex2::new(dimensions, initializer))
+            }
+            AlgorithmKind::Value => Self::Value(Box::new(Value::new(dimensions, initializer))),
+            AlgorithmKind::Wavelet => Self::Wavelet(Wavelet::new(dimensions, initializer)),
+        }
+    }
+
+    /// Like `new_with_kind`, but returns an error if the kind doesn't support the given number
+    /// of dimensions, or if it's 0.
+    pub fn try_new_with_kind<R: RandomAlgorithm>(
+        kind: AlgorithmKind,
+        dimensions: usize,
+        initializer: AlgorithmInitializer<R>,
+    ) -> Result<Self, Error> {
+        kind.check_dimensions(dimensions)?;
+
+        Ok(Self::new_with_kind(kind, dimensions, initializer))
+    }
+
+    /// Returns the kind of the algorithm.
+    pub fn kind(&self) -> AlgorithmKind {
+        match self {
+            Self::Perlin(_) => AlgorithmKind::Perlin,
+            Self::Simplex(_) => AlgorithmKind::Simplex,
+            Self::OpenSimplex2(_) => AlgorithmKind::OpenSimplex2,
+            Self::Value(_) => AlgorithmKind::Value,
+            Self::Wavelet(_) => AlgorithmKind::Wavelet,
+        }
+    }
+}
+
+// Calls the same method on whichever algorithm is inside.
+macro_rules! dispatch {
+    ($self:ident, $algorithm:ident => $call:expr) => {
+        match $self {
+            AnyAlgorithm::Perlin($algorithm) => $call,
+            AnyAlgorithm::Simplex($algorithm) => $call,
+            AnyAlgorithm::OpenSimplex2($algorithm) => $call,
+            AnyAlgorithm::Value($algorithm) => $call,
+            AnyAlgorithm::Wavelet($algorithm) => $call,
+        }
+    };
+}
+
+impl Algorithm for AnyAlgorithm {
+    fn new<R: RandomAlgorithm>(dimensions: usize, initializer: AlgorithmInitializer<R>) -> Self {
+        Self::new_with_kind(AlgorithmKind::default(), dimensions, initializer)
+    }
+
+    fn renew<R: RandomAlgorithm>(
+        &self,
+        dimensions: usize,
+        initializer: AlgorithmInitializer<R>,
+    ) -> Self {
+        Self::new_with_kind(self.kind(), dimensions, initializer)
+    }
+
+    fn generate(&self, f: &[f32]) -> f32 {
+        dispatch!(self, algorithm => algorithm.generate(f))
+    }
+
+    fn generate_2d_lanes(&self, x: &[f32; LANES], y: &[f32; LANES]) -> [f32; LANES] {
+        dispatch!(self, algorithm => algorithm.generate_2d_lanes(x, y))
+    }
+
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        dispatch!(self, algorithm => algorithm.generate_f64(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    #[test]
+    fn kinds_round_trip_through_their_names() {
+        for &kind in &AlgorithmKind::ALL {
+            assert_eq!(kind.to_string().parse::<AlgorithmKind>(), Ok(kind));
+        }
+        assert_eq!(
+            "OpenSimplex2".parse::<AlgorithmKind>(),
+            Err(Error::NotFound("OpenSimplex2".to_string()))
+        );
+        assert_eq!("PERLIN".parse(), Ok(AlgorithmKind::Perlin));
+    }
+
+    #[test]
+    fn dispatches_to_the_algorithm_of_its_kind() {
+        let initializer = || AlgorithmInitializer::new(Random::new_mt_from_seed(9));
+        let perlin = Perlin::new(2, initializer());
+        let any = AnyAlgorithm::new_with_kind(AlgorithmKind::Perlin, 2, initializer());
+        assert_eq!(any.kind(), AlgorithmKind::Perlin);
+        for i in 0..20 {
+            let f = [i as f32 * 0.31, 1.7 - i as f32 * 0.19];
+            assert_eq!(any.generate(&f), perlin.generate(&f));
+        }
+    }
+
+    #[test]
+    fn renewing_keeps_the_kind() {
+        let initializer = |seed| AlgorithmInitializer::new(Random::new_mt_from_seed(seed));
+        for &kind in &AlgorithmKind::ALL {
+            let any = AnyAlgorithm::new_with_kind(kind, 2, initializer(1));
+            let renewed = any.renew(2, initializer(2));
+            let fresh = AnyAlgorithm::new_with_kind(kind, 2, initializer(2));
+            assert_eq!(renewed.kind(), kind);
+            let f = [0.37, 1.41];
+            assert_eq!(renewed.generate(&f), fresh.generate(&f));
+        }
+    }
+
+    #[test]
+    fn unsupported_dimensions_are_rejected() {
+        let initializer = || AlgorithmInitializer::new(Random::new_mt_from_seed(9));
+        assert_eq!(
+            AnyAlgorithm::try_new_with_kind(AlgorithmKind::Wavelet, 4, initializer()).err(),
+            Some(Error::DimensionMismatch {
+                expected: 3,
+                actual: 4
+            })
+        );
+        assert!(AnyAlgorithm::try_new_with_kind(AlgorithmKind::Perlin, 0, initializer()).is_err());
+        for &kind in &AlgorithmKind::ALL {
+            let dimensions = kind.max_dimensions();
+            let any = AnyAlgorithm::try_new_with_kind(kind, dimensions, initializer()).unwrap();
+            any.generate(&[0.3; 4][..dimensions]);
+        }
+    }
+}
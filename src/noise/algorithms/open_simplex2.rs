@@ -0,0 +1,471 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::math;
+use crate::noise::algorithms::{wrap_skewed, Algorithm, AlgorithmInitializer};
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use derivative::Derivative;
+use std::f32::consts::PI;
+
+const SKEW_2D: f32 = 0.366_025_42;
+const UNSKEW_2D: f32 = -0.211_324_87;
+const RSQUARED_2D: f32 = 0.5;
+const NORMALIZER_2D: f32 = 0.010_016_341;
+
+const FALLBACK_ROTATE_3D: f32 = 2.0 / 3.0;
+const RSQUARED_3D: f32 = 0.6;
+const NORMALIZER_3D: f32 = 0.079_698_38;
+
+const SKEW_4D: f32 = -0.138_196_6;
+const UNSKEW_4D: f32 = 0.309_017;
+const LATTICE_STEP_4D: f32 = 0.2;
+const RSQUARED_4D: f32 = 0.6;
+const NORMALIZER_4D: f32 = 0.022_006_593;
+
+/// OpenSimplex2 noise algorithm.
+///
+/// A modern take on simplex noise by K.jpg, without the axis-aligned artifacts the classic
+/// `Simplex` algorithm shows in 2D. One dimensional noise is taken along a line of the two
+/// dimensional noise.
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct OpenSimplex2 {
+    dimensions: usize,
+    #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
+    map: [u8; 256],
+    #[derivative(Debug = "ignore")]
+    gradients: Vec<[f32; 4]>,
+}
+
+impl OpenSimplex2 {
+    // Returns the gradients for the given number of dimensions, spread evenly in every
+    // direction, and scaled so that the noise stays between -1.0 and 1.0.
+    fn gradients(dimensions: usize) -> Vec<[f32; 4]> {
+        match dimensions {
+            1 | 2 => (0..24)
+                .map(|i| {
+                    let (sin, cos) = math::sin_cos((i as f32 + 0.5) * PI / 12.0);
+                    [cos / NORMALIZER_2D, sin / NORMALIZER_2D, 0.0, 0.0]
+                })
+                .collect(),
+            3 => Self::gradients_3d(),
+            _ => Self::face_gradients_4d(),
+        }
+    }
+
+    // Returns the 48 gradients of the reference implementation: every permutation of
+    // (a, a, 1) and (b, c, 0), with every combination of signs. They all have the same length.
+    fn gradients_3d() -> Vec<[f32; 4]> {
+        const A: f32 = 2.224_744_9;
+        const B: f32 = 3.086_266_5;
+        const C: f32 = 1.172_151_3;
+        const PERMUTATIONS: [[usize; 2]; 6] = [[0, 1], [0, 2], [1, 0], [1, 2], [2, 0], [2, 1]];
+
+        let mut gradients = Vec::with_capacity(48);
+        for signs in 0..8 {
+            let sign = |i: usize| if signs & (1 << i) == 0 { 1.0 } else { -1.0 };
+            for one in 0..3 {
+                let mut gradient = [0.0; 4];
+                for (axis, g) in gradient.iter_mut().enumerate().take(3) {
+                    *g = sign(axis) * if axis == one { 1.0 } else { A } / NORMALIZER_3D;
+                }
+                gradients.push(gradient);
+            }
+            // These have a zero coordinate, which has no sign, so they only need four of the
+            // sign combinations.
+            if signs < 4 {
+                for &[b, c] in &PERMUTATIONS {
+                    let mut gradient = [0.0; 4];
+                    gradient[b] = sign(0) * B / NORMALIZER_3D;
+                    gradient[c] = sign(1) * C / NORMALIZER_3D;
+                    gradients.push(gradient);
+                }
+            }
+        }
+
+        gradients
+    }
+
+    // Returns the unit vectors pointing at the middles of the faces of a tesseract, i.e. all the
+    // vectors with one coordinate 0 and the others -1 or 1, normalized.
+    fn face_gradients_4d() -> Vec<[f32; 4]> {
+        let length = math::sqrt(3.0);
+        let mut gradients = Vec::new();
+        for zero in 0..4 {
+            for signs in 0..1 << 3 {
+                let mut gradient = [0.0; 4];
+                let mut bit = 0;
+                for (axis, g) in gradient.iter_mut().enumerate() {
+                    if axis != zero {
+                        let sign = if signs & (1 << bit) == 0 { 1.0 } else { -1.0 };
+                        *g = sign / length / NORMALIZER_4D;
+                        bit += 1;
+                    }
+                }
+                gradients.push(gradient);
+            }
+        }
+
+        gradients
+    }
+
+    fn gradient(&self, lattice: [i32; 4], copy: i32, offset: [f32; 4]) -> f32 {
+        let mut index = usize::from(self.map[(copy & 0xFF) as usize]);
+        for &n in &lattice[..self.dimensions.max(2)] {
+            index = usize::from(self.map[(index as i32 + n) as usize & 0xFF]);
+        }
+        let gradient = &self.gradients[index % self.gradients.len()];
+
+        gradient.iter().zip(&offset).map(|(g, d)| g * d).sum()
+    }
+
+    fn noise_2d(&self, x: f32, y: f32) -> f32 {
+        let s = SKEW_2D * (x + y);
+        let (xs, ys) = (x + s, y + s);
+
+        let (xsb, ysb) = (xs.floor(), ys.floor());
+        let (xi, yi) = (xs - xsb, ys - ysb);
+        let base = [xsb as i32, ysb as i32, 0, 0];
+
+        let t = (xi + yi) * UNSKEW_2D;
+        let (dx0, dy0) = (xi + t, yi + t);
+
+        let mut value = 0.0;
+        let a0 = RSQUARED_2D - dx0 * dx0 - dy0 * dy0;
+        if a0 > 0.0 {
+            value += (a0 * a0) * (a0 * a0) * self.gradient(base, 0, [dx0, dy0, 0.0, 0.0]);
+        }
+
+        let a1 = 2.0 * (1.0 + 2.0 * UNSKEW_2D) * (1.0 / UNSKEW_2D + 2.0) * t
+            + (-2.0 * (1.0 + 2.0 * UNSKEW_2D) * (1.0 + 2.0 * UNSKEW_2D) + a0);
+        if a1 > 0.0 {
+            let dx1 = dx0 - (1.0 + 2.0 * UNSKEW_2D);
+            let dy1 = dy0 - (1.0 + 2.0 * UNSKEW_2D);
+            let lattice = [base[0] + 1, base[1] + 1, 0, 0];
+            value += (a1 * a1) * (a1 * a1) * self.gradient(lattice, 0, [dx1, dy1, 0.0, 0.0]);
+        }
+
+        let (dx2, dy2, lattice) = if dy0 > dx0 {
+            (
+                dx0 - UNSKEW_2D,
+                dy0 - (UNSKEW_2D + 1.0),
+                [base[0], base[1] + 1, 0, 0],
+            )
+        } else {
+            (
+                dx0 - (UNSKEW_2D + 1.0),
+                dy0 - UNSKEW_2D,
+                [base[0] + 1, base[1], 0, 0],
+            )
+        };
+        let a2 = RSQUARED_2D - dx2 * dx2 - dy2 * dy2;
+        if a2 > 0.0 {
+            value += (a2 * a2) * (a2 * a2) * self.gradient(lattice, 0, [dx2, dy2, 0.0, 0.0]);
+        }
+
+        value
+    }
+
+    fn noise_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        // Rotate onto the body-centered cubic lattice, made of two offset cubic lattices.
+        let r = FALLBACK_ROTATE_3D * (x + y + z);
+        let rotated = [r - x, r - y, r - z];
+
+        let mut base = [0; 4];
+        let mut relative = [0.0; 3];
+        let mut negative_sign = [0; 3];
+        let mut absolute = [0.0; 3];
+        for axis in 0..3 {
+            let rounded = rotated[axis].round();
+            base[axis] = rounded as i32;
+            relative[axis] = rotated[axis] - rounded;
+            // -1 if positive, 1 if negative.
+            negative_sign[axis] = if relative[axis] > 0.0 { -1 } else { 1 };
+            absolute[axis] = relative[axis].abs();
+        }
+
+        let mut value = 0.0;
+        let mut a = RSQUARED_3D - relative.iter().map(|d| d * d).sum::<f32>();
+        for copy in 0..2 {
+            // The closest point of the cube.
+            if a > 0.0 {
+                let offset = [relative[0], relative[1], relative[2], 0.0];
+                value += (a * a) * (a * a) * self.gradient(base, copy, offset);
+            }
+
+            // The second closest point, along the axis the point is farthest along.
+            let [ax, ay, az] = absolute;
+            let farthest = if ax >= ay && ax >= az {
+                0
+            } else if ay > ax && ay >= az {
+                1
+            } else {
+                2
+            };
+            let mut b = a + absolute[farthest] + absolute[farthest];
+            if b > 1.0 {
+                b -= 1.0;
+                let mut lattice = base;
+                lattice[farthest] -= negative_sign[farthest];
+                let mut offset = [relative[0], relative[1], relative[2], 0.0];
+                offset[farthest] += negative_sign[farthest] as f32;
+                value += (b * b) * (b * b) * self.gradient(lattice, copy, offset);
+            }
+
+            // Move on to the other lattice, which is offset by half a cell.
+            for axis in 0..3 {
+                absolute[axis] = 0.5 - absolute[axis];
+                relative[axis] = negative_sign[axis] as f32 * absolute[axis];
+                if negative_sign[axis] < 0 {
+                    base[axis] += 1;
+                }
+                negative_sign[axis] = -negative_sign[axis];
+            }
+            a += (0.75 - absolute[0]) - (absolute[1] + absolute[2]);
+        }
+
+        value
+    }
+
+    fn noise_4d(&self, x: f32, y: f32, z: f32, w: f32) -> f32 {
+        let s = SKEW_4D * (x + y + z + w);
+        let skewed = [x + s, y + s, z + s, w + s];
+
+        let mut base = [0; 4];
+        let mut relative = [0.0; 4];
+        for axis in 0..4 {
+            let floor = skewed[axis].floor();
+            base[axis] = floor as i32;
+            relative[axis] = skewed[axis] - floor;
+        }
+
+        // Start on the lattice copy most likely to have a contributing point in the base
+        // simplex of its cell.
+        let sum: f32 = relative.iter().sum();
+        let starting_lattice = (sum * 1.25) as i32;
+        let starting_offset = starting_lattice as f32 * -LATTICE_STEP_4D;
+        for r in &mut relative {
+            *r += starting_offset;
+        }
+        let mut ssi = (sum + starting_offset * 4.0) * UNSKEW_4D;
+        let mut copy = starting_lattice;
+
+        // One point from each of five copies of the lattice.
+        let mut value = 0.0;
+        for i in 0..5 {
+            // The closest vertex of the simplex whose base vertex is the current one.
+            let score0 = 1.0 + ssi * (-1.0 / UNSKEW_4D);
+            let [xsi, ysi, zsi, wsi] = relative;
+            let axis = if xsi >= ysi && xsi >= zsi && xsi >= wsi && xsi >= score0 {
+                Some(0)
+            } else if ysi > xsi && ysi >= zsi && ysi >= wsi && ysi >= score0 {
+                Some(1)
+            } else if zsi > xsi && zsi > ysi && zsi >= wsi && zsi >= score0 {
+                Some(2)
+            } else if wsi > xsi && wsi > ysi && wsi > zsi && wsi >= score0 {
+                Some(3)
+            } else {
+                None
+            };
+            if let Some(axis) = axis {
+                base[axis] += 1;
+                relative[axis] -= 1.0;
+                ssi -= UNSKEW_4D;
+            }
+
+            let offset = [
+                relative[0] + ssi,
+                relative[1] + ssi,
+                relative[2] + ssi,
+                relative[3] + ssi,
+            ];
+            let a = offset.iter().map(|d| d * d).sum::<f32>();
+            if a < RSQUARED_4D {
+                let falloff = (a - RSQUARED_4D) * (a - RSQUARED_4D);
+                value += falloff * falloff * self.gradient(base, copy, offset);
+            }
+
+            // Move on to the next lattice copy, shifted by -0.2 along every axis.
+            for r in &mut relative {
+                *r += LATTICE_STEP_4D;
+            }
+            ssi += LATTICE_STEP_4D * 4.0 * UNSKEW_4D;
+            copy -= 1;
+
+            // Since the first copy isn't always the same one, the base vertex has to wrap.
+            if i == starting_lattice {
+                for b in &mut base {
+                    *b -= 1;
+                }
+                copy += 5;
+            }
+        }
+
+        value
+    }
+}
+
+impl Algorithm for OpenSimplex2 {
+    fn new<R: RandomAlgorithm>(
+        dimensions: usize,
+        mut initializer: AlgorithmInitializer<R>,
+    ) -> Self {
+        Self {
+            dimensions,
+            map: initializer.map(),
+            gradients: Self::gradients(dimensions),
+        }
+    }
+
+    fn generate(&self, f: &[f32]) -> f32 {
+        assert!(f.len() >= self.dimensions);
+
+        let value = match self.dimensions {
+            1 => self.noise_2d(f[0], 0.0),
+            2 => self.noise_2d(f[0], f[1]),
+            3 => self.noise_3d(f[0], f[1], f[2]),
+            4 => self.noise_4d(f[0], f[1], f[2], f[3]),
+            _ => unreachable!(),
+        };
+
+        value.clamp(-0.99999, 0.99999)
+    }
+
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        // The lattices repeat every 256 cells, since that's the size of the map.
+        let value = match self.dimensions {
+            1 | 2 => {
+                let y = if self.dimensions == 1 { 0.0 } else { f[1] };
+                let [x, y, ..] = wrap_skewed(
+                    &[f[0], y],
+                    1.0,
+                    0.366_025_403_784_438_65,
+                    -0.211_324_865_405_187_13,
+                    256.0,
+                );
+                self.noise_2d(x, y)
+            }
+            // The rotation onto the body-centered cubic lattice is its own inverse.
+            3 => {
+                let [x, y, z, _] = wrap_skewed(&f[..3], -1.0, -2.0 / 3.0, -2.0 / 3.0, 256.0);
+                self.noise_3d(x, y, z)
+            }
+            4 => {
+                let [x, y, z, w] = wrap_skewed(
+                    &f[..4],
+                    1.0,
+                    -0.138_196_601_125_011,
+                    0.309_016_994_374_947,
+                    256.0,
+                );
+                self.noise_4d(x, y, z, w)
+            }
+            _ => unreachable!(),
+        };
+
+        value.clamp(-0.99999, 0.99999)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Random, Rng};
+
+    fn noise(dimensions: usize, seed: u32) -> OpenSimplex2 {
+        OpenSimplex2::new(
+            dimensions,
+            AlgorithmInitializer::new(Random::new_mt_from_seed(seed)),
+        )
+    }
+
+    #[test]
+    fn stays_in_range_without_clamping() {
+        // The normalizers should keep the noise within -1.0 and 1.0, while still coming close
+        // to those limits.
+        let mut random = Random::new_mt_from_seed(1);
+        let (two, three, four) = (noise(2, 3), noise(3, 3), noise(4, 3));
+        let mut largest = [0.0_f32; 3];
+        for _ in 0..200_000 {
+            let f: Vec<f32> = (0..4).map(|_| random.get_f32(-100.0, 100.0)).collect();
+            let values = [
+                two.noise_2d(f[0], f[1]),
+                three.noise_3d(f[0], f[1], f[2]),
+                four.noise_4d(f[0], f[1], f[2], f[3]),
+            ];
+            for (value, peak) in values.iter().zip(&mut largest) {
+                assert!(value.abs() <= 1.0, "{}", value);
+                *peak = peak.max(value.abs());
+            }
+        }
+        assert!(largest.iter().all(|&value| value > 0.9), "{:?}", largest);
+    }
+
+    #[test]
+    fn gradients_3d_match_the_reference_set() {
+        let gradients = OpenSimplex2::gradients_3d();
+        assert_eq!(gradients.len(), 48);
+        for (i, gradient) in gradients.iter().enumerate() {
+            let length = gradient.iter().map(|g| g * g).sum::<f32>().sqrt() * NORMALIZER_3D;
+            assert!((length - 3.301_44).abs() < 1e-4, "{}", length);
+            assert!(gradients[..i].iter().all(|other| other != gradient));
+        }
+    }
+
+    #[test]
+    fn is_deterministic_and_varies() {
+        for dimensions in 1..=4 {
+            let a = noise(dimensions, 7);
+            let b = noise(dimensions, 7);
+            let mut distinct = false;
+            for i in 0..100 {
+                let f = [i as f32 * 0.31, 0.7, 1.9, 2.3];
+                assert_eq!(a.generate(&f), b.generate(&f));
+                if (a.generate(&f) - a.generate(&[0.0; 4])).abs() > 1e-3 {
+                    distinct = true;
+                }
+            }
+            assert!(distinct);
+        }
+    }
+
+    #[test]
+    fn lattice_points_are_zero_in_2d() {
+        let noise = noise(2, 11);
+        assert_eq!(noise.generate(&[0.0, 0.0]), 0.0);
+    }
+}
@@ -31,7 +31,9 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-use crate::noise::algorithms::AlgorithmInitializer;
+#[cfg(feature = "simd")]
+use crate::noise::algorithms::{generate_2d_lanes_one_by_one, LANES};
+use crate::noise::algorithms::{wrap_axes, AlgorithmInitializer, AnalyticDerivative};
 use crate::noise::{Algorithm, MAX_DIMENSIONS};
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use derivative::Derivative;
@@ -40,13 +42,19 @@ use ilyvion_util::multi_dimensional::Window2D;
 /// Perlin noise algorithm.
 #[derive(Clone, Copy, Derivative)]
 #[derivative(Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
 pub struct Perlin {
     dimensions: usize,
     /** Randomized map of indexes into buffer */
     #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
     pub map: [u8; 256],
     /** Random 256 x ndim buffer */
     #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
     pub buffer: [f32; MAX_DIMENSIONS * 256],
 }
 
@@ -356,6 +364,52 @@ impl Perlin {
     fn cubic_f32(a: f32) -> f32 {
         a * a * (3.0 - 2.0 * a)
     }
+
+    // The same as `perlin_2d`, for `LANES` coordinates at once. The lattice lookups are done
+    // lane by lane, the arithmetic on all lanes at once.
+    #[cfg(feature = "simd")]
+    fn perlin_2d_lanes(&self, x: &[f32; LANES], y: &[f32; LANES]) -> [f32; LANES] {
+        use wide::f32x8;
+
+        let mut r = [[0.0; LANES]; 2];
+        let mut w = [[0.0; LANES]; 2];
+        // The gradients of the four corners of the cell, one coordinate at a time.
+        let mut gradients = [[[0.0; LANES]; 2]; 4];
+        for lane in 0..LANES {
+            let n = [x[lane].floor() as i32, y[lane].floor() as i32];
+            for (axis, &f) in [x[lane], y[lane]].iter().enumerate() {
+                r[axis][lane] = f - n[axis] as f32;
+                w[axis][lane] = Self::cubic_f32(r[axis][lane]);
+            }
+            for (corner, gradient) in gradients.iter_mut().enumerate() {
+                let (dx, dy) = ((corner & 1) as i32, (corner >> 1) as i32);
+                let mut index = i32::from(self.map[((n[0] + dx) & 0xFF) as usize]);
+                index = i32::from(self.map[((index + n[1] + dy) & 0xFF) as usize]);
+                let offset = index as usize * MAX_DIMENSIONS;
+                gradient[0][lane] = self.buffer[offset];
+                gradient[1][lane] = self.buffer[offset + 1];
+            }
+        }
+
+        let (rx, ry) = (f32x8::from(r[0]), f32x8::from(r[1]));
+        let (wx, wy) = (f32x8::from(w[0]), f32x8::from(w[1]));
+        let one = f32x8::splat(1.0);
+        let lattice = |corner: usize, fx: f32x8, fy: f32x8| {
+            f32x8::from(gradients[corner][0]) * fx + f32x8::from(gradients[corner][1]) * fy
+        };
+        let a = lattice(0, rx, ry);
+        let b = lattice(1, rx - one, ry);
+        let c = lattice(2, rx, ry - one);
+        let d = lattice(3, rx - one, ry - one);
+        let top = a + wx * (b - a);
+        let bottom = c + wx * (d - c);
+        let value = top + wy * (bottom - top);
+
+        value
+            .max(f32x8::splat(-0.99999))
+            .min(f32x8::splat(0.99999))
+            .to_array()
+    }
 }
 
 impl Algorithm for Perlin {
@@ -388,6 +442,80 @@ impl Algorithm for Perlin {
             _ => unreachable!(),
         };
 
-        value.max(-0.99999).min(0.99999)
+        value.clamp(-0.99999, 0.99999)
+    }
+
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        self.generate(&wrap_axes(&f[..self.dimensions], 256.0))
+    }
+
+    #[cfg(feature = "simd")]
+    fn generate_2d_lanes(&self, x: &[f32; LANES], y: &[f32; LANES]) -> [f32; LANES] {
+        if self.dimensions == 2 {
+            self.perlin_2d_lanes(x, y)
+        } else {
+            generate_2d_lanes_one_by_one(self, x, y)
+        }
+    }
+}
+
+impl AnalyticDerivative for Perlin {
+    fn generate_with_derivative(&self, f: &[f32]) -> (f32, [f32; MAX_DIMENSIONS]) {
+        let dimensions = self.dimensions;
+        let mut n = [0; MAX_DIMENSIONS];
+        let mut r = [0.0; MAX_DIMENSIONS];
+        let mut w = [0.0; MAX_DIMENSIONS];
+        let mut dw = [0.0; MAX_DIMENSIONS];
+        for i in 0..dimensions {
+            n[i] = f[i].floor() as i32;
+            r[i] = f[i] - n[i] as f32;
+            w[i] = Self::cubic_f32(r[i]);
+            dw[i] = 6.0 * r[i] * (1.0 - r[i]);
+        }
+
+        // The value is the sum of the lattice values of the corners of the cell, weighted by the
+        // interpolation curves, so the derivative is the sum of the derivatives of those terms.
+        let mut derivative = [0.0; MAX_DIMENSIONS];
+        for bits in 0..1 << dimensions {
+            // Like libtcod's, `perlin_4d` looks two of its corners up as if their fourth
+            // coordinate were 0, so those corners don't depend on it.
+            let flat_corner = dimensions == 4 && bits & 0b0111 == 0b0110;
+            let mut n_index = 0;
+            let mut offset = [0.0; MAX_DIMENSIONS];
+            let mut weights = [0.0; MAX_DIMENSIONS];
+            let mut weight_derivatives = [0.0; MAX_DIMENSIONS];
+            for i in 0..dimensions {
+                let bit = (bits >> i) & 1;
+                if flat_corner && i == 3 {
+                    n_index = i32::from(self.map[(n_index & 0xFF) as usize]);
+                } else {
+                    n_index = i32::from(self.map[((n_index + n[i] + bit) & 0xFF) as usize]);
+                    offset[i] = r[i] - bit as f32;
+                }
+                if bit == 1 {
+                    weights[i] = w[i];
+                    weight_derivatives[i] = dw[i];
+                } else {
+                    weights[i] = 1.0 - w[i];
+                    weight_derivatives[i] = -dw[i];
+                }
+            }
+            let gradient = &self.buffer[n_index as usize * MAX_DIMENSIONS..][..dimensions];
+            let lattice: f32 = gradient.iter().zip(&offset).map(|(g, o)| g * o).sum();
+            let weight: f32 = weights[..dimensions].iter().product();
+
+            for k in 0..dimensions {
+                let other_weights: f32 = (0..dimensions)
+                    .filter(|&i| i != k)
+                    .map(|i| weights[i])
+                    .product();
+                if !(flat_corner && k == 3) {
+                    derivative[k] += weight * gradient[k];
+                }
+                derivative[k] += lattice * weight_derivatives[k] * other_weights;
+            }
+        }
+
+        (self.generate(f), derivative)
     }
 }
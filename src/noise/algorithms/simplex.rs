@@ -31,8 +31,10 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-use crate::noise::algorithms::AlgorithmInitializer;
-use crate::noise::Algorithm;
+#[cfg(feature = "simd")]
+use crate::noise::algorithms::{generate_2d_lanes_one_by_one, LANES};
+use crate::noise::algorithms::{wrap_skewed, AlgorithmInitializer, AnalyticDerivative};
+use crate::noise::{Algorithm, MAX_DIMENSIONS};
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::util::FloorRem;
 
@@ -41,9 +43,14 @@ use derivative::Derivative;
 /// Simplex noise algorithm.
 #[derive(Clone, Copy, Derivative)]
 #[derivative(Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
 pub struct Simplex {
     dimensions: usize,
     #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
     map: [u8; 256],
 }
 
@@ -69,6 +76,35 @@ impl Algorithm for Simplex {
             _ => unreachable!(),
         }
     }
+
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        // The skew factors of the `simplex_*d` functions.
+        let (skew, unskew) = match self.dimensions {
+            1 => (0.0, 0.0),
+            2 => (0.366_025_403, -0.211_324_865),
+            3 => (0.333_333_333, -0.166_666_667),
+            4 => (0.309_016_994, -0.138_196_601),
+            _ => unreachable!(),
+        };
+        let scale = f64::from(Self::SIMPLEX_SCALE);
+
+        self.generate(&wrap_skewed(
+            &f[..self.dimensions],
+            scale,
+            skew,
+            unskew,
+            256.0,
+        ))
+    }
+
+    #[cfg(feature = "simd")]
+    fn generate_2d_lanes(&self, x: &[f32; LANES], y: &[f32; LANES]) -> [f32; LANES] {
+        if self.dimensions == 2 {
+            self.simplex_2d_lanes(x, y)
+        } else {
+            generate_2d_lanes_one_by_one(self, x, y)
+        }
+    }
 }
 
 impl Simplex {
@@ -213,6 +249,83 @@ impl Simplex {
         40.0 * (n0 + n1 + n2)
     }
 
+    // The same as `simplex_2d`, for `LANES` coordinates at once. The skewing and the hashing
+    // are done lane by lane, the falloff and the gradients on all lanes at once.
+    #[cfg(feature = "simd")]
+    #[allow(clippy::many_single_char_names)]
+    fn simplex_2d_lanes(&self, x: &[f32; LANES], y: &[f32; LANES]) -> [f32; LANES] {
+        use wide::{f32x8, CmpLt};
+
+        const F2: f64 = 0.366_025_403;
+        const G2: f64 = 0.211_324_865;
+        // The gradients picked by `simplex_gradient_2d`, as multipliers of x and y.
+        const GRADIENTS: [(f32, f32); 8] = [
+            (1.0, 2.0),
+            (-1.0, 2.0),
+            (1.0, -2.0),
+            (-1.0, -2.0),
+            (2.0, 1.0),
+            (2.0, -1.0),
+            (-2.0, 1.0),
+            (-2.0, -1.0),
+        ];
+
+        // The offsets and the gradients of the three corners of the simplex.
+        let mut offsets = [[[0.0; LANES]; 2]; 3];
+        let mut gradients = [[[0.0; LANES]; 2]; 3];
+        for lane in 0..LANES {
+            let (f0, f1) = (x[lane], y[lane]);
+            let s = f64::from(f0 + f1) * F2 * f64::from(Self::SIMPLEX_SCALE);
+            let xs = f0 * Self::SIMPLEX_SCALE + s as f32;
+            let ys = f1 * Self::SIMPLEX_SCALE + s as f32;
+            let i = xs.floor() as i32;
+            let j = ys.floor() as i32;
+            let t = (f64::from(i) + f64::from(j)) * G2;
+            let xo = f64::from(i) - t;
+            let yo = f64::from(j) - t;
+            let x0 = f0 * Self::SIMPLEX_SCALE - xo as f32;
+            let y0 = f1 * Self::SIMPLEX_SCALE - yo as f32;
+            let ii = i.floor_modulo(256);
+            let jj = j.floor_modulo(256);
+            let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+            let corners = [
+                (x0, y0, 0, 0),
+                (
+                    x0 - i1 as f32 + G2 as f32,
+                    y0 - j1 as f32 + G2 as f32,
+                    i1,
+                    j1,
+                ),
+                (
+                    x0 - 1.0 + (2.0 * G2) as f32,
+                    y0 - 1.0 + (2.0 * G2) as f32,
+                    1,
+                    1,
+                ),
+            ];
+            for (corner, &(cx, cy, di, dj)) in corners.iter().enumerate() {
+                offsets[corner][0][lane] = cx;
+                offsets[corner][1][lane] = cy;
+                let idx = (ii + di + i32::from(self.map[((jj + dj) & 0xFF) as usize])) & 0xFF;
+                let h = i32::from(self.map[idx as usize]) & 0x7;
+                let (gx, gy) = GRADIENTS[h as usize];
+                gradients[corner][0][lane] = gx;
+                gradients[corner][1][lane] = gy;
+            }
+        }
+
+        let mut value = f32x8::ZERO;
+        for (offset, gradient) in offsets.iter().zip(&gradients) {
+            let (cx, cy) = (f32x8::from(offset[0]), f32x8::from(offset[1]));
+            let t = f32x8::splat(0.5) - cx * cx - cy * cy;
+            let t2 = t * t;
+            let n = (f32x8::from(gradient[0]) * cx + f32x8::from(gradient[1]) * cy) * t2 * t2;
+            value += t.cmp_lt(f32x8::ZERO).blend(f32x8::ZERO, n);
+        }
+
+        (f32x8::splat(40.0) * value).to_array()
+    }
+
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::many_single_char_names)]
     fn simplex_3d(&self, f0: f32, f1: f32, f2: f32) -> f32 {
@@ -523,6 +636,16 @@ impl Simplex {
         27.0 * (n0 + n1 + n2 + n3 + n4)
     }
 
+    fn simplex_gradient(&self, h: i32, x: [f32; MAX_DIMENSIONS]) -> f32 {
+        match self.dimensions {
+            1 => Self::simplex_gradient_1d(h, x[0]),
+            2 => Self::simplex_gradient_2d(h, x[0], x[1]),
+            3 => Self::simplex_gradient_3d(h, x[0], x[1], x[2]),
+            4 => Self::simplex_gradient_4d(h, x[0], x[1], x[2], x[3]),
+            _ => unreachable!(),
+        }
+    }
+
     fn simplex_gradient_1d(mut h: i32, x: f32) -> f32 {
         h &= 0xF;
         let mut grad = 1.0 + (h & 7) as f32;
@@ -568,3 +691,78 @@ impl Simplex {
             + if h & 4 == 4 { -w } else { w })
     }
 }
+
+impl AnalyticDerivative for Simplex {
+    #[allow(clippy::many_single_char_names)]
+    fn generate_with_derivative(&self, f: &[f32]) -> (f32, [f32; MAX_DIMENSIONS]) {
+        // The skew factors, radii and scales of the `simplex_*d` functions.
+        let (skew, unskew, radius, scale) = match self.dimensions {
+            1 => (0.0, 0.0, 1.0, 0.25),
+            2 => (0.366_025_403, 0.211_324_865, 0.5, 40.0),
+            3 => (0.333_333_333, 0.166_666_667, 0.6, 32.0),
+            4 => (0.309_016_994, 0.138_196_601, 0.6, 27.0),
+            _ => unreachable!(),
+        };
+        let dimensions = self.dimensions;
+        let f = &f[..dimensions];
+
+        let s = f64::from(f.iter().sum::<f32>()) * skew * f64::from(Self::SIMPLEX_SCALE);
+        let mut cell = [0; MAX_DIMENSIONS];
+        for (c, &fe) in cell.iter_mut().zip(f) {
+            *c = (fe * Self::SIMPLEX_SCALE + s as f32).floor() as i32;
+        }
+        let t = cell.iter().map(|&c| f64::from(c)).sum::<f64>() * unskew;
+        let mut x0 = [0.0; MAX_DIMENSIONS];
+        for i in 0..dimensions {
+            x0[i] = f[i] * Self::SIMPLEX_SCALE - (f64::from(cell[i]) - t) as f32;
+        }
+
+        // The corners of the simplex are reached by stepping along the axes in the order of
+        // the offsets into the cell, largest first.
+        let mut order = [0, 1, 2, 3];
+        order[..dimensions].sort_by(|&a, &b| x0[b].partial_cmp(&x0[a]).unwrap());
+
+        let mut derivative = [0.0; MAX_DIMENSIONS];
+        let mut step = [0; MAX_DIMENSIONS];
+        for corner in 0..=dimensions {
+            if corner > 0 {
+                step[order[corner - 1]] = 1;
+            }
+            let mut x = [0.0; MAX_DIMENSIONS];
+            for i in 0..dimensions {
+                x[i] = x0[i] - step[i] as f32 + (corner as f64 * unskew) as f32;
+            }
+            let falloff = radius - x.iter().map(|xe| xe * xe).sum::<f32>();
+            if falloff <= 0.0 {
+                continue;
+            }
+
+            let mut h = 0;
+            for i in (0..dimensions).rev() {
+                h = i32::from(
+                    self.map[((cell[i].floor_modulo(256) + step[i] + h) & 0xFF) as usize],
+                );
+            }
+            // The gradient functions are linear, so each component of the gradient is its value
+            // along that axis.
+            let mut gradient = [0.0; MAX_DIMENSIONS];
+            for (k, g) in gradient.iter_mut().enumerate().take(dimensions) {
+                let mut axis = [0.0; MAX_DIMENSIONS];
+                axis[k] = 1.0;
+                *g = self.simplex_gradient(h, axis);
+            }
+            let dot: f32 = gradient.iter().zip(&x).map(|(g, xe)| g * xe).sum();
+
+            let falloff2 = falloff * falloff;
+            let falloff4 = falloff2 * falloff2;
+            for i in 0..dimensions {
+                derivative[i] += falloff4 * gradient[i] - 8.0 * falloff2 * falloff * dot * x[i];
+            }
+        }
+        for d in &mut derivative {
+            *d *= scale * Self::SIMPLEX_SCALE;
+        }
+
+        (self.generate(f), derivative)
+    }
+}
@@ -0,0 +1,144 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::noise::algorithms::{wrap_axes, Algorithm, AlgorithmInitializer};
+use crate::noise::MAX_DIMENSIONS;
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use derivative::Derivative;
+
+/// Value noise algorithm.
+///
+/// Every point of the integer lattice gets a random value, and the values in between are
+/// interpolated with a cubic curve. It's faster than Perlin noise but blockier, which makes it
+/// a good fit for low-frequency uses like biome masks.
+#[derive(Clone, Copy, Derivative)]
+#[derivative(Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Value {
+    dimensions: usize,
+    #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
+    map: [u8; 256],
+    #[derivative(Debug = "ignore")]
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
+    values: [f32; 256],
+}
+
+impl Value {
+    fn lattice(&self, n: &[i32]) -> f32 {
+        let mut index = 0;
+        for &ni in n {
+            index = usize::from(self.map[(index as i32 + ni) as usize & 0xFF]);
+        }
+
+        self.values[index]
+    }
+
+    fn cubic(t: f32) -> f32 {
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Algorithm for Value {
+    fn new<R: RandomAlgorithm>(
+        dimensions: usize,
+        mut initializer: AlgorithmInitializer<R>,
+    ) -> Self {
+        Self {
+            dimensions,
+            map: initializer.map(),
+            values: initializer.values(),
+        }
+    }
+
+    fn generate(&self, f: &[f32]) -> f32 {
+        let mut n = [0; MAX_DIMENSIONS];
+        let mut w = [0.0; MAX_DIMENSIONS];
+        for i in 0..self.dimensions {
+            let floor = f[i].floor();
+            n[i] = floor as i32;
+            w[i] = Self::cubic(f[i] - floor);
+        }
+
+        // Every corner of the lattice cell around the point, weighted by how close it is.
+        let mut value = 0.0;
+        let mut corner = [0; MAX_DIMENSIONS];
+        for bits in 0..1 << self.dimensions {
+            let mut weight = 1.0;
+            for i in 0..self.dimensions {
+                if bits & (1 << i) == 0 {
+                    corner[i] = n[i];
+                    weight *= 1.0 - w[i];
+                } else {
+                    corner[i] = n[i] + 1;
+                    weight *= w[i];
+                }
+            }
+            value += weight * self.lattice(&corner[..self.dimensions]);
+        }
+
+        value.clamp(-0.99999, 0.99999)
+    }
+
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        self.generate(&wrap_axes(&f[..self.dimensions], 256.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    #[test]
+    fn lattice_points_have_their_own_values() {
+        let value = Value::new(2, AlgorithmInitializer::new(Random::new_mt_from_seed(3)));
+        let corner = value.lattice(&[4, -7]);
+        assert_eq!(
+            value.generate(&[4.0, -7.0]),
+            corner.clamp(-0.99999, 0.99999)
+        );
+
+        // Halfway between two lattice points, the cubic curve gives both the same weight.
+        let next = value.lattice(&[5, -7]);
+        let halfway = value.generate(&[4.5, -7.0]);
+        assert!((halfway - (corner + next) / 2.0).abs() < 1e-6);
+
+        for i in 0..100 {
+            let v = value.generate(&[i as f32 * 0.37, i as f32 * -0.91]);
+            assert!(v > -1.0 && v < 1.0);
+        }
+    }
+}
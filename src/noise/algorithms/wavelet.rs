@@ -31,7 +31,7 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-use crate::noise::algorithms::AlgorithmInitializer;
+use crate::noise::algorithms::{wrap_axes, AlgorithmInitializer};
 use crate::noise::Algorithm;
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::random::{Random, Rng};
@@ -52,9 +52,17 @@ const WAVELET_SCALE: f32 = 2.0;
 /// Wavelet noise algorithm.
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
 pub struct Wavelet {
     dimensions: usize,
     #[derivative(Debug = "ignore")]
+    #[cfg_attr(
+        feature = "serialization",
+        serde(with = "crate::util::big_array::boxed")
+    )]
     tile_data: Box<[f32; WAVELET_TILE_SIZE_CUBED]>,
 }
 
@@ -68,6 +76,12 @@ impl Algorithm for Wavelet {
         }
     }
 
+    fn generate_f64(&self, f: &[f64]) -> f32 {
+        // The tile repeats along each axis.
+        let period = WAVELET_TILE_SIZE as f64 / f64::from(WAVELET_SCALE);
+        self.generate(&wrap_axes(&f[..self.dimensions], period))
+    }
+
     #[allow(clippy::many_single_char_names)]
     fn generate(&self, f: &[f32]) -> f32 {
         if self.dimensions > 3 {
@@ -119,7 +133,7 @@ impl Algorithm for Wavelet {
             }
         }
 
-        result.max(-1.0).min(1.0)
+        result.clamp(-1.0, 1.0)
     }
 }
 
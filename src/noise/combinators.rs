@@ -0,0 +1,506 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Noise combinators, for declaring how several noise generators make up the final noise.
+//!
+//! Every noise generator, and every combination of them, is a `Source`. The combinators own
+//! their sources, but a source can also be borrowed, so a generator can be used in several
+//! places.
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::noise::{FractalMode, Noise};
+//! # use doryen_extra::noise::algorithms::Simplex;
+//! # use doryen_extra::noise::combinators::{Fractal, Source};
+//! let mountains = Fractal::new(
+//!     Noise::<Simplex>::from_seed(1, 2),
+//!     FractalMode::RidgedMultifractal,
+//!     6.0,
+//! );
+//! let plains =
+//!     Fractal::new(Noise::<Simplex>::from_seed(2, 2), FractalMode::Fbm, 4.0).multiply(0.25);
+//! let control = Noise::<Simplex>::from_seed(3, 2);
+//! let terrain = plains
+//!     .select(mountains, &control, 0.2, 0.1)
+//!     .terrace(vec![-1.0, -0.2, 0.1, 0.5, 1.0]);
+//! let height = terrain.get(&[1.3, 2.7]);
+//! assert!((-1.0..=1.0).contains(&height));
+//! ```
+
+use crate::noise::algorithms::Algorithm;
+use crate::noise::{FractalMode, Noise};
+use crate::Error;
+
+/// Something that gives a noise value at any coordinates.
+pub trait Source {
+    /// Returns the value at the given coordinates.
+    fn get(&self, f: &[f32]) -> f32;
+
+    /// Returns a source adding the values of this source and `other`.
+    fn add<S: Source>(self, other: S) -> Add<Self, S>
+    where
+        Self: Sized,
+    {
+        Add::new(self, other)
+    }
+
+    /// Returns a source multiplying the values of this source and `other`.
+    fn multiply<S: Source>(self, other: S) -> Multiply<Self, S>
+    where
+        Self: Sized,
+    {
+        Multiply::new(self, other)
+    }
+
+    /// Returns a source giving the smaller of the values of this source and `other`.
+    fn min<S: Source>(self, other: S) -> Min<Self, S>
+    where
+        Self: Sized,
+    {
+        Min::new(self, other)
+    }
+
+    /// Returns a source giving the larger of the values of this source and `other`.
+    fn max<S: Source>(self, other: S) -> Max<Self, S>
+    where
+        Self: Sized,
+    {
+        Max::new(self, other)
+    }
+
+    /// Returns a source giving the values of this source where `control` is below `threshold`,
+    /// and those of `high` where it's above. See `Select`.
+    fn select<H: Source, C: Source>(
+        self,
+        high: H,
+        control: C,
+        threshold: f32,
+        falloff: f32,
+    ) -> Select<Self, H, C>
+    where
+        Self: Sized,
+    {
+        Select::new(self, high, control, threshold, falloff)
+    }
+
+    /// Returns a source remapping the values of this source along a curve. See `Curve`.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than two control points.
+    fn curve(self, points: Vec<(f32, f32)>) -> Curve<Self>
+    where
+        Self: Sized,
+    {
+        Curve::new(self, points)
+    }
+
+    /// Returns a source turning the values of this source into terraces. See `Terrace`.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than two control points.
+    fn terrace(self, points: Vec<f32>) -> Terrace<Self>
+    where
+        Self: Sized,
+    {
+        Terrace::new(self, points)
+    }
+}
+
+/// A noise generator is a source of its flat noise, see `Noise::flat`.
+impl<A: Algorithm> Source for Noise<A> {
+    fn get(&self, f: &[f32]) -> f32 {
+        self.flat(f)
+    }
+}
+
+/// A constant is a source giving the same value everywhere.
+impl Source for f32 {
+    fn get(&self, _f: &[f32]) -> f32 {
+        *self
+    }
+}
+
+impl<S: Source + ?Sized> Source for &S {
+    fn get(&self, f: &[f32]) -> f32 {
+        (**self).get(f)
+    }
+}
+
+impl<S: Source + ?Sized> Source for Box<S> {
+    fn get(&self, f: &[f32]) -> f32 {
+        (**self).get(f)
+    }
+}
+
+/// A source of the fractal noise of a noise generator.
+#[derive(Debug, Clone)]
+pub struct Fractal<A: Algorithm> {
+    noise: Noise<A>,
+    mode: FractalMode,
+    octaves: f32,
+}
+
+impl<A: Algorithm> Fractal<A> {
+    /// Returns a source combining the given number of octaves of `noise` as `mode` says.
+    pub fn new(noise: Noise<A>, mode: FractalMode, octaves: f32) -> Self {
+        Self {
+            noise,
+            mode,
+            octaves,
+        }
+    }
+}
+
+impl<A: Algorithm> Source for Fractal<A> {
+    fn get(&self, f: &[f32]) -> f32 {
+        match self.mode {
+            FractalMode::Flat => self.noise.flat(f),
+            FractalMode::Fbm => self.noise.fbm(f, self.octaves),
+            FractalMode::Turbulence => self.noise.turbulence(f, self.octaves),
+            FractalMode::RidgedMultifractal => self.noise.ridged_multifractal(f, self.octaves),
+            FractalMode::Billow => self.noise.billow(f, self.octaves),
+        }
+    }
+}
+
+macro_rules! binary_combinator {
+    ($(#[$attr:meta])* $name:ident, |$a:ident, $b:ident| $combine:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $name<A: Source, B: Source> {
+            a: A,
+            b: B,
+        }
+
+        impl<A: Source, B: Source> $name<A, B> {
+            /// Returns a source combining the values of `a` and `b`.
+            pub fn new(a: A, b: B) -> Self {
+                Self { a, b }
+            }
+        }
+
+        impl<A: Source, B: Source> Source for $name<A, B> {
+            fn get(&self, f: &[f32]) -> f32 {
+                let $a = self.a.get(f);
+                let $b = self.b.get(f);
+                $combine
+            }
+        }
+    };
+}
+
+binary_combinator!(
+    /// A source adding the values of two sources.
+    Add,
+    |a, b| a + b
+);
+binary_combinator!(
+    /// A source multiplying the values of two sources.
+    Multiply,
+    |a, b| a * b
+);
+binary_combinator!(
+    /// A source giving the smaller of the values of two sources.
+    Min,
+    |a, b| a.min(b)
+);
+binary_combinator!(
+    /// A source giving the larger of the values of two sources.
+    Max,
+    |a, b| a.max(b)
+);
+
+/// A source picking between two sources, depending on the value of a control source.
+///
+/// Where the control value is below `threshold - falloff`, the value of the low source is
+/// given; where it's above `threshold + falloff`, the value of the high source. In between,
+/// the two are blended smoothly, so there are no sharp edges between them.
+#[derive(Debug, Clone)]
+pub struct Select<L: Source, H: Source, C: Source> {
+    low: L,
+    high: H,
+    control: C,
+    threshold: f32,
+    falloff: f32,
+}
+
+impl<L: Source, H: Source, C: Source> Select<L, H, C> {
+    /// Returns a source picking between `low` and `high` depending on `control`.
+    pub fn new(low: L, high: H, control: C, threshold: f32, falloff: f32) -> Self {
+        Self {
+            low,
+            high,
+            control,
+            threshold,
+            falloff: falloff.abs(),
+        }
+    }
+}
+
+impl<L: Source, H: Source, C: Source> Source for Select<L, H, C> {
+    fn get(&self, f: &[f32]) -> f32 {
+        let control = self.control.get(f);
+        if control <= self.threshold - self.falloff {
+            return self.low.get(f);
+        }
+        if control >= self.threshold + self.falloff {
+            return self.high.get(f);
+        }
+
+        let t = (control - (self.threshold - self.falloff)) / (2.0 * self.falloff);
+        let t = t * t * (3.0 - 2.0 * t);
+        let low = self.low.get(f);
+        low + t * (self.high.get(f) - low)
+    }
+}
+
+/// A source remapping the values of another source along a smooth curve through control
+/// points, each mapping an input value to an output value.
+///
+/// Between control points, the curve is a Catmull-Rom spline; below the first and above the
+/// last control point, it's flat.
+#[derive(Debug, Clone)]
+pub struct Curve<S: Source> {
+    source: S,
+    points: Vec<(f32, f32)>,
+}
+
+impl<S: Source> Curve<S> {
+    /// Returns a source remapping the values of `source` along a curve through `points`, which
+    /// can be given in any order.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than two control points.
+    pub fn new(source: S, points: Vec<(f32, f32)>) -> Self {
+        Self::try_new(source, points).unwrap()
+    }
+
+    /// Like `new`, but returns an error instead of panicking if there are fewer than two control
+    /// points.
+    pub fn try_new(source: S, mut points: Vec<(f32, f32)>) -> Result<Self, Error> {
+        if points.len() < 2 {
+            return Err(Error::OutOfRange(
+                "the number of control points of the curve",
+            ));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(Self { source, points })
+    }
+}
+
+impl<S: Source> Source for Curve<S> {
+    fn get(&self, f: &[f32]) -> f32 {
+        let value = self.source.get(f);
+        let last = self.points.len() - 1;
+        let i = self.points.partition_point(|&(input, _)| input <= value);
+        if i == 0 {
+            return self.points[0].1;
+        }
+        if i > last {
+            return self.points[last].1;
+        }
+
+        let (x1, y1) = self.points[i - 1];
+        let (x2, y2) = self.points[i];
+        let y0 = self.points[i.saturating_sub(2)].1;
+        let y3 = self.points[(i + 1).min(last)].1;
+        let t = if x2 > x1 {
+            (value - x1) / (x2 - x1)
+        } else {
+            0.0
+        };
+
+        cubic_interpolate(y0, y1, y2, y3, t)
+    }
+}
+
+// Catmull-Rom interpolation between `b` and `c`.
+fn cubic_interpolate(a: f32, b: f32, c: f32, d: f32, t: f32) -> f32 {
+    let p = -0.5 * a + 1.5 * b - 1.5 * c + 0.5 * d;
+    let q = a - 2.5 * b + 2.0 * c - 0.5 * d;
+    let r = -0.5 * a + 0.5 * c;
+
+    ((p * t + q) * t + r) * t + b
+}
+
+/// A source turning the values of another source into terraces, i.e. flat steps at the control
+/// points with steep rises between them, like eroded cliffs.
+#[derive(Debug, Clone)]
+pub struct Terrace<S: Source> {
+    source: S,
+    points: Vec<f32>,
+    inverted: bool,
+}
+
+impl<S: Source> Terrace<S> {
+    /// Returns a source turning the values of `source` into terraces at `points`, which can be
+    /// given in any order.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer than two control points.
+    pub fn new(source: S, points: Vec<f32>) -> Self {
+        Self::try_new(source, points).unwrap()
+    }
+
+    /// Like `new`, but returns an error instead of panicking if there are fewer than two control
+    /// points.
+    pub fn try_new(source: S, mut points: Vec<f32>) -> Result<Self, Error> {
+        if points.len() < 2 {
+            return Err(Error::OutOfRange(
+                "the number of control points of the terrace",
+            ));
+        }
+        points.sort_by(f32::total_cmp);
+
+        Ok(Self {
+            source,
+            points,
+            inverted: false,
+        })
+    }
+
+    /// Sets whether the terraces are inverted, i.e. flat at the top of each step rather than at
+    /// the bottom.
+    pub fn inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+}
+
+impl<S: Source> Source for Terrace<S> {
+    fn get(&self, f: &[f32]) -> f32 {
+        let value = self.source.get(f);
+        let last = self.points.len() - 1;
+        let i = self.points.partition_point(|&point| point <= value);
+        if i == 0 {
+            return self.points[0];
+        }
+        if i > last {
+            return self.points[last];
+        }
+
+        let (low, high) = (self.points[i - 1], self.points[i]);
+        let mut t = if high > low {
+            (value - low) / (high - low)
+        } else {
+            0.0
+        };
+        if self.inverted {
+            t = 1.0 - t;
+            t *= t;
+            low + (1.0 - t) * (high - low)
+        } else {
+            t *= t;
+            low + t * (high - low)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noise::algorithms::Simplex;
+
+    fn noise(seed: u32) -> Noise<Simplex> {
+        Noise::from_seed(seed, 2)
+    }
+
+    #[test]
+    fn arithmetic_combines_values() {
+        let (a, b) = (noise(1), noise(2));
+        for i in 0..20 {
+            let f = [i as f32 * 0.37, 2.0 - i as f32 * 0.23];
+            let (va, vb) = (a.flat(&f), b.flat(&f));
+            assert_eq!((&a).add(&b).get(&f), va + vb);
+            assert_eq!((&a).multiply(&b).get(&f), va * vb);
+            assert_eq!((&a).min(&b).get(&f), va.min(vb));
+            assert_eq!((&a).max(&b).get(&f), va.max(vb));
+        }
+    }
+
+    #[test]
+    fn select_blends_between_sources() {
+        let select = Select::new(-1.0_f32, 1.0_f32, noise(3), 0.0, 0.25);
+        let control = noise(3);
+        for i in 0..50 {
+            let f = [i as f32 * 0.17, i as f32 * 0.29];
+            let c = control.flat(&f);
+            let value = select.get(&f);
+            if c <= -0.25 {
+                assert_eq!(value, -1.0);
+            } else if c >= 0.25 {
+                assert_eq!(value, 1.0);
+            } else {
+                assert!((-1.0..=1.0).contains(&value));
+            }
+        }
+        assert_eq!(
+            Select::new(-1.0_f32, 1.0_f32, 0.5_f32, 0.0, 0.0).get(&[0.0]),
+            1.0
+        );
+    }
+
+    #[test]
+    fn curve_goes_through_its_control_points() {
+        let points = vec![(1.0_f32, 0.0), (-1.0, -1.0), (0.0, 0.5), (0.5, 0.25)];
+        for &(input, output) in &points {
+            assert_eq!(input.curve(points.clone()).get(&[0.0]), output);
+        }
+        assert_eq!((-2.0_f32).curve(points.clone()).get(&[0.0]), -1.0);
+        assert_eq!(2.0_f32.curve(points).get(&[0.0]), 0.0);
+        assert_eq!(
+            Curve::try_new(0.0_f32, vec![(0.0, 0.0)]).err(),
+            Some(Error::OutOfRange(
+                "the number of control points of the curve"
+            ))
+        );
+    }
+
+    #[test]
+    fn terraces_are_flat_at_their_control_points() {
+        let points = vec![-1.0, 0.0, 1.0];
+        assert_eq!(0.0_f32.terrace(points.clone()).get(&[0.0]), 0.0);
+        assert_eq!(0.5_f32.terrace(points.clone()).get(&[0.0]), 0.25);
+        assert_eq!(
+            Terrace::new(0.5_f32, points.clone())
+                .inverted(true)
+                .get(&[0.0]),
+            0.75
+        );
+        assert_eq!(5.0_f32.terrace(points).get(&[0.0]), 1.0);
+    }
+}
@@ -0,0 +1,209 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::heightmap::HeightMap;
+use crate::noise::algorithms::Algorithm;
+use crate::noise::{FractalMode, Noise};
+use crate::{Error, Position, Rectangle, USize};
+
+/// Builds height maps out of a 2D noise generator, in one call.
+///
+/// The noise coordinates for cell `(x, y)` of the built map are
+/// `((x + position.x) * scale, (y + position.y) * scale)`, so neighboring maps can be built
+/// seamlessly by moving the position along.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, USize};
+/// # use doryen_extra::noise::{FractalMode, Noise, NoiseMapBuilder};
+/// # use doryen_extra::random::Random;
+/// let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+/// let map = NoiseMapBuilder::new(&noise, USize::new(64, 32))
+///     .position(Position::new(64, 0))
+///     .scale(0.05)
+///     .octaves(6.0)
+///     .mode(FractalMode::RidgedMultifractal)
+///     .build();
+/// assert_eq!((map.width(), map.height()), (64, 32));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NoiseMapBuilder<'a, A: Algorithm> {
+    noise: &'a Noise<A>,
+    size: USize,
+    position: Position,
+    scale: f32,
+    octaves: f32,
+    mode: FractalMode,
+}
+
+impl<'a, A: Algorithm> NoiseMapBuilder<'a, A> {
+    /// Returns a builder of maps of the given size, at position `(0, 0)`, with a scale of
+    /// `1.0`, 4 octaves and `FractalMode::Fbm`.
+    pub fn new(noise: &'a Noise<A>, size: USize) -> Self {
+        Self {
+            noise,
+            size,
+            position: Position::new(0, 0),
+            scale: 1.0,
+            octaves: 4.0,
+            mode: FractalMode::default(),
+        }
+    }
+
+    /// Sets the position of the upper-left cell of the map in the noise, before scaling.
+    pub fn position(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets how far apart, in noise coordinates, neighboring cells are.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the number of octaves.
+    pub fn octaves(mut self, octaves: f32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    /// Sets how the octaves are combined.
+    pub fn mode(mut self, mode: FractalMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Fills `buffer`, in row-major order, with the noise values of the map.
+    ///
+    /// # Panics
+    /// * If the noise generator isn't 2D.
+    /// * If the length of `buffer` isn't the number of cells in the map.
+    pub fn fill(&self, buffer: &mut [f32]) {
+        self.noise
+            .fill_2d(buffer, self.area(), self.scale, self.octaves, self.mode);
+    }
+
+    /// Returns a height map with the noise values.
+    ///
+    /// # Panics
+    /// * If the noise generator isn't 2D.
+    /// * If the width or the height of the map is 0.
+    pub fn build(&self) -> HeightMap {
+        self.try_build().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `build`, but returns an error instead of panicking if the noise generator isn't 2D
+    /// or if the width or the height of the map is 0.
+    pub fn try_build(&self) -> Result<HeightMap, Error> {
+        let mut height_map =
+            HeightMap::try_new(self.size.width as usize, self.size.height as usize)?;
+        self.noise.try_fill_2d(
+            height_map.values_mut(),
+            self.area(),
+            self.scale,
+            self.octaves,
+            self.mode,
+        )?;
+
+        Ok(height_map)
+    }
+
+    fn area(&self) -> Rectangle {
+        Rectangle::new(self.position, self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    const MODES: [FractalMode; 5] = [
+        FractalMode::Flat,
+        FractalMode::Fbm,
+        FractalMode::Turbulence,
+        FractalMode::RidgedMultifractal,
+        FractalMode::Billow,
+    ];
+
+    fn assert_matches_cell_by_cell<A: Algorithm>(noise: &Noise<A>) {
+        for &mode in &MODES {
+            let map = NoiseMapBuilder::new(noise, USize::new(13, 5))
+                .position(Position::new(-7, 3))
+                .scale(0.37)
+                .octaves(3.5)
+                .mode(mode)
+                .build();
+            for (position, value) in map.enumerate_cells() {
+                let f = [
+                    (position.x as f32 - 7.0) * 0.37,
+                    (position.y as f32 + 3.0) * 0.37,
+                ];
+                let expected = match mode {
+                    FractalMode::Flat => noise.flat(&f),
+                    FractalMode::Fbm => noise.fbm(&f, 3.5),
+                    FractalMode::Turbulence => noise.turbulence(&f, 3.5),
+                    FractalMode::RidgedMultifractal => noise.ridged_multifractal(&f, 3.5),
+                    FractalMode::Billow => noise.billow(&f, 3.5),
+                };
+                assert_eq!(value, expected, "{:?} at {:?}", mode, position);
+            }
+        }
+    }
+
+    #[test]
+    fn build_matches_cell_by_cell() {
+        assert_matches_cell_by_cell(&Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(1)));
+        assert_matches_cell_by_cell(&Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(2)));
+        assert_matches_cell_by_cell(&Noise::new_open_simplex2(
+            2,
+            2.0,
+            Random::new_mt_from_seed(3),
+        ));
+    }
+
+    #[test]
+    fn try_build_reports_errors() {
+        let noise_3d = Noise::new_simplex(3, 2.0, Random::new_mt_from_seed(1));
+        assert!(NoiseMapBuilder::new(&noise_3d, USize::new(4, 4))
+            .try_build()
+            .is_err());
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        assert_eq!(
+            NoiseMapBuilder::new(&noise, USize::new(0, 4))
+                .try_build()
+                .unwrap_err(),
+            Error::EmptySize
+        );
+    }
+}
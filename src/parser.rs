@@ -0,0 +1,787 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Configuration file parsing.
+//!
+//! A port of libtcod's parse toolkit, which reads text configuration files made up of typed
+//! structures, like this one:
+//! ```text
+//! // Comments can be written like this...
+//! item_type "long sword" {
+//!     /* ...or like this */
+//!     cost = 300
+//!     weight = 3.5
+//!     damage = 2d6+1
+//!     color = "#C0C0C0"
+//!     damage_types = ["slashing", "piercing"]
+//!     two_handed
+//!     effect {
+//!         kind = "bleeding"
+//!     }
+//! }
+//! ```
+//!
+//! The layout of each kind of structure is declared up front as a [`StructType`], with the
+//! names and types of its properties, the flags it can have, and the structures it can contain.
+//! A [`Parser`] holding the struct types then turns a file into a list of [`Entity`] values,
+//! which have all been checked against their declarations, or returns an error telling the line
+//! and column where the file went wrong. Alternatively, a [`Schema`] declares a struct type along
+//! with how to fill in a Rust type from it, and turns a file straight into values of that type.
+//!
+//! # Values
+//!
+//! * `Bool`: `true` or `false`
+//! * `Char`: a quoted character, like `'@'`, or its code, like `64`
+//! * `Int`: a decimal or hexadecimal (`0x`) integer
+//! * `Float`: a decimal number
+//! * `String`: a double quoted string, where adjacent strings are joined together and `\n`,
+//!   `\t`, `\r`, `\"`, `\'` and `\\` are escapes
+//! * `Color`: a `"#RRGGBB"` string, or the red, green and blue components, like `192, 192, 192`
+//! * `Dice`: a dice specification, quoted or not, like `2d6+1`; see [`Dice::new`]
+//! * `ValueList`: a string that must be one of a fixed set of values
+//! * `List`: a list of values of one of the other types between square brackets
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::parser::{Parser, StructType, Value, ValueType};
+//! let mut parser = Parser::new();
+//! parser
+//!     .new_struct("item_type")
+//!     .add_property("cost", ValueType::Int, true)
+//!     .add_property("weight", ValueType::Float, false)
+//!     .add_flag("two_handed");
+//!
+//! let entities = parser
+//!     .parse_str(r#"item_type "long sword" { cost = 300 two_handed }"#)
+//!     .unwrap();
+//! assert_eq!(entities[0].name(), Some("long sword"));
+//! assert_eq!(entities[0].get("cost").and_then(Value::as_int), Some(300));
+//! assert_eq!(entities[0].get("weight"), None);
+//! assert!(entities[0].has_flag("two_handed"));
+//!
+//! let error = parser.parse_str("item_type { weight = 2.0 }").unwrap_err();
+//! assert_eq!(error.to_string(), "1:26: missing mandatory property 'cost'");
+//! ```
+//!
+//! [`StructType`]: ./struct.StructType.html
+//! [`Parser`]: ./struct.Parser.html
+//! [`Entity`]: ./struct.Entity.html
+//! [`Schema`]: ./struct.Schema.html
+//! [`Dice::new`]: ../random/struct.Dice.html#method.new
+
+mod lexer;
+mod schema;
+
+pub use schema::{FromValue, Schema};
+
+use crate::color::Color;
+use crate::parser::lexer::{syntax_error, Lexer, Token};
+use crate::random::Dice;
+use crate::Error;
+use std::convert::TryFrom;
+use std::io;
+
+/// The type of a property.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ValueType {
+    /// A boolean
+    Bool,
+    /// A single character
+    Char,
+    /// An integer
+    Int,
+    /// A floating point number
+    Float,
+    /// A string
+    String,
+    /// A color
+    Color,
+    /// A dice specification
+    Dice,
+    /// A string that must be one of the given values
+    ValueList(Vec<String>),
+    /// A list of values of the given type
+    List(Box<Self>),
+}
+
+/// The value of a property.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    /// A boolean
+    Bool(bool),
+    /// A single character
+    Char(char),
+    /// An integer
+    Int(i32),
+    /// A floating point number
+    Float(f32),
+    /// A string, which is also the type of `ValueType::ValueList` values
+    String(String),
+    /// A color
+    Color(Color),
+    /// A dice specification
+    Dice(Dice),
+    /// A list of values
+    List(Vec<Self>),
+}
+
+impl Value {
+    /// Returns the boolean, if this is a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the character, if this is a `Char`.
+    pub fn as_char(&self) -> Option<char> {
+        match *self {
+            Self::Char(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is an `Int`.
+    pub fn as_int(&self) -> Option<i32> {
+        match *self {
+            Self::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the floating point number, if this is a `Float`.
+    pub fn as_float(&self) -> Option<f32> {
+        match *self {
+            Self::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if this is a `String`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the color, if this is a `Color`.
+    pub fn as_color(&self) -> Option<Color> {
+        match *self {
+            Self::Color(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the dice, if this is a `Dice`.
+    pub fn as_dice(&self) -> Option<Dice> {
+        match *self {
+            Self::Dice(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the values, if this is a `List`.
+    pub fn as_list(&self) -> Option<&[Self]> {
+        match self {
+            Self::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Property {
+    name: String,
+    value_type: ValueType,
+    mandatory: bool,
+}
+
+/// The declaration of a kind of structure: the properties, flags and other structures it can
+/// contain.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StructType {
+    name: String,
+    properties: Vec<Property>,
+    flags: Vec<String>,
+    structs: Vec<String>,
+}
+
+impl StructType {
+    /// Returns a new struct type with the given name, without any properties, flags or
+    /// structures.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            properties: Vec::new(),
+            flags: Vec::new(),
+            structs: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the struct type.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds a property. A mandatory property must be set in every structure of this type.
+    pub fn add_property(
+        &mut self,
+        name: &str,
+        value_type: ValueType,
+        mandatory: bool,
+    ) -> &mut Self {
+        self.properties.retain(|p| p.name != name);
+        self.properties.push(Property {
+            name: name.to_string(),
+            value_type,
+            mandatory,
+        });
+        self
+    }
+
+    /// Adds a property whose value must be one of the given strings.
+    pub fn add_value_list(&mut self, name: &str, values: &[&str], mandatory: bool) -> &mut Self {
+        let values = values.iter().map(|&value| value.to_string()).collect();
+        self.add_property(name, ValueType::ValueList(values), mandatory)
+    }
+
+    /// Adds a property holding a list of values of the given type.
+    pub fn add_list_property(
+        &mut self,
+        name: &str,
+        value_type: ValueType,
+        mandatory: bool,
+    ) -> &mut Self {
+        self.add_property(name, ValueType::List(Box::new(value_type)), mandatory)
+    }
+
+    /// Adds a flag, a name that a structure of this type either has or doesn't have.
+    pub fn add_flag(&mut self, name: &str) -> &mut Self {
+        if !self.has_flag(name) {
+            self.flags.push(name.to_string());
+        }
+        self
+    }
+
+    /// Allows structures of the struct type with the given name inside structures of this type.
+    pub fn add_structure(&mut self, name: &str) -> &mut Self {
+        if !self.structs.iter().any(|s| s == name) {
+            self.structs.push(name.to_string());
+        }
+        self
+    }
+
+    /// Returns the type of the property with the given name, if there is one.
+    pub fn property_type(&self, name: &str) -> Option<&ValueType> {
+        self.property(name).map(|p| &p.value_type)
+    }
+
+    /// Returns whether the property with the given name is mandatory, or `None` if there is no
+    /// such property.
+    pub fn is_mandatory(&self, name: &str) -> Option<bool> {
+        self.property(name).map(|p| p.mandatory)
+    }
+
+    /// Returns whether this struct type has a flag with the given name.
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+}
+
+/// A structure read from a configuration file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entity {
+    type_name: String,
+    name: Option<String>,
+    line: usize,
+    column: usize,
+    properties: Vec<(String, Value)>,
+    flags: Vec<String>,
+    children: Vec<Self>,
+}
+
+impl Entity {
+    /// Returns the name of the entity's struct type.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the name of the entity, if it was given one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the line and column the entity starts at in the file it was read from.
+    pub fn position(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    /// Returns the value of the property with the given name, if it was set.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns an iterator over the properties that were set, in the order they were set in.
+    pub fn properties(&self) -> impl Iterator<Item = (&str, &Value)> + '_ {
+        self.properties
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns whether the entity has the flag with the given name.
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    /// Returns the flags the entity has, in the order they were given in.
+    pub fn flags(&self) -> impl Iterator<Item = &str> + '_ {
+        self.flags.iter().map(String::as_str)
+    }
+
+    /// Returns the structures inside the entity, in the order they were given in.
+    pub fn children(&self) -> &[Self] {
+        &self.children
+    }
+}
+
+/// A configuration file parser, holding the declarations of the structures files can contain.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Parser {
+    structs: Vec<StructType>,
+}
+
+impl Parser {
+    /// Returns a new parser without any struct types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a new struct type with the given name, replacing any struct type with the same
+    /// name, and returns it so that its properties can be added.
+    pub fn new_struct(&mut self, name: &str) -> &mut StructType {
+        self.add_struct(StructType::new(name))
+    }
+
+    /// Adds a struct type, replacing any struct type with the same name, and returns it.
+    pub fn add_struct(&mut self, struct_type: StructType) -> &mut StructType {
+        let index =
+            if let Some(index) = self.structs.iter().position(|s| s.name == struct_type.name) {
+                self.structs[index] = struct_type;
+                index
+            } else {
+                self.structs.push(struct_type);
+                self.structs.len() - 1
+            };
+
+        &mut self.structs[index]
+    }
+
+    /// Returns the struct type with the given name, if there is one.
+    pub fn struct_type(&self, name: &str) -> Option<&StructType> {
+        self.structs.iter().find(|s| s.name == name)
+    }
+
+    /// Parses a configuration file, given as a string, into the entities at its top level. Any
+    /// declared struct type can be used at the top level.
+    pub fn parse_str(&self, s: &str) -> Result<Vec<Entity>, Error> {
+        let mut lexer = Lexer::new(s);
+        let mut entities = Vec::new();
+        loop {
+            match lexer.next_token()? {
+                (Token::End, ..) => return Ok(entities),
+                (Token::Word(word), line, column) => {
+                    let struct_type = self.struct_type(&word).ok_or_else(|| {
+                        syntax_error(line, column, format!("unknown structure '{}'", word))
+                    })?;
+                    entities.push(self.parse_entity(&mut lexer, struct_type, (line, column))?);
+                }
+                (_, line, column) => {
+                    return Err(syntax_error(line, column, "expected a structure"))
+                }
+            }
+        }
+    }
+
+    /// Parses a configuration file, like `parse_str`. Errors in the file are returned as errors
+    /// of the `InvalidData` kind.
+    pub fn load_from_file<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<Vec<Entity>> {
+        let s = std::fs::read_to_string(path)?;
+        self.parse_str(&s)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    // Parses the rest of an entity whose struct type name has just been read.
+    fn parse_entity(
+        &self,
+        lexer: &mut Lexer<'_>,
+        struct_type: &StructType,
+        position: (usize, usize),
+    ) -> Result<Entity, Error> {
+        let name = match lexer.peek()? {
+            (Token::String(_), ..) => match lexer.next_token()? {
+                (Token::String(name), ..) => Some(name),
+                _ => unreachable!("a string was just peeked"),
+            },
+            _ => None,
+        };
+        lexer.expect_symbol('{')?;
+
+        let mut entity = Entity {
+            type_name: struct_type.name.clone(),
+            name,
+            line: position.0,
+            column: position.1,
+            properties: Vec::new(),
+            flags: Vec::new(),
+            children: Vec::new(),
+        };
+        loop {
+            let (word, line, column) = match lexer.next_token()? {
+                (Token::Symbol('}'), line, column) => {
+                    if let Some(missing) = struct_type
+                        .properties
+                        .iter()
+                        .find(|p| p.mandatory && entity.get(&p.name).is_none())
+                    {
+                        let message = format!("missing mandatory property '{}'", missing.name);
+                        return Err(syntax_error(line, column, message));
+                    }
+                    return Ok(entity);
+                }
+                (Token::Word(word), line, column) => (word, line, column),
+                (_, line, column) => {
+                    return Err(syntax_error(line, column, "expected a property or '}'"))
+                }
+            };
+
+            if let Some(property) = struct_type.property(&word) {
+                lexer.expect_symbol('=')?;
+                let value = parse_value(lexer, &property.value_type)?;
+                entity.properties.retain(|(set, _)| *set != word);
+                entity.properties.push((word, value));
+            } else if struct_type.has_flag(&word) {
+                if !entity.has_flag(&word) {
+                    entity.flags.push(word);
+                }
+            } else if struct_type.structs.contains(&word) {
+                let child_type = self.struct_type(&word).ok_or_else(|| {
+                    syntax_error(line, column, format!("undeclared structure '{}'", word))
+                })?;
+                entity
+                    .children
+                    .push(self.parse_entity(lexer, child_type, (line, column))?);
+            } else {
+                let message = format!(
+                    "unknown property, flag or structure '{}' in '{}'",
+                    word, struct_type.name
+                );
+                return Err(syntax_error(line, column, message));
+            }
+        }
+    }
+}
+
+fn parse_value(lexer: &mut Lexer<'_>, value_type: &ValueType) -> Result<Value, Error> {
+    let (token, line, column) = lexer.next_token()?;
+    let invalid = |what: &str| syntax_error(line, column, format!("expected {}", what));
+    let value = match (value_type, token) {
+        (ValueType::Bool, Token::Word(word)) => match word.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => return Err(invalid("'true' or 'false'")),
+        },
+        (ValueType::Char, Token::Char(c)) => Value::Char(c),
+        (ValueType::Char, Token::Word(word)) => parse_int(&word)
+            .and_then(|code| std::char::from_u32(code as u32))
+            .map(Value::Char)
+            .ok_or_else(|| invalid("a character"))?,
+        (ValueType::Int, Token::Word(word)) => {
+            Value::Int(parse_int(&word).ok_or_else(|| invalid("an integer"))?)
+        }
+        (ValueType::Float, Token::Word(word)) => {
+            Value::Float(word.parse().map_err(|_| invalid("a number"))?)
+        }
+        (ValueType::String, Token::String(s)) => Value::String(s),
+        (ValueType::ValueList(values), Token::String(s)) => {
+            if !values.contains(&s) {
+                let message = format!("expected one of {}", values.join(", "));
+                return Err(syntax_error(line, column, message));
+            }
+            Value::String(s)
+        }
+        (ValueType::Color, Token::String(s)) | (ValueType::Color, Token::Word(s))
+            if s.starts_with('#') =>
+        {
+            Value::Color(parse_hex_color(&s).ok_or_else(|| invalid("a '#RRGGBB' color"))?)
+        }
+        (ValueType::Color, Token::Word(word)) => {
+            let component = |s: &str| s.parse::<u8>().ok();
+            let r = component(&word).ok_or_else(|| invalid("a color"))?;
+            let mut rest = [0; 2];
+            for c in &mut rest {
+                lexer.expect_symbol(',')?;
+                *c = match lexer.next_token()? {
+                    (Token::Word(next), ..) => component(&next),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid("a color"))?;
+            }
+            Value::Color(Color::new(r, rest[0], rest[1]))
+        }
+        (ValueType::Dice, Token::String(s)) | (ValueType::Dice, Token::Word(s)) => Value::Dice(
+            Dice::try_new(&s).map_err(|error| syntax_error(line, column, error.to_string()))?,
+        ),
+        (ValueType::List(element_type), Token::Symbol('[')) => {
+            let mut values = Vec::new();
+            if let (Token::Symbol(']'), ..) = lexer.peek()? {
+                lexer.next_token()?;
+            } else {
+                loop {
+                    values.push(parse_value(lexer, element_type)?);
+                    match lexer.next_token()? {
+                        (Token::Symbol(','), ..) => {}
+                        (Token::Symbol(']'), ..) => break,
+                        (_, list_line, list_column) => {
+                            return Err(syntax_error(list_line, list_column, "expected ',' or ']'"))
+                        }
+                    }
+                }
+            }
+            Value::List(values)
+        }
+        (value_type, _) => return Err(invalid(type_description(value_type))),
+    };
+
+    Ok(value)
+}
+
+fn type_description(value_type: &ValueType) -> &'static str {
+    match value_type {
+        ValueType::Bool => "'true' or 'false'",
+        ValueType::Char => "a character",
+        ValueType::Int => "an integer",
+        ValueType::Float => "a number",
+        ValueType::String | ValueType::ValueList(_) => "a string",
+        ValueType::Color => "a color",
+        ValueType::Dice => "a dice specification",
+        ValueType::List(_) => "a list",
+    }
+}
+
+fn parse_int(s: &str) -> Option<i32> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let has_sign = |number: &str| number.starts_with(['-', '+']);
+    if negative && has_sign(digits) {
+        return None;
+    }
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) if has_sign(hex) => return None,
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i64>().ok()?,
+    };
+
+    i32::try_from(if negative {
+        value.checked_neg()?
+    } else {
+        value
+    })
+    .ok()
+}
+
+fn parse_hex_color(s: &str) -> Option<Color> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    Some(Color::new(component(0)?, component(2)?, component(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> Parser {
+        let mut parser = Parser::new();
+        parser
+            .new_struct("item_type")
+            .add_property("cost", ValueType::Int, true)
+            .add_property("weight", ValueType::Float, false)
+            .add_property("glyph", ValueType::Char, false)
+            .add_property("stackable", ValueType::Bool, false)
+            .add_property("description", ValueType::String, false)
+            .add_property("color", ValueType::Color, false)
+            .add_property("damage", ValueType::Dice, false)
+            .add_value_list("material", &["wood", "iron"], false)
+            .add_list_property("tags", ValueType::String, false)
+            .add_list_property("colors", ValueType::Color, false)
+            .add_flag("two_handed")
+            .add_structure("effect");
+        parser
+            .new_struct("effect")
+            .add_property("kind", ValueType::String, true);
+        parser
+    }
+
+    #[test]
+    fn parses_every_type() {
+        let entities = parser()
+            .parse_str(
+                r##"
+                item_type "sword" {
+                    cost = 0x12C
+                    weight = 3.5
+                    glyph = '/'
+                    stackable = false
+                    description = "A long, "
+                                  "sharp \"blade\"."
+                    color = "#C0C0FF"
+                    damage = 2d6+1
+                    material = "iron"
+                    tags = ["weapon", "metal"]
+                    colors = [#FF0000, 0, 128, 255]
+                    two_handed
+                    effect { kind = "bleeding" }
+                }
+                item_type { cost = -5 glyph = 64 tags = [] color = 1,2,3 damage = "1d4" }
+                "##,
+            )
+            .unwrap();
+
+        assert_eq!(entities.len(), 2);
+        let sword = &entities[0];
+        assert_eq!(sword.type_name(), "item_type");
+        assert_eq!(sword.name(), Some("sword"));
+        assert_eq!(sword.get("cost"), Some(&Value::Int(300)));
+        assert_eq!(sword.get("weight"), Some(&Value::Float(3.5)));
+        assert_eq!(sword.get("glyph"), Some(&Value::Char('/')));
+        assert_eq!(sword.get("stackable"), Some(&Value::Bool(false)));
+        assert_eq!(
+            sword.get("description").and_then(Value::as_str),
+            Some("A long, sharp \"blade\".")
+        );
+        assert_eq!(
+            sword.get("color"),
+            Some(&Value::Color(Color::new(192, 192, 255)))
+        );
+        assert!(sword.get("damage").and_then(Value::as_dice).is_some());
+        assert_eq!(sword.get("material").and_then(Value::as_str), Some("iron"));
+        assert_eq!(
+            sword.get("tags"),
+            Some(&Value::List(vec![
+                Value::String("weapon".to_string()),
+                Value::String("metal".to_string())
+            ]))
+        );
+        assert_eq!(
+            sword.get("colors"),
+            Some(&Value::List(vec![
+                Value::Color(Color::new(255, 0, 0)),
+                Value::Color(Color::new(0, 128, 255))
+            ]))
+        );
+        assert!(sword.has_flag("two_handed"));
+        assert_eq!(sword.children().len(), 1);
+        assert_eq!(
+            sword.children()[0].get("kind").and_then(Value::as_str),
+            Some("bleeding")
+        );
+
+        let other = &entities[1];
+        assert_eq!(other.name(), None);
+        assert_eq!(other.get("cost").and_then(Value::as_int), Some(-5));
+        assert_eq!(other.get("glyph").and_then(Value::as_char), Some('@'));
+        assert_eq!(other.get("tags").and_then(Value::as_list), Some(&[][..]));
+        assert_eq!(
+            other.get("color").and_then(Value::as_color),
+            Some(Color::new(1, 2, 3))
+        );
+        assert!(!other.has_flag("two_handed"));
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let parser = parser();
+        let error = |s| match parser.parse_str(s) {
+            Err(Error::Syntax { line, column, .. }) => (line, column),
+            other => panic!("expected a syntax error, got {:?}", other),
+        };
+
+        assert_eq!(error("item_type {\n  cost = 1.5\n}"), (2, 10));
+        assert_eq!(error("item_type {\n  cost = 1\n  size = 2\n}"), (3, 3));
+        assert_eq!(error("item_type {\n  weight = 1\n}"), (3, 1));
+        assert_eq!(error("item_type { cost = 1 material = \"gold\" }"), (1, 33));
+        assert_eq!(
+            error("item_type { cost = 1 tags = [\"a\", \"b\" }"),
+            (1, 39)
+        );
+        assert_eq!(error("item_type { cost = 1 damage = 2d }"), (1, 31));
+        assert_eq!(error("item_type { cost = 1 color = 1, 2 }"), (1, 35));
+        assert_eq!(error("effect { kind = \"a\" effect { } }"), (1, 21));
+        assert_eq!(error("weapon { }"), (1, 1));
+        assert_eq!(error("item_type {\n  description = \"open\n}"), (2, 17));
+    }
+
+    #[test]
+    fn rejects_malformed_integers() {
+        assert_eq!(parse_int("-5"), Some(-5));
+        assert_eq!(parse_int("-0x10"), Some(-16));
+        assert_eq!(parse_int("-2147483648"), Some(i32::MIN));
+        assert_eq!(parse_int("--5"), None);
+        assert_eq!(parse_int("-+5"), None);
+        assert_eq!(parse_int("0x-5"), None);
+        assert_eq!(parse_int("--9223372036854775808"), None);
+        assert_eq!(parse_int("-9223372036854775808"), None);
+
+        let parser = parser();
+        assert!(parser
+            .parse_str("item_type { cost = --9223372036854775808 }")
+            .is_err());
+        assert!(parser.parse_str("item_type { cost = --5 }").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::Error;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, PartialEq, Debug)]
+pub(super) enum Token {
+    // Anything that isn't quoted or a symbol: names, numbers, booleans, dice and the like
+    Word(String),
+    String(String),
+    Char(char),
+    Symbol(char),
+    End,
+}
+
+// A token along with the line and column it starts at.
+pub(super) type Spanned = (Token, usize, usize);
+
+const SYMBOLS: &[char] = &['{', '}', '[', ']', ',', '='];
+
+pub(super) struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    peeked: Option<Spanned>,
+}
+
+impl<'a> Lexer<'a> {
+    pub(super) fn new(s: &'a str) -> Self {
+        Self {
+            chars: s.chars().peekable(),
+            line: 1,
+            column: 1,
+            peeked: None,
+        }
+    }
+
+    pub(super) fn peek(&mut self) -> Result<&Spanned, Error> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_token()?);
+        }
+
+        Ok(self.peeked.as_ref().expect("a token was just peeked"))
+    }
+
+    pub(super) fn next_token(&mut self) -> Result<Spanned, Error> {
+        match self.peeked.take() {
+            Some(spanned) => Ok(spanned),
+            None => self.read_token(),
+        }
+    }
+
+    pub(super) fn expect_symbol(&mut self, symbol: char) -> Result<(), Error> {
+        match self.next_token()? {
+            (Token::Symbol(c), ..) if c == symbol => Ok(()),
+            (_, line, column) => Err(syntax_error(line, column, format!("expected '{}'", symbol))),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), Error> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') => {
+                    let (line, column) = (self.line, self.column);
+                    self.bump();
+                    match self.bump() {
+                        Some('/') => while !matches!(self.bump(), Some('\n') | None) {},
+                        Some('*') => loop {
+                            match self.bump() {
+                                Some('*') if self.chars.peek() == Some(&'/') => {
+                                    self.bump();
+                                    break;
+                                }
+                                Some(_) => {}
+                                None => {
+                                    return Err(syntax_error(line, column, "unterminated comment"))
+                                }
+                            }
+                        },
+                        _ => return Err(syntax_error(line, column, "unexpected '/'")),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Spanned, Error> {
+        self.skip_whitespace_and_comments()?;
+        let (line, column) = (self.line, self.column);
+        let token = match self.bump() {
+            None => Token::End,
+            Some('"') => {
+                let mut s = self.quoted_contents('"', line, column)?;
+                // Adjacent strings are joined into one.
+                loop {
+                    self.skip_whitespace_and_comments()?;
+                    if self.chars.peek() != Some(&'"') {
+                        break;
+                    }
+                    let (string_line, string_column) = (self.line, self.column);
+                    self.bump();
+                    s.push_str(&self.quoted_contents('"', string_line, string_column)?);
+                }
+                Token::String(s)
+            }
+            Some('\'') => {
+                let s = self.quoted_contents('\'', line, column)?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Token::Char(c),
+                    _ => return Err(syntax_error(line, column, "expected a single character")),
+                }
+            }
+            Some(c) if SYMBOLS.contains(&c) => Token::Symbol(c),
+            Some(first) => {
+                let mut word = first.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || SYMBOLS.contains(&c) || c == '"' || c == '\'' {
+                        break;
+                    }
+                    word.push(c);
+                    self.bump();
+                }
+                Token::Word(word)
+            }
+        };
+
+        Ok((token, line, column))
+    }
+
+    // Reads the rest of a quoted string or character whose opening quote is at the given line
+    // and column.
+    fn quoted_contents(
+        &mut self,
+        quote: char,
+        line: usize,
+        column: usize,
+    ) -> Result<String, Error> {
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('\\') => match self.bump() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some(c @ '"') | Some(c @ '\'') | Some(c @ '\\') => s.push(c),
+                    _ => return Err(syntax_error(self.line, self.column - 1, "unknown escape")),
+                },
+                Some('\n') | None => {
+                    let message = if quote == '"' {
+                        "unterminated string"
+                    } else {
+                        "unterminated character"
+                    };
+                    return Err(syntax_error(line, column, message));
+                }
+                Some(c) if c == quote => return Ok(s),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+}
+
+pub(super) fn syntax_error<S: Into<String>>(line: usize, column: usize, message: S) -> Error {
+    Error::Syntax {
+        line,
+        column,
+        message: message.into(),
+    }
+}
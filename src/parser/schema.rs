@@ -0,0 +1,434 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::color::Color;
+use crate::parser::lexer::syntax_error;
+use crate::parser::{Entity, Parser, StructType, Value, ValueType};
+use crate::random::Dice;
+use crate::Error;
+use derivative::Derivative;
+use std::io;
+
+/// A Rust type that property values can be converted into.
+pub trait FromValue: Sized {
+    /// Returns the type of property that holds values of this type.
+    fn value_type() -> ValueType;
+
+    /// Converts a value into this type, or returns `None` if it's a value of another type.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl FromValue for bool {
+    fn value_type() -> ValueType {
+        ValueType::Bool
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl FromValue for char {
+    fn value_type() -> ValueType {
+        ValueType::Char
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_char()
+    }
+}
+
+impl FromValue for i32 {
+    fn value_type() -> ValueType {
+        ValueType::Int
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_int()
+    }
+}
+
+impl FromValue for f32 {
+    fn value_type() -> ValueType {
+        ValueType::Float
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_float()
+    }
+}
+
+impl FromValue for String {
+    fn value_type() -> ValueType {
+        ValueType::String
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().map(Self::from)
+    }
+}
+
+impl FromValue for Color {
+    fn value_type() -> ValueType {
+        ValueType::Color
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_color()
+    }
+}
+
+impl FromValue for Dice {
+    fn value_type() -> ValueType {
+        ValueType::Dice
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_dice()
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn value_type() -> ValueType {
+        ValueType::List(Box::new(T::value_type()))
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_list()?.iter().map(T::from_value).collect()
+    }
+}
+
+type Setter<T> = Box<dyn Fn(&mut T, &Entity)>;
+
+/// Maps the entities of one struct type directly onto values of a Rust type, so that there's no
+/// need to walk through the parsed entities by hand.
+///
+/// A schema declares the struct type from the setters that are added to it: every property gets
+/// the type its setter takes, and every flag and structure inside gets its own setter. Parsing a
+/// file with the schema starts each entity from `T::default()` and calls the setters of the
+/// properties, flags and structures the entity has, in the order they're added to the schema.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::parser::Schema;
+/// # use doryen_extra::random::Dice;
+/// #[derive(Default)]
+/// struct Effect {
+///     kind: String,
+/// }
+///
+/// #[derive(Default)]
+/// struct Item {
+///     name: String,
+///     cost: i32,
+///     damage: Option<Dice>,
+///     tags: Vec<String>,
+///     two_handed: bool,
+///     effects: Vec<Effect>,
+/// }
+///
+/// let effect = Schema::new("effect").property("kind", true, |e: &mut Effect, kind| e.kind = kind);
+/// let items = Schema::new("item_type")
+///     .name(|item: &mut Item, name| item.name = name.to_string())
+///     .property("cost", true, |item, cost| item.cost = cost)
+///     .property("damage", false, |item, damage| item.damage = Some(damage))
+///     .property("tags", false, |item, tags| item.tags = tags)
+///     .flag("two_handed", |item| item.two_handed = true)
+///     .structure(effect, |item, effect| item.effects.push(effect))
+///     .parse_str(
+///         r#"item_type "long sword" {
+///             cost = 300
+///             damage = 2d6
+///             tags = ["weapon"]
+///             two_handed
+///             effect { kind = "bleeding" }
+///         }"#,
+///     )
+///     .unwrap();
+/// assert_eq!(items[0].name, "long sword");
+/// assert_eq!(items[0].cost, 300);
+/// assert!(items[0].two_handed);
+/// assert_eq!(items[0].effects[0].kind, "bleeding");
+/// ```
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Schema<T> {
+    struct_type: StructType,
+    // The struct types of the structures inside, and the ones inside those
+    nested: Vec<StructType>,
+    #[derivative(Debug = "ignore")]
+    setters: Vec<Setter<T>>,
+}
+
+impl<T: Default> Schema<T> {
+    /// Returns a new schema for the struct type with the given name.
+    pub fn new(name: &str) -> Self {
+        Self {
+            struct_type: StructType::new(name),
+            nested: Vec::new(),
+            setters: Vec::new(),
+        }
+    }
+
+    /// Returns the struct type the schema declares.
+    pub fn struct_type(&self) -> &StructType {
+        &self.struct_type
+    }
+
+    /// Adds a setter that's given the name of the entity, for entities that have one.
+    pub fn name<F: Fn(&mut T, &str) + 'static>(mut self, setter: F) -> Self {
+        self.setters.push(Box::new(move |value, entity| {
+            if let Some(name) = entity.name() {
+                setter(value, name);
+            }
+        }));
+        self
+    }
+
+    /// Adds a property whose type is decided by the setter's argument. The setter is only
+    /// called for entities that set the property, which a mandatory property always is.
+    pub fn property<V, F>(mut self, name: &str, mandatory: bool, setter: F) -> Self
+    where
+        V: FromValue,
+        F: Fn(&mut T, V) + 'static,
+    {
+        self.struct_type
+            .add_property(name, V::value_type(), mandatory);
+        let property = name.to_string();
+        self.setters.push(Box::new(move |value, entity| {
+            if let Some(v) = entity.get(&property).and_then(V::from_value) {
+                setter(value, v);
+            }
+        }));
+        self
+    }
+
+    /// Adds a property whose value must be one of the given strings.
+    pub fn value_list<F>(mut self, name: &str, values: &[&str], mandatory: bool, setter: F) -> Self
+    where
+        F: Fn(&mut T, &str) + 'static,
+    {
+        self.struct_type.add_value_list(name, values, mandatory);
+        let property = name.to_string();
+        self.setters.push(Box::new(move |value, entity| {
+            if let Some(v) = entity.get(&property).and_then(Value::as_str) {
+                setter(value, v);
+            }
+        }));
+        self
+    }
+
+    /// Adds a flag. The setter is only called for entities that have the flag.
+    pub fn flag<F: Fn(&mut T) + 'static>(mut self, name: &str, setter: F) -> Self {
+        self.struct_type.add_flag(name);
+        let flag = name.to_string();
+        self.setters.push(Box::new(move |value, entity| {
+            if entity.has_flag(&flag) {
+                setter(value);
+            }
+        }));
+        self
+    }
+
+    /// Allows the structures of another schema inside the structures of this one. The setter is
+    /// called once for each of them, in the order they appear in.
+    pub fn structure<C, F>(mut self, schema: Schema<C>, setter: F) -> Self
+    where
+        C: Default + 'static,
+        F: Fn(&mut T, C) + 'static,
+    {
+        let name = schema.struct_type.name.clone();
+        self.struct_type.add_structure(&name);
+        self.nested.push(schema.struct_type.clone());
+        self.nested.extend(schema.nested.iter().cloned());
+        self.setters.push(Box::new(move |value, entity| {
+            for child in entity.children().iter().filter(|c| c.type_name() == name) {
+                setter(value, schema.build(child));
+            }
+        }));
+        self
+    }
+
+    /// Returns a parser that knows the struct type of this schema and of all the structures
+    /// inside it.
+    pub fn parser(&self) -> Parser {
+        let mut parser = Parser::new();
+        for struct_type in &self.nested {
+            parser.add_struct(struct_type.clone());
+        }
+        parser.add_struct(self.struct_type.clone());
+        parser
+    }
+
+    /// Builds a value from an entity of this schema's struct type.
+    pub fn build(&self, entity: &Entity) -> T {
+        let mut value = T::default();
+        for setter in &self.setters {
+            setter(&mut value, entity);
+        }
+        value
+    }
+
+    /// Parses a configuration file, given as a string, and builds a value from each of the
+    /// structures at its top level, which must all be of this schema's struct type.
+    pub fn parse_str(&self, s: &str) -> Result<Vec<T>, Error> {
+        self.parser()
+            .parse_str(s)?
+            .iter()
+            .map(|entity| {
+                if entity.type_name() == self.struct_type.name {
+                    Ok(self.build(entity))
+                } else {
+                    let (line, column) = entity.position();
+                    let message = format!("expected '{}'", self.struct_type.name);
+                    Err(syntax_error(line, column, message))
+                }
+            })
+            .collect()
+    }
+
+    /// Parses a configuration file, like `parse_str`. Errors in the file are returned as errors
+    /// of the `InvalidData` kind.
+    pub fn load_from_file<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<Vec<T>> {
+        let s = std::fs::read_to_string(path)?;
+        self.parse_str(&s)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Monster {
+        name: String,
+        glyph: char,
+        speed: f32,
+        color: Color,
+        kind: String,
+        resistances: Vec<String>,
+        undead: bool,
+        attacks: Vec<Attack>,
+    }
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Attack {
+        verb: String,
+        damage: i32,
+    }
+
+    fn schema() -> Schema<Monster> {
+        let attack = Schema::new("attack")
+            .property("verb", true, |a: &mut Attack, verb| a.verb = verb)
+            .property("damage", true, |a, damage| a.damage = damage);
+        Schema::new("monster")
+            .name(|m: &mut Monster, name| m.name = name.to_string())
+            .property("glyph", true, |m, glyph| m.glyph = glyph)
+            .property("speed", false, |m, speed| m.speed = speed)
+            .property("color", false, |m, color| m.color = color)
+            .value_list("kind", &["beast", "humanoid"], false, |m, kind| {
+                m.kind = kind.to_string()
+            })
+            .property("resistances", false, |m, r| m.resistances = r)
+            .flag("undead", |m| m.undead = true)
+            .structure(attack, |m, attack| m.attacks.push(attack))
+    }
+
+    #[test]
+    fn builds_values() {
+        let monsters = schema()
+            .parse_str(
+                r#"
+                monster "zombie" {
+                    glyph = 'z'
+                    color = 0, 128, 0
+                    kind = "humanoid"
+                    resistances = ["cold", "poison"]
+                    undead
+                    attack { verb = "bites" damage = 3 }
+                    attack { verb = "claws" damage = 2 }
+                }
+                monster { glyph = 'r' speed = 1.5 }
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            monsters,
+            vec![
+                Monster {
+                    name: "zombie".to_string(),
+                    glyph: 'z',
+                    color: Color::new(0, 128, 0),
+                    kind: "humanoid".to_string(),
+                    resistances: vec!["cold".to_string(), "poison".to_string()],
+                    undead: true,
+                    attacks: vec![
+                        Attack {
+                            verb: "bites".to_string(),
+                            damage: 3
+                        },
+                        Attack {
+                            verb: "claws".to_string(),
+                            damage: 2
+                        },
+                    ],
+                    ..Monster::default()
+                },
+                Monster {
+                    glyph: 'r',
+                    speed: 1.5,
+                    ..Monster::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn declares_the_struct_type() {
+        let schema = schema();
+        let struct_type = schema.struct_type();
+        assert_eq!(struct_type.property_type("speed"), Some(&ValueType::Float));
+        assert_eq!(
+            struct_type.property_type("resistances"),
+            Some(&ValueType::List(Box::new(ValueType::String)))
+        );
+        assert_eq!(struct_type.is_mandatory("glyph"), Some(true));
+        assert!(struct_type.has_flag("undead"));
+
+        let error = schema
+            .parse_str("monster { glyph = 'a' }\n  attack { verb = \"hits\" damage = 1 }")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "2:3: expected 'monster'");
+    }
+}
@@ -0,0 +1,353 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Path finding.
+//!
+//! This toolkit finds paths between the cells of a map, the same way libtcod's path toolkit
+//! does.
+
+use crate::fov::FovMap;
+use crate::grid::Grid;
+use crate::{Position, USize};
+use derivative::Derivative;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// A Dijkstra map: the distance from every cell of a map to the closest of one or more roots,
+/// along with the paths leading there.
+///
+/// Unlike a single path search, a Dijkstra map answers "how far is it" and "which way do I go"
+/// for every cell at once, which makes it cheap to have many monsters chase (or, with the
+/// distances reversed, flee) the same target.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::Position;
+/// # use doryen_extra::grid::Grid;
+/// # use doryen_extra::path::Dijkstra;
+/// let mut walkable = Grid::new(10, 10, true);
+/// walkable[Position::new(5, 5)] = false;
+/// let mut dijkstra = Dijkstra::new(walkable, 1.41);
+/// dijkstra.compute(Position::new(0, 0));
+/// assert_eq!(dijkstra.distance(Position::new(3, 0)), Some(3.0));
+/// assert_eq!(dijkstra.distance(Position::new(5, 5)), None);
+///
+/// assert!(dijkstra.set_path(Position::new(0, 9)));
+/// assert_eq!(dijkstra.walk(), Some(Position::new(0, 1)));
+/// ```
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Dijkstra {
+    #[derivative(Debug = "ignore")]
+    cost: Box<dyn Fn(Position, Position) -> f32>,
+    diagonal_cost: f32,
+    distances: Grid<f32>,
+    parents: Grid<Option<Position>>,
+    // The path, in reverse order, so the next step can be popped off the end.
+    path: Vec<Position>,
+}
+
+impl Dijkstra {
+    /// Returns a new Dijkstra map over the cells of `walkable` that are `true`.
+    ///
+    /// Moving diagonally costs `diagonal_cost`, and moving orthogonally costs 1; a
+    /// `diagonal_cost` of 0 disables diagonal moves.
+    pub fn new(walkable: Grid<bool>, diagonal_cost: f32) -> Self {
+        Self::new_with_cost(walkable.size(), diagonal_cost, move |_, to| {
+            if walkable.get(to).copied().unwrap_or(false) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Returns a new Dijkstra map of the given size, where the cost of moving between two
+    /// adjacent cells is given by `cost`, called with the cell moved from and the cell moved to.
+    /// A cost of 0 or less means the move isn't possible, which lets terrain like swamps and
+    /// roads shape the paths.
+    ///
+    /// The cost of diagonal moves is multiplied by `diagonal_cost`; a `diagonal_cost` of 0
+    /// disables diagonal moves.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, USize};
+    /// # use doryen_extra::path::Dijkstra;
+    /// // Moving along the road on row 2 is much cheaper than moving anywhere else.
+    /// let mut dijkstra = Dijkstra::new_with_cost(USize::new(10, 5), 0.0, |_, to| {
+    ///     if to.y == 2 { 0.1 } else { 1.0 }
+    /// });
+    /// dijkstra.compute(Position::new(0, 0));
+    /// assert!(dijkstra.set_path(Position::new(9, 0)));
+    /// assert_eq!(dijkstra.path().nth(1), Some(Position::new(0, 2)));
+    /// ```
+    pub fn new_with_cost<F>(size: USize, diagonal_cost: f32, cost: F) -> Self
+    where
+        F: Fn(Position, Position) -> f32 + 'static,
+    {
+        let (width, height) = (size.width as usize, size.height as usize);
+        Self {
+            cost: Box::new(cost),
+            diagonal_cost,
+            distances: Grid::new(width, height, f32::INFINITY),
+            parents: Grid::new(width, height, None),
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns a new Dijkstra map over the walkable cells of `map`.
+    pub fn new_from_map(map: &FovMap, diagonal_cost: f32) -> Self {
+        let size = map.size();
+        let walkable = Grid::new_with(size.width as usize, size.height as usize, |position| {
+            map.is_walkable(position)
+        });
+
+        Self::new(walkable, diagonal_cost)
+    }
+
+    /// Computes the distance from `root` to every cell, replacing the previous results.
+    pub fn compute(&mut self, root: Position) {
+        self.compute_from(std::iter::once(root));
+    }
+
+    /// Computes the distance from every cell to the closest of `roots`, replacing the previous
+    /// results. Roots outside the map are ignored.
+    pub fn compute_from<I: IntoIterator<Item = Position>>(&mut self, roots: I) {
+        self.distances.fill(f32::INFINITY);
+        self.parents.fill(None);
+        self.path.clear();
+
+        let mut open = BinaryHeap::new();
+        for root in roots {
+            if self.distances.in_bounds(root) {
+                self.distances[root] = 0.0;
+                open.push(Node {
+                    distance: 0.0,
+                    position: root,
+                });
+            }
+        }
+
+        while let Some(Node { distance, position }) = open.pop() {
+            if distance > self.distances[position] {
+                continue;
+            }
+            for neighbor in position.neighbors8() {
+                let cost = match self.move_cost(position, neighbor) {
+                    Some(cost) => cost,
+                    None => continue,
+                };
+                let neighbor_distance = distance + cost;
+                if neighbor_distance < self.distances[neighbor] {
+                    self.distances[neighbor] = neighbor_distance;
+                    self.parents[neighbor] = Some(position);
+                    open.push(Node {
+                        distance: neighbor_distance,
+                        position: neighbor,
+                    });
+                }
+            }
+        }
+    }
+
+    // Returns the cost of moving between two adjacent cells, or `None` if it's not possible.
+    fn move_cost(&self, from: Position, to: Position) -> Option<f32> {
+        if !self.distances.in_bounds(to) {
+            return None;
+        }
+        let cost = if from.x != to.x && from.y != to.y {
+            if self.diagonal_cost <= 0.0 {
+                return None;
+            }
+            (self.cost)(from, to) * self.diagonal_cost
+        } else {
+            (self.cost)(from, to)
+        };
+
+        if cost > 0.0 {
+            Some(cost)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the distance from `position` to the closest root, or `None` if no root can be
+    /// reached from it.
+    pub fn distance(&self, position: Position) -> Option<f32> {
+        self.distances
+            .get(position)
+            .copied()
+            .filter(|distance| distance.is_finite())
+    }
+
+    /// Returns the distance of every cell to the closest root; cells that can't reach any root
+    /// have an infinite distance.
+    pub fn distances(&self) -> &Grid<f32> {
+        &self.distances
+    }
+
+    /// Sets the path to the one going from the closest root to `destination`, returning whether
+    /// such a path exists. The path is left empty if it doesn't.
+    pub fn set_path(&mut self, destination: Position) -> bool {
+        self.path.clear();
+        if self.distance(destination).is_none() {
+            return false;
+        }
+
+        let mut current = destination;
+        while let Some(parent) = self.parents[current] {
+            self.path.push(current);
+            current = parent;
+        }
+
+        true
+    }
+
+    /// Returns the cells of the path, from the first step after the root to the destination.
+    pub fn path(&self) -> impl Iterator<Item = Position> + '_ {
+        self.path.iter().rev().copied()
+    }
+
+    /// Returns the number of steps left on the path.
+    pub fn path_len(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Returns whether there are no steps left on the path.
+    pub fn is_path_empty(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Removes the next step of the path and returns it, or `None` if the path is empty.
+    pub fn walk(&mut self) -> Option<Position> {
+        self.path.pop()
+    }
+
+    /// Reverses the path, so that walking it leads from the destination back towards the root.
+    /// The root itself still isn't part of the path.
+    pub fn reverse_path(&mut self) {
+        self.path.reverse();
+    }
+}
+
+// An open node of the search, ordered so that `BinaryHeap` pops the closest one first.
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    distance: f32,
+    position: Position,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .partial_cmp(&self.distance)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walkable_from(rows: &[&str]) -> Grid<bool> {
+        Grid::new_with(rows[0].len(), rows.len(), |position| {
+            rows[position.y as usize].as_bytes()[position.x as usize] != b'#'
+        })
+    }
+
+    #[test]
+    fn distances_and_paths() {
+        let walkable = walkable_from(&[
+            ".....", //
+            ".###.", ".#...", ".#.#.",
+        ]);
+        let mut dijkstra = Dijkstra::new(walkable, 0.0);
+        dijkstra.compute(Position::new(0, 3));
+        assert_eq!(dijkstra.distance(Position::new(0, 0)), Some(3.0));
+        assert_eq!(dijkstra.distance(Position::new(2, 3)), Some(12.0));
+        assert_eq!(dijkstra.distance(Position::new(1, 1)), None);
+        assert_eq!(dijkstra.distance(Position::new(9, 9)), None);
+
+        assert!(!dijkstra.set_path(Position::new(1, 1)));
+        assert!(dijkstra.set_path(Position::new(2, 2)));
+        assert_eq!(dijkstra.path_len(), 11);
+        assert_eq!(dijkstra.path().last(), Some(Position::new(2, 2)));
+        dijkstra.reverse_path();
+        assert_eq!(dijkstra.walk(), Some(Position::new(2, 2)));
+        assert_eq!(dijkstra.walk(), Some(Position::new(3, 2)));
+    }
+
+    #[test]
+    fn multiple_roots() {
+        let mut dijkstra = Dijkstra::new(Grid::new(10, 1, true), 1.0);
+        dijkstra.compute_from(vec![Position::new(0, 0), Position::new(9, 0)]);
+        assert_eq!(dijkstra.distance(Position::new(3, 0)), Some(3.0));
+        assert_eq!(dijkstra.distance(Position::new(7, 0)), Some(2.0));
+        assert!(dijkstra.set_path(Position::new(7, 0)));
+        assert_eq!(
+            dijkstra.path().collect::<Vec<_>>(),
+            vec![Position::new(8, 0), Position::new(7, 0)]
+        );
+    }
+
+    #[test]
+    fn cost_callback() {
+        // Entering the swamp in the middle column costs 10, and the cells at the bottom of it
+        // can't be entered at all.
+        let mut dijkstra = Dijkstra::new_with_cost(USize::new(3, 4), 1.0, |_, to| match to {
+            Position { x: 1, y: 3 } => 0.0,
+            Position { x: 1, .. } => 10.0,
+            _ => 1.0,
+        });
+        dijkstra.compute(Position::new(0, 3));
+        assert_eq!(dijkstra.distance(Position::new(2, 3)), Some(11.0));
+        assert_eq!(dijkstra.distance(Position::new(1, 2)), Some(10.0));
+        assert!(dijkstra.set_path(Position::new(2, 3)));
+        assert_eq!(dijkstra.path_len(), 2);
+    }
+}
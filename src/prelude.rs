@@ -0,0 +1,56 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Prelude
+//!
+//! Re-exports the most commonly used items of the crate, so they can all be imported at once:
+//! ```
+//! use doryen_extra::prelude::*;
+//!
+//! let mut random = Random::new_mt_from_seed(42);
+//! let mut map = HeightMap::new(20, 20);
+//! map.add_hill(FPosition::new(10.0, 10.0), 5.0, 1.0);
+//! let _ = random.get_i32(0, 10);
+//! ```
+
+pub use crate::bsp::Bsp;
+pub use crate::color::Color;
+pub use crate::console::ConsoleTarget;
+pub use crate::fov::{CellKind, FovAlgorithm, FovMap};
+pub use crate::grid::Grid;
+pub use crate::heightmap::HeightMap;
+pub use crate::namegen::NameGenerator;
+pub use crate::noise::algorithms::{AlgorithmKind, OpenSimplex2, Perlin, Simplex, Wavelet};
+pub use crate::noise::{DynNoise, FractalMode, Noise, NoiseMapBuilder};
+pub use crate::path::Dijkstra;
+pub use crate::random::{Dice, Random, Rng, WeightedTable};
+pub use crate::{Direction, Error, FPosition, FSize, Position, Rectangle, Size, UPosition, USize};
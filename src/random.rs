@@ -31,18 +31,37 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-//! Pseudorandom number generator using the Mersenne Twister or Complementary Multiply With Carry
-//! algorithms.
+//! Pseudorandom number generator using the Mersenne Twister, Complementary Multiply With Carry,
+//! PCG32 or xoshiro256++ algorithms.
+//!
+//! Mersenne Twister and Complementary Multiply With Carry are the algorithms of libtcod, but their
+//! states take kilobytes; PCG32 and xoshiro256++ only take 16 and 40 bytes, which matters when
+//! there's a generator per entity or per map chunk.
 //!
 //! This toolkit used to be named `mersenne` in libtcod.
 
 pub mod algorithms;
+mod recorder;
+mod weighted;
+
+pub use recorder::{Draw, Recorder, Replay};
+pub use weighted::WeightedTable;
 
-use crate::random::algorithms::Algorithm;
-use crate::random::algorithms::{ComplementaryMultiplyWithCarry, MersenneTwister};
+use crate::random::algorithms::{split_mix_64, Algorithm, Seedable};
+use crate::random::algorithms::{
+    ComplementaryMultiplyWithCarry, MersenneTwister, Pcg32, Xoshiro256PlusPlus,
+};
+use crate::{math, Error, FPosition, FRectangle, Position, Rectangle};
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::atomic::{self, AtomicU64};
 use std::time::SystemTime;
 
+// The largest mean `Rng::get_poisson` handles at once; `exp(-POISSON_STEP)` is still well within
+// the range of an `f64`.
+const POISSON_STEP: f64 = 500.0;
+
 /// Trait providing methods for generating random numbers.
 pub trait Rng {
     /// Get an `i32` between `min` and `max`.
@@ -62,10 +81,486 @@ pub trait Rng {
 
     /// Get an `f64` between `min` and `max`, using gaussian distribution with the given `mean`.
     fn get_f64_mean(&mut self, min: f64, max: f64, mean: f64) -> f64;
+
+    /// Get a `usize` below `len`, every value being equally likely, whatever the distribution.
+    ///
+    /// The default implementation scales `get_uniform_f64`, so it's only as uniform as that is.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is 0 or greater than `u32::MAX`.
+    fn get_index(&mut self, len: usize) -> usize {
+        assert!(
+            len > 0 && len <= u32::MAX as usize,
+            "get_index requires a length between 1 and u32::MAX, got {}",
+            len
+        );
+
+        ((self.get_uniform_f64() * len as f64) as usize).min(len - 1)
+    }
+
+    /// Get an `f32` between 0.0 and 1.0, linearly distributed, whatever the distribution.
+    ///
+    /// The default implementation calls `get_f32`, so implementors whose `get_f32` isn't
+    /// linearly distributed should override it.
+    fn get_uniform_f32(&mut self) -> f32 {
+        self.get_f32(0.0, 1.0)
+    }
+
+    /// Get an `f64` between 0.0 and 1.0, linearly distributed, whatever the distribution.
+    ///
+    /// The default implementation calls `get_f64`, so implementors whose `get_f64` isn't
+    /// linearly distributed should override it.
+    fn get_uniform_f64(&mut self) -> f64 {
+        self.get_f64(0.0, 1.0)
+    }
+
+    /// Returns one of the `items`, every item being equally likely, or `None` if there are no
+    /// items.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let monster = random.choose(&["orc", "goblin", "troll"]).unwrap();
+    /// assert!(["orc", "goblin", "troll"].contains(monster));
+    /// ```
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T>
+    where
+        Self: Sized,
+    {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.get_index(items.len())])
+        }
+    }
+
+    /// Returns one of the items, each item being as likely as its weight says, or `None` if
+    /// there's no item with a positive weight. Weights that aren't positive are never picked.
+    ///
+    /// This takes time proportional to the number of items; when picking from the same items
+    /// many times, a `WeightedTable` is faster.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let loot = [("gold", 10.0), ("potion", 3.0), ("sword", 0.5)];
+    /// let item = random.choose_weighted(&loot).unwrap();
+    /// assert!(["gold", "potion", "sword"].contains(item));
+    /// ```
+    fn choose_weighted<'a, T>(&mut self, items: &'a [(T, f32)]) -> Option<&'a T>
+    where
+        Self: Sized,
+    {
+        let weight = |&(_, weight): &(T, f32)| if weight > 0.0 { weight } else { 0.0 };
+        let total: f32 = items.iter().map(weight).sum();
+        if !(total > 0.0 && total.is_finite()) {
+            return None;
+        }
+
+        let mut target = self.get_uniform_f32() * total;
+        let mut chosen = None;
+        for item in items.iter().filter(|&item| weight(item) > 0.0) {
+            chosen = Some(&item.0);
+            target -= weight(item);
+            if target < 0.0 {
+                break;
+            }
+        }
+
+        chosen
+    }
+
+    /// Shuffles the `items` in place, every order being equally likely, using the Fisher-Yates
+    /// algorithm.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let mut deck: Vec<u32> = (1..=52).collect();
+    /// random.shuffle(&mut deck);
+    /// let mut sorted = deck.clone();
+    /// sorted.sort_unstable();
+    /// assert_eq!(sorted, (1..=52).collect::<Vec<_>>());
+    /// ```
+    fn shuffle<T>(&mut self, items: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.get_index(i + 1));
+        }
+    }
+
+    /// Returns `n` different items picked from the `items` at random, in random order, or all
+    /// of them, shuffled, if there are fewer than `n`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let heroes = ["knight", "mage", "rogue", "cleric", "ranger"];
+    /// let party = random.sample(3, &heroes);
+    /// assert_eq!(party.len(), 3);
+    /// assert!(party.iter().all(|hero| heroes.contains(hero)));
+    /// ```
+    fn sample<'a, T>(&mut self, n: usize, items: &'a [T]) -> Vec<&'a T>
+    where
+        Self: Sized,
+    {
+        self.sample_indices(n, 0..items.len())
+            .into_iter()
+            .map(|i| &items[i])
+            .collect()
+    }
+
+    /// Returns `n` different numbers picked from the `range` at random, in random order, or all
+    /// of them, shuffled, if there are fewer than `n`.
+    ///
+    /// This takes time and memory proportional to `n`, not to the length of the range, so it
+    /// can be used to pick a few unique cells out of a large map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let (width, height) = (200, 100);
+    /// let treasure_cells = random.sample_indices(10, 0..width * height);
+    /// assert_eq!(treasure_cells.len(), 10);
+    /// ```
+    fn sample_indices(&mut self, n: usize, range: Range<usize>) -> Vec<usize>
+    where
+        Self: Sized,
+    {
+        let len = range.len();
+        let n = n.min(len);
+
+        // Robert Floyd's algorithm, which picks a subset with every subset equally likely; the
+        // subset is then shuffled, so every order is equally likely too.
+        let mut picked = HashSet::with_capacity(n);
+        let mut indices = Vec::with_capacity(n);
+        for j in len - n..len {
+            let t = self.get_index(j + 1);
+            let index = if picked.insert(t) {
+                t
+            } else {
+                picked.insert(j);
+                j
+            };
+            indices.push(range.start + index);
+        }
+        self.shuffle(&mut indices);
+
+        indices
+    }
+
+    /// Returns a position inside the `rectangle`, every cell being equally likely.
+    ///
+    /// # Panics
+    ///
+    /// If the `rectangle` is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, Rectangle, USize};
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let room = Rectangle::new(Position::new(10, 5), USize::new(8, 4));
+    /// let position = random.position_in(room);
+    /// assert!((10..18).contains(&position.x) && (5..9).contains(&position.y));
+    /// ```
+    fn position_in(&mut self, rectangle: Rectangle) -> Position
+    where
+        Self: Sized,
+    {
+        assert!(
+            rectangle.size.width > 0 && rectangle.size.height > 0,
+            "position_in requires a non-empty rectangle"
+        );
+
+        Position::new(
+            rectangle.position.x + self.get_index(rectangle.size.width as usize) as i32,
+            rectangle.position.y + self.get_index(rectangle.size.height as usize) as i32,
+        )
+    }
+
+    /// Returns a position inside the `rectangle`, uniformly distributed.
+    fn fposition_in(&mut self, rectangle: FRectangle) -> FPosition
+    where
+        Self: Sized,
+    {
+        FPosition::new(
+            rectangle.position.x + self.get_uniform_f32() * rectangle.size.width,
+            rectangle.position.y + self.get_uniform_f32() * rectangle.size.height,
+        )
+    }
+
+    /// Returns a cell whose distance to the `center` is at most `radius`, every such cell being
+    /// equally likely.
+    ///
+    /// # Panics
+    ///
+    /// If part of the circle lies outside the range of `i32` coordinates.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::Position;
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let center = Position::new(20, 20);
+    /// let spot = random.position_in_circle(center, 3);
+    /// let (dx, dy) = (spot.x - center.x, spot.y - center.y);
+    /// assert!(dx * dx + dy * dy <= 9);
+    /// ```
+    fn position_in_circle(&mut self, center: Position, radius: u32) -> Position
+    where
+        Self: Sized,
+    {
+        let radius = i64::from(radius);
+        let fits = |coordinate: i32| {
+            i64::from(coordinate) - radius >= i64::from(i32::MIN)
+                && i64::from(coordinate) + radius <= i64::from(i32::MAX)
+        };
+        assert!(
+            fits(center.x) && fits(center.y),
+            "position_in_circle requires a circle within the range of i32 coordinates, got a \
+             radius of {} around {:?}",
+            radius,
+            center
+        );
+        let side = (2 * radius + 1) as usize;
+        // The circle covers more than three quarters of its bounding square, so few picks are
+        // rejected.
+        loop {
+            let dx = self.get_index(side) as i64 - radius;
+            let dy = self.get_index(side) as i64 - radius;
+            if dx * dx + dy * dy <= radius * radius {
+                return Position::new(center.x + dx as i32, center.y + dy as i32);
+            }
+        }
+    }
+
+    /// Returns a point whose distance to the `center` is at most `radius`, uniformly distributed
+    /// over the area of the circle.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::FPosition;
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let point = random.point_in_circle(FPosition::new(1.0, 2.0), 5.0);
+    /// let (dx, dy) = (point.x - 1.0, point.y - 2.0);
+    /// assert!(dx * dx + dy * dy <= 25.0 + 1e-4);
+    /// ```
+    fn point_in_circle(&mut self, center: FPosition, radius: f32) -> FPosition
+    where
+        Self: Sized,
+    {
+        self.point_on_ring(center, 0.0, radius)
+    }
+
+    /// Returns a point whose distance to the `center` is between `inner_radius` and
+    /// `outer_radius`, uniformly distributed over the area of the ring. With equal radii, the
+    /// point is on the circle of that radius, e.g. to spawn monsters just out of sight.
+    fn point_on_ring(
+        &mut self,
+        center: FPosition,
+        inner_radius: f32,
+        outer_radius: f32,
+    ) -> FPosition
+    where
+        Self: Sized,
+    {
+        let (inner, outer) = (inner_radius * inner_radius, outer_radius * outer_radius);
+        let distance = math::sqrt(inner + self.get_uniform_f32() * (outer - inner));
+        let (sin, cos) = math::sin_cos(self.get_uniform_f32() * 2.0 * std::f32::consts::PI);
+
+        FPosition::new(center.x + distance * cos, center.y + distance * sin)
+    }
+
+    /// Get an `f64` from the exponential distribution with the given `rate`, i.e. the time
+    /// between events that happen `rate` times per unit of time on average, e.g. for spawn
+    /// timers. The mean is `1.0 / rate`.
+    ///
+    /// # Panics
+    ///
+    /// If `rate` isn't positive and finite.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// // A wandering monster shows up every 200 turns, on average.
+    /// let turns_until_spawn = random.get_exponential(1.0 / 200.0);
+    /// assert!(turns_until_spawn >= 0.0);
+    /// ```
+    fn get_exponential(&mut self, rate: f64) -> f64
+    where
+        Self: Sized,
+    {
+        assert!(
+            rate > 0.0 && rate.is_finite(),
+            "get_exponential requires a positive rate, got {}",
+            rate
+        );
+
+        loop {
+            let u = self.get_uniform_f64();
+            if u < 1.0 {
+                return -math::ln_f64(1.0 - u) / rate;
+            }
+        }
+    }
+
+    /// Get a `u32` from the Poisson distribution with the given `mean`, i.e. the number of
+    /// events in a unit of time when they happen `mean` times per unit of time on average,
+    /// e.g. for drop counts.
+    ///
+    /// This takes time proportional to `mean`.
+    ///
+    /// # Panics
+    ///
+    /// If `mean` is negative or not finite.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let gold_coins = random.get_poisson(2.5);
+    /// ```
+    fn get_poisson(&mut self, mean: f64) -> u32
+    where
+        Self: Sized,
+    {
+        assert!(
+            mean >= 0.0 && mean.is_finite(),
+            "get_poisson requires a non-negative mean, got {}",
+            mean
+        );
+        // Knuth's algorithm multiplies uniform numbers until their product drops below
+        // `exp(-mean)`; the product is scaled back up by `exp(POISSON_STEP)` along the way, so
+        // that it doesn't underflow for large means.
+        let mut left = mean;
+        let mut count = 0;
+        let mut product = 1.0;
+        loop {
+            count += 1;
+            product *= self.get_uniform_f64();
+            while product < 1.0 && left > 0.0 {
+                let step = left.min(POISSON_STEP);
+                product *= math::exp_f64(step);
+                left -= step;
+            }
+            if product <= 1.0 {
+                return count - 1;
+            }
+        }
+    }
+
+    /// Get a `u32` from the binomial distribution, i.e. the number of successes among `trials`
+    /// attempts that each succeed with the given `probability`, e.g. how many of a volley of
+    /// arrows hit.
+    ///
+    /// This takes time proportional to the expected number of successes or failures, whichever
+    /// is smaller.
+    ///
+    /// # Panics
+    ///
+    /// If `probability` isn't between 0.0 and 1.0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let hits = random.get_binomial(10, 0.3);
+    /// assert!(hits <= 10);
+    /// ```
+    fn get_binomial(&mut self, trials: u32, probability: f64) -> u32
+    where
+        Self: Sized,
+    {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "get_binomial requires a probability between 0.0 and 1.0, got {}",
+            probability
+        );
+        if probability > 0.5 {
+            return trials - self.get_binomial(trials, 1.0 - probability);
+        }
+        if probability == 0.0 {
+            return 0;
+        }
+
+        // Jump from success to success; the number of trials up to the next one is
+        // geometrically distributed. `ln(1 - p)` is computed as `ln_1p(-p)`, since `1.0 - p`
+        // rounds to `1.0` for tiny probabilities, and the jumps would then never get past the
+        // last trial.
+        let log_failure = math::ln_1p_f64(-probability);
+        let mut successes = 0;
+        let mut trial = 0.0;
+        loop {
+            let u = self.get_uniform_f64();
+            if u >= 1.0 {
+                continue;
+            }
+            trial += (math::ln_f64(1.0 - u) / log_failure).floor() + 1.0;
+            if trial > f64::from(trials) {
+                return successes;
+            }
+            successes += 1;
+        }
+    }
+
+    /// Get an `f64` between `min` and `max` from the triangular distribution, whose density
+    /// rises linearly from `min` up to `mode` and falls linearly down to `max`, e.g. for damage
+    /// that's usually close to its typical value.
+    ///
+    /// # Panics
+    ///
+    /// If `mode` isn't between `min` and `max`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut random = Random::new_pcg32_from_seed(7);
+    /// let damage = random.get_triangular(4.0, 12.0, 6.0);
+    /// assert!((4.0..=12.0).contains(&damage));
+    /// ```
+    fn get_triangular(&mut self, min: f64, max: f64, mode: f64) -> f64
+    where
+        Self: Sized,
+    {
+        assert!(
+            min <= mode && mode <= max,
+            "get_triangular requires min <= mode <= max, got {}, {} and {}",
+            min,
+            max,
+            mode
+        );
+
+        let width = max - min;
+        if width <= 0.0 {
+            return min;
+        }
+        let u = self.get_uniform_f64();
+        let split = (mode - min) / width;
+        if u < split {
+            min + math::sqrt_f64(u * width * (mode - min))
+        } else {
+            max - math::sqrt_f64((1.0 - u) * width * (max - mode))
+        }
+    }
 }
 
 /// pseudorandom number generator toolkit
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
 pub struct Random<A: Algorithm> {
     /* algorithm identifier */
     algo: A,
@@ -78,12 +573,121 @@ pub struct Random<A: Algorithm> {
 }
 
 impl<A: Algorithm> Random<A> {
+    /// Returns a new `Random` using the given algorithm instance.
+    pub fn new_with_algorithm(algorithm: A) -> Self {
+        Self {
+            algo: algorithm,
+            distribution: Distribution::Linear,
+
+            y2: None,
+        }
+    }
+
+    /// Returns the algorithm instance used to generate numbers.
+    pub fn algorithm(&self) -> &A {
+        &self.algo
+    }
+
+    /// Returns the algorithm instance used to generate numbers, mutably.
+    pub fn algorithm_mut(&mut self) -> &mut A {
+        &mut self.algo
+    }
+
+    /// Returns a `Random` that generates the same numbers as this one, but records every draw
+    /// from the algorithm so it can be replayed later. See `Recorder` for details.
+    pub fn into_recording(self) -> Random<Recorder<A>> {
+        Random {
+            algo: Recorder::new(self.algo),
+            distribution: self.distribution,
+
+            y2: self.y2,
+        }
+    }
+
+    /// Returns a new, independent `Random` of the same algorithm and distribution, seeded from
+    /// this one's next 64 bits through a SplitMix64 mixing step, which keeps the numbers of the
+    /// child uncorrelated with those of its parent.
+    ///
+    /// This advances the parent, so every call gives a different child, in a sequence that only
+    /// depends on the parent's seed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// let mut world = Random::new_pcg32_from_seed(2019);
+    /// let mut terrain = world.split();
+    /// let mut loot = world.split();
+    /// assert_ne!(terrain.get_i32(0, 1_000_000), loot.get_i32(0, 1_000_000));
+    /// ```
+    pub fn split(&mut self) -> Self
+    where
+        A: Seedable,
+    {
+        let mut state = next_u64(&mut self.algo);
+        self.child(split_mix_64(&mut state))
+    }
+
+    /// Returns a new, independent `Random` of the same algorithm and distribution for the
+    /// given `stream`, without advancing this one.
+    ///
+    /// As long as the parent isn't used in between, forking the same stream gives the same child
+    /// and different streams give uncorrelated ones, so each subsystem can be given a stream
+    /// number of its own, and the order they're set up in doesn't matter.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::random::{Random, Rng};
+    /// const TERRAIN: u64 = 1;
+    /// const MONSTERS: u64 = 2;
+    /// let world = Random::new_xoshiro_from_seed(2019);
+    /// let mut monsters = world.fork(MONSTERS);
+    /// let mut terrain = world.fork(TERRAIN);
+    /// let first_monster = monsters.get_i32(0, 1_000_000);
+    /// assert_eq!(first_monster, world.fork(MONSTERS).get_i32(0, 1_000_000));
+    /// assert_ne!(first_monster, terrain.get_i32(0, 1_000_000));
+    /// ```
+    pub fn fork(&self, mut stream: u64) -> Self
+    where
+        A: Seedable + Clone,
+    {
+        let mut state = next_u64(&mut self.algo.clone()) ^ split_mix_64(&mut stream);
+        self.child(split_mix_64(&mut state))
+    }
+
+    fn child(&self, seed: u64) -> Self
+    where
+        A: Seedable,
+    {
+        Self {
+            algo: A::from_u64(seed),
+            distribution: self.distribution,
+
+            y2: None,
+        }
+    }
+
     fn default_seed() -> u64 {
         let now = SystemTime::now();
         let duration_since = now.duration_since(SystemTime::UNIX_EPOCH).unwrap();
         duration_since.as_secs()
     }
 
+    // A seed for the generators with 64-bit seeds. Unlike `default_seed`, it differs between
+    // generators created in the same second, or even the same instant, within a process.
+    fn fresh_seed() -> u64 {
+        static CREATED: AtomicU64 = AtomicU64::new(0);
+
+        let now = SystemTime::now();
+        let nanos = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        let mut state = nanos
+            ^ CREATED
+                .fetch_add(1, atomic::Ordering::Relaxed)
+                .rotate_left(32);
+        split_mix_64(&mut state)
+    }
+
     fn get_i(&mut self, mut min: i32, mut max: i32) -> i32 {
         match max.cmp(&min) {
             Ordering::Less => std::mem::swap(&mut min, &mut max),
@@ -130,9 +734,9 @@ impl<A: Algorithm> Random<A> {
         let (x1, x2, w) = loop {
             let x1 = self.algo.get_double() * 2.0 - 1.0;
             let x2 = self.algo.get_double() * 2.0 - 1.0;
-            let w = x1.powi(2) + x2.powi(2);
+            let w = x1 * x1 + x2 * x2;
             if w < 1.0 {
-                break (x1, x2, (-2.0 * w.ln() / w).sqrt());
+                break (x1, x2, math::sqrt_f64(-2.0 * math::ln_f64(w) / w));
             }
         };
 
@@ -305,6 +909,32 @@ impl<A: Algorithm> Rng for Random<A> {
         }
     }
 
+    fn get_index(&mut self, len: usize) -> usize {
+        assert!(
+            len > 0 && len <= u32::MAX as usize,
+            "get_index requires a length between 1 and u32::MAX, got {}",
+            len
+        );
+
+        // Numbers below the threshold are rejected, so that every remainder is equally likely.
+        let len = len as u32;
+        let threshold = len.wrapping_neg() % len;
+        loop {
+            let value = self.algo.get_int();
+            if value >= threshold {
+                return (value % len) as usize;
+            }
+        }
+    }
+
+    fn get_uniform_f32(&mut self) -> f32 {
+        self.algo.get_float()
+    }
+
+    fn get_uniform_f64(&mut self) -> f64 {
+        self.algo.get_double()
+    }
+
     fn get_f32(&mut self, min: f32, max: f32) -> f32 {
         match self.distribution {
             Distribution::Linear => self.get_f(min, max),
@@ -353,6 +983,10 @@ impl<A: Algorithm> Rng for Random<A> {
     }
 }
 
+fn next_u64<A: Algorithm>(algorithm: &mut A) -> u64 {
+    u64::from(algorithm.get_int()) << 32 | u64::from(algorithm.get_int())
+}
+
 impl Random<MersenneTwister> {
     /// Returns a new `Random` using the Mersenne Twister algorithm.
     pub fn new_mt() -> Self {
@@ -388,8 +1022,36 @@ impl Random<ComplementaryMultiplyWithCarry> {
     }
 }
 
+impl Random<Pcg32> {
+    /// Returns a new `Random` using the PCG32 algorithm.
+    pub fn new_pcg32() -> Self {
+        Self::new_pcg32_from_seed(Self::fresh_seed())
+    }
+
+    /// Returns a new `Random` using the PCG32 algorithm, seeded with the given `seed`.
+    pub fn new_pcg32_from_seed(seed: u64) -> Self {
+        Self::new_with_algorithm(Pcg32::new(seed))
+    }
+}
+
+impl Random<Xoshiro256PlusPlus> {
+    /// Returns a new `Random` using the xoshiro256++ algorithm.
+    pub fn new_xoshiro() -> Self {
+        Self::new_xoshiro_from_seed(Self::fresh_seed())
+    }
+
+    /// Returns a new `Random` using the xoshiro256++ algorithm, seeded with the given `seed`.
+    pub fn new_xoshiro_from_seed(seed: u64) -> Self {
+        Self::new_with_algorithm(Xoshiro256PlusPlus::new(seed))
+    }
+}
+
 /// The distribution to use when generating random numbers
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
 pub enum Distribution {
     /// Linear distribution; all numbers are equally likely.
     Linear,
@@ -423,7 +1085,7 @@ fn hash(data: &[u8]) -> u32 {
 */
 
 /// Represents a set of dice and rules for calculating their value when rolled
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Dice {
     nb_rolls: i32,
     nb_faces: i32,
@@ -448,7 +1110,7 @@ impl Dice {
         let mut s = s.as_ref();
 
         /* get multiplier */
-        let multiplier = if let Some(m) = s.find(|c| c == '*' || c == 'x') {
+        let multiplier = if let Some(m) = s.find(['*', 'x']) {
             let value = s[0..m].parse::<f32>().unwrap_or_default();
             s = &s[m + 1..];
 
@@ -459,13 +1121,13 @@ impl Dice {
 
         /* get rolls */
         let r = s
-            .find(|c| c == 'd' || c == 'D')
+            .find(['d', 'D'])
             .expect("Incorrect dice specification format");
         let nb_rolls = s[0..r].parse::<i32>().unwrap_or_default();
         s = &s[r + 1..];
 
         /* get faces */
-        let nb_faces = if let Some(f) = s.find(|c| c == '+' || c == '-') {
+        let nb_faces = if let Some(f) = s.find(['+', '-']) {
             let value = s[0..f].parse::<i32>().unwrap_or_default();
             s = &s[f..];
 
@@ -492,6 +1154,56 @@ impl Dice {
         }
     }
 
+    /// Create a new `Dice` with the given dice specification, or return an error if the
+    /// specification isn't valid. See the documentation of `new()` for the format of the
+    /// specification; unlike `new()`, which treats any part it can't parse as 0, this requires
+    /// every part to be a valid number, the number of rolls to not be negative and the number of
+    /// faces to be at least 1.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::random::Dice;
+    /// assert!(Dice::try_new("5*3d6+2").is_ok());
+    /// assert!(Dice::try_new("3d").is_err());
+    /// assert!(Dice::try_new("three d6").is_err());
+    /// ```
+    pub fn try_new<S: AsRef<str>>(specification: S) -> Result<Self, Error> {
+        let specification = specification.as_ref();
+        let invalid = || Error::InvalidDice(specification.to_owned());
+        let mut s = specification.trim();
+
+        let multiplier = if let Some(m) = s.find(['*', 'x']) {
+            let value = s[0..m].parse::<f32>().map_err(|_| invalid())?;
+            s = &s[m + 1..];
+
+            value
+        } else {
+            1.0
+        };
+
+        let r = s.find(['d', 'D']).ok_or_else(invalid)?;
+        let nb_rolls = s[0..r].parse::<i32>().map_err(|_| invalid())?;
+        s = &s[r + 1..];
+
+        let (faces, add_sub) = if let Some(f) = s.find(['+', '-']) {
+            (&s[0..f], s[f..].parse::<f32>().map_err(|_| invalid())?)
+        } else {
+            (s, 0.0)
+        };
+        let nb_faces = faces.parse::<i32>().map_err(|_| invalid())?;
+
+        if nb_rolls < 0 || nb_faces < 1 {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            multiplier,
+            nb_rolls,
+            nb_faces,
+            add_sub,
+        })
+    }
+
     /// Roll the dice according to their parameters. See the documentation of `new()` for how these
     /// parameters get used.
     pub fn roll<R: Rng>(&self, mersenne: &mut R) -> i32 {
@@ -559,3 +1271,273 @@ impl rand_core::SeedableRng for Random<ComplementaryMultiplyWithCarry> {
         Self::new_cmwc_from_seed(seed)
     }
 }
+
+#[cfg(feature = "rng_support")]
+impl rand_core::SeedableRng for Random<Pcg32> {
+    type Seed = [u8; 8];
+
+    /// Uses the seed as the generator's seed, read as a little-endian 64-bit number, like
+    /// xoshiro256++ reads its state.
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new_pcg32_from_seed(u64::from_le_bytes(seed))
+    }
+}
+
+#[cfg(feature = "rng_support")]
+impl rand_core::SeedableRng for Random<Xoshiro256PlusPlus> {
+    type Seed = [u8; 32];
+
+    /// Uses the seed as the generator's state, read as four little-endian 64-bit numbers. An
+    /// all-zero seed, which xoshiro256++ can't work with, is treated like `seed_from_u64(0)`.
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut state = [0; 4];
+        for (s, chunk) in state.iter_mut().zip(seed.chunks_exact(8)) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            *s = u64::from_le_bytes(bytes);
+        }
+        if state == [0; 4] {
+            return Self::new_xoshiro_from_seed(0);
+        }
+
+        Self::new_with_algorithm(Xoshiro256PlusPlus::from_state(state))
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new_xoshiro_from_seed(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_indices_are_unique_and_in_range() {
+        let mut random = Random::new_pcg32_from_seed(5);
+        for &(n, start, end) in &[
+            (0, 0, 10),
+            (5, 10, 20),
+            (10, 10, 20),
+            (15, 3, 8),
+            (8, 0, 1000),
+        ] {
+            let indices = random.sample_indices(n, start..end);
+            assert_eq!(indices.len(), n.min(end - start));
+            assert!(indices.iter().all(|i| (start..end).contains(i)));
+            let mut unique = indices.clone();
+            unique.sort_unstable();
+            unique.dedup();
+            assert_eq!(unique.len(), indices.len());
+        }
+    }
+
+    #[test]
+    fn shuffle_gives_every_order() {
+        let mut random = Random::new_pcg32_from_seed(5);
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..6_000 {
+            let mut items = [1, 2, 3];
+            random.shuffle(&mut items);
+            *counts.entry(items).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 6);
+        assert!(
+            counts.values().all(|&count| (800..1_200).contains(&count)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn points_on_a_ring_stay_between_its_radii() {
+        let mut random = Random::new_xoshiro_from_seed(5);
+        let center = FPosition::new(-3.0, 4.0);
+        for _ in 0..1_000 {
+            let point = random.point_on_ring(center, 2.0, 3.0);
+            let distance = math::hypot(point.x - center.x, point.y - center.y);
+            assert!(
+                (2.0 - 1e-4..=3.0 + 1e-4).contains(&distance),
+                "{}",
+                distance
+            );
+        }
+        let point = random.point_on_ring(center, 1.5, 1.5);
+        let distance = math::hypot(point.x - center.x, point.y - center.y);
+        assert!((distance - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn positions_in_a_circle_cover_it() {
+        let mut random = Random::new_xoshiro_from_seed(5);
+        let mut seen = HashSet::new();
+        for _ in 0..2_000 {
+            let position = random.position_in_circle(Position::new(0, 0), 2);
+            assert!(position.x * position.x + position.y * position.y <= 4);
+            seen.insert((position.x, position.y));
+        }
+        // 13 cells are within a distance of 2.
+        assert_eq!(seen.len(), 13);
+        assert_eq!(
+            random.position_in_circle(Position::new(7, 7), 0),
+            Position::new(7, 7)
+        );
+    }
+
+    // Checks that the mean and variance of `count` draws are about the expected ones.
+    fn assert_moments(count: usize, expected: (f64, f64), mut draw: impl FnMut() -> f64) {
+        let values: Vec<f64> = (0..count).map(|_| draw()).collect();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / count as f64;
+
+        for &(actual, wanted) in &[(mean, expected.0), (variance, expected.1)] {
+            assert!(
+                (actual - wanted).abs() <= wanted.abs() * 0.05 + 0.01,
+                "expected about {}, got {}",
+                wanted,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn distributions_have_the_expected_moments() {
+        let mut random = Random::new_xoshiro_from_seed(5);
+        let count = 40_000;
+
+        assert_moments(count, (2.0, 4.0), || random.get_exponential(0.5));
+
+        for &mean in &[0.0, 3.5, 1_200.0] {
+            assert_moments(count / 4, (mean, mean), || {
+                f64::from(random.get_poisson(mean))
+            });
+        }
+
+        for &(trials, p) in &[(10, 0.3), (1_000, 0.9), (5, 0.0), (5, 1.0)] {
+            let n = f64::from(trials);
+            assert_moments(count, (n * p, n * p * (1.0 - p)), || {
+                f64::from(random.get_binomial(trials, p))
+            });
+        }
+
+        // `1.0 - p` rounds to `1.0` for probabilities this small.
+        let mut pcg = Random::new_pcg32_from_seed(1);
+        assert_eq!(pcg.get_binomial(10, 1e-17), 0);
+
+        // For a triangular distribution, the mean is (a + b + c) / 3 and the variance is
+        // (a² + b² + c² - ab - ac - bc) / 18.
+        assert_moments(count, (22.0 / 3.0, 52.0 / 18.0), || {
+            random.get_triangular(4.0, 12.0, 6.0)
+        });
+    }
+
+    #[test]
+    fn position_in_circle_near_the_edge_of_the_coordinates() {
+        let mut random = Random::new_pcg32_from_seed(3);
+        let center = Position::new(i32::MAX - 2, i32::MIN + 2);
+        for _ in 0..100 {
+            let spot = random.position_in_circle(center, 2);
+            let (dx, dy) = (
+                i64::from(spot.x) - i64::from(center.x),
+                i64::from(spot.y) - i64::from(center.y),
+            );
+            assert!(dx * dx + dy * dy <= 4);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "position_in_circle requires a circle within the range")]
+    fn position_in_circle_rejects_huge_radii() {
+        Random::new_pcg32_from_seed(3).position_in_circle(Position::new(0, 0), 1 << 31);
+    }
+
+    #[cfg(feature = "serialization")]
+    fn assert_resumes_after_round_trip<A>(mut random: Random<A>)
+    where
+        A: Algorithm + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        // An odd number of Gaussian draws leaves the second Box-Muller value cached.
+        random.distribution = Distribution::Gaussian;
+        for _ in 0..1001 {
+            random.get_f64(0.0, 1.0);
+        }
+
+        let json = serde_json::to_string(&random).unwrap();
+        let mut reloaded: Random<A> = serde_json::from_str(&json).unwrap();
+        for _ in 0..5000 {
+            assert_eq!(
+                random.get_f64(0.0, 1.0).to_bits(),
+                reloaded.get_f64(0.0, 1.0).to_bits()
+            );
+        }
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloaded_generators_continue_where_they_left_off() {
+        assert_resumes_after_round_trip(Random::new_mt_from_seed(7));
+        assert_resumes_after_round_trip(Random::new_cmwc_from_seed(7));
+        assert_resumes_after_round_trip(Random::new_pcg32_from_seed(7));
+        assert_resumes_after_round_trip(Random::new_xoshiro_from_seed(7));
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_out_of_range_positions() {
+        let mut mt = serde_json::to_value(Random::new_mt_from_seed(7)).unwrap();
+        mt["algo"]["cur_mt"] = 625.into();
+        assert!(serde_json::from_value::<Random<MersenneTwister>>(mt).is_err());
+
+        let mut cmwc = serde_json::to_value(Random::new_cmwc_from_seed(7)).unwrap();
+        cmwc["algo"]["cur"] = 4096.into();
+        assert!(serde_json::from_value::<Random<ComplementaryMultiplyWithCarry>>(cmwc).is_err());
+
+        let mut xoshiro = serde_json::to_value(Random::new_xoshiro_from_seed(7)).unwrap();
+        xoshiro["algo"]["state"] = serde_json::json!([0, 0, 0, 0]);
+        assert!(serde_json::from_value::<Random<Xoshiro256PlusPlus>>(xoshiro).is_err());
+    }
+
+    #[test]
+    fn generators_created_together_differ() {
+        fn draws<A: Algorithm>(random: &mut Random<A>) -> Vec<i32> {
+            (0..4).map(|_| random.get_i32(0, 1000)).collect()
+        }
+
+        assert_ne!(
+            draws(&mut Random::new_pcg32()),
+            draws(&mut Random::new_pcg32())
+        );
+        assert_ne!(
+            draws(&mut Random::new_xoshiro()),
+            draws(&mut Random::new_xoshiro())
+        );
+    }
+
+    #[cfg(feature = "rng_support")]
+    #[test]
+    fn xoshiro_seed_is_its_state() {
+        use rand_core::{RngCore, SeedableRng};
+
+        let mut seed = [0; 32];
+        for (i, chunk) in seed.chunks_exact_mut(8).enumerate() {
+            chunk.copy_from_slice(&(i as u64 + 1).to_le_bytes());
+        }
+        // The first output of the reference implementation with the state [1, 2, 3, 4] is
+        // 41_943_041, which is drawn as its high and then its low half.
+        let mut random = Random::<Xoshiro256PlusPlus>::from_seed(seed);
+        assert_eq!(random.next_u32(), 0);
+        assert_eq!(random.next_u32(), 41_943_041);
+
+        let mut zero = Random::<Xoshiro256PlusPlus>::from_seed([0; 32]);
+        assert_eq!(zero.next_u64(), Random::new_xoshiro_from_seed(0).next_u64());
+    }
+
+    #[cfg(feature = "rng_support")]
+    #[test]
+    fn pcg32_seed_is_little_endian() {
+        use rand_core::{RngCore, SeedableRng};
+
+        let mut random = Random::<Pcg32>::from_seed(7_u64.to_le_bytes());
+        assert_eq!(random.next_u64(), Random::new_pcg32_from_seed(7).next_u64());
+    }
+}
@@ -33,6 +33,10 @@
 
 //! Random number generator algorithms.
 
+#[cfg(feature = "serialization")]
+use crate::Error;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
 use std::mem::{transmute, MaybeUninit};
 
 const RAND_DIV: f32 = 1.0 / 0xffff_ffff_u32 as f32; // u32::MAX
@@ -126,13 +130,52 @@ pub trait Algorithm {
     }
 }
 
+/// Random number generator algorithms that can be created from a 64-bit seed, which is what
+/// `Random::split` and `Random::fork` need to derive child generators.
+pub trait Seedable: Algorithm {
+    /// Create a new algorithm instance seeded with `seed`. Algorithms with a 32-bit seed fold
+    /// the two halves of `seed` together.
+    fn from_u64(seed: u64) -> Self;
+}
+
 /// Mersenne Twister algorithm.
 #[derive(Clone, Copy)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "MersenneTwisterData")
+)]
 pub struct MersenneTwister {
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
     mt: [u32; Self::MT19937_RECURRENCE_DEGREE],
     cur_mt: usize,
 }
 
+// `MersenneTwister` as it comes out of the deserializer, before its position is validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct MersenneTwisterData {
+    #[serde(with = "crate::util::big_array")]
+    mt: [u32; MersenneTwister::MT19937_RECURRENCE_DEGREE],
+    cur_mt: usize,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<MersenneTwisterData> for MersenneTwister {
+    type Error = Error;
+
+    fn try_from(data: MersenneTwisterData) -> Result<Self, Self::Error> {
+        if data.cur_mt > Self::MT19937_RECURRENCE_DEGREE {
+            return Err(Error::OutOfRange("cur_mt"));
+        }
+
+        Ok(Self {
+            mt: data.mt,
+            cur_mt: data.cur_mt,
+        })
+    }
+}
+
 impl MersenneTwister {
     const MT19937: u32 = 1_812_433_253;
     const MT19937_WORD_SIZE: usize = 32;
@@ -233,14 +276,54 @@ impl Algorithm for MersenneTwister {
     }
 }
 
+impl Seedable for MersenneTwister {
+    fn from_u64(seed: u64) -> Self {
+        Self::new(fold_seed(seed))
+    }
+}
+
 /// Complementary-Multiply-With-Carry algorithm.
 #[derive(Clone, Copy)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "ComplementaryMultiplyWithCarryData")
+)]
 pub struct ComplementaryMultiplyWithCarry {
+    #[cfg_attr(feature = "serialization", serde(with = "crate::util::big_array"))]
     q: [u32; 4096],
     c: u32,
     cur: usize,
 }
 
+// `ComplementaryMultiplyWithCarry` as it comes out of the deserializer, before its position is
+// validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct ComplementaryMultiplyWithCarryData {
+    #[serde(with = "crate::util::big_array")]
+    q: [u32; 4096],
+    c: u32,
+    cur: usize,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<ComplementaryMultiplyWithCarryData> for ComplementaryMultiplyWithCarry {
+    type Error = Error;
+
+    fn try_from(data: ComplementaryMultiplyWithCarryData) -> Result<Self, Self::Error> {
+        if data.cur > 4095 {
+            return Err(Error::OutOfRange("cur"));
+        }
+
+        Ok(Self {
+            q: data.q,
+            c: data.c,
+            cur: data.cur,
+        })
+    }
+}
+
 impl ComplementaryMultiplyWithCarry {
     /// Create a new Complementary-Multiply-With-Carry algorithm instance.
     #[allow(unsafe_code)]
@@ -257,7 +340,7 @@ impl ComplementaryMultiplyWithCarry {
         let cur = 0;
 
         Self {
-            q: unsafe { transmute(q) },
+            q: unsafe { transmute::<[MaybeUninit<u32>; 4096], [u32; 4096]>(q) },
             c,
             cur,
         }
@@ -298,6 +381,186 @@ impl Algorithm for ComplementaryMultiplyWithCarry {
     }
 }
 
+impl Seedable for ComplementaryMultiplyWithCarry {
+    fn from_u64(seed: u64) -> Self {
+        Self::new(fold_seed(seed))
+    }
+}
+
+/// Permuted Congruential Generator algorithm, the PCG-XSH-RR variant with 64 bits of state and
+/// 32-bit output.
+///
+/// Its whole state is two 64-bit integers, so it's cheap to create and to keep around, e.g. one
+/// per entity or per map chunk. Generators created with different streams give different
+/// sequences, even from the same seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+    const DEFAULT_STREAM: u64 = 0xda3e_39cb_94b9_5bdb;
+
+    /// Create a new PCG32 algorithm instance, using the default stream.
+    pub fn new(seed: u64) -> Self {
+        Self::new_with_stream(seed, Self::DEFAULT_STREAM)
+    }
+
+    /// Create a new PCG32 algorithm instance, using the given stream.
+    pub fn new_with_stream(seed: u64, stream: u64) -> Self {
+        let mut pcg = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(seed);
+        pcg.step();
+
+        pcg
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+}
+
+impl Algorithm for Pcg32 {
+    fn get_int(&mut self) -> u32 {
+        let state = self.state;
+        self.step();
+
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        let rotation = (state >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+}
+
+impl Seedable for Pcg32 {
+    fn from_u64(seed: u64) -> Self {
+        Self::new(seed)
+    }
+}
+
+/// The xoshiro256++ algorithm.
+///
+/// Its state is four 64-bit integers, it's very fast, and its output passes all the usual
+/// statistical tests. Every 64-bit number it generates is used as two 32-bit ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "Xoshiro256PlusPlusData")
+)]
+pub struct Xoshiro256PlusPlus {
+    state: [u64; 4],
+    spare: Option<u32>,
+}
+
+// `Xoshiro256PlusPlus` as it comes out of the deserializer, before its state is validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct Xoshiro256PlusPlusData {
+    state: [u64; 4],
+    spare: Option<u32>,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<Xoshiro256PlusPlusData> for Xoshiro256PlusPlus {
+    type Error = Error;
+
+    fn try_from(data: Xoshiro256PlusPlusData) -> Result<Self, Self::Error> {
+        // An all-zero state only ever generates zeroes.
+        if data.state == [0; 4] {
+            return Err(Error::InvalidData("the state is all zeroes"));
+        }
+
+        Ok(Self {
+            state: data.state,
+            spare: data.spare,
+        })
+    }
+}
+
+impl Xoshiro256PlusPlus {
+    /// Create a new xoshiro256++ algorithm instance. Its state is filled by a SplitMix64
+    /// generator seeded with `seed`, as the algorithm's authors recommend.
+    pub fn new(seed: u64) -> Self {
+        let mut split_mix = seed;
+        let mut state = [0; 4];
+        for s in &mut state {
+            *s = split_mix_64(&mut split_mix);
+        }
+
+        Self { state, spare: None }
+    }
+
+    // Creates an instance with the given state, which mustn't be all zeroes.
+    #[cfg(feature = "rng_support")]
+    pub(crate) fn from_state(state: [u64; 4]) -> Self {
+        debug_assert!(
+            state != [0; 4],
+            "the xoshiro256++ state can't be all zeroes"
+        );
+        Self { state, spare: None }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let result = (self.state[0].wrapping_add(self.state[3]))
+            .rotate_left(23)
+            .wrapping_add(self.state[0]);
+
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+}
+
+impl Algorithm for Xoshiro256PlusPlus {
+    fn get_int(&mut self) -> u32 {
+        if let Some(spare) = self.spare.take() {
+            return spare;
+        }
+
+        let value = self.next_u64();
+        self.spare = Some(value as u32);
+        (value >> 32) as u32
+    }
+}
+
+impl Seedable for Xoshiro256PlusPlus {
+    fn from_u64(seed: u64) -> Self {
+        Self::new(seed)
+    }
+}
+
+fn fold_seed(seed: u64) -> u32 {
+    (seed ^ (seed >> 32)) as u32
+}
+
+// Advances the SplitMix64 generator whose state is `state`, and returns its next number.
+pub(crate) fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 struct Bits<'a, A: Algorithm + ?Sized> {
     algorithm: &'a mut A,
     bits: u32,
@@ -325,3 +588,65 @@ impl<'a, A: Algorithm + ?Sized> Bits<'a, A> {
         bit
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcg32_matches_reference_output() {
+        // The output of the reference implementation's demo program, seeded with 42 on stream 54.
+        let mut pcg = Pcg32::new_with_stream(42, 54);
+        let expected = [
+            0xa15c_02b7,
+            0x7b47_f409,
+            0xba1d_3330,
+            0x83d2_f293,
+            0xbfa4_784b,
+            0xcbed_606e,
+        ];
+        for &value in &expected {
+            assert_eq!(pcg.get_int(), value);
+        }
+    }
+
+    #[test]
+    fn xoshiro256_plus_plus_matches_reference_output() {
+        let mut xoshiro = Xoshiro256PlusPlus {
+            state: [1, 2, 3, 4],
+            spare: None,
+        };
+        let expected = [
+            41_943_041,
+            58_720_359,
+            3_588_806_011_781_223,
+            3_591_011_842_654_386,
+            9_228_616_714_210_784_205,
+        ];
+        for &value in &expected {
+            assert_eq!(xoshiro.next_u64(), value);
+        }
+    }
+
+    #[test]
+    fn xoshiro256_plus_plus_uses_both_halves() {
+        let mut xoshiro = Xoshiro256PlusPlus::new(7);
+        let mut copy = xoshiro;
+        let value = copy.next_u64();
+        assert_eq!(xoshiro.get_int(), (value >> 32) as u32);
+        assert_eq!(xoshiro.get_int(), value as u32);
+    }
+
+    #[test]
+    fn split_mix_64_matches_reference_output() {
+        let mut state = 1_234_567;
+        let expected = [
+            6_457_827_717_110_365_317,
+            3_203_168_211_198_807_973,
+            9_817_491_932_198_370_423,
+        ];
+        for &value in &expected {
+            assert_eq!(split_mix_64(&mut state), value);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::random::algorithms::Algorithm;
+use crate::random::Random;
+#[cfg(feature = "serialization")]
+use crate::Error;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+
+/// A single number drawn from a random number generator algorithm, as recorded by a `Recorder`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Draw {
+    /// The number that was drawn
+    pub value: u32,
+    /// The tag that was set when the number was drawn, if any
+    pub tag: Option<String>,
+}
+
+/// An algorithm wrapper that records every number drawn from the algorithm it wraps, along with
+/// a tag identifying the code that drew it.
+///
+/// Together with `Replay`, this makes desync bugs and world generation regressions reproducible:
+/// record the draws of a run, save them, and feed them back into the code under test later. The
+/// tags tell you which part of the code drew which numbers, so when a replay goes off the rails,
+/// you can see where.
+///
+/// Everything a `Random` generates is derived from the 32-bit integers of its algorithm, so
+/// that's what gets recorded; the distribution of the `Random` doesn't matter.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::random::{Random, Rng};
+/// let mut random = Random::new_mt_from_seed(42).into_recording();
+/// random.tag("rooms");
+/// let rooms = random.get_i32(3, 8);
+/// random.tag("loot");
+/// let loot = random.get_f32(0.0, 1.0);
+///
+/// let draws = random.algorithm().draws().to_vec();
+/// assert_eq!(draws[0].tag.as_deref(), Some("rooms"));
+///
+/// let mut replay = Random::new_replay(draws);
+/// assert_eq!(replay.get_i32(3, 8), rooms);
+/// assert_eq!(replay.get_f32(0.0, 1.0), loot);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Recorder<A: Algorithm> {
+    algorithm: A,
+    tag: Option<String>,
+    draws: Vec<Draw>,
+}
+
+impl<A: Algorithm> Recorder<A> {
+    /// Returns a recorder wrapping the given algorithm, with no draws recorded and no tag set.
+    pub fn new(algorithm: A) -> Self {
+        Self {
+            algorithm,
+            tag: None,
+            draws: Vec::new(),
+        }
+    }
+
+    /// Sets the tag recorded with every following draw.
+    pub fn set_tag<S: Into<String>>(&mut self, tag: S) {
+        self.tag = Some(tag.into());
+    }
+
+    /// Clears the tag, so that the following draws are recorded without one.
+    pub fn clear_tag(&mut self) {
+        self.tag = None;
+    }
+
+    /// Returns the draws recorded so far, in the order they were drawn.
+    pub fn draws(&self) -> &[Draw] {
+        &self.draws
+    }
+
+    /// Returns the draws recorded so far, and starts a new recording.
+    pub fn take_draws(&mut self) -> Vec<Draw> {
+        std::mem::take(&mut self.draws)
+    }
+
+    /// Returns the wrapped algorithm.
+    pub fn inner(&self) -> &A {
+        &self.algorithm
+    }
+}
+
+impl<A: Algorithm> Algorithm for Recorder<A> {
+    fn get_int(&mut self) -> u32 {
+        let value = self.algorithm.get_int();
+        self.draws.push(Draw {
+            value,
+            tag: self.tag.clone(),
+        });
+
+        value
+    }
+}
+
+/// An algorithm that plays back draws recorded by a `Recorder`.
+///
+/// # Panics
+///
+/// Drawing more numbers than were recorded panics, since it means the code under test has
+/// diverged from the recording. With tag checking turned on, drawing a number under a different
+/// tag than the one it was recorded with panics too, which pinpoints where the divergence
+/// happened.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "ReplayData")
+)]
+pub struct Replay {
+    draws: Vec<Draw>,
+    position: usize,
+    tag: Option<String>,
+    check_tags: bool,
+}
+
+// `Replay` as it comes out of the deserializer, before its position is validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct ReplayData {
+    draws: Vec<Draw>,
+    position: usize,
+    tag: Option<String>,
+    check_tags: bool,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<ReplayData> for Replay {
+    type Error = Error;
+
+    fn try_from(data: ReplayData) -> Result<Self, Self::Error> {
+        if data.position > data.draws.len() {
+            return Err(Error::OutOfRange("position"));
+        }
+
+        Ok(Self {
+            draws: data.draws,
+            position: data.position,
+            tag: data.tag,
+            check_tags: data.check_tags,
+        })
+    }
+}
+
+impl Replay {
+    /// Returns an algorithm that plays back the given draws, without tag checking.
+    pub fn new(draws: Vec<Draw>) -> Self {
+        Self {
+            draws,
+            position: 0,
+            tag: None,
+            check_tags: false,
+        }
+    }
+
+    /// Turns on tag checking, which makes every draw verify that the current tag is the one the
+    /// number was recorded with.
+    pub fn with_tag_checking(mut self) -> Self {
+        self.check_tags = true;
+        self
+    }
+
+    /// Sets the tag compared against the recorded tags of the following draws.
+    pub fn set_tag<S: Into<String>>(&mut self, tag: S) {
+        self.tag = Some(tag.into());
+    }
+
+    /// Clears the tag, so that the following draws are expected to have been recorded without
+    /// one.
+    pub fn clear_tag(&mut self) {
+        self.tag = None;
+    }
+
+    /// Returns the number of draws played back so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of recorded draws that haven't been played back yet.
+    pub fn remaining(&self) -> usize {
+        self.draws.len() - self.position
+    }
+}
+
+impl Algorithm for Replay {
+    fn get_int(&mut self) -> u32 {
+        let draw = self.draws.get(self.position).unwrap_or_else(|| {
+            panic!(
+                "replay desync: draw {} was requested, but only {} draws were recorded",
+                self.position,
+                self.draws.len()
+            )
+        });
+        if self.check_tags && draw.tag != self.tag {
+            panic!(
+                "replay desync at draw {}: recorded with tag {:?}, but drawn with tag {:?}",
+                self.position, draw.tag, self.tag
+            );
+        }
+        self.position += 1;
+
+        draw.value
+    }
+}
+
+impl<A: Algorithm> Random<Recorder<A>> {
+    /// Sets the tag recorded with every following draw. See `Recorder::set_tag`.
+    pub fn tag<S: Into<String>>(&mut self, tag: S) {
+        self.algorithm_mut().set_tag(tag);
+    }
+}
+
+impl Random<Replay> {
+    /// Returns a new `Random` that plays back the given recorded draws. See `Replay`.
+    pub fn new_replay(draws: Vec<Draw>) -> Self {
+        Self::new_with_algorithm(Replay::new(draws))
+    }
+
+    /// Sets the tag compared against the recorded tags of the following draws. See
+    /// `Replay::set_tag`.
+    pub fn tag<S: Into<String>>(&mut self, tag: S) {
+        self.algorithm_mut().set_tag(tag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Distribution, Rng};
+
+    fn generate<A: Algorithm>(
+        random: &mut Random<A>,
+        tag: &dyn Fn(&mut Random<A>, &str),
+    ) -> Vec<f64> {
+        tag(random, "ints");
+        let mut values: Vec<_> = (0..20).map(|_| f64::from(random.get_i32(0, 100))).collect();
+        tag(random, "floats");
+        values.extend((0..20).map(|_| random.get_f64(-1.0, 1.0)));
+        random.distribution = Distribution::Gaussian;
+        tag(random, "gaussian");
+        values.extend((0..5).map(|_| f64::from(random.get_f32(0.0, 1.0))));
+
+        values
+    }
+
+    #[test]
+    fn replay_reproduces_recording() {
+        let mut recording = Random::new_cmwc_from_seed(9).into_recording();
+        let recorded = generate(&mut recording, &|random, tag| random.tag(tag));
+        let draws = recording.algorithm_mut().take_draws();
+        assert!(recording.algorithm().draws().is_empty());
+
+        let mut original = Random::new_cmwc_from_seed(9);
+        assert_eq!(generate(&mut original, &|_, _| ()), recorded);
+
+        let mut replay = Random::new_with_algorithm(Replay::new(draws.clone()).with_tag_checking());
+        assert_eq!(
+            generate(&mut replay, &|random, tag| random.tag(tag)),
+            recorded
+        );
+        assert_eq!(replay.algorithm().remaining(), 0);
+        assert_eq!(replay.algorithm().position(), draws.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "replay desync at draw 0")]
+    fn replay_detects_tag_mismatch() {
+        let mut recording = Random::new_mt_from_seed(1).into_recording();
+        recording.tag("a");
+        recording.get_i32(0, 10);
+
+        let draws = recording.algorithm().draws().to_vec();
+        let mut replay = Random::new_with_algorithm(Replay::new(draws).with_tag_checking());
+        replay.tag("b");
+        replay.get_i32(0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "only 0 draws were recorded")]
+    fn replay_detects_running_out() {
+        Random::new_replay(Vec::new()).get_i32(0, 10);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_out_of_range_positions() {
+        let mut replay = Replay::new(vec![Draw {
+            value: 4,
+            tag: None,
+        }]);
+        replay.get_int();
+        let json = serde_json::to_value(&replay).unwrap();
+        let reloaded: Replay = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(reloaded.position(), 1);
+        assert_eq!(reloaded.remaining(), 0);
+
+        let mut past_the_end = json;
+        past_the_end["position"] = 2.into();
+        assert!(serde_json::from_value::<Replay>(past_the_end).is_err());
+    }
+}
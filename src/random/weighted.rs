@@ -0,0 +1,246 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::random::Rng;
+use crate::Error;
+#[cfg(feature = "serialization")]
+use std::convert::TryFrom;
+
+/// A table of weighted items, from which an item can be picked at random in constant time,
+/// whatever the number of items, using Vose's alias method. Meant for loot tables, encounter
+/// tables, and the like.
+///
+/// Each item is as likely to be picked as its weight says, relative to the sum of the weights.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::random::{Random, WeightedTable};
+/// let encounters = WeightedTable::new(vec![("rats", 6.0), ("bandits", 3.0), ("dragon", 0.1)]);
+/// let mut random = Random::new_pcg32_from_seed(3);
+/// let encounter = encounters.sample(&mut random);
+/// assert!(["rats", "bandits", "dragon"].contains(encounter));
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize),
+    serde(try_from = "WeightedTableData<T>")
+)]
+pub struct WeightedTable<T> {
+    items: Vec<T>,
+    probabilities: Vec<f32>,
+    aliases: Vec<usize>,
+}
+
+// `WeightedTable` as it comes out of the deserializer, before its alias tables are validated.
+#[cfg(feature = "serialization")]
+#[derive(serde_derive::Deserialize)]
+struct WeightedTableData<T> {
+    items: Vec<T>,
+    probabilities: Vec<f32>,
+    aliases: Vec<usize>,
+}
+
+#[cfg(feature = "serialization")]
+impl<T> TryFrom<WeightedTableData<T>> for WeightedTable<T> {
+    type Error = Error;
+
+    fn try_from(data: WeightedTableData<T>) -> Result<Self, Self::Error> {
+        let len = data.items.len();
+        if len == 0 || len > u32::MAX as usize {
+            return Err(Error::OutOfRange("items"));
+        }
+        for &actual in &[data.probabilities.len(), data.aliases.len()] {
+            if actual != len {
+                return Err(Error::LengthMismatch {
+                    expected: len,
+                    actual,
+                });
+            }
+        }
+        if data.aliases.iter().any(|&alias| alias >= len) {
+            return Err(Error::OutOfRange("aliases"));
+        }
+
+        Ok(Self {
+            items: data.items,
+            probabilities: data.probabilities,
+            aliases: data.aliases,
+        })
+    }
+}
+
+impl<T> WeightedTable<T> {
+    /// Returns a table of the given items and their weights.
+    ///
+    /// # Panics
+    ///
+    /// If there are no items, more than `u32::MAX` of them, or if a weight is negative or not
+    /// finite, or if all the weights are 0.
+    pub fn new(items: Vec<(T, f32)>) -> Self {
+        Self::try_new(items).unwrap()
+    }
+
+    /// Like `new`, but returns an error instead of panicking if the weights can't make a table.
+    pub fn try_new(items: Vec<(T, f32)>) -> Result<Self, Error> {
+        let total: f64 = items.iter().map(|&(_, weight)| f64::from(weight)).sum();
+        let valid_weight = |&(_, weight): &(T, f32)| weight >= 0.0 && weight.is_finite();
+        if items.len() > u32::MAX as usize
+            || !items.iter().all(valid_weight)
+            || !(total > 0.0 && total.is_finite())
+        {
+            return Err(Error::OutOfRange("the weights of the table"));
+        }
+
+        // Scale the weights so that they average 1, then pair every column below 1 with one
+        // above it, which fills it up to 1.
+        let len = items.len();
+        let mut scaled: Vec<f64> = items
+            .iter()
+            .map(|&(_, weight)| f64::from(weight) * len as f64 / total)
+            .collect();
+        let mut probabilities = vec![1.0; len];
+        let mut aliases: Vec<usize> = (0..len).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..len).partition(|&i| scaled[i] < 1.0);
+        while let (Some(&less), Some(&more)) = (small.last(), large.last()) {
+            small.pop();
+            probabilities[less] = scaled[less] as f32;
+            aliases[less] = more;
+            scaled[more] -= 1.0 - scaled[less];
+            if scaled[more] < 1.0 {
+                large.pop();
+                small.push(more);
+            }
+        }
+        // Whatever is left over is only off from 1 by rounding errors, and stays at 1.
+
+        Ok(Self {
+            items: items.into_iter().map(|(item, _)| item).collect(),
+            probabilities,
+            aliases,
+        })
+    }
+
+    /// Returns one of the items, at random.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> &T {
+        let column = rng.get_index(self.items.len());
+        if rng.get_uniform_f32() < self.probabilities[column] {
+            &self.items[column]
+        } else {
+            &self.items[self.aliases[column]]
+        }
+    }
+
+    /// Returns the items of the table, in the order they were given in.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    #[test]
+    fn samples_follow_the_weights() {
+        let table = WeightedTable::new(vec![(0, 1.0), (1, 0.0), (2, 3.0), (3, 4.0)]);
+        let mut random = Random::new_xoshiro_from_seed(11);
+        let mut counts = [0; 4];
+        for _ in 0..80_000 {
+            counts[*table.sample(&mut random)] += 1;
+        }
+
+        assert_eq!(counts[1], 0);
+        for &(index, expected) in &[(0, 10_000.0), (2, 30_000.0), (3, 40_000.0)] {
+            let count = f64::from(counts[index]);
+            assert!((count - expected).abs() < expected * 0.05, "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn choose_weighted_follows_the_weights() {
+        let items = [('a', 1.0), ('b', -2.0), ('c', 3.0)];
+        let mut random = Random::new_pcg32_from_seed(11);
+        let mut a = 0;
+        for _ in 0..40_000 {
+            match random.choose_weighted(&items) {
+                Some('a') => a += 1,
+                Some('c') => (),
+                other => panic!("unexpected pick {:?}", other),
+            }
+        }
+        assert!((f64::from(a) - 10_000.0).abs() < 500.0, "{}", a);
+        assert_eq!(random.choose_weighted(&[('a', 0.0)]), None);
+        assert_eq!(random.choose::<char>(&[]), None);
+    }
+
+    #[test]
+    fn invalid_weights_are_rejected() {
+        let error = Err(Error::OutOfRange("the weights of the table"));
+        assert_eq!(WeightedTable::<u8>::try_new(vec![]).map(|_| ()), error);
+        assert_eq!(WeightedTable::try_new(vec![(0, 0.0)]).map(|_| ()), error);
+        assert_eq!(
+            WeightedTable::try_new(vec![(0, 1.0), (1, -1.0)]).map(|_| ()),
+            error
+        );
+        assert_eq!(
+            WeightedTable::try_new(vec![(0, f32::NAN)]).map(|_| ()),
+            error
+        );
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn reloading_rejects_inconsistent_tables() {
+        let table = WeightedTable::new(vec![(0, 1.0), (1, 3.0), (2, 4.0)]);
+        let json = serde_json::to_value(&table).unwrap();
+        let reloaded: WeightedTable<i32> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(reloaded.items(), table.items());
+
+        let mut short = json.clone();
+        short["probabilities"] = serde_json::json!([1.0, 1.0]);
+        assert!(serde_json::from_value::<WeightedTable<i32>>(short).is_err());
+
+        let mut long = json.clone();
+        long["aliases"] = serde_json::json!([0, 1, 2, 2]);
+        assert!(serde_json::from_value::<WeightedTable<i32>>(long).is_err());
+
+        let mut out_of_range = json;
+        out_of_range["aliases"] = serde_json::json!([0, 3, 2]);
+        assert!(serde_json::from_value::<WeightedTable<i32>>(out_of_range).is_err());
+
+        let empty = serde_json::json!({"items": [], "probabilities": [], "aliases": []});
+        assert!(serde_json::from_value::<WeightedTable<i32>>(empty).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Rasterization of circles and ellipses onto a grid of cells.
+
+use crate::{Position, USize};
+use std::collections::BTreeMap;
+
+/// Returns the cells making up the outline of a circle with the given center and radius, in
+/// row-major order.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::Position;
+/// # use doryen_extra::raster::circle;
+/// let cells = circle(Position::ORIGIN, 1);
+/// assert_eq!(cells.len(), 4);
+/// assert!(cells.contains(&Position::new(0, -1)));
+/// ```
+pub fn circle(center: Position, radius: u32) -> Vec<Position> {
+    ellipse(center, USize::new(radius, radius))
+}
+
+/// Returns the cells covered by a filled circle with the given center and radius, in row-major
+/// order. The outline is the same as the one returned by `circle`.
+pub fn filled_circle(center: Position, radius: u32) -> Vec<Position> {
+    filled_ellipse(center, USize::new(radius, radius))
+}
+
+/// Returns the cells making up the outline of an axis-aligned ellipse with the given center and
+/// horizontal and vertical radii, in row-major order.
+///
+/// An ellipse with a radius of 0 degenerates into a straight line.
+pub fn ellipse(center: Position, radii: USize) -> Vec<Position> {
+    let mut cells: Vec<_> = quadrant(radii)
+        .into_iter()
+        .flat_map(|(x, y)| {
+            [(x, y), (-x, y), (x, -y), (-x, -y)]
+                .iter()
+                .map(|&(x, y)| center + Position::new(x as i32, y as i32))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    cells.sort_unstable();
+    cells.dedup();
+
+    cells
+}
+
+/// Returns the cells covered by a filled axis-aligned ellipse with the given center and
+/// horizontal and vertical radii, in row-major order. The outline is the same as the one
+/// returned by `ellipse`.
+pub fn filled_ellipse(center: Position, radii: USize) -> Vec<Position> {
+    let mut spans = BTreeMap::new();
+    for position in ellipse(center, radii) {
+        let span = spans.entry(position.y).or_insert((position.x, position.x));
+        span.0 = span.0.min(position.x);
+        span.1 = span.1.max(position.x);
+    }
+
+    spans
+        .into_iter()
+        .flat_map(|(y, (min_x, max_x))| (min_x..=max_x).map(move |x| Position::new(x, y)))
+        .collect()
+}
+
+// Computes the outline of the lower-right quadrant of an ellipse centered on the origin, using
+// the midpoint ellipse algorithm. All the decision variables are scaled by 4 to keep them integer.
+fn quadrant(radii: USize) -> Vec<(i64, i64)> {
+    let rx = i64::from(radii.width);
+    let ry = i64::from(radii.height);
+    if rx == 0 {
+        return (0..=ry).map(|y| (0, y)).collect();
+    }
+    if ry == 0 {
+        return (0..=rx).map(|x| (x, 0)).collect();
+    }
+
+    let a2 = rx * rx;
+    let b2 = ry * ry;
+    let mut points = Vec::new();
+    let mut x = 0;
+    let mut y = ry;
+
+    let mut d = 4 * b2 - 4 * a2 * ry + a2;
+    while b2 * x <= a2 * y {
+        points.push((x, y));
+        if d < 0 {
+            d += 4 * b2 * (2 * x + 3);
+        } else {
+            d += 4 * b2 * (2 * x + 3) - 8 * a2 * (y - 1);
+            y -= 1;
+        }
+        x += 1;
+    }
+
+    // The second region isn't guaranteed to mirror the first one along the diagonal, so circles
+    // are completed by mirroring the first region instead, which keeps them symmetric.
+    if rx == ry {
+        let mirrored: Vec<_> = points.iter().map(|&(px, py)| (py, px)).collect();
+        points.extend(mirrored);
+        return points;
+    }
+
+    d = b2 * (2 * x + 1) * (2 * x + 1) + 4 * a2 * (y - 1) * (y - 1) - 4 * a2 * b2;
+    while y >= 0 {
+        points.push((x, y));
+        if d > 0 {
+            d += 4 * a2 * (3 - 2 * y);
+        } else {
+            d += 8 * b2 * (x + 1) + 4 * a2 * (3 - 2 * y);
+            x += 1;
+        }
+        y -= 1;
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_outline_is_symmetric_and_closed() {
+        let center = Position::new(10, 5);
+        for radius in 0..12 {
+            let cells = circle(center, radius);
+            for &cell in &cells {
+                let d = cell - center;
+                assert!(cells.contains(&(center + Position::new(d.y, d.x))));
+                assert!(cells.contains(&(center + Position::new(-d.x, d.y))));
+                // Every cell has a neighbor in the outline, so there are no gaps.
+                if radius > 0 {
+                    assert!(cell.neighbors8().any(|neighbor| cells.contains(&neighbor)));
+                }
+            }
+            assert!(cells.contains(&(center + Position::new(radius as i32, 0))));
+            assert!(cells.contains(&(center - Position::new(0, radius as i32))));
+        }
+    }
+
+    #[test]
+    fn small_shapes() {
+        assert_eq!(circle(Position::ORIGIN, 0), vec![Position::ORIGIN]);
+        assert_eq!(filled_circle(Position::ORIGIN, 1).len(), 5);
+        assert_eq!(
+            ellipse(Position::ORIGIN, USize::new(2, 0)),
+            (-2..=2).map(|x| Position::new(x, 0)).collect::<Vec<_>>()
+        );
+
+        let filled = filled_ellipse(Position::new(3, 3), USize::new(4, 2));
+        let outline = ellipse(Position::new(3, 3), USize::new(4, 2));
+        assert!(outline.iter().all(|p| filled.contains(p)));
+        assert!(filled.contains(&Position::new(3, 3)));
+        assert!(filled.contains(&Position::new(-1, 3)));
+        assert!(!filled.contains(&Position::new(-2, 3)));
+        assert!(!filled.contains(&Position::new(3, 6)));
+    }
+}
@@ -0,0 +1,326 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Colored text.
+//!
+//! Parses text containing inline color codes into runs of equally colored text, so it can be
+//! printed on any [`ConsoleTarget`], e.g. for colored message logs. Two syntaxes are understood,
+//! and can be mixed:
+//!
+//! * libtcod's control characters: [`COLCTRL_1`] to [`COLCTRL_5`] switch to one of five
+//!   configurable color pairs, [`COLCTRL_FORE_RGB`] and [`COLCTRL_BACK_RGB`] are followed by three
+//!   characters holding the red, green and blue components of a new foreground or background
+//!   color, and [`COLCTRL_STOP`] goes back to the default colors.
+//! * doryen's `#[color_name]` codes, which switch the foreground to a color registered with
+//!   [`ColorCodeParser::register_color`]. An unknown or empty name, like in `#[]`, goes back to
+//!   the previous foreground color.
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::color::Color;
+//! # use doryen_extra::text::{fore_code, ColorCodeParser, COLCTRL_STOP};
+//! let mut parser = ColorCodeParser::new();
+//! parser.register_color("red", Color::RED);
+//! let text = format!("#[red]Hot#[] and {}cold{}!", fore_code(Color::BLUE), COLCTRL_STOP);
+//! let runs = parser.parse(&text);
+//! assert_eq!(runs.len(), 4);
+//! assert_eq!(runs[0].text, "Hot");
+//! assert_eq!(runs[0].fore, Some(Color::RED));
+//! assert_eq!(runs[2].text, "cold");
+//! assert_eq!(runs[2].fore, Some(Color::BLUE));
+//! assert_eq!(runs[3].fore, None);
+//! ```
+//!
+//! [`ConsoleTarget`]: ../console/trait.ConsoleTarget.html
+//! [`COLCTRL_1`]: ./constant.COLCTRL_1.html
+//! [`COLCTRL_5`]: ./constant.COLCTRL_5.html
+//! [`COLCTRL_FORE_RGB`]: ./constant.COLCTRL_FORE_RGB.html
+//! [`COLCTRL_BACK_RGB`]: ./constant.COLCTRL_BACK_RGB.html
+//! [`COLCTRL_STOP`]: ./constant.COLCTRL_STOP.html
+//! [`ColorCodeParser::register_color`]: ./struct.ColorCodeParser.html#method.register_color
+
+use crate::color::Color;
+use std::collections::HashMap;
+
+/// Switches to the first color control pair.
+pub const COLCTRL_1: char = '\u{1}';
+/// Switches to the second color control pair.
+pub const COLCTRL_2: char = '\u{2}';
+/// Switches to the third color control pair.
+pub const COLCTRL_3: char = '\u{3}';
+/// Switches to the fourth color control pair.
+pub const COLCTRL_4: char = '\u{4}';
+/// Switches to the fifth color control pair.
+pub const COLCTRL_5: char = '\u{5}';
+/// Switches to the foreground color given by the next three characters.
+pub const COLCTRL_FORE_RGB: char = '\u{6}';
+/// Switches to the background color given by the next three characters.
+pub const COLCTRL_BACK_RGB: char = '\u{7}';
+/// Switches back to the default colors.
+pub const COLCTRL_STOP: char = '\u{8}';
+
+/// Returns the control sequence switching the foreground to `color`.
+pub fn fore_code(color: Color) -> String {
+    rgb_code(COLCTRL_FORE_RGB, color)
+}
+
+/// Returns the control sequence switching the background to `color`.
+pub fn back_code(color: Color) -> String {
+    rgb_code(COLCTRL_BACK_RGB, color)
+}
+
+fn rgb_code(control: char, color: Color) -> String {
+    [
+        control,
+        char::from(color.r),
+        char::from(color.g),
+        char::from(color.b),
+    ]
+    .iter()
+    .collect()
+}
+
+/// A piece of text printed with the same colors.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ColoredRun {
+    /// The text, without any color codes; it may contain line breaks
+    pub text: String,
+    /// The foreground color, or `None` for the default one
+    pub fore: Option<Color>,
+    /// The background color, or `None` for the default one
+    pub back: Option<Color>,
+}
+
+/// A parser for text containing color codes.
+///
+/// See the [module documentation](./index.html) for the syntax.
+#[derive(Clone, Debug)]
+pub struct ColorCodeParser {
+    controls: [(Color, Color); 5],
+    colors: HashMap<String, Color>,
+}
+
+impl Default for ColorCodeParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorCodeParser {
+    /// Returns a parser with no named colors, where every color control pair is white on black.
+    pub fn new() -> Self {
+        Self {
+            controls: [(Color::WHITE, Color::BLACK); 5],
+            colors: HashMap::new(),
+        }
+    }
+
+    /// Sets the colors [`COLCTRL_1`] to [`COLCTRL_5`] switch to, where `index` is between 1 and 5.
+    ///
+    /// # Panics
+    /// If `index` isn't between 1 and 5.
+    ///
+    /// [`COLCTRL_1`]: ./constant.COLCTRL_1.html
+    /// [`COLCTRL_5`]: ./constant.COLCTRL_5.html
+    pub fn set_color_control(&mut self, index: usize, fore: Color, back: Color) {
+        assert!(
+            (1..=5).contains(&index),
+            "color control index must be between 1 and 5"
+        );
+        self.controls[index - 1] = (fore, back);
+    }
+
+    /// Associates a name with a color, for use in `#[color_name]` codes.
+    pub fn register_color<S: Into<String>>(&mut self, name: S, value: Color) {
+        self.colors.insert(name.into(), value);
+    }
+
+    /// Splits `text` into runs of equally colored text, with the color codes removed.
+    ///
+    /// Consecutive runs always differ in color, and none of them are empty. Control characters
+    /// cut short by the end of the text are ignored, and a `#[` without a closing `]` is kept as
+    /// text.
+    pub fn parse(&self, text: &str) -> Vec<ColoredRun> {
+        let mut runs = Vec::new();
+        let mut current = String::new();
+        let mut fore = None;
+        let mut back = None;
+        let mut color_stack: Vec<Color> = Vec::new();
+
+        let mut rest = text;
+        while let Some(character) = rest.chars().next() {
+            if rest.starts_with("#[") {
+                if let Some(end) = rest.find(']') {
+                    push_run(&mut runs, &mut current, fore, back);
+                    if let Some(&color) = self.colors.get(&rest[2..end]) {
+                        color_stack.push(color);
+                    } else {
+                        color_stack.pop();
+                    }
+                    fore = color_stack.last().copied();
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+
+            rest = &rest[character.len_utf8()..];
+            match character {
+                COLCTRL_1..=COLCTRL_5 => {
+                    push_run(&mut runs, &mut current, fore, back);
+                    let (control_fore, control_back) =
+                        self.controls[(character as u32 - COLCTRL_1 as u32) as usize];
+                    fore = Some(control_fore);
+                    back = Some(control_back);
+                }
+                COLCTRL_FORE_RGB | COLCTRL_BACK_RGB => {
+                    push_run(&mut runs, &mut current, fore, back);
+                    let mut components = rest.chars().take(3);
+                    let mut next = || components.next().map(|c| (c as u32).min(255) as u8);
+                    if let (Some(r), Some(g), Some(b)) = (next(), next(), next()) {
+                        let color = Some(Color::new(r, g, b));
+                        if character == COLCTRL_FORE_RGB {
+                            fore = color;
+                        } else {
+                            back = color;
+                        }
+                    }
+                    rest = rest.char_indices().nth(3).map_or("", |(i, _)| &rest[i..]);
+                }
+                COLCTRL_STOP => {
+                    push_run(&mut runs, &mut current, fore, back);
+                    fore = None;
+                    back = None;
+                    color_stack.clear();
+                }
+                _ => current.push(character),
+            }
+        }
+        push_run(&mut runs, &mut current, fore, back);
+
+        runs
+    }
+
+    /// Like `parse`, but splits the runs into lines at the line breaks in `text`.
+    pub fn parse_lines(&self, text: &str) -> Vec<Vec<ColoredRun>> {
+        let mut lines = vec![Vec::new()];
+        for run in self.parse(text) {
+            for (i, part) in run.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Vec::new());
+                }
+                if !part.is_empty() {
+                    lines.last_mut().unwrap().push(ColoredRun {
+                        text: part.to_owned(),
+                        fore: run.fore,
+                        back: run.back,
+                    });
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// Returns `text` with the color codes removed.
+    pub fn strip(&self, text: &str) -> String {
+        self.parse(text).into_iter().map(|run| run.text).collect()
+    }
+}
+
+// Ends the current run, merging it into the previous one if they have the same colors.
+fn push_run(
+    runs: &mut Vec<ColoredRun>,
+    current: &mut String,
+    fore: Option<Color>,
+    back: Option<Color>,
+) {
+    if current.is_empty() {
+        return;
+    }
+    match runs.last_mut() {
+        Some(last) if last.fore == fore && last.back == back => last.text.push_str(current),
+        _ => runs.push(ColoredRun {
+            text: current.clone(),
+            fore,
+            back,
+        }),
+    }
+    current.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_codes() {
+        let mut parser = ColorCodeParser::new();
+        parser.set_color_control(2, Color::YELLOW, Color::BLUE);
+        // The last sequence is cut short by the end of the text, and is ignored.
+        let text = format!(
+            "a{}b{}c{}d{}{}",
+            COLCTRL_2,
+            back_code(Color::new(0, 10, 255)),
+            COLCTRL_STOP,
+            COLCTRL_FORE_RGB,
+            char::from(255)
+        );
+        let runs = parser.parse(&text);
+        assert_eq!(parser.strip(&text), "abcd");
+        assert_eq!(runs.len(), 4);
+        assert_eq!((runs[0].fore, runs[0].back), (None, None));
+        assert_eq!(
+            (runs[1].fore, runs[1].back),
+            (Some(Color::YELLOW), Some(Color::BLUE))
+        );
+        assert_eq!(
+            (runs[2].fore, runs[2].back),
+            (Some(Color::YELLOW), Some(Color::new(0, 10, 255)))
+        );
+        assert_eq!((runs[3].fore, runs[3].back), (None, None));
+    }
+
+    #[test]
+    fn named_colors_and_lines() {
+        let mut parser = ColorCodeParser::new();
+        parser.register_color("red", Color::RED);
+        parser.register_color("blue", Color::BLUE);
+        let lines = parser.parse_lines("#[red]r#[blue]b\nb#[]r#[]w #[unclosed");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][0].fore, Some(Color::RED));
+        assert_eq!(lines[0][1].fore, Some(Color::BLUE));
+        assert_eq!(lines[1][0].text, "b");
+        assert_eq!(lines[1][1].fore, Some(Color::RED));
+        assert_eq!(lines[1][2].text, "w #[unclosed");
+        assert_eq!(lines[1][2].fore, None);
+    }
+}
@@ -61,6 +61,17 @@ impl FloorRem for i32 {
     }
 }
 
+impl FloorRem for i64 {
+    fn floor_modulo(self, rhs: Self) -> Self::Output {
+        let m = self % rhs;
+        if m < 0 {
+            m + rhs
+        } else {
+            m
+        }
+    }
+}
+
 impl FloorRem for isize {
     fn floor_modulo(self, rhs: Self) -> Self::Output {
         let m = self % rhs;
@@ -78,3 +89,49 @@ macro_rules! lerp {
         a + $x * ($b - a)
     }};
 }
+
+/// Serializes and deserializes arrays too large for serde's built-in implementations, which
+/// only go up to 32 elements, as sequences. Use with `#[serde(with = "crate::util::big_array")]`.
+#[cfg(feature = "serialization")]
+pub(crate) mod big_array {
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::{Serialize, Serializer};
+    use std::convert::TryFrom;
+
+    pub(crate) fn serialize<S: Serializer, T: Serialize, const N: usize>(
+        array: &[T; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(array.iter())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[T; N], D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        <[T; N]>::try_from(values)
+            .map_err(|_| D::Error::invalid_length(length, &&*format!("{}", N)))
+    }
+
+    /// The same as `big_array`, for boxed arrays.
+    pub(crate) mod boxed {
+        use super::*;
+
+        pub(crate) use super::serialize;
+
+        pub(crate) fn deserialize<
+            'de,
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+            const N: usize,
+        >(
+            deserializer: D,
+        ) -> Result<Box<[T; N]>, D::Error> {
+            let values = Vec::<T>::deserialize(deserializer)?;
+            let length = values.len();
+            Box::<[T; N]>::try_from(values.into_boxed_slice())
+                .map_err(|_| D::Error::invalid_length(length, &&*format!("{}", N)))
+        }
+    }
+}
@@ -0,0 +1,389 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Voronoi diagrams
+//!
+//! Partitions a grid into regions, one per site, where every cell belongs to the region of the
+//! site closest to it. Useful for biome partitioning, territory maps and the like.
+
+use crate::grid::Grid;
+use crate::math;
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{FPosition, Position, Rectangle, USize};
+
+/// The way distances between a cell and a site are measured, which determines the shape of the
+/// regions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum DistanceMetric {
+    /// Straight-line distance, which gives the classic polygonal regions.
+    #[default]
+    Euclidean,
+    /// The sum of the horizontal and vertical distances, which gives diamond-like regions.
+    Manhattan,
+    /// The larger of the horizontal and vertical distances, which gives square-like regions.
+    Chebyshev,
+}
+
+impl DistanceMetric {
+    /// Returns the distance between `a` and `b` according to this metric.
+    pub fn distance(self, a: Position, b: Position) -> f32 {
+        let dx = (a.x as f32 - b.x as f32).abs();
+        let dy = (a.y as f32 - b.y as f32).abs();
+        match self {
+            Self::Euclidean => math::hypot(dx, dy),
+            Self::Manhattan => dx + dy,
+            Self::Chebyshev => dx.max(dy),
+        }
+    }
+}
+
+/// A Voronoi diagram over a grid.
+///
+/// Every cell is labeled with the index of its closest site; when several sites are equally
+/// close, the one with the lowest index wins.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::{Position, USize};
+/// # use doryen_extra::voronoi::{DistanceMetric, Voronoi};
+/// let sites = [Position::new(2, 2), Position::new(17, 2)];
+/// let voronoi = Voronoi::new(USize::new(20, 5), &sites, DistanceMetric::Euclidean);
+/// assert_eq!(voronoi.label(Position::new(0, 4)), Some(0));
+/// assert_eq!(voronoi.label(Position::new(19, 0)), Some(1));
+/// assert_eq!(voronoi.neighbors(0), &[1]);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Voronoi {
+    sites: Vec<Position>,
+    labels: Grid<usize>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Voronoi {
+    /// Computes the Voronoi diagram of the given sites over a grid of the given size, measuring
+    /// distances with `metric`.
+    ///
+    /// # Panics
+    ///
+    /// If there are no sites.
+    pub fn new(size: USize, sites: &[Position], metric: DistanceMetric) -> Self {
+        Self::new_with_distance(size, sites, |a, b| metric.distance(a, b))
+    }
+
+    /// Computes the Voronoi diagram of the given sites over a grid of the given size, measuring
+    /// the distance from each cell to each site with the `distance` function.
+    ///
+    /// # Panics
+    ///
+    /// If there are no sites.
+    pub fn new_with_distance<F: Fn(Position, Position) -> f32>(
+        size: USize,
+        sites: &[Position],
+        distance: F,
+    ) -> Self {
+        assert!(
+            !sites.is_empty(),
+            "a Voronoi diagram needs at least one site"
+        );
+
+        let labels = Grid::new_with(size.width as usize, size.height as usize, |cell| {
+            let mut closest = 0;
+            let mut closest_distance = distance(cell, sites[0]);
+            for (index, &site) in sites.iter().enumerate().skip(1) {
+                let site_distance = distance(cell, site);
+                if site_distance < closest_distance {
+                    closest = index;
+                    closest_distance = site_distance;
+                }
+            }
+            closest
+        });
+
+        let mut neighbors = vec![Vec::new(); sites.len()];
+        for (position, &label) in labels.enumerate() {
+            for other in [position + (1, 0), position + (0, 1)]
+                .iter()
+                .filter_map(|&next| labels.get(next))
+            {
+                if *other != label {
+                    neighbors[label].push(*other);
+                    neighbors[*other].push(label);
+                }
+            }
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        Self {
+            sites: sites.to_vec(),
+            labels,
+            neighbors,
+        }
+    }
+
+    /// Computes the Voronoi diagram of `site_count` randomly placed sites over a grid of the
+    /// given size. Several sites may end up on the same cell.
+    ///
+    /// # Panics
+    ///
+    /// If `site_count` is 0 or the size is empty.
+    pub fn new_random<A: Algorithm>(
+        size: USize,
+        site_count: usize,
+        metric: DistanceMetric,
+        random: &mut Random<A>,
+    ) -> Self {
+        assert!(size.area() > 0, "the grid must not be empty");
+
+        let area = Rectangle::new(Position::ORIGIN, size);
+        let sites: Vec<_> = (0..site_count).map(|_| random.position_in(area)).collect();
+        Self::new(size, &sites, metric)
+    }
+
+    /// Returns the sites the diagram was computed from.
+    pub fn sites(&self) -> &[Position] {
+        &self.sites
+    }
+
+    /// Returns the grid of labels, where each cell holds the index of its closest site.
+    pub fn labels(&self) -> &Grid<usize> {
+        &self.labels
+    }
+
+    /// Returns the index of the site closest to `position`, or `None` if the position is
+    /// outside the grid.
+    pub fn label(&self, position: Position) -> Option<usize> {
+        self.labels.get(position).copied()
+    }
+
+    /// Returns the cells belonging to the region of the given site, in row-major order.
+    pub fn region(&self, site: usize) -> Vec<Position> {
+        self.labels
+            .enumerate()
+            .filter(|&(_, &label)| label == site)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Returns the indices of the sites whose regions share an edge with the region of the
+    /// given site, in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// If `site` is out of range.
+    pub fn neighbors(&self, site: usize) -> &[usize] {
+        &self.neighbors[site]
+    }
+
+    /// Returns the region adjacency graph as a list of edges `(a, b)` with `a < b`.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(a, list)| list.iter().filter(move |&&b| a < b).map(move |&b| (a, b)))
+            .collect()
+    }
+
+    /// Returns the number of cells in each region, indexed by site.
+    pub fn region_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.sites.len()];
+        for &label in &self.labels {
+            sizes[label] += 1;
+        }
+
+        sizes
+    }
+
+    /// Returns the centroid of each region, indexed by site, or `None` for sites whose region
+    /// is empty because another site took all of its cells.
+    pub fn centroids(&self) -> Vec<Option<FPosition>> {
+        let mut sums = vec![(0.0_f64, 0.0_f64, 0_usize); self.sites.len()];
+        for (position, &label) in self.labels.enumerate() {
+            let sum = &mut sums[label];
+            sum.0 += f64::from(position.x);
+            sum.1 += f64::from(position.y);
+            sum.2 += 1;
+        }
+
+        sums.into_iter()
+            .map(|(x, y, count)| {
+                if count == 0 {
+                    None
+                } else {
+                    Some(FPosition::new(
+                        (x / count as f64) as f32,
+                        (y / count as f64) as f32,
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a new diagram where every site has been moved to the centroid of its region,
+    /// measuring distances with `metric`; sites whose region is empty stay where they are. This
+    /// is one step of Lloyd's relaxation: repeating it a few times makes the regions more even
+    /// in size and shape, which often looks better for biomes and territories.
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::{Position, USize};
+    /// # use doryen_extra::voronoi::{DistanceMetric, Voronoi};
+    /// let sites = [Position::new(0, 0), Position::new(1, 0)];
+    /// let voronoi = Voronoi::new(USize::new(10, 1), &sites, DistanceMetric::Euclidean);
+    /// assert_eq!(voronoi.region_sizes(), vec![1, 9]);
+    ///
+    /// let relaxed = voronoi.relaxed(DistanceMetric::Euclidean);
+    /// assert_eq!(relaxed.sites(), &[Position::new(0, 0), Position::new(5, 0)]);
+    /// assert_eq!(relaxed.region_sizes(), vec![3, 7]);
+    /// ```
+    pub fn relaxed(&self, metric: DistanceMetric) -> Self {
+        let sites: Vec<Position> = self
+            .centroids()
+            .into_iter()
+            .zip(&self.sites)
+            .map(|(centroid, &site)| {
+                centroid.map_or(site, |c| {
+                    Position::new(c.x.round() as i32, c.y.round() as i32)
+                })
+            })
+            .collect();
+
+        Self::new(self.labels.size(), &sites, metric)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Distribution;
+
+    #[test]
+    fn labels_adjacency_and_centroids() {
+        let sites = [
+            Position::new(0, 0),
+            Position::new(9, 0),
+            Position::new(0, 9),
+            Position::new(9, 9),
+        ];
+        let voronoi = Voronoi::new(USize::new(10, 10), &sites, DistanceMetric::Manhattan);
+
+        assert_eq!(voronoi.label(Position::new(2, 3)), Some(0));
+        assert_eq!(voronoi.label(Position::new(7, 8)), Some(3));
+        assert_eq!(voronoi.label(Position::new(10, 0)), None);
+        assert_eq!(voronoi.region_sizes().iter().sum::<usize>(), 100);
+        assert_eq!(voronoi.region_sizes()[3], 25);
+        assert_eq!(voronoi.region(3).len(), 25);
+
+        assert_eq!(voronoi.neighbors(0), &[1, 2]);
+        assert_eq!(voronoi.edges(), vec![(0, 1), (0, 2), (1, 3), (2, 3)]);
+
+        let centroids = voronoi.centroids();
+        assert_eq!(centroids[0], Some(FPosition::new(2.0, 2.0)));
+        assert_eq!(centroids[3], Some(FPosition::new(7.0, 7.0)));
+    }
+
+    #[test]
+    fn metrics_and_custom_distance() {
+        let a = Position::new(1, 1);
+        let b = Position::new(4, 5);
+        assert_eq!(DistanceMetric::Euclidean.distance(a, b), 5.0);
+        assert_eq!(DistanceMetric::Manhattan.distance(a, b), 7.0);
+        assert_eq!(DistanceMetric::Chebyshev.distance(a, b), 4.0);
+
+        // Horizontal distance only, which gives vertical stripes.
+        let sites = [
+            Position::new(1, 0),
+            Position::new(6, 0),
+            Position::new(6, 0),
+        ];
+        let voronoi = Voronoi::new_with_distance(USize::new(8, 4), &sites, |cell, site| {
+            (cell.x - site.x).abs() as f32
+        });
+        assert!(voronoi.region(0).iter().all(|p| p.x <= 3));
+        assert_eq!(voronoi.region(0).len(), 16);
+        assert_eq!(voronoi.centroids()[2], None);
+        assert_eq!(voronoi.neighbors(2), &[] as &[usize]);
+
+        let mut random = Random::new_mt_from_seed(5);
+        let random_voronoi = Voronoi::new_random(
+            USize::new(30, 20),
+            8,
+            DistanceMetric::Euclidean,
+            &mut random,
+        );
+        assert_eq!(random_voronoi.sites().len(), 8);
+        assert_eq!(random_voronoi.labels().size(), USize::new(30, 20));
+    }
+
+    #[test]
+    fn random_sites_with_any_distribution() {
+        for &distribution in &[
+            Distribution::Linear,
+            Distribution::Gaussian,
+            Distribution::GaussianRange,
+            Distribution::GaussianInverse,
+            Distribution::GaussianRangeInverse,
+        ] {
+            let mut random = Random::new_mt_from_seed(5);
+            random.distribution = distribution;
+            let voronoi = Voronoi::new_random(
+                USize::new(40, 30),
+                10,
+                DistanceMetric::Euclidean,
+                &mut random,
+            );
+            assert!(voronoi
+                .sites()
+                .iter()
+                .all(|&site| voronoi.label(site).is_some()));
+            assert!(
+                voronoi
+                    .region_sizes()
+                    .iter()
+                    .filter(|&&size| size > 0)
+                    .count()
+                    > 1
+            );
+        }
+    }
+}
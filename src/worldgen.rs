@@ -0,0 +1,407 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # World generation.
+//!
+//! A batteries-included world generator that chains the height map, noise and erosion tools
+//! into a finished map: an eroded FBM elevation, a water level, temperature and moisture layers,
+//! and the biome classification those produce. It covers the same ground as the classic libtcod
+//! world generator sample, in a single call.
+//!
+//! # Examples
+//! ```
+//! # use doryen_extra::USize;
+//! # use doryen_extra::random::Random;
+//! # use doryen_extra::worldgen::WorldGenerator;
+//! let mut random = Random::new_mt_from_seed(42);
+//! let world = WorldGenerator::default().generate(USize::new(80, 50), &mut random);
+//! let image = world.to_image();
+//! assert_eq!(image.size(), USize::new(80, 50));
+//! ```
+
+use crate::color::Color;
+use crate::grid::Grid;
+use crate::heightmap::{FbmCoordinateParameters, HeightMap};
+use crate::image::Image;
+use crate::noise::{Noise, DEFAULT_LACUNARITY};
+use crate::random::algorithms::Algorithm;
+use crate::random::{Random, Rng};
+use crate::{Position, UPosition, USize};
+
+/// The kinds of terrain a generated world is made of.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum Biome {
+    /// Water far below the water level
+    DeepOcean,
+    /// Water just below the water level
+    Ocean,
+    /// Land just above the water level
+    Beach,
+    /// Land too cold for anything to grow
+    Snow,
+    /// Cold and dry land
+    Tundra,
+    /// Cold and wet land
+    Taiga,
+    /// Temperate and dry land
+    Grassland,
+    /// Temperate land with average rainfall
+    TemperateForest,
+    /// Temperate and wet land
+    TemperateRainforest,
+    /// Hot and dry land
+    Desert,
+    /// Hot land with average rainfall
+    Savanna,
+    /// Hot and wet land
+    TropicalRainforest,
+    /// Land high above the water level
+    Mountain,
+}
+
+impl Biome {
+    /// All the biomes, in declaration order.
+    pub const ALL: [Self; 13] = [
+        Self::DeepOcean,
+        Self::Ocean,
+        Self::Beach,
+        Self::Snow,
+        Self::Tundra,
+        Self::Taiga,
+        Self::Grassland,
+        Self::TemperateForest,
+        Self::TemperateRainforest,
+        Self::Desert,
+        Self::Savanna,
+        Self::TropicalRainforest,
+        Self::Mountain,
+    ];
+
+    /// Returns the color the biome is drawn with.
+    pub fn color(self) -> Color {
+        match self {
+            Self::DeepOcean => Color::new(0, 0, 100),
+            Self::Ocean => Color::new(30, 60, 180),
+            Self::Beach => Color::new(230, 210, 150),
+            Self::Snow => Color::new(245, 245, 250),
+            Self::Tundra => Color::new(170, 180, 160),
+            Self::Taiga => Color::new(60, 110, 80),
+            Self::Grassland => Color::new(140, 190, 80),
+            Self::TemperateForest => Color::new(50, 140, 50),
+            Self::TemperateRainforest => Color::new(20, 100, 60),
+            Self::Desert => Color::new(220, 190, 120),
+            Self::Savanna => Color::new(180, 180, 70),
+            Self::TropicalRainforest => Color::new(0, 90, 20),
+            Self::Mountain => Color::new(120, 110, 100),
+        }
+    }
+
+    /// Returns whether the biome is under water.
+    pub fn is_water(self) -> bool {
+        matches!(self, Self::DeepOcean | Self::Ocean)
+    }
+}
+
+/// A world generator.
+///
+/// All the levels are relative: `elevation` runs from 0 at the bottom of the ocean to 1 at the
+/// highest peak, while `temperature` and `moisture` run from 0 to 1.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct WorldGenerator {
+    /// The number of noise features across the width of the map; larger values give smaller
+    /// continents
+    pub frequency: f32,
+    /// The number of octaves of the elevation FBM
+    pub octaves: f32,
+    /// The number of rain drops simulated per cell; 0 disables erosion
+    pub erosion_drops_per_cell: f32,
+    /// The amount of ground eroded on a drop's path
+    pub erosion_coefficient: f32,
+    /// The amount of ground deposited when a drop stops flowing
+    pub aggregation_coefficient: f32,
+    /// The fraction, between 0 and 1, of the map that is under water
+    pub water_fraction: f32,
+    /// The fraction of the way from the water level to the highest peak above which land is
+    /// mountains
+    pub mountain_level: f32,
+    /// The fraction of the way from the water level to the highest peak below which land is
+    /// beach
+    pub beach_level: f32,
+    /// How much colder land gets with its height above the water level
+    pub lapse_rate: f32,
+    /// How much the temperature noise disturbs the latitude based temperature
+    pub temperature_variation: f32,
+}
+
+impl Default for WorldGenerator {
+    /// Returns a generator producing a handful of continents, about 60% water.
+    fn default() -> Self {
+        Self {
+            frequency: 4.0,
+            octaves: 6.0,
+            erosion_drops_per_cell: 1.0,
+            erosion_coefficient: 0.05,
+            aggregation_coefficient: 0.05,
+            water_fraction: 0.6,
+            mountain_level: 0.7,
+            beach_level: 0.03,
+            lapse_rate: 0.5,
+            temperature_variation: 0.2,
+        }
+    }
+}
+
+impl WorldGenerator {
+    /// Generates a world of the given size.
+    ///
+    /// # Panics
+    /// If either dimension of `size` is 0.
+    pub fn generate<A: Algorithm>(&self, size: USize, random: &mut Random<A>) -> World {
+        let width = size.width as usize;
+        let height = size.height as usize;
+
+        let mut elevation = self.noise_layer(size, self.octaves, random);
+        if self.erosion_drops_per_cell > 0.0 {
+            let drops = (self.erosion_drops_per_cell * (width * height) as f32) as u32;
+            elevation.rain_erosion(
+                drops,
+                self.erosion_coefficient,
+                self.aggregation_coefficient,
+                random,
+            );
+            elevation.normalize(0.0, 1.0);
+        }
+        let water_level = quantile(elevation.values(), self.water_fraction);
+
+        let land_height = |position: UPosition| {
+            let value = elevation.value(position);
+            if value <= water_level || water_level >= 1.0 {
+                0.0
+            } else {
+                (value - water_level) / (1.0 - water_level)
+            }
+        };
+
+        let mut temperature = self.noise_layer(size, 3.0, random);
+        for y in 0..height {
+            let latitude = ((2 * y + 1) as f32 / height as f32 - 1.0).abs();
+            for x in 0..width {
+                let position = UPosition::new(x as u32, y as u32);
+                let variation = (temperature.value(position) - 0.5) * self.temperature_variation;
+                let value = 1.0 - latitude + variation - land_height(position) * self.lapse_rate;
+                temperature.set_value(position, value.clamp(0.0, 1.0));
+            }
+        }
+
+        let moisture = self.noise_layer(size, 4.0, random);
+
+        let biomes = Grid::new_with(width, height, |position| {
+            let position = UPosition::new(position.x as u32, position.y as u32);
+            let value = elevation.value(position);
+            if value < water_level {
+                if value < water_level * 0.7 {
+                    Biome::DeepOcean
+                } else {
+                    Biome::Ocean
+                }
+            } else {
+                self.classify_land(
+                    land_height(position),
+                    temperature.value(position),
+                    moisture.value(position),
+                )
+            }
+        });
+
+        World {
+            elevation,
+            water_level,
+            temperature,
+            moisture,
+            biomes,
+        }
+    }
+
+    /// Returns the biome of a land cell with the given height above the water level,
+    /// temperature and moisture, all between 0 and 1.
+    pub fn classify_land(&self, height: f32, temperature: f32, moisture: f32) -> Biome {
+        if height >= self.mountain_level {
+            if temperature < 0.3 {
+                Biome::Snow
+            } else {
+                Biome::Mountain
+            }
+        } else if height < self.beach_level {
+            Biome::Beach
+        } else if temperature < 0.15 {
+            Biome::Snow
+        } else if temperature < 0.35 {
+            if moisture < 0.4 {
+                Biome::Tundra
+            } else {
+                Biome::Taiga
+            }
+        } else if temperature < 0.7 {
+            if moisture < 0.3 {
+                Biome::Grassland
+            } else if moisture < 0.65 {
+                Biome::TemperateForest
+            } else {
+                Biome::TemperateRainforest
+            }
+        } else if moisture < 0.25 {
+            Biome::Desert
+        } else if moisture < 0.55 {
+            Biome::Savanna
+        } else {
+            Biome::TropicalRainforest
+        }
+    }
+
+    fn noise_layer<A: Algorithm>(
+        &self,
+        size: USize,
+        octaves: f32,
+        random: &mut Random<A>,
+    ) -> HeightMap {
+        let seed = random.get_i32(0, i32::MAX - 1) as u32;
+        let mut noise = Noise::new_simplex(2, DEFAULT_LACUNARITY, Random::new_mt_from_seed(seed));
+        let mut layer = HeightMap::new(size.width as usize, size.height as usize);
+        let coordinates = FbmCoordinateParameters {
+            mul_x: self.frequency,
+            mul_y: self.frequency * size.height as f32 / size.width as f32,
+            add_x: 0.0,
+            add_y: 0.0,
+        };
+        layer.add_fbm(&mut noise, octaves, coordinates, 0.0, 1.0);
+        layer.normalize(0.0, 1.0);
+
+        layer
+    }
+}
+
+/// Returns the value below which `fraction` of `values` lie.
+fn quantile(values: &[f32], fraction: f32) -> f32 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let index = (fraction.clamp(0.0, 1.0) * sorted.len() as f32) as usize;
+    if index >= sorted.len() {
+        f32::INFINITY
+    } else {
+        sorted[index]
+    }
+}
+
+/// A generated world.
+#[derive(Clone, Debug)]
+pub struct World {
+    /// The elevation of each cell, between 0 and 1
+    pub elevation: HeightMap,
+    /// The elevation below which cells are under water
+    pub water_level: f32,
+    /// The temperature of each cell, between 0 and 1
+    pub temperature: HeightMap,
+    /// The moisture of each cell, between 0 and 1
+    pub moisture: HeightMap,
+    /// The biome of each cell
+    pub biomes: Grid<Biome>,
+}
+
+impl World {
+    /// Returns the size of the world.
+    pub fn size(&self) -> USize {
+        self.biomes.size()
+    }
+
+    /// Returns the biome at the given position, or `None` if it's outside the world.
+    pub fn biome(&self, position: Position) -> Option<Biome> {
+        self.biomes.get(position).copied()
+    }
+
+    /// Returns the color of each cell.
+    pub fn colors(&self) -> Grid<Color> {
+        self.biomes.map(|biome| biome.color())
+    }
+
+    /// Returns an image with one pixel per cell, colored by biome.
+    pub fn to_image(&self) -> Image {
+        let pixels = self.biomes.iter().map(|biome| biome.color()).collect();
+
+        Image::new_with_pixels(self.size(), pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn water_fraction_is_respected() {
+        let generator = WorldGenerator::default();
+        let world = generator.generate(USize::new(64, 48), &mut Random::new_mt_from_seed(3));
+        let water = world.biomes.iter().filter(|biome| biome.is_water()).count();
+        let fraction = water as f32 / (64 * 48) as f32;
+        assert!((fraction - generator.water_fraction).abs() < 0.01);
+    }
+
+    #[test]
+    fn deterministic() {
+        let generator = WorldGenerator::default();
+        let size = USize::new(32, 32);
+        let first = generator.generate(size, &mut Random::new_mt_from_seed(11));
+        let second = generator.generate(size, &mut Random::new_mt_from_seed(11));
+        assert_eq!(first.biomes, second.biomes);
+        assert_eq!(first.elevation.values(), second.elevation.values());
+    }
+
+    #[test]
+    fn poles_are_colder_than_the_equator() {
+        let world = WorldGenerator::default()
+            .generate(USize::new(40, 40), &mut Random::new_mt_from_seed(5));
+        let row_average = |y: u32| {
+            (0..40)
+                .map(|x| world.temperature.value(UPosition::new(x, y)))
+                .sum::<f32>()
+                / 40.0
+        };
+        assert!(row_average(0) < row_average(20));
+        assert!(row_average(39) < row_average(20));
+    }
+}
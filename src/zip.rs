@@ -0,0 +1,434 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Compression toolkit
+//!
+//! A buffer for writing and reading back ints, floats, strings, colors, images, consoles and raw
+//! data, as a simple binary persistence path that doesn't require serde. The layout is the same
+//! as the one used by libtcod's zip toolkit, so save files written by games using libtcod can be
+//! read, and the other way around.
+//!
+//! Values have to be read back in the same order they were written in. With the `compression`
+//! feature enabled, buffers can be saved to and loaded from gzip compressed files, like libtcod
+//! does; otherwise, they can still be written to and read from any `Write` or `Read`
+//! uncompressed.
+
+use crate::color::Color;
+use crate::console::{Cell, ConsoleTarget};
+use crate::grid::Grid;
+use crate::image::Image;
+use crate::{Error, Position, USize};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+/// A buffer of values written and read in sequence.
+///
+/// # Examples
+/// ```
+/// # use doryen_extra::color::Color;
+/// # use doryen_extra::zip::Zip;
+/// let mut zip = Zip::new();
+/// zip.put_int(42);
+/// zip.put_string("Rogue");
+/// zip.put_color(Color::RED);
+///
+/// let mut bytes = Vec::new();
+/// zip.write_to(&mut bytes).unwrap();
+///
+/// let mut zip = Zip::read_from(bytes.as_slice()).unwrap();
+/// assert_eq!(zip.get_int(), Ok(42));
+/// assert_eq!(zip.get_string(), Ok(Some("Rogue".to_string())));
+/// assert_eq!(zip.get_color(), Ok(Color::RED));
+/// assert_eq!(zip.remaining_bytes(), 0);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Zip {
+    buffer: Vec<u8>,
+    offset: usize,
+}
+
+impl Zip {
+    /// Returns an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a buffer holding the given bytes, ready to be read from the beginning.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            buffer: bytes,
+            offset: 0,
+        }
+    }
+
+    /// Returns the bytes written to the buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns the bytes written to the buffer, consuming it.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+
+    /// Returns the number of bytes in the buffer.
+    pub fn current_bytes(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of bytes left to read.
+    pub fn remaining_bytes(&self) -> usize {
+        self.buffer.len() - self.offset
+    }
+
+    /// Skips the given number of bytes, or to the end of the buffer if there aren't that many
+    /// left.
+    pub fn skip_bytes(&mut self, count: usize) {
+        self.offset = (self.offset + count).min(self.buffer.len());
+    }
+
+    /// Writes a single byte.
+    pub fn put_char(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    /// Writes a 32-bit integer.
+    pub fn put_int(&mut self, value: i32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes a 32-bit float.
+    pub fn put_float(&mut self, value: f32) {
+        self.buffer
+            .extend_from_slice(&value.to_bits().to_le_bytes());
+    }
+
+    /// Writes a string, as its length followed by its bytes and a terminating zero.
+    ///
+    /// # Panics
+    ///
+    /// If the string is longer than `i32::MAX` bytes.
+    pub fn put_string<S: AsRef<str>>(&mut self, value: S) {
+        let bytes = value.as_ref().as_bytes();
+        self.put_length(bytes.len());
+        self.buffer.extend_from_slice(bytes);
+        self.buffer.push(0);
+    }
+
+    /// Writes the red, green and blue components of a color. The alpha component isn't stored.
+    pub fn put_color(&mut self, value: Color) {
+        self.buffer.extend_from_slice(&[value.r, value.g, value.b]);
+    }
+
+    /// Writes an image, as its width and height followed by the colors of its pixels.
+    pub fn put_image(&mut self, image: &Image) {
+        let size = image.size();
+        self.put_int(size.width as i32);
+        self.put_int(size.height as i32);
+        for &pixel in image.pixels() {
+            self.put_color(pixel);
+        }
+    }
+
+    /// Writes the contents of a console, as its width and height followed by the character,
+    /// foreground color and background color of each cell.
+    ///
+    /// Like in libtcod, each character is stored as a single byte, so character codes above 255
+    /// are truncated.
+    pub fn put_console<C: ConsoleTarget>(&mut self, console: &C) {
+        let size = console.size();
+        self.put_int(size.width as i32);
+        self.put_int(size.height as i32);
+        for y in 0..size.height as i32 {
+            for x in 0..size.width as i32 {
+                let position = Position::new(x, y);
+                self.put_char(console.ascii(position).unwrap_or_default() as u8);
+                self.put_color(console.fore(position).unwrap_or_default());
+                self.put_color(console.back(position).unwrap_or_default());
+            }
+        }
+    }
+
+    /// Writes raw data, as its length followed by the bytes.
+    ///
+    /// # Panics
+    ///
+    /// If the data is longer than `i32::MAX` bytes.
+    pub fn put_data(&mut self, data: &[u8]) {
+        self.put_length(data.len());
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Reads a single byte.
+    pub fn get_char(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads a 32-bit integer.
+    pub fn get_int(&mut self) -> Result<i32, Error> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+
+        Ok(i32::from_le_bytes(bytes))
+    }
+
+    /// Reads a 32-bit float.
+    pub fn get_float(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_bits(self.get_int()? as u32))
+    }
+
+    /// Reads a string. Returns `None` if a null string was stored, which libtcod allows.
+    pub fn get_string(&mut self) -> Result<Option<String>, Error> {
+        let length = match self.get_length()? {
+            Some(length) => length,
+            None => return Ok(None),
+        };
+        let bytes = self.take(length + 1)?;
+        if bytes[length] != 0 {
+            return Err(Error::InvalidData("string isn't zero-terminated"));
+        }
+
+        String::from_utf8(bytes[..length].to_vec())
+            .map(Some)
+            .map_err(|_| Error::InvalidData("string isn't valid UTF-8"))
+    }
+
+    /// Reads a color. The alpha component is always 255.
+    pub fn get_color(&mut self) -> Result<Color, Error> {
+        let bytes = self.take(3)?;
+
+        Ok(Color::new(bytes[0], bytes[1], bytes[2]))
+    }
+
+    /// Reads an image.
+    pub fn get_image(&mut self) -> Result<Image, Error> {
+        let (size, area) = self.get_area(3)?;
+        let pixels = (0..area)
+            .map(|_| self.get_color())
+            .collect::<Result<_, _>>()?;
+
+        Image::try_new_with_pixels(size, pixels)
+    }
+
+    /// Reads the contents of a console into a buffer of cells.
+    pub fn get_console(&mut self) -> Result<Grid<Cell>, Error> {
+        let (size, area) = self.get_area(7)?;
+        let cells = (0..area)
+            .map(|_| {
+                Ok(Cell {
+                    ascii: u16::from(self.get_char()?),
+                    fore: self.get_color()?,
+                    back: self.get_color()?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Grid::try_new_with_values(size.width as usize, size.height as usize, cells)
+    }
+
+    /// Reads raw data. Returns `None` if null data was stored, which libtcod allows.
+    pub fn get_data(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        match self.get_length()? {
+            Some(length) => Ok(Some(self.take(length)?.to_vec())),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the buffer to `writer` uncompressed, as the number of bytes followed by the bytes.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer is longer than `u32::MAX` bytes.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let length = u32::try_from(self.buffer.len()).expect("the buffer is too large");
+        writer.write_all(&length.to_le_bytes())?;
+        writer.write_all(&self.buffer)
+    }
+
+    /// Reads a buffer written by `write_to` from `reader`.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut length = [0; 4];
+        reader.read_exact(&mut length)?;
+        let length = u32::from_le_bytes(length) as usize;
+        // The length can't be trusted, so the buffer only grows as the data actually arrives.
+        let mut buffer = Vec::new();
+        reader.take(length as u64).read_to_end(&mut buffer)?;
+        if buffer.len() < length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(Self::from_bytes(buffer))
+    }
+
+    /// Saves the buffer to a gzip compressed file, in the same format as libtcod.
+    #[cfg(feature = "compression")]
+    pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        self.write_to(&mut encoder)?;
+        encoder.finish()?;
+
+        Ok(())
+    }
+
+    /// Loads a buffer from a gzip compressed file, such as one saved by `save_to_file` or by
+    /// libtcod.
+    #[cfg(feature = "compression")]
+    pub fn load_from_file<P: AsRef<std::path::Path>>(path: P) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::read_from(flate2::read::GzDecoder::new(file))
+    }
+
+    fn put_length(&mut self, length: usize) {
+        self.put_int(i32::try_from(length).expect("the value is too large to store"));
+    }
+
+    fn get_length(&mut self) -> Result<Option<usize>, Error> {
+        match self.get_int()? {
+            -1 => Ok(None),
+            length if length < 0 => Err(Error::InvalidData("negative length")),
+            length => Ok(Some(length as usize)),
+        }
+    }
+
+    fn get_size(&mut self) -> Result<USize, Error> {
+        let width = self.get_int()?;
+        let height = self.get_int()?;
+        if width < 0 || height < 0 {
+            return Err(Error::InvalidData("negative size"));
+        }
+
+        Ok(USize::new(width as u32, height as u32))
+    }
+
+    // Reads a size, and returns it along with its area, after checking that the rest of the
+    // buffer is large enough for `bytes_per_cell` bytes per cell.
+    fn get_area(&mut self, bytes_per_cell: usize) -> Result<(USize, usize), Error> {
+        let size = self.get_size()?;
+        let area = size
+            .width
+            .checked_mul(size.height)
+            .ok_or(Error::InvalidData("size is too large"))? as usize;
+        match area.checked_mul(bytes_per_cell) {
+            Some(bytes) if bytes <= self.remaining_bytes() => Ok((size, area)),
+            _ => Err(Error::UnexpectedEnd),
+        }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&[u8], Error> {
+        if self.remaining_bytes() < count {
+            return Err(Error::UnexpectedEnd);
+        }
+        let start = self.offset;
+        self.offset += count;
+
+        Ok(&self.buffer[start..self.offset])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut image = Image::new(USize::new(2, 2));
+        image.put_pixel(Position::new(1, 0), Color::BLUE);
+        let mut console: Grid<Cell> = Grid::new_default(3, 1);
+        console.print_char(Position::new(2, 0), '@', Some(Color::YELLOW), None);
+
+        let mut zip = Zip::new();
+        zip.put_char(7);
+        zip.put_float(-1.5);
+        zip.put_data(&[1, 2, 3]);
+        zip.put_string("");
+        zip.put_int(-1);
+        zip.put_image(&image);
+        zip.put_console(&console);
+        assert_eq!(&zip.as_bytes()[..5], &[7, 0, 0, 0xc0, 0xbf]);
+
+        let mut bytes = Vec::new();
+        zip.write_to(&mut bytes).unwrap();
+        let mut read = Zip::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(read.current_bytes(), zip.current_bytes());
+        assert_eq!(read.get_char(), Ok(7));
+        assert_eq!(read.get_float(), Ok(-1.5));
+        assert_eq!(read.get_data(), Ok(Some(vec![1, 2, 3])));
+        assert_eq!(read.get_string(), Ok(Some(String::new())));
+        assert_eq!(read.get_string(), Ok(None));
+        assert_eq!(read.get_image(), Ok(image));
+        assert_eq!(read.get_console(), Ok(console));
+        assert_eq!(read.get_int(), Err(Error::UnexpectedEnd));
+    }
+
+    #[test]
+    fn rejects_untrusted_sizes() {
+        let header = |width, height| {
+            let mut zip = Zip::new();
+            zip.put_int(width);
+            zip.put_int(height);
+            Zip::from_bytes(zip.as_bytes().to_vec())
+        };
+        assert_eq!(
+            header(70_000, 70_000).get_image(),
+            Err(Error::InvalidData("size is too large"))
+        );
+        assert_eq!(
+            header(1_000, 1_000).get_console(),
+            Err(Error::UnexpectedEnd)
+        );
+
+        let truncated = [0xff, 0xff, 0xff, 0xff, 1, 2, 3];
+        assert_eq!(
+            Zip::read_from(&truncated[..]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_file() {
+        let path = std::env::temp_dir().join(format!("doryen-extra-zip-{}", std::process::id()));
+        let mut zip = Zip::new();
+        for i in 0..1000 {
+            zip.put_int(i % 10);
+        }
+        zip.save_to_file(&path).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() < 4000);
+
+        let mut loaded = Zip::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.as_bytes(), zip.as_bytes());
+        loaded.skip_bytes(4 * 999);
+        assert_eq!(loaded.get_int(), Ok(9));
+    }
+}